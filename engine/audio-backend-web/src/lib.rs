@@ -2,7 +2,7 @@ extern crate oxygengine_audio as audio;
 extern crate oxygengine_backend_web as web;
 extern crate oxygengine_core as core;
 
-use audio::resource::*;
+use audio::{controller::*, resource::*};
 use core::{
     assets::{asset::AssetId, database::AssetsDatabase},
     ecs::Entity,
@@ -36,11 +36,16 @@ enum AudioCache {
     Streaming(HtmlAudioElement, MediaElementAudioSourceNode),
 }
 
+/// (source, gain, notify ended, ended closure)
+type AudioVoiceCache = (AudioBufferSourceNode, GainNode, Arc<AtomicBool>, WebClosure);
+
 pub struct WebAudio {
     context: AudioContext,
+    master: GainNode,
     table_forward: HashMap<String, AssetId>,
     table_backward: HashMap<AssetId, String>,
     sources_cache: HashMap<Entity, AudioCache>,
+    voices_cache: HashMap<AudioVoiceId, AudioVoiceCache>,
 }
 
 unsafe impl Send for WebAudio {}
@@ -48,11 +53,18 @@ unsafe impl Sync for WebAudio {}
 
 impl Default for WebAudio {
     fn default() -> Self {
+        let context = AudioContext::new().unwrap();
+        let master = context.create_gain().unwrap();
+        master
+            .connect_with_audio_node(&context.destination())
+            .expect("Could not connect master gain with audio output");
         Self {
-            context: AudioContext::new().unwrap(),
+            context,
+            master,
             table_forward: Default::default(),
             table_backward: Default::default(),
             sources_cache: Default::default(),
+            voices_cache: Default::default(),
         }
     }
 }
@@ -81,8 +93,8 @@ impl Audio for WebAudio {
                 .context
                 .create_media_element_source(audio.as_ref())
                 .unwrap();
-            node.connect_with_audio_node(&self.context.destination())
-                .expect("Could not connect audio stream source with audio output");
+            node.connect_with_audio_node(&self.master)
+                .expect("Could not connect audio stream source with master gain");
             audio.load();
             audio.set_loop(looped);
             audio.set_playback_rate(playback_rate as f64);
@@ -111,7 +123,7 @@ impl Audio for WebAudio {
             let audio2 = audio.clone();
             let gain = self.context.create_gain().unwrap();
             let gain2 = gain.clone();
-            let destination = self.context.destination();
+            let destination = self.master.clone();
             let context = self.context.clone();
             let promise = self.context.decode_audio_data(&buffer.buffer()).unwrap();
             let future = JsFuture::from(promise).and_then(move |buff| {
@@ -121,7 +133,7 @@ impl Audio for WebAudio {
                     .connect_with_audio_node(gain.as_ref())
                     .expect("Could not connect audio source with gain");
                 gain.connect_with_audio_node(destination.as_ref())
-                    .expect("Could not connect gain with audio output");
+                    .expect("Could not connect gain with master gain");
                 audio.set_buffer(Some(&buff));
                 audio.set_loop(looped);
                 audio.playback_rate().set_value(playback_rate);
@@ -150,13 +162,13 @@ impl Audio for WebAudio {
                         .disconnect()
                         .expect("Could not disconnect audio source from gain");
                     gain.disconnect()
-                        .expect("Could not disconnect gain from audio output");
+                        .expect("Could not disconnect gain from master gain");
                     notify_ended.store(true, Ordering::Relaxed);
                     ended_closure.release();
                 }
                 AudioCache::Streaming(_, audio) => audio
                     .disconnect()
-                    .expect("Could not disconnect audio stream source from audio output"),
+                    .expect("Could not disconnect audio stream source from master gain"),
             }
         }
     }
@@ -242,4 +254,75 @@ impl Audio for WebAudio {
             }
         }
     }
+
+    fn create_voice(&mut self, id: AudioVoiceId, data: &[u8], params: &AudioPlayParams) {
+        let buffer = Uint8Array::from(data);
+        let audio = self.context.create_buffer_source().unwrap();
+        let notify_ended = Arc::new(AtomicBool::new(false));
+        let notify_ended2 = notify_ended.clone();
+        let closure = Closure::wrap(Box::new(move |_: web_sys::Event| {
+            notify_ended.store(true, Ordering::Relaxed);
+        }) as Box<dyn FnMut(_)>);
+        audio
+            .add_event_listener_with_callback("ended", closure.as_ref().unchecked_ref())
+            .unwrap();
+        let ended_closure = WebClosure::acquire(closure);
+        let audio2 = audio.clone();
+        let gain = self.context.create_gain().unwrap();
+        let gain2 = gain.clone();
+        let master = self.master.clone();
+        let context = self.context.clone();
+        let params = params.clone();
+        let destroyed = notify_ended2.clone();
+        let promise = self.context.decode_audio_data(&buffer.buffer()).unwrap();
+        let future = JsFuture::from(promise).and_then(move |buff| {
+            assert!(buff.is_instance_of::<AudioBuffer>());
+            let buff: AudioBuffer = buff.dyn_into().unwrap();
+            // NOTE: voice might get destroyed before its data was decoded.
+            if destroyed.load(Ordering::Relaxed) {
+                return future::ok(JsValue::null());
+            }
+            audio
+                .connect_with_audio_node(gain.as_ref())
+                .expect("Could not connect audio voice with gain");
+            gain.connect_with_audio_node(master.as_ref())
+                .expect("Could not connect gain with master gain");
+            audio.set_buffer(Some(&buff));
+            audio.set_loop(params.looped);
+            audio.playback_rate().set_value(params.playback_rate);
+            gain.gain().set_value(params.volume);
+            if context.state() != AudioContextState::Running {
+                drop(context.resume());
+            }
+            audio.start().expect("Could not start audio voice");
+            future::ok(JsValue::null())
+        });
+        drop(future_to_promise(future));
+        self.voices_cache
+            .insert(id, (audio2, gain2, notify_ended2, ended_closure));
+    }
+
+    fn destroy_voice(&mut self, id: AudioVoiceId) {
+        if let Some((audio, gain, notify_ended, mut ended_closure)) = self.voices_cache.remove(&id)
+        {
+            notify_ended.store(true, Ordering::Relaxed);
+            if audio.buffer().is_some() {
+                drop(audio.stop());
+            }
+            drop(audio.disconnect());
+            drop(gain.disconnect());
+            ended_closure.release();
+        }
+    }
+
+    fn has_voice_ended(&self, id: AudioVoiceId) -> bool {
+        self.voices_cache
+            .get(&id)
+            .map(|(_, _, notify_ended, _)| notify_ended.load(Ordering::Relaxed))
+            .unwrap_or(true)
+    }
+
+    fn set_master_volume(&mut self, volume: Scalar) {
+        self.master.gain().set_value(volume);
+    }
 }
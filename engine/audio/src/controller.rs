@@ -0,0 +1,171 @@
+use core::{id::ID, Scalar};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+pub type AudioVoiceId = ID<AudioVoice>;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioPlayParams {
+    #[serde(default)]
+    pub looped: bool,
+    #[serde(default = "AudioPlayParams::default_playback_rate")]
+    pub playback_rate: Scalar,
+    #[serde(default = "AudioPlayParams::default_volume")]
+    pub volume: Scalar,
+}
+
+impl Default for AudioPlayParams {
+    fn default() -> Self {
+        Self {
+            looped: false,
+            playback_rate: 1.0,
+            volume: 1.0,
+        }
+    }
+}
+
+impl AudioPlayParams {
+    fn default_playback_rate() -> Scalar {
+        1.0
+    }
+
+    fn default_volume() -> Scalar {
+        1.0
+    }
+
+    pub fn looped(mut self, value: bool) -> Self {
+        self.looped = value;
+        self
+    }
+
+    pub fn playback_rate(mut self, value: Scalar) -> Self {
+        self.playback_rate = value;
+        self
+    }
+
+    pub fn volume(mut self, value: Scalar) -> Self {
+        self.volume = value;
+        self
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AudioVoiceStatus {
+    /// Waiting for audio asset to be loaded.
+    Pending,
+    Playing,
+    /// Voice was stopped, has ended or its audio asset could not be loaded.
+    Stopped,
+}
+
+#[derive(Debug, Clone)]
+pub struct AudioVoice {
+    pub(crate) path: String,
+    pub(crate) params: AudioPlayParams,
+    pub(crate) status: AudioVoiceStatus,
+    pub(crate) load_requested: bool,
+}
+
+impl AudioVoice {
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn params(&self) -> &AudioPlayParams {
+        &self.params
+    }
+
+    pub fn status(&self) -> AudioVoiceStatus {
+        self.status
+    }
+}
+
+/// Fire-and-forget audio playback, independent of entities with `AudioSource` component.
+/// Any number of voices can play at once, all of them (together with audio sources) go through
+/// master volume.
+#[derive(Debug, Clone)]
+pub struct AudioController {
+    volume: Scalar,
+    pub(crate) volume_dirty: bool,
+    pub(crate) voices: HashMap<AudioVoiceId, AudioVoice>,
+    pub(crate) to_stop: Vec<AudioVoiceId>,
+}
+
+impl Default for AudioController {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            volume_dirty: true,
+            voices: Default::default(),
+            to_stop: vec![],
+        }
+    }
+}
+
+impl AudioController {
+    /// Requests playback of audio asset (for example: `audio://sfx/jump.ogg`). Asset gets loaded
+    /// if it is not already and voice starts playing once it is - voice gets stopped if asset
+    /// fails to load.
+    pub fn play(&mut self, asset_path: &str, params: AudioPlayParams) -> AudioVoiceId {
+        let id = AudioVoiceId::new();
+        let path = asset_path
+            .strip_prefix("audio://")
+            .unwrap_or(asset_path)
+            .to_owned();
+        self.voices.insert(
+            id,
+            AudioVoice {
+                path,
+                params,
+                status: AudioVoiceStatus::Pending,
+                load_requested: false,
+            },
+        );
+        id
+    }
+
+    pub fn stop(&mut self, id: AudioVoiceId) {
+        if let Some(voice) = self.voices.get_mut(&id) {
+            if voice.status != AudioVoiceStatus::Stopped {
+                voice.status = AudioVoiceStatus::Stopped;
+                self.to_stop.push(id);
+            }
+        }
+    }
+
+    pub fn stop_all(&mut self) {
+        let ids = self.voices.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            self.stop(id);
+        }
+    }
+
+    pub fn voice(&self, id: AudioVoiceId) -> Option<&AudioVoice> {
+        self.voices.get(&id)
+    }
+
+    pub fn is_playing(&self, id: AudioVoiceId) -> bool {
+        self.voices
+            .get(&id)
+            .map(|voice| voice.status == AudioVoiceStatus::Playing)
+            .unwrap_or(false)
+    }
+
+    pub fn playing_count(&self) -> usize {
+        self.voices
+            .values()
+            .filter(|voice| voice.status == AudioVoiceStatus::Playing)
+            .count()
+    }
+
+    /// Master volume.
+    pub fn volume(&self) -> Scalar {
+        self.volume
+    }
+
+    /// Sets master volume.
+    pub fn set_volume(&mut self, value: Scalar) {
+        self.volume = value.max(0.0);
+        self.volume_dirty = true;
+    }
+}
@@ -2,15 +2,17 @@ extern crate oxygengine_core as core;
 
 pub mod audio_asset_protocol;
 pub mod component;
+pub mod controller;
 pub mod resource;
 pub mod system;
 
 pub mod prelude {
-    pub use crate::{audio_asset_protocol::*, component::*, resource::*, system::*};
+    pub use crate::{audio_asset_protocol::*, component::*, controller::*, resource::*, system::*};
 }
 
 use crate::{
     component::{AudioSource, AudioSourcePrefabProxy},
+    controller::AudioController,
     resource::Audio,
     system::{audio_system, AudioSystemResources},
 };
@@ -30,6 +32,7 @@ where
     A: Audio + 'static,
 {
     builder.install_resource(data);
+    builder.install_resource(AudioController::default());
     builder.install_system::<AudioSystemResources<A>>("audio", audio_system::<A>, &[])?;
     Ok(())
 }
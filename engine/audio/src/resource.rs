@@ -1,3 +1,4 @@
+use crate::controller::{AudioPlayParams, AudioVoiceId};
 use core::{
     assets::{asset::AssetId, database::AssetsDatabase},
     ecs::Entity,
@@ -43,4 +44,11 @@ pub trait Audio: Send + Sync {
     fn get_source_state(&self, entity: Entity) -> Option<AudioState>;
     fn get_asset_id(&self, path: &str) -> Option<AssetId>;
     fn update_cache(&mut self, _assets: &AssetsDatabase) {}
+    /// Creates and starts voice requested with `AudioController`.
+    fn create_voice(&mut self, id: AudioVoiceId, data: &[u8], params: &AudioPlayParams);
+    fn destroy_voice(&mut self, id: AudioVoiceId);
+    /// Tells if voice has finished playing (looped voices never end on their own).
+    fn has_voice_ended(&self, id: AudioVoiceId) -> bool;
+    /// Sets volume applied to all voices and audio sources.
+    fn set_master_volume(&mut self, volume: Scalar);
 }
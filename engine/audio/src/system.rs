@@ -1,6 +1,7 @@
 use crate::{
     audio_asset_protocol::AudioAsset,
     component::{AudioSource, AudioSourceDirtyMode},
+    controller::{AudioController, AudioVoiceStatus},
     resource::{Audio, AudioPlayState},
 };
 use core::{
@@ -11,8 +12,9 @@ use core::{
 pub type AudioSystemResources<'a, A> = (
     WorldRef,
     &'a EntityChanges,
-    &'a AssetsDatabase,
+    &'a mut AssetsDatabase,
     &'a mut A,
+    &'a mut AudioController,
     Comp<&'a mut AudioSource>,
 );
 
@@ -20,11 +22,52 @@ pub fn audio_system<A>(universe: &mut Universe)
 where
     A: Audio + 'static,
 {
-    let (world, changes, assets, mut audio, ..) =
+    let (world, changes, mut assets, mut audio, mut controller, ..) =
         universe.query_resources::<AudioSystemResources<A>>();
 
     audio.update_cache(&assets);
 
+    if controller.volume_dirty {
+        audio.set_master_volume(controller.volume());
+        controller.volume_dirty = false;
+    }
+    for id in std::mem::take(&mut controller.to_stop) {
+        audio.destroy_voice(id);
+    }
+    // NOTE: voices that have ended stay until next frame so their status can be observed.
+    controller
+        .voices
+        .retain(|_, voice| voice.status != AudioVoiceStatus::Stopped);
+    for (id, voice) in controller.voices.iter_mut() {
+        match voice.status {
+            AudioVoiceStatus::Pending => {
+                let path = format!("audio://{}", voice.path);
+                let asset = assets
+                    .asset_by_path(&path)
+                    .and_then(|asset| asset.get::<AudioAsset>());
+                if let Some(asset) = asset {
+                    audio.create_voice(*id, asset.bytes(), &voice.params);
+                    voice.status = AudioVoiceStatus::Playing;
+                } else if !voice.load_requested {
+                    voice.load_requested = true;
+                    if assets.load(&path).is_err() {
+                        voice.status = AudioVoiceStatus::Stopped;
+                    }
+                } else if assets.loading_status(&path).is_none() {
+                    // asset is neither loaded nor loading anymore, so its loading has failed.
+                    voice.status = AudioVoiceStatus::Stopped;
+                }
+            }
+            AudioVoiceStatus::Playing => {
+                if audio.has_voice_ended(*id) {
+                    audio.destroy_voice(*id);
+                    voice.status = AudioVoiceStatus::Stopped;
+                }
+            }
+            AudioVoiceStatus::Stopped => {}
+        }
+    }
+
     for entity in changes.despawned() {
         audio.destroy_source(entity);
     }
@@ -85,3 +128,115 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        audio_asset_protocol::AudioAssetProtocol,
+        controller::{AudioPlayParams, AudioVoiceId},
+        resource::AudioState,
+    };
+    use core::{assets::asset::AssetId, ecs::Entity, fetch::engines::map::MapFetchEngine, Scalar};
+    use std::{
+        collections::HashMap,
+        sync::{atomic::AtomicBool, Arc},
+    };
+
+    #[derive(Default)]
+    struct FakeAudio {
+        voices: HashMap<AudioVoiceId, Vec<u8>>,
+    }
+
+    impl Audio for FakeAudio {
+        fn create_source(
+            &mut self,
+            _: Entity,
+            _: &[u8],
+            _: bool,
+            _: bool,
+            _: Scalar,
+            _: Scalar,
+            _: bool,
+            _: Arc<AtomicBool>,
+        ) {
+        }
+
+        fn destroy_source(&mut self, _: Entity) {}
+
+        fn has_source(&mut self, _: Entity) -> bool {
+            false
+        }
+
+        fn update_source(&mut self, _: Entity, _: bool, _: Scalar, _: Scalar, _: Option<bool>) {}
+
+        fn get_source_state(&self, _: Entity) -> Option<AudioState> {
+            None
+        }
+
+        fn get_asset_id(&self, _: &str) -> Option<AssetId> {
+            None
+        }
+
+        fn create_voice(&mut self, id: AudioVoiceId, data: &[u8], _: &AudioPlayParams) {
+            self.voices.insert(id, data.to_owned());
+        }
+
+        fn destroy_voice(&mut self, id: AudioVoiceId) {
+            self.voices.remove(&id);
+        }
+
+        fn has_voice_ended(&self, _: AudioVoiceId) -> bool {
+            false
+        }
+
+        fn set_master_volume(&mut self, _: Scalar) {}
+    }
+
+    #[test]
+    fn test_voice_of_unloaded_asset() {
+        let mut assets =
+            AssetsDatabase::new(MapFetchEngine::default().insert("jump.ogg", vec![1, 2, 3]));
+        assets.register(AudioAssetProtocol);
+        let mut controller = AudioController::default();
+        let jump = controller.play("audio://jump.ogg", Default::default());
+        let missing = controller.play("audio://missing.ogg", Default::default());
+        let mut universe = Universe::default();
+        universe.insert_resource(EntityChanges::default());
+        universe.insert_resource(assets);
+        universe.insert_resource(FakeAudio::default());
+        universe.insert_resource(controller);
+
+        // voice requests its asset load, missing one fails right away.
+        audio_system::<FakeAudio>(&mut universe);
+        {
+            let controller = universe.expect_resource::<AudioController>();
+            assert_eq!(
+                controller.voice(jump).unwrap().status(),
+                AudioVoiceStatus::Pending
+            );
+            assert_eq!(
+                controller.voice(missing).unwrap().status(),
+                AudioVoiceStatus::Stopped
+            );
+            assert_eq!(
+                universe.expect_resource::<AssetsDatabase>().loading_count(),
+                1
+            );
+        }
+
+        universe.expect_resource_mut::<AssetsDatabase>().process();
+        audio_system::<FakeAudio>(&mut universe);
+        assert!(universe
+            .expect_resource::<AudioController>()
+            .is_playing(jump));
+        assert!(universe
+            .expect_resource::<AudioController>()
+            .voice(missing)
+            .is_none());
+        assert_eq!(
+            universe.expect_resource::<FakeAudio>().voices.get(&jump),
+            Some(&vec![1, 2, 3])
+        );
+    }
+}
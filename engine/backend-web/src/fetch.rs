@@ -1,6 +1,14 @@
-use core::fetch::{FetchEngine, FetchProcess, FetchStatus};
+use crate::closure::WebClosure;
+use core::{
+    fetch::{
+        FetchCancelReason, FetchEngine, FetchProcess, FetchProcessId, FetchProcessRegistry,
+        FetchStatus,
+    },
+    Scalar,
+};
 use futures::{future, TryFutureExt};
 use js_sys::*;
+use std::{cell::RefCell, rc::Rc, time::Duration};
 use wasm_bindgen::{prelude::*, JsCast};
 use wasm_bindgen_futures::{future_to_promise, JsFuture};
 use web_sys::*;
@@ -15,6 +23,8 @@ pub struct WebFetchEngine {
     cors: bool,
     cache: bool,
     credentials: bool,
+    timeout: Option<Duration>,
+    processes: FetchProcessRegistry,
 }
 
 impl WebFetchEngine {
@@ -24,6 +34,8 @@ impl WebFetchEngine {
             cors: true,
             cache: true,
             credentials: true,
+            timeout: None,
+            processes: Default::default(),
         }
     }
 
@@ -41,6 +53,11 @@ impl WebFetchEngine {
         self.credentials = value;
         self
     }
+
+    pub fn timeout(mut self, value: Option<Duration>) -> Self {
+        self.timeout = value;
+        self
+    }
 }
 
 impl FetchEngine for WebFetchEngine {
@@ -66,7 +83,21 @@ impl FetchEngine for WebFetchEngine {
         let full_path = format!("{}/{}", self.root_path, path);
         let request = Request::new_with_str_and_init(&full_path, &opts).unwrap();
         let request_promise = window().fetch_with_request(&request);
-        let process = FetchProcess::new_start();
+        let process = match self.timeout {
+            Some(timeout) => {
+                let process = FetchProcess::new_start_with_timeout(timeout);
+                let mut process2 = process.clone();
+                let on_timeout = Closure::once_into_js(move || {
+                    process2.expire();
+                });
+                let _ = window().set_timeout_with_callback_and_timeout_and_arguments_0(
+                    on_timeout.unchecked_ref(),
+                    timeout.as_millis() as i32,
+                );
+                process
+            }
+            None => FetchProcess::new_start(),
+        };
         let mut process2 = process.clone();
         // TODO: when web-sys will support ReadableStream we will be able to track progress.
         let future = JsFuture::from(request_promise)
@@ -85,6 +116,195 @@ impl FetchEngine for WebFetchEngine {
             });
         // TODO: fail process on error catch.
         drop(future_to_promise(future));
+        self.processes.register(&process);
+        Ok(Box::new(process))
+    }
+
+    fn cancel(&mut self, mut reader: FetchProcess) {
+        if !self.processes.cancel(reader.id(), FetchCancelReason::User) {
+            reader.cancel(FetchCancelReason::User);
+        }
+    }
+
+    fn cancel_by_id(&mut self, id: FetchProcessId) -> bool {
+        self.processes.cancel(id, FetchCancelReason::User)
+    }
+}
+
+/// Streams resources over web socket. Every fetch opens new socket to engine URL and once it
+/// gets open, requested path is sent as text message. Incoming binary messages get appended to
+/// payload and process is done when server closes socket cleanly.
+///
+/// Server may send text message with total payload size in bytes before binary messages, then
+/// fetch process reports progress after every received message.
+///
+/// Socket of canceled process gets closed within `CANCEL_CHECK_INTERVAL` milliseconds.
+#[derive(Default, Clone)]
+pub struct WebSocketFetchEngine {
+    url: String,
+    protocols: Vec<String>,
+    processes: FetchProcessRegistry,
+}
+
+impl WebSocketFetchEngine {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.to_owned(),
+            protocols: vec![],
+            processes: Default::default(),
+        }
+    }
+
+    /// Web socket subprotocols requested when opening connection.
+    pub fn protocols(mut self, value: Vec<String>) -> Self {
+        self.protocols = value;
+        self
+    }
+}
+
+/// Interval in milliseconds at which web socket fetch checks if its process got canceled.
+const CANCEL_CHECK_INTERVAL: i32 = 100;
+
+struct SocketStream {
+    socket: WebSocket,
+    payload: Vec<u8>,
+    total_size: Option<usize>,
+    cancel_check: Option<i32>,
+    closures: Vec<WebClosure>,
+}
+
+impl SocketStream {
+    /// Detaches socket handlers and releases them once current event handler completes.
+    fn release(stream: &Rc<RefCell<Self>>) {
+        let closures = {
+            let mut stream = stream.borrow_mut();
+            stream.socket.set_onopen(None);
+            stream.socket.set_onmessage(None);
+            stream.socket.set_onerror(None);
+            stream.socket.set_onclose(None);
+            if let Some(handle) = stream.cancel_check.take() {
+                window().clear_interval_with_handle(handle);
+            }
+            std::mem::take(&mut stream.closures)
+        };
+        let release = Closure::once_into_js(move || drop(closures));
+        let _ = window()
+            .set_timeout_with_callback_and_timeout_and_arguments_0(release.unchecked_ref(), 0);
+    }
+}
+
+impl FetchEngine for WebSocketFetchEngine {
+    fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+        let protocols = self.protocols.iter().map(JsValue::from).collect::<Array>();
+        let socket = WebSocket::new_with_str_sequence(&self.url, &protocols)
+            .map_err(|_| FetchStatus::Canceled(FetchCancelReason::Error))?;
+        socket.set_binary_type(BinaryType::Arraybuffer);
+        let process = FetchProcess::new_start();
+        let stream = Rc::new(RefCell::new(SocketStream {
+            socket: socket.clone(),
+            payload: vec![],
+            total_size: None,
+            cancel_check: None,
+            closures: Vec::with_capacity(5),
+        }));
+        let mut closures = Vec::with_capacity(5);
+        {
+            // NOTE: process can be canceled from any thread, so instead of closing socket in
+            // cancel callback we check process status on the thread that owns socket.
+            let stream2 = stream.clone();
+            let process2 = process.clone();
+            let closure = Closure::wrap(Box::new(move || {
+                if let FetchStatus::Canceled(_) = process2.status() {
+                    drop(stream2.borrow().socket.close());
+                    SocketStream::release(&stream2);
+                }
+            }) as Box<dyn FnMut()>);
+            stream.borrow_mut().cancel_check = window()
+                .set_interval_with_callback_and_timeout_and_arguments_0(
+                    closure.as_ref().unchecked_ref(),
+                    CANCEL_CHECK_INTERVAL,
+                )
+                .ok();
+            closures.push(WebClosure::acquire(closure));
+        }
+        {
+            let socket2 = socket.clone();
+            let path = path.to_owned();
+            let closure = Closure::wrap(Box::new(move |_: Event| {
+                drop(socket2.send_with_str(&path));
+            }) as Box<dyn FnMut(_)>);
+            socket.set_onopen(Some(closure.as_ref().unchecked_ref()));
+            closures.push(WebClosure::acquire(closure));
+        }
+        {
+            let stream2 = stream.clone();
+            let mut process2 = process.clone();
+            let closure = Closure::wrap(Box::new(move |event: MessageEvent| {
+                let data = event.data();
+                let mut stream = stream2.borrow_mut();
+                if data.is_instance_of::<ArrayBuffer>() {
+                    let typebuf: Uint8Array = Uint8Array::new(&data);
+                    let start = stream.payload.len();
+                    stream.payload.resize(start + typebuf.length() as usize, 0);
+                    typebuf.copy_to(&mut stream.payload[start..]);
+                } else if let Some(size) = data.as_string().and_then(|text| text.parse().ok()) {
+                    stream.total_size = Some(size);
+                }
+                if let FetchStatus::InProgress(_) = process2.status() {
+                    let progress = match stream.total_size {
+                        Some(size) if size > 0 => {
+                            (stream.payload.len() as Scalar / size as Scalar).min(1.0)
+                        }
+                        _ => 0.0,
+                    };
+                    process2.progress(progress);
+                }
+            }) as Box<dyn FnMut(_)>);
+            socket.set_onmessage(Some(closure.as_ref().unchecked_ref()));
+            closures.push(WebClosure::acquire(closure));
+        }
+        {
+            let stream2 = stream.clone();
+            let mut process2 = process.clone();
+            let closure = Closure::wrap(Box::new(move |_: Event| {
+                if let FetchStatus::InProgress(_) = process2.status() {
+                    process2.cancel(FetchCancelReason::Error);
+                }
+                SocketStream::release(&stream2);
+            }) as Box<dyn FnMut(_)>);
+            socket.set_onerror(Some(closure.as_ref().unchecked_ref()));
+            closures.push(WebClosure::acquire(closure));
+        }
+        {
+            let stream2 = stream.clone();
+            let mut process2 = process.clone();
+            let closure = Closure::wrap(Box::new(move |event: CloseEvent| {
+                // NOTE: canceled or already failed processes must not be overridden.
+                if let FetchStatus::InProgress(_) = process2.status() {
+                    if event.was_clean() {
+                        let payload = std::mem::take(&mut stream2.borrow_mut().payload);
+                        process2.done(payload);
+                    } else {
+                        process2.cancel(FetchCancelReason::Error);
+                    }
+                }
+                SocketStream::release(&stream2);
+            }) as Box<dyn FnMut(_)>);
+            socket.set_onclose(Some(closure.as_ref().unchecked_ref()));
+            closures.push(WebClosure::acquire(closure));
+        }
+        stream.borrow_mut().closures = closures;
+        self.processes.register(&process);
         Ok(Box::new(process))
     }
+
+    fn cancel(&mut self, mut reader: FetchProcess) {
+        if !self.processes.cancel(reader.id(), FetchCancelReason::User) {
+            reader.cancel(FetchCancelReason::User);
+        }
+    }
+
+    fn cancel_by_id(&mut self, id: FetchProcessId) -> bool {
+        self.processes.cancel(id, FetchCancelReason::User)
+    }
 }
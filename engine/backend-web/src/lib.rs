@@ -4,8 +4,9 @@ pub mod app;
 pub mod closure;
 pub mod fetch;
 pub mod log;
+pub mod offload;
 pub mod storage;
 
 pub mod prelude {
-    pub use crate::{app::*, closure::*, fetch::*, log::*, storage::*};
+    pub use crate::{app::*, closure::*, fetch::*, log::*, offload::*, storage::*};
 }
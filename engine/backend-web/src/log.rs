@@ -1,4 +1,4 @@
-use core::log::{Log, Logger};
+use core::log::{Log, LogRecord, LogSink, Logger};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -7,6 +7,12 @@ extern "C" {
     pub fn console_log(s: &str);
 }
 
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = info)]
+    pub fn console_info(s: &str);
+}
+
 #[wasm_bindgen]
 extern "C" {
     #[wasm_bindgen(js_namespace = console, js_name = warn)]
@@ -40,12 +46,19 @@ extern "C" {
 pub struct WebLogger;
 
 impl Logger for WebLogger {
-    fn log(&mut self, mode: Log, message: String) {
-        match mode {
-            Log::Info => console_log(&format!("[INFO] {}", message)),
-            Log::Warning => console_warn(&format!("[WARNING] {}", message)),
-            Log::Error => console_error(&format!("[ERROR] {}", message)),
-            Log::Debug => console_debug(&format!("[DEBUG] {}", message)),
+    fn log(&mut self, record: &LogRecord) {
+        LogSink::log(self, record);
+    }
+}
+
+impl LogSink for WebLogger {
+    fn log(&self, record: &LogRecord) {
+        match record.level {
+            Log::Trace => console_debug(&format!("[TRACE] {}", record)),
+            Log::Debug => console_debug(&format!("[DEBUG] {}", record)),
+            Log::Info => console_info(&format!("[INFO] {}", record)),
+            Log::Warning => console_warn(&format!("[WARNING] {}", record)),
+            Log::Error => console_error(&format!("[ERROR] {}", record)),
         }
     }
 }
@@ -0,0 +1,74 @@
+use core::{
+    fetch::{FetchCancelReason, FetchProcess, FetchStatus},
+    offload::{OffloadEngine, OffloadTaskRegistry},
+};
+use js_sys::*;
+use wasm_bindgen::{prelude::*, JsCast};
+use web_sys::*;
+
+/// Runs offloaded tasks in web workers spawned from given script.
+/// Worker script is expected to load application module and respond to
+/// `[task, input]` message with result bytes produced by `web_offload_worker_run`,
+/// or with `null` when task has failed.
+#[derive(Clone)]
+pub struct WebWorkerOffloadEngine {
+    script_url: String,
+}
+
+impl WebWorkerOffloadEngine {
+    pub fn new(script_url: &str) -> Self {
+        Self {
+            script_url: script_url.to_owned(),
+        }
+    }
+}
+
+impl OffloadEngine for WebWorkerOffloadEngine {
+    fn offload(&mut self, task: &str, input: Vec<u8>) -> Result<Box<FetchProcess>, FetchStatus> {
+        let worker = match Worker::new(&self.script_url) {
+            Ok(worker) => worker,
+            Err(_) => return Err(FetchStatus::Canceled(FetchCancelReason::Error)),
+        };
+        let process = FetchProcess::new_start();
+        let mut process2 = process.clone();
+        let mut process3 = process.clone();
+        let worker2 = worker.clone();
+        let worker3 = worker.clone();
+        let on_message = Closure::once_into_js(move |event: MessageEvent| {
+            let data = event.data();
+            if data.is_instance_of::<Uint8Array>() {
+                process2.done(Uint8Array::new(&data).to_vec());
+            } else {
+                process2.cancel(FetchCancelReason::Error);
+            }
+            worker2.terminate();
+        });
+        let on_error = Closure::once_into_js(move |_: ErrorEvent| {
+            process3.cancel(FetchCancelReason::Error);
+            worker3.terminate();
+        });
+        worker.set_onmessage(Some(on_message.unchecked_ref()));
+        worker.set_onerror(Some(on_error.unchecked_ref()));
+        let message = Array::of2(
+            &JsValue::from_str(task),
+            &Uint8Array::from(input.as_slice()),
+        );
+        if worker.post_message(&message).is_err() {
+            worker.terminate();
+            return Err(FetchStatus::Canceled(FetchCancelReason::Error));
+        }
+        Ok(Box::new(process))
+    }
+}
+
+/// Worker side entry point - runs registered task on input received from main thread.
+pub fn web_offload_worker_run(
+    registry: &OffloadTaskRegistry,
+    task: &str,
+    input: &Uint8Array,
+) -> JsValue {
+    match registry.run(task, &input.to_vec()) {
+        Ok(bytes) => Uint8Array::from(bytes.as_slice()).into(),
+        Err(_) => JsValue::null(),
+    }
+}
@@ -1,5 +1,8 @@
 use core::storage::{StorageEngine, StorageError, StorageResult};
+use serde::{de::DeserializeOwned, Serialize};
 use std::fmt::Write;
+use wasm_bindgen::JsCast;
+use web_sys::{DomException, Storage};
 
 fn window() -> web_sys::Window {
     web_sys::window().expect("no global `window` exists")
@@ -79,3 +82,134 @@ impl WebStorageEngine {
         Ok(result)
     }
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebStorageError {
+    /// Browser does not allow to access local storage (for example in private mode).
+    Unavailable,
+    /// key of the item that did not fit in storage.
+    QuotaExceeded(String),
+    CouldNotSerialize(String),
+    /// key and error message.
+    CouldNotStore(String, String),
+    /// key and error message.
+    CouldNotRemove(String, String),
+}
+
+/// Key-value persistence resource backed by browser local storage. Values are stored as JSON,
+/// under keys prefixed with storage prefix so many games can share one origin.
+#[derive(Debug, Default, Clone)]
+pub struct WebStorage {
+    prefix: String,
+}
+
+impl WebStorage {
+    pub fn new(prefix: &str) -> Self {
+        Self {
+            prefix: prefix.to_owned(),
+        }
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Tells if browser allows to access local storage.
+    pub fn is_available(&self) -> bool {
+        Self::storage().is_ok()
+    }
+
+    pub fn set<T>(&mut self, key: &str, value: &T) -> Result<(), WebStorageError>
+    where
+        T: Serialize,
+    {
+        let data = serde_json::to_string(value)
+            .map_err(|error| WebStorageError::CouldNotSerialize(error.to_string()))?;
+        Self::storage()?
+            .set_item(&self.full_key(key), &data)
+            .map_err(|error| {
+                let quota = error
+                    .dyn_ref::<DomException>()
+                    .map(|error| {
+                        error.name() == "QuotaExceededError"
+                            || error.name() == "NS_ERROR_DOM_QUOTA_REACHED"
+                    })
+                    .unwrap_or(false);
+                if quota {
+                    WebStorageError::QuotaExceeded(key.to_owned())
+                } else {
+                    WebStorageError::CouldNotStore(key.to_owned(), format!("{:?}", error))
+                }
+            })
+    }
+
+    /// Returns `None` if storage is not available, there is no item under given key or it could
+    /// not be deserialized into requested type.
+    pub fn get<T>(&self, key: &str) -> Option<T>
+    where
+        T: DeserializeOwned,
+    {
+        let data = Self::storage().ok()?.get_item(&self.full_key(key)).ok()??;
+        serde_json::from_str(&data).ok()
+    }
+
+    pub fn has(&self, key: &str) -> bool {
+        Self::storage()
+            .ok()
+            .and_then(|storage| storage.get_item(&self.full_key(key)).ok())
+            .flatten()
+            .is_some()
+    }
+
+    pub fn remove(&mut self, key: &str) -> Result<(), WebStorageError> {
+        Self::storage()?
+            .remove_item(&self.full_key(key))
+            .map_err(|error| {
+                WebStorageError::CouldNotRemove(key.to_owned(), format!("{:?}", error))
+            })
+    }
+
+    fn full_key(&self, key: &str) -> String {
+        format!("{}{}", self.prefix, key)
+    }
+
+    fn storage() -> Result<Storage, WebStorageError> {
+        // NOTE: `window.localStorage` throws in some browsers private mode.
+        match web_sys::window().map(|window| window.local_storage()) {
+            Some(Ok(Some(storage))) => Ok(storage),
+            _ => Err(WebStorageError::Unavailable),
+        }
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use wasm_bindgen_test::*;
+
+    wasm_bindgen_test_configure!(run_in_browser);
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Settings {
+        volume: f32,
+        name: String,
+        levels: Vec<u32>,
+    }
+
+    #[wasm_bindgen_test]
+    fn test_web_storage() {
+        let mut storage = WebStorage::new("oxygengine-test-");
+        let settings = Settings {
+            volume: 0.5,
+            name: "player".to_owned(),
+            levels: vec![1, 2, 3],
+        };
+        storage.set("settings", &settings).unwrap();
+        assert!(storage.has("settings"));
+        assert_eq!(storage.get::<Settings>("settings"), Some(settings));
+        assert_eq!(storage.get::<u32>("settings"), None);
+        storage.remove("settings").unwrap();
+        assert_eq!(storage.get::<Settings>("settings"), None);
+    }
+}
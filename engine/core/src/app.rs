@@ -3,7 +3,10 @@ use crate::{
         commands::UniverseCommands,
         hierarchy::{hierarchy_system, Hierarchy, HierarchySystemResources},
         life_cycle::EntityChanges,
-        pipeline::{PipelineBuilder, PipelineBuilderError, PipelineEngine, PipelineLayer},
+        pipeline::{
+            group::SystemGroup, PipelineBuilder, PipelineBuilderError, PipelineEngine,
+            PipelineLayer,
+        },
         AccessType, Multiverse, System,
     },
     state::{State, StateToken},
@@ -109,6 +112,72 @@ impl AppParams {
     }
 }
 
+/// Fixed timestep settings resource. Every frame, app delta time is added to accumulator and
+/// `State::on_fixed_process` gets called once for every whole `fixed_delta_time` accumulated.
+///
+/// To avoid spiral of death (when fixed steps take longer than frame time, every next frame has
+/// to run even more of them), number of fixed steps per frame is clamped to `max_substeps` and
+/// time that did not fit is dropped - simulation slows down instead of freezing the app.
+#[derive(Debug, Clone)]
+pub struct AppFixedTimestep {
+    /// Fixed delta time in seconds. Zero or less disables fixed steps.
+    pub fixed_delta_time: Scalar,
+    /// Maximal number of fixed steps per frame.
+    pub max_substeps: usize,
+    accumulator: Scalar,
+    substeps: usize,
+}
+
+impl Default for AppFixedTimestep {
+    fn default() -> Self {
+        Self::new(1.0 / 60.0, 5)
+    }
+}
+
+impl AppFixedTimestep {
+    pub fn new(fixed_delta_time: Scalar, max_substeps: usize) -> Self {
+        Self {
+            fixed_delta_time,
+            max_substeps,
+            accumulator: 0.0,
+            substeps: 0,
+        }
+    }
+
+    /// Number of fixed steps performed during last frame.
+    pub fn substeps(&self) -> usize {
+        self.substeps
+    }
+
+    /// Part of fixed step accumulated after last fixed step, in range `[0; 1)` - useful for
+    /// interpolating rendered state between fixed steps.
+    pub fn alpha(&self) -> Scalar {
+        if self.fixed_delta_time > 0.0 {
+            self.accumulator / self.fixed_delta_time
+        } else {
+            0.0
+        }
+    }
+
+    pub(crate) fn accumulate(&mut self, delta_time: Scalar) -> usize {
+        if self.fixed_delta_time <= 0.0 {
+            self.substeps = 0;
+            return 0;
+        }
+        self.accumulator += delta_time.max(0.0);
+        let mut substeps = 0;
+        while self.accumulator >= self.fixed_delta_time && substeps < self.max_substeps {
+            self.accumulator -= self.fixed_delta_time;
+            substeps += 1;
+        }
+        if self.accumulator >= self.fixed_delta_time {
+            self.accumulator %= self.fixed_delta_time;
+        }
+        self.substeps = substeps;
+        substeps
+    }
+}
+
 pub struct AppLifeCycle {
     pub running: bool,
     pub delta_time_limit: Option<Duration>,
@@ -288,6 +357,7 @@ where
         .with_resource(UniverseCommands::default())
         .with_resource(EntityChanges::default())
         .with_resource(Hierarchy::default())
+        .with_resource(AppFixedTimestep::default())
         .with_system_on_layer::<HierarchySystemResources>(
             "hierarchy",
             hierarchy_system,
@@ -430,6 +500,23 @@ where
         Ok(self)
     }
 
+    #[inline]
+    pub fn install_system_group(
+        &mut self,
+        group: SystemGroup<PB>,
+    ) -> Result<(), PipelineBuilderError> {
+        group.install(&mut self.pipeline_builder)
+    }
+
+    #[inline]
+    pub fn with_system_group(
+        mut self,
+        group: SystemGroup<PB>,
+    ) -> Result<Self, PipelineBuilderError> {
+        self.install_system_group(group)?;
+        Ok(self)
+    }
+
     #[inline]
     pub fn build<P, S, AL>(self, state: S, life_cycle: AL) -> App
     where
@@ -1,8 +1,60 @@
 use crate::id::ID;
-use std::{any::Any, mem::replace};
+use std::{any::Any, fmt, marker::PhantomData, mem::replace, sync::Arc};
 
 pub type AssetId = ID<Asset>;
 
+/// Typed reference-counted handle to loaded asset, obtained from `AssetsDatabase::handle`.
+///
+/// Asset gets unloaded by database once all handles to it are dropped. Asset ids are never
+/// reused, so handle of unloaded asset never resolves to another asset.
+pub struct AssetHandle<T> {
+    id: AssetId,
+    pub(crate) token: Arc<()>,
+    _phantom: PhantomData<fn() -> T>,
+}
+
+impl<T> AssetHandle<T> {
+    pub(crate) fn new(id: AssetId, token: Arc<()>) -> Self {
+        Self {
+            id,
+            token,
+            _phantom: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> AssetId {
+        self.id
+    }
+
+    /// Number of living handles to this asset.
+    pub fn count(&self) -> usize {
+        Arc::strong_count(&self.token)
+    }
+}
+
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        Self::new(self.id, self.token.clone())
+    }
+}
+
+impl<T> PartialEq for AssetHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for AssetHandle<T> {}
+
+impl<T> fmt::Debug for AssetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AssetHandle")
+            .field("id", &self.id)
+            .field("count", &self.count())
+            .finish()
+    }
+}
+
 pub struct Asset {
     id: AssetId,
     protocol: String,
@@ -70,4 +122,11 @@ impl Asset {
     {
         replace(&mut self.data, Box::new(data))
     }
+
+    pub(crate) fn set_boxed(
+        &mut self,
+        data: Box<dyn Any + Send + Sync>,
+    ) -> Box<dyn Any + Send + Sync> {
+        replace(&mut self.data, data)
+    }
 }
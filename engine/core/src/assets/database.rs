@@ -1,11 +1,16 @@
 use crate::{
     assets::{
-        asset::{Asset, AssetId},
+        asset::{Asset, AssetHandle, AssetId},
         protocol::{AssetLoadResult, AssetProtocol, AssetVariant, Meta},
     },
     fetch::{FetchEngine, FetchProcess, FetchStatus},
 };
-use std::{any::TypeId, collections::HashMap};
+use std::{
+    any::{Any, TypeId},
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    hash::{Hash, Hasher},
+    sync::{Arc, Weak},
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum LoadStatus {
@@ -42,6 +47,14 @@ pub struct AssetsDatabase {
     yielded: HashMap<String, (String, Meta, Vec<(String, String)>)>,
     lately_loaded: Vec<(String, AssetId)>,
     lately_unloaded: Vec<(String, AssetId)>,
+    lately_reloaded: Vec<(String, AssetId)>,
+    /// {full path: forced} of loaded assets being fetched again.
+    reloading: HashMap<String, bool>,
+    watched: HashSet<String>,
+    /// {full path: hash of fetched bytes}
+    content_hashes: HashMap<String, u64>,
+    /// Assets unloaded once all their handles are dropped.
+    handles: HashMap<AssetId, Weak<()>>,
     error_reporters: HashMap<TypeId, Box<dyn AssetsDatabaseErrorReporter>>,
     defer_lately_cleanup: bool,
 }
@@ -61,6 +74,11 @@ impl AssetsDatabase {
             yielded: Default::default(),
             lately_loaded: vec![],
             lately_unloaded: vec![],
+            lately_reloaded: vec![],
+            reloading: Default::default(),
+            watched: Default::default(),
+            content_hashes: Default::default(),
+            handles: Default::default(),
             error_reporters: Default::default(),
             defer_lately_cleanup: true,
         }
@@ -178,6 +196,20 @@ impl AssetsDatabase {
             .filter_map(move |(prot, id)| if protocol == prot { Some(id) } else { None })
     }
 
+    /// Assets which data got replaced by reload since last processing - they keep their ids.
+    pub fn lately_reloaded(&self) -> impl Iterator<Item = &AssetId> {
+        self.lately_reloaded.iter().map(|(_, id)| id)
+    }
+
+    pub fn lately_reloaded_protocol<'a>(
+        &'a self,
+        protocol: &'a str,
+    ) -> impl Iterator<Item = &'a AssetId> {
+        self.lately_reloaded
+            .iter()
+            .filter_map(move |(prot, id)| if protocol == prot { Some(id) } else { None })
+    }
+
     pub fn is_ready(&self) -> bool {
         self.loading.is_empty() && self.yielded.is_empty()
     }
@@ -275,6 +307,137 @@ impl AssetsDatabase {
         }
     }
 
+    /// Fetches loaded asset again and replaces its data in place once done, reporting it in
+    /// `lately_reloaded`. If reload fails, previously loaded data is kept. Assets that are not
+    /// loaded yet are just requested to load.
+    pub fn mark_dirty(&mut self, path: &str) -> Result<(), LoadStatus> {
+        if !self.table.contains_key(Self::clean_path(path)) {
+            return self.load(path);
+        }
+        self.reload(path, true)
+    }
+
+    /// Starts watching asset for changes of its content, checked with `check_watched`.
+    pub fn watch(&mut self, path: &str) {
+        self.watched.insert(Self::clean_path(path).to_owned());
+    }
+
+    pub fn unwatch(&mut self, path: &str) {
+        self.watched.remove(Self::clean_path(path));
+    }
+
+    pub fn is_watching(&self, path: &str) -> bool {
+        self.watched.contains(Self::clean_path(path))
+    }
+
+    /// Fetches loaded watched assets again - ones which content has changed get reloaded.
+    pub fn check_watched(&mut self) {
+        let watched = self
+            .watched
+            .iter()
+            .filter(|path| self.table.contains_key(path.as_str()))
+            .cloned()
+            .collect::<Vec<_>>();
+        for path in watched {
+            let _ = self.reload(&path, false);
+        }
+    }
+
+    /// Status of asset that is being loaded - assets that are already fetched but wait for
+    /// their dependencies report `FetchStatus::InProgress(1.0)`.
+    ///
+    /// # Returns
+    /// `None` if asset is not being loaded (either it is loaded already, was never requested
+    /// or its loading has failed).
+    pub fn loading_status(&self, path: &str) -> Option<FetchStatus> {
+        let path = Self::clean_path(path);
+        let parts = path.split("://").take(2).collect::<Vec<_>>();
+        if parts.len() != 2 {
+            return None;
+        }
+        if let Some((prot, reader)) = self.loading.get(parts[1]) {
+            if prot == parts[0] {
+                return Some(reader.status());
+            }
+        }
+        if let Some((prot, _, _)) = self.yielded.get(parts[1]) {
+            if prot == parts[0] {
+                return Some(FetchStatus::InProgress(1.0));
+            }
+        }
+        None
+    }
+
+    fn reload(&mut self, path: &str, forced: bool) -> Result<(), LoadStatus> {
+        let path = Self::clean_path(path);
+        let (prot, subpath) = match path.split_once("://") {
+            Some(parts) => parts,
+            None => return Err(LoadStatus::InvalidPath(path.to_owned())),
+        };
+        if self.reloading.contains_key(path) || self.loading.contains_key(subpath) {
+            return Ok(());
+        }
+        let reader = match self.fetch_engine_mut() {
+            Some(engine) => engine.fetch(subpath).map_err(LoadStatus::FetchError)?,
+            None => return Err(LoadStatus::NoFetchEngine),
+        };
+        self.loading
+            .insert(subpath.to_owned(), (prot.to_owned(), reader));
+        self.reloading.insert(path.to_owned(), forced);
+        Ok(())
+    }
+
+    /// Replaces data of asset being reloaded or inserts new asset.
+    fn store(&mut self, protocol: &str, path: &str, data: Box<dyn Any + Send + Sync>) {
+        let full_path = format!("{}://{}", protocol, path);
+        if self.reloading.remove(&full_path).is_some() {
+            if let Some(id) = self.table.get(&full_path).copied() {
+                if let Some((_, asset)) = self.assets.get_mut(&id) {
+                    let (unload, kept) = match self.protocols.get_mut(protocol) {
+                        Some(handler) => {
+                            let unload = handler.on_unload(asset).unwrap_or_default();
+                            asset.set_boxed(data);
+                            // NOTE: reloaded data can reuse assets of previous one, so these
+                            // are kept.
+                            let kept = if unload.is_empty() {
+                                vec![]
+                            } else {
+                                handler.on_unload(asset).unwrap_or_default()
+                            };
+                            (unload, kept)
+                        }
+                        None => {
+                            asset.set_boxed(data);
+                            (vec![], vec![])
+                        }
+                    };
+                    let kept = kept
+                        .iter()
+                        .filter_map(|v| self.variant_id(v))
+                        .collect::<HashSet<_>>();
+                    let unload = unload
+                        .into_iter()
+                        .filter(|v| {
+                            self.variant_id(v)
+                                .map(|id| !kept.contains(&id))
+                                .unwrap_or_default()
+                        })
+                        .collect::<Vec<_>>();
+                    self.lately_reloaded.push((protocol.to_owned(), id));
+                    self.remove_by_variants(&unload);
+                    return;
+                }
+            }
+        }
+        self.insert(Asset::new_boxed(protocol, path, data));
+    }
+
+    fn content_hash(data: &[u8]) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        hasher.finish()
+    }
+
     pub fn insert(&mut self, asset: Asset) -> AssetId {
         let path = asset.to_full_path();
         let path = Self::clean_path(&path);
@@ -288,6 +451,8 @@ impl AssetsDatabase {
     pub fn remove_by_id(&mut self, id: AssetId) -> Option<Asset> {
         if let Some((path, asset)) = self.assets.remove(&id) {
             self.table.remove(&path);
+            self.handles.remove(&id);
+            self.content_hashes.remove(&path);
             self.lately_unloaded.push((asset.protocol().to_owned(), id));
             if let Some(protocol) = self.protocols.get_mut(asset.protocol()) {
                 if let Some(list) = protocol.on_unload(&asset) {
@@ -303,6 +468,8 @@ impl AssetsDatabase {
     pub fn remove_by_path(&mut self, path: &str) -> Option<Asset> {
         let path = Self::clean_path(path);
         if let Some(id) = self.table.remove(path) {
+            self.content_hashes.remove(path);
+            self.handles.remove(&id);
             if let Some((_, asset)) = self.assets.remove(&id) {
                 self.lately_unloaded.push((asset.protocol().to_owned(), id));
                 if let Some(protocol) = self.protocols.get_mut(asset.protocol()) {
@@ -325,6 +492,13 @@ impl AssetsDatabase {
         }
     }
 
+    fn variant_id(&self, variant: &AssetVariant) -> Option<AssetId> {
+        match variant {
+            AssetVariant::Id(id) => Some(*id),
+            AssetVariant::Path(path) => self.id_by_path(path),
+        }
+    }
+
     pub fn id_by_path(&self, path: &str) -> Option<AssetId> {
         let path = Self::clean_path(path);
         self.table.get(path).cloned()
@@ -348,6 +522,42 @@ impl AssetsDatabase {
         None
     }
 
+    /// Creates handle to loaded asset of given type - from now on asset stays loaded only as
+    /// long as any of its handles is alive.
+    pub fn handle<T>(&mut self, path: &str) -> Option<AssetHandle<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        let id = self.id_by_path(path)?;
+        self.handle_by_id(id)
+    }
+
+    pub fn handle_by_id<T>(&mut self, id: AssetId) -> Option<AssetHandle<T>>
+    where
+        T: Any + Send + Sync,
+    {
+        if !self.asset_by_id(id)?.is::<T>() {
+            return None;
+        }
+        let token = match self.handles.get(&id).and_then(|token| token.upgrade()) {
+            Some(token) => token,
+            None => {
+                let token = Arc::new(());
+                self.handles.insert(id, Arc::downgrade(&token));
+                token
+            }
+        };
+        Some(AssetHandle::new(id, token))
+    }
+
+    /// Data of asset pointed by handle, or `None` if asset got unloaded.
+    pub fn get<T>(&self, handle: &AssetHandle<T>) -> Option<&T>
+    where
+        T: Any + Send + Sync,
+    {
+        self.asset_by_id(handle.id())?.get::<T>()
+    }
+
     pub fn defer_lately_cleanup(&mut self) {
         self.defer_lately_cleanup = true;
     }
@@ -358,6 +568,16 @@ impl AssetsDatabase {
         } else {
             self.lately_loaded.clear();
             self.lately_unloaded.clear();
+            self.lately_reloaded.clear();
+        }
+        let dropped = self
+            .handles
+            .iter()
+            .filter(|(_, token)| token.strong_count() == 0)
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        for id in dropped {
+            self.remove_by_id(id);
         }
         let to_dispatch = {
             let mut bytes_read = 0;
@@ -375,12 +595,18 @@ impl AssetsDatabase {
                 .collect::<Vec<_>>()
         };
         for (path, prot, data) in to_dispatch {
+            let full_path = format!("{}://{}", prot, path);
+            let hash = Self::content_hash(&data);
+            if self.reloading.get(&full_path) == Some(&false)
+                && self.content_hashes.get(&full_path) == Some(&hash)
+            {
+                self.reloading.remove(&full_path);
+                continue;
+            }
+            self.content_hashes.insert(full_path.to_owned(), hash);
             if let Some(protocol) = self.protocols.get_mut(&prot) {
                 match protocol.on_load_with_path(&path, data) {
-                    AssetLoadResult::Data(data) => {
-                        let asset = Asset::new_boxed(&prot, &path, data);
-                        self.insert(asset);
-                    }
+                    AssetLoadResult::Data(data) => self.store(&prot, &path, data),
                     AssetLoadResult::Yield(meta, list) => {
                         let list = list
                             .into_iter()
@@ -389,6 +615,7 @@ impl AssetsDatabase {
                         self.yielded.insert(path, (prot, meta, list));
                     }
                     AssetLoadResult::Error(message) => {
+                        self.reloading.remove(&full_path);
                         for reporter in self.error_reporters.values_mut() {
                             reporter.on_report(&prot, &path, &message);
                         }
@@ -396,12 +623,22 @@ impl AssetsDatabase {
                 }
             }
         }
-        self.loading.retain(|_, (_, reader)| {
-            matches!(
-                reader.status(),
-                FetchStatus::InProgress(_) | FetchStatus::Done
-            )
-        });
+        let reloading = &mut self.reloading;
+        self.loading
+            .retain(|path, (prot, reader)| match reader.status() {
+                FetchStatus::InProgress(_) | FetchStatus::Done => true,
+                FetchStatus::Read => false,
+                status => {
+                    let full_path = format!("{}://{}", prot, path);
+                    if reloading.remove(&full_path).is_some() {
+                        error!(
+                            "Assets database reloading `{}` failed: {:?}, previous asset is kept",
+                            full_path, status
+                        );
+                    }
+                    false
+                }
+            });
         let yielded = std::mem::take(&mut self.yielded);
         for (path, (prot, meta, list)) in yielded {
             if list.iter().all(|(_, path)| self.table.contains_key(path)) {
@@ -416,10 +653,7 @@ impl AssetsDatabase {
                         })
                         .collect::<Vec<_>>();
                     match protocol.on_resume(meta, &list) {
-                        AssetLoadResult::Data(data) => {
-                            let asset = Asset::new_boxed(&prot, &path, data);
-                            self.insert(asset);
-                        }
+                        AssetLoadResult::Data(data) => self.store(&prot, &path, data),
                         AssetLoadResult::Yield(meta, list) => {
                             let list = list
                                 .into_iter()
@@ -428,6 +662,7 @@ impl AssetsDatabase {
                             self.yielded.insert(path, (prot, meta, list));
                         }
                         AssetLoadResult::Error(message) => {
+                            self.reloading.remove(&format!("{}://{}", prot, path));
                             for reporter in self.error_reporters.values_mut() {
                                 reporter.on_report(&prot, &path, &message);
                             }
@@ -450,6 +685,7 @@ mod tests {
     use super::*;
     use crate::{
         assets::protocols::{
+            json::{JsonAsset, JsonAssetProtocol},
             meta::{MetaAsset, MetaAssetProtocol},
             text::{TextAsset, TextAssetProtocol},
         },
@@ -543,4 +779,119 @@ mod tests {
         assert_eq!(database.yielded_count(), 0);
         assert_eq!(database.yielded_deps_count(), 0);
     }
+
+    #[test]
+    fn test_database_handles() {
+        let mut fetch_engine = engines::map::MapFetchEngine::default();
+        fetch_engine.map.insert("a.txt".to_owned(), b"A".to_vec());
+        fetch_engine.map.insert("b.txt".to_owned(), b"B".to_vec());
+
+        let mut database = AssetsDatabase::new(fetch_engine);
+        database.register(TextAssetProtocol);
+        assert_eq!(database.load("txt://a.txt"), Ok(()));
+        assert_eq!(database.load("txt://b.txt"), Ok(()));
+        database.process();
+        assert_eq!(database.loaded_count(), 2);
+        assert!(database.handle::<JsonAsset>("txt://a.txt").is_none());
+
+        let handle = database.handle::<TextAsset>("txt://a.txt").unwrap();
+        let clone = handle.clone();
+        assert_eq!(handle.count(), 2);
+        assert_eq!(database.get(&handle).unwrap().get(), "A");
+        drop(handle);
+        database.process();
+        assert_eq!(database.get(&clone).unwrap().get(), "A");
+        drop(clone);
+        database.process();
+        assert!(database.asset_by_path("txt://a.txt").is_none());
+        assert_eq!(database.loaded_count(), 1);
+
+        let handle = database.handle::<TextAsset>("txt://b.txt").unwrap();
+        database.remove_by_path("txt://b.txt");
+        assert!(database.get(&handle).is_none());
+    }
+
+    #[test]
+    fn test_database_reload() {
+        let engine = |content: &str| {
+            let mut fetch_engine = engines::map::MapFetchEngine::default();
+            fetch_engine
+                .map
+                .insert("a.json".to_owned(), content.as_bytes().to_vec());
+            fetch_engine
+        };
+        let value = |database: &AssetsDatabase| {
+            database
+                .asset_by_path("json://a.json")
+                .unwrap()
+                .get::<JsonAsset>()
+                .unwrap()
+                .get()
+                .to_owned()
+        };
+
+        let mut database = AssetsDatabase::new(engine("1"));
+        database.register(JsonAssetProtocol);
+        assert_eq!(database.load("json://a.json"), Ok(()));
+        database.process();
+        let id = database.id_by_path("json://a.json").unwrap();
+        assert_eq!(value(&database), serde_json::json!(1));
+
+        database.watch("json://a.json");
+        database.check_watched();
+        database.process();
+        assert_eq!(database.lately_reloaded().count(), 0);
+
+        database.push_fetch_engine(Box::new(engine("2")));
+        database.check_watched();
+        database.process();
+        assert_eq!(database.lately_reloaded().collect::<Vec<_>>(), vec![&id]);
+        assert_eq!(database.id_by_path("json://a.json"), Some(id));
+        assert_eq!(value(&database), serde_json::json!(2));
+        database.process();
+        assert_eq!(database.lately_reloaded().count(), 0);
+
+        database.push_fetch_engine(Box::new(engine("{")));
+        assert_eq!(database.mark_dirty("json://a.json"), Ok(()));
+        database.process();
+        assert_eq!(database.lately_reloaded().count(), 0);
+        assert_eq!(database.id_by_path("json://a.json"), Some(id));
+        assert_eq!(value(&database), serde_json::json!(2));
+    }
+
+    #[test]
+    fn test_database_reload_unload() {
+        let engine = |targets: &[&str]| {
+            let list = targets.iter().fold(MetaAsset::default(), |meta, target| {
+                meta.with_target(target)
+            });
+            let mut fetch_engine = engines::map::MapFetchEngine::default();
+            fetch_engine.map.insert(
+                "assets.asset".to_owned(),
+                serde_json::to_string(&list).unwrap().into_bytes(),
+            );
+            fetch_engine.map.insert("a.txt".to_owned(), b"A".to_vec());
+            fetch_engine.map.insert("b.txt".to_owned(), b"B".to_vec());
+            fetch_engine
+        };
+
+        let mut database = AssetsDatabase::new(engine(&["txt://a.txt", "txt://b.txt"]));
+        database.register(TextAssetProtocol);
+        database.register(MetaAssetProtocol);
+        assert_eq!(database.load("meta://assets.asset"), Ok(()));
+        for _ in 0..2 {
+            database.process();
+        }
+        assert_eq!(database.loaded_count(), 3);
+        let a = database.id_by_path("txt://a.txt").unwrap();
+
+        database.push_fetch_engine(Box::new(engine(&["txt://a.txt"])));
+        assert_eq!(database.mark_dirty("meta://assets.asset"), Ok(()));
+        for _ in 0..2 {
+            database.process();
+        }
+        assert_eq!(database.loaded_count(), 2);
+        assert_eq!(database.id_by_path("txt://a.txt"), Some(a));
+        assert!(database.asset_by_path("txt://b.txt").is_none());
+    }
 }
@@ -33,6 +33,13 @@ impl PrefabProxy<NonPersistentPrefabProxy> for NonPersistent {
     ) -> Result<Self, PrefabError> {
         Ok(NonPersistent(state_token))
     }
+
+    fn to_proxy_with_extras(
+        &self,
+        _: &HashMap<Entity, String>,
+    ) -> Option<NonPersistentPrefabProxy> {
+        Some(NonPersistentPrefabProxy)
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -135,6 +142,14 @@ where
     ) -> Result<Self, PrefabError> {
         Ok(Events::new(proxy.capacity, proxy.auto_clear))
     }
+
+    fn to_proxy_with_extras(&self, _: &HashMap<Entity, String>) -> Option<EventsPrefabProxy<T>> {
+        Some(EventsPrefabProxy {
+            capacity: self.capacity,
+            auto_clear: self.auto_clear,
+            _phantom: PhantomData,
+        })
+    }
 }
 
 #[derive(Default, Serialize, Deserialize)]
@@ -3,7 +3,7 @@ use crate::{
         commands::{DespawnEntity, UniverseCommands},
         components::Name,
         life_cycle::EntityChanges,
-        Comp, Entity, Universe, WorldRef,
+        Comp, Entity, Universe, World, WorldRef,
     },
     prefab::{Prefab, PrefabError, PrefabProxy},
     state::StateToken,
@@ -29,6 +29,15 @@ impl PrefabProxy<ParentPrefabProxy> for Parent {
             )))
         }
     }
+
+    fn to_proxy_with_extras(
+        &self,
+        entity_names: &HashMap<Entity, String>,
+    ) -> Option<ParentPrefabProxy> {
+        entity_names
+            .get(&self.0)
+            .map(|name| ParentPrefabProxy(name.to_owned()))
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -158,6 +167,58 @@ impl<'a> Iterator for HierarchyIter<'a> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReparentError {
+    EntityDoesNotExists(Entity),
+    /// New parent is the child itself or one of its descendants.
+    Cycle {
+        child: Entity,
+        parent: Entity,
+    },
+}
+
+/// Tells if `ancestor` is `entity` or any of its parents, following `Parent` components (so it
+/// works also before hierarchy system updates `Hierarchy` resource).
+pub fn is_ancestor(world: &World, ancestor: Entity, mut entity: Entity) -> bool {
+    for _ in 0..=world.len() {
+        if entity == ancestor {
+            return true;
+        }
+        match world.get::<&Parent>(entity) {
+            Ok(parent) => entity = parent.0,
+            Err(_) => return false,
+        }
+    }
+    false
+}
+
+/// Changes `Parent` component of child entity - `None` makes it a root. `Hierarchy` resource
+/// gets updated with next hierarchy system run.
+pub fn reparent(
+    world: &mut World,
+    child: Entity,
+    new_parent: Option<Entity>,
+) -> Result<(), ReparentError> {
+    if !world.contains(child) {
+        return Err(ReparentError::EntityDoesNotExists(child));
+    }
+    match new_parent {
+        Some(parent) => {
+            if !world.contains(parent) {
+                return Err(ReparentError::EntityDoesNotExists(parent));
+            }
+            if is_ancestor(world, child, parent) {
+                return Err(ReparentError::Cycle { child, parent });
+            }
+            let _ = world.insert_one(child, Parent(parent));
+        }
+        None => {
+            let _ = world.remove_one::<Parent>(child);
+        }
+    }
+    Ok(())
+}
+
 pub type HierarchySystemResources<'a> = (
     WorldRef,
     &'a mut UniverseCommands,
@@ -248,4 +309,42 @@ mod tests {
 
         foo::<Parent>();
     }
+
+    #[test]
+    fn test_reparent() {
+        let mut world = World::new();
+        let a = world.spawn(());
+        let b = world.spawn((Parent(a),));
+        let c = world.spawn(());
+
+        assert_eq!(reparent(&mut world, c, Some(b)), Ok(()));
+        assert_eq!(world.get::<&Parent>(c).unwrap().0, b);
+        assert!(is_ancestor(&world, a, c));
+
+        assert_eq!(
+            reparent(&mut world, a, Some(c)),
+            Err(ReparentError::Cycle {
+                child: a,
+                parent: c
+            })
+        );
+        assert_eq!(
+            reparent(&mut world, a, Some(a)),
+            Err(ReparentError::Cycle {
+                child: a,
+                parent: a
+            })
+        );
+        assert!(world.get::<&Parent>(a).is_err());
+
+        assert_eq!(reparent(&mut world, c, None), Ok(()));
+        assert!(world.get::<&Parent>(c).is_err());
+        assert!(!is_ancestor(&world, a, c));
+
+        world.despawn(b).unwrap();
+        assert_eq!(
+            reparent(&mut world, c, Some(b)),
+            Err(ReparentError::EntityDoesNotExists(b))
+        );
+    }
 }
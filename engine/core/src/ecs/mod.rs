@@ -5,7 +5,7 @@ pub mod life_cycle;
 pub mod pipeline;
 
 use crate::{
-    app::AppLifeCycle,
+    app::{AppFixedTimestep, AppLifeCycle},
     ecs::{
         commands::UniverseCommands,
         components::NonPersistent,
@@ -395,6 +395,18 @@ impl Universe {
         for state in states.iter_mut().take(count) {
             state.on_process_background(self);
         }
+        let fixed = self.resource_mut::<AppFixedTimestep>().map(|mut fixed| {
+            let delta_time = self.expect_resource::<AppLifeCycle>().delta_time_seconds();
+            (fixed.accumulate(delta_time), fixed.fixed_delta_time)
+        });
+        if let Some((substeps, fixed_delta_time)) = fixed {
+            for _ in 0..substeps {
+                states
+                    .last_mut()
+                    .unwrap()
+                    .on_fixed_process(self, fixed_delta_time);
+            }
+        }
         let change = states.last_mut().unwrap().on_process(self);
         match &change {
             StateChange::Pop | StateChange::Swap(_) => {
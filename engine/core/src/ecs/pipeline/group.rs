@@ -0,0 +1,238 @@
+use crate::ecs::{
+    pipeline::{PipelineBuilder, PipelineBuilderError, PipelineLayer},
+    AccessType, System,
+};
+use std::collections::HashMap;
+
+type SystemGroupInstaller<PB> =
+    fn(&mut PB, &str, System, &[&str], PipelineLayer, bool) -> Result<(), PipelineBuilderError>;
+
+struct SystemGroupItem<PB>
+where
+    PB: PipelineBuilder,
+{
+    name: String,
+    system: System,
+    before: Vec<String>,
+    after: Vec<String>,
+    lock_on_single_thread: bool,
+    installer: SystemGroupInstaller<PB>,
+}
+
+/// Named group of systems (input, simulation, render) that get installed into pipeline builder
+/// ordered by their `before`/`after` constraints instead of order of declaration.
+///
+/// Systems without constraints between them keep order of declaration. `after` constraints can
+/// also point to systems already installed in pipeline builder, `before` constraints can point
+/// only to systems of this group.
+pub struct SystemGroup<PB>
+where
+    PB: PipelineBuilder,
+{
+    name: String,
+    layer: PipelineLayer,
+    systems: Vec<SystemGroupItem<PB>>,
+}
+
+impl<PB> SystemGroup<PB>
+where
+    PB: PipelineBuilder,
+{
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            layer: PipelineLayer::Main,
+            systems: vec![],
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn layer(&self) -> PipelineLayer {
+        self.layer
+    }
+
+    pub fn with_layer(mut self, layer: PipelineLayer) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    pub fn add_system<AT: AccessType>(
+        &mut self,
+        name: &str,
+        system: System,
+        before: &[&str],
+        after: &[&str],
+    ) -> Result<(), PipelineBuilderError> {
+        self.add_system_inner::<AT>(name, system, before, after, false)
+    }
+
+    pub fn add_system_on_single_thread<AT: AccessType>(
+        &mut self,
+        name: &str,
+        system: System,
+        before: &[&str],
+        after: &[&str],
+    ) -> Result<(), PipelineBuilderError> {
+        self.add_system_inner::<AT>(name, system, before, after, true)
+    }
+
+    pub fn with_system<AT: AccessType>(
+        mut self,
+        name: &str,
+        system: System,
+        before: &[&str],
+        after: &[&str],
+    ) -> Result<Self, PipelineBuilderError> {
+        self.add_system::<AT>(name, system, before, after)?;
+        Ok(self)
+    }
+
+    pub fn with_system_on_single_thread<AT: AccessType>(
+        mut self,
+        name: &str,
+        system: System,
+        before: &[&str],
+        after: &[&str],
+    ) -> Result<Self, PipelineBuilderError> {
+        self.add_system_on_single_thread::<AT>(name, system, before, after)?;
+        Ok(self)
+    }
+
+    /// Names of group systems in order they get installed.
+    pub fn resolve(&self) -> Result<Vec<&str>, PipelineBuilderError> {
+        Ok(self
+            .resolve_dependencies()?
+            .into_iter()
+            .map(|(index, _)| self.systems[index].name.as_str())
+            .collect())
+    }
+
+    /// Installs group systems into pipeline builder, each one depending on systems that it has
+    /// to run after.
+    pub fn install(self, builder: &mut PB) -> Result<(), PipelineBuilderError> {
+        let order = self.resolve_dependencies()?;
+        for (index, dependencies) in order {
+            let item = &self.systems[index];
+            let dependencies = dependencies
+                .iter()
+                .map(|name| name.as_str())
+                .collect::<Vec<_>>();
+            (item.installer)(
+                builder,
+                &item.name,
+                item.system,
+                &dependencies,
+                self.layer,
+                item.lock_on_single_thread,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn add_system_inner<AT: AccessType>(
+        &mut self,
+        name: &str,
+        system: System,
+        before: &[&str],
+        after: &[&str],
+        lock_on_single_thread: bool,
+    ) -> Result<(), PipelineBuilderError> {
+        if self.systems.iter().any(|item| item.name == name) {
+            return Err(PipelineBuilderError::DuplicateSystem(name.to_owned()));
+        }
+        self.systems.push(SystemGroupItem {
+            name: name.to_owned(),
+            system,
+            before: before.iter().map(|name| name.to_string()).collect(),
+            after: after.iter().map(|name| name.to_string()).collect(),
+            lock_on_single_thread,
+            installer: PB::add_system_on_layer::<AT>,
+        });
+        Ok(())
+    }
+
+    /// [(system index, names of systems it runs after)] in order of installation.
+    fn resolve_dependencies(&self) -> Result<Vec<(usize, Vec<String>)>, PipelineBuilderError> {
+        let indices = self
+            .systems
+            .iter()
+            .enumerate()
+            .map(|(index, item)| (item.name.as_str(), index))
+            .collect::<HashMap<_, _>>();
+        // [(group systems that have to run first, names of systems outside of group)]
+        let mut dependencies = vec![(vec![], vec![]); self.systems.len()];
+        for (index, item) in self.systems.iter().enumerate() {
+            for name in &item.after {
+                match indices.get(name.as_str()) {
+                    Some(other) => dependencies[index].0.push(*other),
+                    None => dependencies[index].1.push(name.to_owned()),
+                }
+            }
+            for name in &item.before {
+                match indices.get(name.as_str()) {
+                    Some(other) => dependencies[*other].0.push(index),
+                    None => return Err(PipelineBuilderError::DependencyNotFound(name.to_owned())),
+                }
+            }
+        }
+
+        let mut resolved = vec![false; self.systems.len()];
+        let mut result = Vec::with_capacity(self.systems.len());
+        while result.len() < self.systems.len() {
+            let index = (0..self.systems.len())
+                .find(|index| {
+                    !resolved[*index] && dependencies[*index].0.iter().all(|other| resolved[*other])
+                })
+                .ok_or_else(|| {
+                    PipelineBuilderError::SystemsCycle(self.find_cycle(&dependencies, &resolved))
+                })?;
+            resolved[index] = true;
+            let (inner, outer) = &dependencies[index];
+            let names = inner
+                .iter()
+                .map(|other| self.systems[*other].name.to_owned())
+                .chain(outer.iter().cloned())
+                .collect();
+            result.push((index, names));
+        }
+        Ok(result)
+    }
+
+    fn find_cycle(
+        &self,
+        dependencies: &[(Vec<usize>, Vec<String>)],
+        resolved: &[bool],
+    ) -> Vec<String> {
+        // every unresolved system waits for another unresolved one, so walking them eventually
+        // visits some system twice.
+        let mut path = vec![];
+        let mut current = match resolved.iter().position(|resolved| !resolved) {
+            Some(index) => index,
+            None => return vec![],
+        };
+        while !path.contains(&current) {
+            path.push(current);
+            current = match dependencies[current]
+                .0
+                .iter()
+                .copied()
+                .find(|other| !resolved[*other])
+            {
+                Some(other) => other,
+                None => break,
+            };
+        }
+        let start = path
+            .iter()
+            .position(|index| *index == current)
+            .unwrap_or_default();
+        path[start..]
+            .iter()
+            .rev()
+            .map(|index| self.systems[*index].name.to_owned())
+            .collect()
+    }
+}
@@ -1,4 +1,5 @@
 pub mod engines;
+pub mod group;
 
 use crate::ecs::{AccessType, System, Universe};
 pub use hecs::*;
@@ -10,6 +11,9 @@ pub type PipelineId = ID<PhantomData<dyn PipelineEngine + Send + Sync>>;
 #[derive(Debug, Clone, PartialEq)]
 pub enum PipelineBuilderError {
     DependencyNotFound(String),
+    DuplicateSystem(String),
+    /// Names of systems that depend on each other, in order they would have to run.
+    SystemsCycle(Vec<String>),
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -361,6 +365,7 @@ mod tests {
     use super::*;
     use crate::ecs::pipeline::{
         engines::{default::DefaultPipelineEngine, sequence::SequencePipelineEngine},
+        group::SystemGroup,
         LinearPipelineBuilder, ParallelPipelineBuilder,
     };
 
@@ -719,4 +724,78 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_system_group() {
+        fn system_a(_: &mut Universe) {}
+        fn system_b(_: &mut Universe) {}
+        fn system_c(_: &mut Universe) {}
+
+        let mut builder = LinearPipelineBuilder::default()
+            .with_system::<()>("input", system_a, &[])
+            .unwrap();
+        let group = SystemGroup::<LinearPipelineBuilder>::new("simulation")
+            .with_system::<()>("render", system_c, &[], &["physics"])
+            .unwrap()
+            .with_system::<()>("physics", system_b, &[], &["input", "ai"])
+            .unwrap()
+            .with_system::<()>("ai", system_a, &[], &[])
+            .unwrap()
+            .with_system::<()>("audio", system_a, &["render"], &[])
+            .unwrap();
+        assert_eq!(
+            group.resolve().unwrap(),
+            vec!["ai", "physics", "audio", "render"]
+        );
+        group.install(&mut builder).unwrap();
+        assert_eq!(
+            builder
+                .systems_main
+                .iter()
+                .map(|meta| meta.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["input", "ai", "physics", "audio", "render"]
+        );
+
+        assert_eq!(
+            SystemGroup::<LinearPipelineBuilder>::new("group")
+                .with_system::<()>("a", system_a, &["b"], &[])
+                .unwrap()
+                .resolve(),
+            Err(PipelineBuilderError::DependencyNotFound("b".to_owned()))
+        );
+        assert!(matches!(
+            SystemGroup::<LinearPipelineBuilder>::new("group")
+                .with_system::<()>("a", system_a, &[], &[])
+                .unwrap()
+                .with_system::<()>("a", system_b, &[], &[]),
+            Err(PipelineBuilderError::DuplicateSystem(name)) if name == "a"
+        ));
+    }
+
+    #[test]
+    fn test_system_group_cycle() {
+        fn system(_: &mut Universe) {}
+
+        let group = SystemGroup::<LinearPipelineBuilder>::new("simulation")
+            .with_system::<()>("a", system, &[], &[])
+            .unwrap()
+            .with_system::<()>("b", system, &[], &["a", "d"])
+            .unwrap()
+            .with_system::<()>("c", system, &[], &["b"])
+            .unwrap()
+            .with_system::<()>("d", system, &[], &["c"])
+            .unwrap();
+        assert_eq!(
+            group.resolve(),
+            Err(PipelineBuilderError::SystemsCycle(vec![
+                "c".to_owned(),
+                "d".to_owned(),
+                "b".to_owned(),
+            ]))
+        );
+        let mut builder = LinearPipelineBuilder::default();
+        assert!(group.install(&mut builder).is_err());
+        assert!(builder.systems_main.is_empty());
+    }
 }
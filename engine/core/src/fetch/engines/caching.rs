@@ -0,0 +1,205 @@
+use crate::fetch::{FetchEngine, FetchProcess, FetchProcessId, FetchStatus};
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+#[derive(Default)]
+struct FetchCache {
+    max_bytes: usize,
+    bytes: usize,
+    counter: u64,
+    /// { path: (payload, last use) }
+    entries: HashMap<String, (Vec<u8>, u64)>,
+}
+
+impl FetchCache {
+    fn get(&mut self, path: &str) -> Option<Vec<u8>> {
+        self.counter = self.counter.wrapping_add(1);
+        let counter = self.counter;
+        self.entries.get_mut(path).map(|(bytes, last_use)| {
+            *last_use = counter;
+            bytes.to_owned()
+        })
+    }
+
+    fn insert(&mut self, path: String, bytes: Vec<u8>) {
+        self.remove(&path);
+        if bytes.len() > self.max_bytes {
+            return;
+        }
+        self.counter = self.counter.wrapping_add(1);
+        self.bytes += bytes.len();
+        self.entries.insert(path, (bytes, self.counter));
+        self.evict();
+    }
+
+    fn remove(&mut self, path: &str) -> bool {
+        if let Some((bytes, _)) = self.entries.remove(path) {
+            self.bytes -= bytes.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn evict(&mut self) {
+        while self.bytes > self.max_bytes {
+            let path = match self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_use))| *last_use)
+                .map(|(path, _)| path.to_owned())
+            {
+                Some(path) => path,
+                None => return,
+            };
+            self.remove(&path);
+        }
+    }
+}
+
+/// Wraps another fetch engine and keeps completed payloads in memory, evicting least recently
+/// used ones when total size exceeds `max_bytes`.
+pub struct CachingFetchEngine<E>
+where
+    E: FetchEngine,
+{
+    inner: E,
+    cache: Arc<RwLock<FetchCache>>,
+}
+
+impl<E> CachingFetchEngine<E>
+where
+    E: FetchEngine,
+{
+    pub fn new(inner: E, max_bytes: usize) -> Self {
+        Self {
+            inner,
+            cache: Arc::new(RwLock::new(FetchCache {
+                max_bytes,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub fn inner(&self) -> &E {
+        &self.inner
+    }
+
+    pub fn inner_mut(&mut self) -> &mut E {
+        &mut self.inner
+    }
+
+    pub fn max_bytes(&self) -> usize {
+        self.cache
+            .read()
+            .map(|cache| cache.max_bytes)
+            .unwrap_or_default()
+    }
+
+    pub fn set_max_bytes(&mut self, value: usize) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.max_bytes = value;
+            cache.evict();
+        }
+    }
+
+    pub fn cached_bytes(&self) -> usize {
+        self.cache
+            .read()
+            .map(|cache| cache.bytes)
+            .unwrap_or_default()
+    }
+
+    pub fn is_cached(&self, path: &str) -> bool {
+        self.cache
+            .read()
+            .map(|cache| cache.entries.contains_key(path))
+            .unwrap_or_default()
+    }
+
+    /// Removes cached payload so next fetch of given path goes to inner engine.
+    pub fn invalidate(&mut self, path: &str) -> bool {
+        self.cache
+            .write()
+            .map(|mut cache| cache.remove(path))
+            .unwrap_or_default()
+    }
+
+    pub fn clear(&mut self) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.entries.clear();
+            cache.bytes = 0;
+        }
+    }
+}
+
+impl<E> FetchEngine for CachingFetchEngine<E>
+where
+    E: FetchEngine,
+{
+    fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+        if let Some(bytes) = self
+            .cache
+            .write()
+            .ok()
+            .and_then(|mut cache| cache.get(path))
+        {
+            return Ok(Box::new(FetchProcess::new_done(bytes)));
+        }
+        let mut process = self.inner.fetch(path)?;
+        let cache = self.cache.clone();
+        let path = path.to_owned();
+        process.add_finish_callback(move |result| {
+            if let Ok(bytes) = result {
+                if let Ok(mut cache) = cache.write() {
+                    cache.insert(path, bytes.to_owned());
+                }
+            }
+        });
+        Ok(process)
+    }
+
+    fn cancel(&mut self, reader: FetchProcess) {
+        self.inner.cancel(reader)
+    }
+
+    fn cancel_by_id(&mut self, id: FetchProcessId) -> bool {
+        self.inner.cancel_by_id(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::engines::map::MapFetchEngine;
+
+    #[test]
+    fn test_caching_fetch_engine() {
+        let mut map = HashMap::new();
+        map.insert("a".to_owned(), vec![1, 2, 3]);
+        map.insert("b".to_owned(), vec![4, 5, 6]);
+        map.insert("c".to_owned(), vec![7, 8, 9]);
+        let mut engine = CachingFetchEngine::new(MapFetchEngine::new(map), 6);
+
+        assert_eq!(engine.fetch("a").unwrap().read(), Some(vec![1, 2, 3]));
+        assert!(engine.is_cached("a"));
+        engine.inner_mut().map.insert("a".to_owned(), vec![0]);
+        assert_eq!(engine.fetch("a").unwrap().read(), Some(vec![1, 2, 3]));
+        assert!(engine.invalidate("a"));
+        assert_eq!(engine.fetch("a").unwrap().read(), Some(vec![0]));
+
+        assert_eq!(engine.fetch("b").unwrap().read(), Some(vec![4, 5, 6]));
+        assert_eq!(engine.cached_bytes(), 4);
+        engine.fetch("a").unwrap();
+        assert_eq!(engine.fetch("c").unwrap().read(), Some(vec![7, 8, 9]));
+        assert!(engine.is_cached("a"));
+        assert!(!engine.is_cached("b"));
+        assert!(engine.is_cached("c"));
+        assert_eq!(engine.cached_bytes(), 4);
+
+        assert!(engine.fetch("d").is_err());
+        assert!(!engine.is_cached("d"));
+    }
+}
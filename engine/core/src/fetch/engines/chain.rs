@@ -0,0 +1,182 @@
+use crate::fetch::{
+    FetchCancelReason, FetchEngine, FetchProcess, FetchProcessId, FetchProcessRegistry, FetchStatus,
+};
+use std::sync::{Arc, RwLock};
+
+type FetchEngines = Arc<RwLock<Vec<Box<dyn FetchEngine>>>>;
+
+/// Tries ordered list of fetch engines one after another, falling back to next engine whenever
+/// previous one fails to fetch given path with `FetchCancelReason::Error`.
+#[derive(Default)]
+pub struct ChainFetchEngine {
+    engines: FetchEngines,
+    processes: FetchProcessRegistry,
+}
+
+impl ChainFetchEngine {
+    pub fn new(engines: Vec<Box<dyn FetchEngine>>) -> Self {
+        Self {
+            engines: Arc::new(RwLock::new(engines)),
+            processes: Default::default(),
+        }
+    }
+
+    pub fn with<E>(mut self, engine: E) -> Self
+    where
+        E: FetchEngine + 'static,
+    {
+        self.push(engine);
+        self
+    }
+
+    pub fn push<E>(&mut self, engine: E)
+    where
+        E: FetchEngine + 'static,
+    {
+        if let Ok(mut engines) = self.engines.write() {
+            engines.push(Box::new(engine));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.engines
+            .read()
+            .map(|engines| engines.len())
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl FetchEngine for ChainFetchEngine {
+    fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+        let proxy = FetchProcess::new_start();
+        attempt(self.engines.clone(), path.to_owned(), 0, proxy.clone());
+        match proxy.status() {
+            FetchStatus::Canceled(FetchCancelReason::Error) => {
+                Err(FetchStatus::Canceled(FetchCancelReason::Error))
+            }
+            _ => {
+                self.processes.register(&proxy);
+                Ok(Box::new(proxy))
+            }
+        }
+    }
+
+    fn cancel(&mut self, mut reader: FetchProcess) {
+        if !self.processes.cancel(reader.id(), FetchCancelReason::User) {
+            reader.cancel(FetchCancelReason::User);
+        }
+    }
+
+    fn cancel_by_id(&mut self, id: FetchProcessId) -> bool {
+        self.processes.cancel(id, FetchCancelReason::User)
+    }
+}
+
+fn attempt(engines: FetchEngines, path: String, mut index: usize, mut proxy: FetchProcess) {
+    loop {
+        let result = match engines.write() {
+            Ok(mut engines) => match engines.get_mut(index) {
+                Some(engine) => engine.fetch(&path),
+                None => break,
+            },
+            Err(_) => break,
+        };
+        let mut process = match result {
+            Ok(process) => process,
+            Err(_) => {
+                index += 1;
+                continue;
+            }
+        };
+        let engines = engines.clone();
+        process.add_finish_callback(move |result| {
+            // proxy might have been canceled by user in the meantime.
+            if !matches!(proxy.status(), FetchStatus::InProgress(_)) {
+                return;
+            }
+            match result {
+                Ok(bytes) => proxy.done(bytes.to_owned()),
+                Err(FetchCancelReason::Error) => attempt(engines, path, index + 1, proxy),
+                Err(reason) => proxy.cancel(reason),
+            }
+        });
+        return;
+    }
+    proxy.cancel(FetchCancelReason::Error);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fetch::engines::map::MapFetchEngine;
+    use std::collections::HashMap;
+
+    struct ManualFetchEngine {
+        processes: Arc<RwLock<Vec<FetchProcess>>>,
+    }
+
+    impl FetchEngine for ManualFetchEngine {
+        fn fetch(&mut self, _: &str) -> Result<Box<FetchProcess>, FetchStatus> {
+            let process = FetchProcess::new_start();
+            self.processes.write().unwrap().push(process.clone());
+            Ok(Box::new(process))
+        }
+    }
+
+    #[test]
+    fn test_chain_fetch_engine() {
+        let mut first = HashMap::new();
+        first.insert("a".to_owned(), vec![1]);
+        let mut second = HashMap::new();
+        second.insert("a".to_owned(), vec![2]);
+        second.insert("b".to_owned(), vec![3]);
+        let processes = Arc::new(RwLock::new(vec![]));
+        let mut engine = ChainFetchEngine::default()
+            .with(ManualFetchEngine {
+                processes: processes.clone(),
+            })
+            .with(MapFetchEngine::new(first))
+            .with(MapFetchEngine::new(second));
+        assert_eq!(engine.len(), 3);
+
+        let a = engine.fetch("a").unwrap();
+        assert!(matches!(a.status(), FetchStatus::InProgress(_)));
+        processes.write().unwrap()[0].cancel(FetchCancelReason::Error);
+        assert_eq!(a.read(), Some(vec![1]));
+
+        let b = engine.fetch("b").unwrap();
+        processes.write().unwrap()[1].cancel(FetchCancelReason::Error);
+        assert_eq!(b.read(), Some(vec![3]));
+
+        let c = engine.fetch("c").unwrap();
+        processes.write().unwrap()[2].done(vec![4]);
+        assert_eq!(c.read(), Some(vec![4]));
+
+        let d = engine.fetch("d").unwrap();
+        processes.write().unwrap()[3].cancel(FetchCancelReason::Error);
+        assert_eq!(d.status(), FetchStatus::Canceled(FetchCancelReason::Error));
+
+        let e = engine.fetch("e").unwrap();
+        processes.write().unwrap()[4].cancel(FetchCancelReason::User);
+        assert_eq!(e.status(), FetchStatus::Canceled(FetchCancelReason::User));
+
+        let f = engine.fetch("f").unwrap();
+        assert!(engine.cancel_by_id(f.id()));
+        assert_eq!(f.status(), FetchStatus::Canceled(FetchCancelReason::User));
+        assert!(!engine.cancel_by_id(f.id()));
+        let g = engine.fetch("g").unwrap();
+        processes.write().unwrap()[6].done(vec![5]);
+        assert!(!engine.cancel_by_id(g.id()));
+        assert_eq!(g.read(), Some(vec![5]));
+
+        let mut engine = ChainFetchEngine::default().with(MapFetchEngine::default());
+        assert_eq!(
+            engine.fetch("a").unwrap_err(),
+            FetchStatus::Canceled(FetchCancelReason::Error)
+        );
+    }
+}
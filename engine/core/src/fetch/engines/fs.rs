@@ -1,14 +1,19 @@
 #![cfg(not(feature = "web"))]
 
-use crate::fetch::{FetchCancelReason, FetchEngine, FetchProcess, FetchStatus};
+use crate::fetch::{
+    FetchCancelReason, FetchEngine, FetchProcess, FetchProcessId, FetchProcessRegistry, FetchStatus,
+};
 use std::{
     env::var,
     path::{Path, PathBuf},
+    time::Duration,
 };
 
 #[derive(Clone)]
 pub struct FsFetchEngine {
     root_path: PathBuf,
+    timeout: Option<Duration>,
+    processes: FetchProcessRegistry,
 }
 
 impl Default for FsFetchEngine {
@@ -18,6 +23,8 @@ impl Default for FsFetchEngine {
                 Ok(value) => value.into(),
                 Err(_) => Default::default(),
             },
+            timeout: None,
+            processes: Default::default(),
         }
     }
 }
@@ -29,8 +36,17 @@ impl FsFetchEngine {
                 Ok(value) => value.into(),
                 Err(_) => root_path.as_ref().into(),
             },
+            timeout: None,
+            processes: Default::default(),
         }
     }
+
+    /// Timeout of parallel reads - synchronous reads complete before `fetch` returns, so they
+    /// never time out.
+    pub fn timeout(mut self, value: Option<Duration>) -> Self {
+        self.timeout = value;
+        self
+    }
 }
 
 impl FetchEngine for FsFetchEngine {
@@ -38,9 +54,18 @@ impl FetchEngine for FsFetchEngine {
         #[cfg(feature = "parallel")]
         {
             let path = self.root_path.join(path);
-            let process = FetchProcess::new_start();
+            let process = match self.timeout {
+                Some(timeout) => FetchProcess::new_start_with_timeout(timeout),
+                None => FetchProcess::new_start(),
+            };
+            self.processes.register(&process);
             let mut p = process.clone();
             rayon::spawn(move || {
+                // reads that waited in queue past their deadline are not started at all, while
+                // results of reads expired by process poller in the meantime get ignored.
+                if p.poll_timeout() {
+                    return;
+                }
                 if let Ok(bytes) = std::fs::read(path) {
                     p.done(bytes);
                 } else {
@@ -58,4 +83,14 @@ impl FetchEngine for FsFetchEngine {
             }
         }
     }
+
+    fn cancel(&mut self, mut reader: FetchProcess) {
+        if !self.processes.cancel(reader.id(), FetchCancelReason::User) {
+            reader.cancel(FetchCancelReason::User);
+        }
+    }
+
+    fn cancel_by_id(&mut self, id: FetchProcessId) -> bool {
+        self.processes.cancel(id, FetchCancelReason::User)
+    }
 }
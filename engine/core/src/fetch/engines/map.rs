@@ -1,15 +1,57 @@
 use crate::fetch::{FetchCancelReason, FetchEngine, FetchProcess, FetchStatus};
 use std::collections::HashMap;
+#[cfg(not(feature = "web"))]
+use std::path::Path;
 
+/// Serves payloads from memory - useful for tests and embedded asset bundles.
 #[derive(Default, Clone)]
 pub struct MapFetchEngine {
     pub map: HashMap<String, Vec<u8>>,
 }
 
+/// In-memory fetch engine - same engine as `MapFetchEngine`.
+pub type MemoryFetchEngine = MapFetchEngine;
+
 impl MapFetchEngine {
     pub fn new(map: HashMap<String, Vec<u8>>) -> Self {
         Self { map }
     }
+
+    /// Reads all files from given directory (recursively), keyed by paths relative to it with
+    /// `/` as separator.
+    #[cfg(not(feature = "web"))]
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        fn read_dir(
+            root: &Path,
+            path: &Path,
+            map: &mut HashMap<String, Vec<u8>>,
+        ) -> std::io::Result<()> {
+            for entry in std::fs::read_dir(path)? {
+                let path = entry?.path();
+                if path.is_dir() {
+                    read_dir(root, &path, map)?;
+                } else if let Ok(relative) = path.strip_prefix(root) {
+                    let key = relative
+                        .components()
+                        .map(|component| component.as_os_str().to_string_lossy())
+                        .collect::<Vec<_>>()
+                        .join("/");
+                    map.insert(key, std::fs::read(&path)?);
+                }
+            }
+            Ok(())
+        }
+
+        let root = path.as_ref();
+        let mut map = HashMap::new();
+        read_dir(root, root, &mut map)?;
+        Ok(Self { map })
+    }
+
+    pub fn insert(mut self, path: impl ToString, bytes: Vec<u8>) -> Self {
+        self.map.insert(path.to_string(), bytes);
+        self
+    }
 }
 
 impl FetchEngine for MapFetchEngine {
@@ -21,3 +63,38 @@ impl FetchEngine for MapFetchEngine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_map_fetch_engine() {
+        let mut engine = MemoryFetchEngine::default()
+            .insert("a.txt", b"a".to_vec())
+            .insert("b/c.txt", b"c".to_vec());
+        assert_eq!(engine.fetch("a.txt").unwrap().read(), Some(b"a".to_vec()));
+        assert_eq!(engine.fetch("b/c.txt").unwrap().read(), Some(b"c".to_vec()));
+        assert_eq!(
+            engine.fetch("d.txt").unwrap_err(),
+            FetchStatus::Canceled(FetchCancelReason::Error)
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "web"))]
+    fn test_map_fetch_engine_from_dir() {
+        let root =
+            std::env::temp_dir().join(format!("oxygengine-map-fetch-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("b")).unwrap();
+        std::fs::write(root.join("a.txt"), b"a").unwrap();
+        std::fs::write(root.join("b").join("c.txt"), b"c").unwrap();
+        let result = MapFetchEngine::from_dir(&root);
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let mut engine = result.unwrap();
+        assert_eq!(engine.map.len(), 2);
+        assert_eq!(engine.fetch("a.txt").unwrap().read(), Some(b"a".to_vec()));
+        assert_eq!(engine.fetch("b/c.txt").unwrap().read(), Some(b"c".to_vec()));
+    }
+}
@@ -1,3 +1,5 @@
+pub mod caching;
+pub mod chain;
 #[cfg(not(feature = "web"))]
 pub mod fs;
 pub mod map;
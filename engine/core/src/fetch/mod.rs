@@ -1,17 +1,26 @@
 pub mod engines;
 
 use crate::{id::ID, Scalar};
+#[cfg(not(feature = "web"))]
+use std::time::Instant;
 use std::{
+    collections::HashMap,
     mem::replace,
-    sync::{Arc, RwLock},
+    ops::Range,
+    sync::{Arc, Mutex, RwLock},
+    time::Duration,
 };
 
 pub type FetchProcessId = ID<FetchProcess>;
 
+type FetchFinishCallback = Box<dyn FnOnce(Result<&[u8], FetchCancelReason>) + Send>;
+type FetchProgressCallback = Box<dyn Fn(Scalar) + Send>;
+
 #[derive(Debug, PartialEq, Copy, Clone)]
 pub enum FetchCancelReason {
     User,
     Error,
+    Timeout,
 }
 
 #[derive(Debug, PartialEq, Copy, Clone)]
@@ -33,6 +42,11 @@ impl Default for FetchStatus {
 pub struct FetchProcess {
     id: FetchProcessId,
     inner: Arc<RwLock<(FetchStatus, Option<Vec<u8>>)>>,
+    finish_callbacks: Arc<Mutex<Vec<FetchFinishCallback>>>,
+    progress_callbacks: Arc<Mutex<Vec<FetchProgressCallback>>>,
+    timeout: Option<Duration>,
+    #[cfg(not(feature = "web"))]
+    started: Instant,
 }
 
 impl Default for FetchProcess {
@@ -47,6 +61,11 @@ impl FetchProcess {
         Self {
             id: FetchProcessId::new(),
             inner: Arc::new(RwLock::new((FetchStatus::Empty, None))),
+            finish_callbacks: Default::default(),
+            progress_callbacks: Default::default(),
+            timeout: None,
+            #[cfg(not(feature = "web"))]
+            started: Instant::now(),
         }
     }
 
@@ -55,14 +74,34 @@ impl FetchProcess {
         Self {
             id: FetchProcessId::new(),
             inner: Arc::new(RwLock::new((FetchStatus::InProgress(0.0), None))),
+            finish_callbacks: Default::default(),
+            progress_callbacks: Default::default(),
+            timeout: None,
+            #[cfg(not(feature = "web"))]
+            started: Instant::now(),
         }
     }
 
+    /// Starts process that gets canceled with `FetchCancelReason::Timeout` when it
+    /// does not complete in given time. Deadline is checked whenever status or data of process
+    /// is queried, so no separate polling is needed.
+    #[inline]
+    pub fn new_start_with_timeout(timeout: Duration) -> Self {
+        let mut result = Self::new_start();
+        result.timeout = Some(timeout);
+        result
+    }
+
     #[inline]
     pub fn new_done(data: Vec<u8>) -> Self {
         Self {
             id: FetchProcessId::new(),
             inner: Arc::new(RwLock::new((FetchStatus::Done, Some(data)))),
+            finish_callbacks: Default::default(),
+            progress_callbacks: Default::default(),
+            timeout: None,
+            #[cfg(not(feature = "web"))]
+            started: Instant::now(),
         }
     }
 
@@ -71,6 +110,11 @@ impl FetchProcess {
         Self {
             id: FetchProcessId::new(),
             inner: Arc::new(RwLock::new((FetchStatus::Canceled(reason), None))),
+            finish_callbacks: Default::default(),
+            progress_callbacks: Default::default(),
+            timeout: None,
+            #[cfg(not(feature = "web"))]
+            started: Instant::now(),
         }
     }
 
@@ -79,7 +123,14 @@ impl FetchProcess {
         self.id
     }
 
+    #[inline]
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
     pub fn status(&self) -> FetchStatus {
+        #[cfg(not(feature = "web"))]
+        self.expire_if_due();
         self.inner.read().map(|meta| meta.0).unwrap_or_default()
     }
 
@@ -91,19 +142,146 @@ impl FetchProcess {
 
     pub fn progress(&mut self, value: Scalar) {
         if let Ok(mut meta) = self.inner.write() {
-            *meta = (FetchStatus::InProgress(value), None);
+            if meta.0 != FetchStatus::Canceled(FetchCancelReason::Timeout) {
+                *meta = (FetchStatus::InProgress(value), None);
+                if let Ok(callbacks) = self.progress_callbacks.lock() {
+                    for callback in callbacks.iter() {
+                        callback(value);
+                    }
+                }
+            }
         }
     }
 
     pub fn done(&mut self, data: Vec<u8>) {
         if let Ok(mut meta) = self.inner.write() {
-            *meta = (FetchStatus::Done, Some(data));
+            if meta.0 != FetchStatus::Canceled(FetchCancelReason::Timeout) {
+                self.finish_callbacks(Ok(&data));
+                *meta = (FetchStatus::Done, Some(data));
+            }
+        }
+    }
+
+    /// Registers callback called with new progress value every time process reports progress.
+    /// Callback is called while process is locked so it must not access that process.
+    pub fn on_progress<F>(&mut self, callback: F)
+    where
+        F: Fn(Scalar) + Send + 'static,
+    {
+        if let Ok(meta) = self.inner.read() {
+            if matches!(meta.0, FetchStatus::Empty | FetchStatus::InProgress(_)) {
+                if let Ok(mut callbacks) = self.progress_callbacks.lock() {
+                    callbacks.push(Box::new(callback));
+                }
+            }
+        }
+    }
+
+    /// Registers callback called exactly once with payload when process is done - immediately
+    /// if it already is and its data was not read yet. Callback is called while process is
+    /// locked so it must not access that process.
+    pub fn on_done<F>(&mut self, callback: F)
+    where
+        F: FnOnce(&[u8]) + Send + 'static,
+    {
+        self.add_finish_callback(move |result| {
+            if let Ok(bytes) = result {
+                callback(bytes);
+            }
+        });
+    }
+
+    /// Registers callback called exactly once with reason when process gets canceled -
+    /// immediately if it already is. Callback is called while process is locked so it must
+    /// not access that process.
+    pub fn on_cancel<F>(&mut self, callback: F)
+    where
+        F: FnOnce(FetchCancelReason) + Send + 'static,
+    {
+        self.add_finish_callback(move |result| {
+            if let Err(reason) = result {
+                callback(reason);
+            }
+        });
+    }
+
+    /// Registers callback called with payload once process is done or with reason once it
+    /// gets canceled - immediately if that already happened (and data was not read yet).
+    /// Callback is called while process is locked so it must not access that process.
+    pub(crate) fn add_finish_callback<F>(&mut self, callback: F)
+    where
+        F: FnOnce(Result<&[u8], FetchCancelReason>) + Send + 'static,
+    {
+        if let Ok(meta) = self.inner.read() {
+            match (meta.0, meta.1.as_ref()) {
+                (FetchStatus::Done, Some(data)) => callback(Ok(data)),
+                (FetchStatus::Canceled(reason), _) => callback(Err(reason)),
+                (FetchStatus::Empty, _) | (FetchStatus::InProgress(_), _) => {
+                    if let Ok(mut callbacks) = self.finish_callbacks.lock() {
+                        callbacks.push(Box::new(callback));
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn finish_callbacks(&self, result: Result<&[u8], FetchCancelReason>) {
+        if let Ok(mut callbacks) = self.progress_callbacks.lock() {
+            callbacks.clear();
+        }
+        if let Ok(mut callbacks) = self.finish_callbacks.lock() {
+            for callback in callbacks.drain(..) {
+                callback(result);
+            }
+        }
+    }
+
+    fn clear_callbacks(&self) {
+        if let Ok(mut callbacks) = self.progress_callbacks.lock() {
+            callbacks.clear();
+        }
+        if let Ok(mut callbacks) = self.finish_callbacks.lock() {
+            callbacks.clear();
+        }
+    }
+
+    /// Cancels process with `FetchCancelReason::Timeout` if it is still in progress.
+    /// Late results are ignored once process has timed out.
+    pub fn expire(&mut self) -> bool {
+        self.expire_inner()
+    }
+
+    fn expire_inner(&self) -> bool {
+        if let Ok(mut meta) = self.inner.write() {
+            if matches!(meta.0, FetchStatus::Empty | FetchStatus::InProgress(_)) {
+                *meta = (FetchStatus::Canceled(FetchCancelReason::Timeout), None);
+                self.finish_callbacks(Err(FetchCancelReason::Timeout));
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Expires process if its timeout has passed - returns true if process has timed out.
+    #[cfg(not(feature = "web"))]
+    pub fn poll_timeout(&mut self) -> bool {
+        self.status() == FetchStatus::Canceled(FetchCancelReason::Timeout)
+    }
+
+    #[cfg(not(feature = "web"))]
+    fn expire_if_due(&self) {
+        if let Some(timeout) = self.timeout {
+            if self.started.elapsed() >= timeout {
+                self.expire_inner();
+            }
         }
     }
 
     pub fn cancel(&mut self, reason: FetchCancelReason) {
         if let Ok(mut meta) = self.inner.write() {
             *meta = (FetchStatus::Canceled(reason), None);
+            self.finish_callbacks(Err(reason));
         }
     }
 
@@ -112,16 +290,34 @@ impl FetchProcess {
     }
 
     pub fn read(&self) -> Option<Vec<u8>> {
+        #[cfg(not(feature = "web"))]
+        self.expire_if_due();
         if let Ok(mut meta) = self.inner.write() {
             if meta.0 == FetchStatus::Done {
                 let old: (FetchStatus, Option<Vec<u8>>) =
                     replace(&mut meta, (FetchStatus::Read, None));
+                self.clear_callbacks();
                 return old.1;
             }
         }
         None
     }
 
+    /// Copies part of payload of done process without consuming it - unlike `read`, status
+    /// stays at `FetchStatus::Done`. Range gets clamped to payload size.
+    pub fn read_range(&self, range: Range<usize>) -> Option<Vec<u8>> {
+        if let Ok(meta) = self.inner.read() {
+            if meta.0 == FetchStatus::Done {
+                if let Some(bytes) = meta.1.as_ref() {
+                    let end = range.end.min(bytes.len());
+                    let start = range.start.min(end);
+                    return Some(bytes[start..end].to_vec());
+                }
+            }
+        }
+        None
+    }
+
     pub fn byte_size(&self) -> Option<usize> {
         if let Ok(meta) = self.inner.read() {
             if meta.0 == FetchStatus::Done {
@@ -134,12 +330,171 @@ impl FetchProcess {
     }
 }
 
+/// Group of fetch processes reported as one - useful for loading screens.
+#[derive(Default, Clone)]
+pub struct BatchFetch {
+    processes: Vec<FetchProcess>,
+}
+
+impl BatchFetch {
+    pub fn new(processes: Vec<FetchProcess>) -> Self {
+        Self { processes }
+    }
+
+    pub fn processes(&self) -> &[FetchProcess] {
+        &self.processes
+    }
+
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    /// Average progress of all processes, where finished ones count as complete.
+    pub fn progress(&self) -> Scalar {
+        if self.processes.is_empty() {
+            return 1.0;
+        }
+        let total = self
+            .processes
+            .iter()
+            .map(|process| match process.status() {
+                FetchStatus::Empty => 0.0,
+                FetchStatus::InProgress(value) => value,
+                _ => 1.0,
+            })
+            .sum::<Scalar>();
+        total / self.processes.len() as Scalar
+    }
+
+    /// Canceled if any process got canceled, `Done` once all are done and `InProgress` with
+    /// average progress otherwise.
+    pub fn status(&self) -> FetchStatus {
+        let mut done = true;
+        let mut read = true;
+        for process in &self.processes {
+            match process.status() {
+                FetchStatus::Canceled(reason) => return FetchStatus::Canceled(reason),
+                FetchStatus::Done => read = false,
+                FetchStatus::Read => done = false,
+                _ => {
+                    done = false;
+                    read = false;
+                }
+            }
+        }
+        if done {
+            FetchStatus::Done
+        } else if read {
+            FetchStatus::Read
+        } else {
+            FetchStatus::InProgress(self.progress())
+        }
+    }
+
+    /// Payloads of all processes, in order of their paths, once all of them are done.
+    pub fn read(&self) -> Option<Vec<Vec<u8>>> {
+        if self.status() != FetchStatus::Done {
+            return None;
+        }
+        self.processes
+            .iter()
+            .map(|process| process.read())
+            .collect()
+    }
+
+    pub fn cancel(&mut self, reason: FetchCancelReason) {
+        for process in &mut self.processes {
+            if matches!(
+                process.status(),
+                FetchStatus::Empty | FetchStatus::InProgress(_)
+            ) {
+                process.cancel(reason);
+            }
+        }
+    }
+}
+
+/// In-flight processes of fetch engine, kept so they can be canceled by their id.
+#[derive(Default, Clone)]
+pub struct FetchProcessRegistry {
+    processes: HashMap<FetchProcessId, FetchProcess>,
+}
+
+impl FetchProcessRegistry {
+    pub fn len(&self) -> usize {
+        self.processes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.processes.is_empty()
+    }
+
+    pub fn contains(&self, id: FetchProcessId) -> bool {
+        self.processes.contains_key(&id)
+    }
+
+    /// Tracks process, forgetting ones that are not in progress anymore.
+    pub fn register(&mut self, process: &FetchProcess) {
+        self.processes.retain(|_, process| {
+            matches!(
+                process.status(),
+                FetchStatus::Empty | FetchStatus::InProgress(_)
+            )
+        });
+        self.processes.insert(process.id(), process.clone());
+    }
+
+    /// Cancels tracked process if it is still in progress.
+    pub fn cancel(&mut self, id: FetchProcessId, reason: FetchCancelReason) -> bool {
+        match self.processes.remove(&id) {
+            Some(mut process)
+                if matches!(
+                    process.status(),
+                    FetchStatus::Empty | FetchStatus::InProgress(_)
+                ) =>
+            {
+                process.cancel(reason);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
 pub trait FetchEngine: Send + Sync {
     fn fetch(&mut self, path: &str) -> Result<Box<FetchProcess>, FetchStatus>;
 
     fn cancel(&mut self, mut reader: FetchProcess) {
         reader.cancel(FetchCancelReason::User)
     }
+
+    /// Fetches all paths at once - paths that fail to start fetching get reported as canceled
+    /// processes.
+    fn fetch_many(&mut self, paths: &[&str]) -> BatchFetch {
+        BatchFetch::new(
+            paths
+                .iter()
+                .map(|path| match self.fetch(path) {
+                    Ok(process) => *process,
+                    Err(FetchStatus::Canceled(reason)) => FetchProcess::new_cancel(reason),
+                    Err(_) => FetchProcess::new_cancel(FetchCancelReason::Error),
+                })
+                .collect(),
+        )
+    }
+
+    /// Cancels in-flight process with `FetchCancelReason::User`. Supported only by engines that
+    /// track their processes.
+    ///
+    /// # Returns
+    /// `true` if process was found in progress and got canceled.
+    fn cancel_by_id(&mut self, _: FetchProcessId) -> bool {
+        false
+    }
 }
 
 #[cfg(test)]
@@ -170,4 +525,128 @@ mod tests {
         assert_eq!(reader.status(), FetchStatus::Read);
         assert_eq!(reader2.status(), FetchStatus::Read);
     }
+
+    #[test]
+    #[cfg(not(feature = "web"))]
+    fn test_fetch_timeout() {
+        let mut process = FetchProcess::new_start_with_timeout(Duration::from_millis(10));
+        let reader = process.clone();
+        assert!(!process.poll_timeout());
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(
+            reader.status(),
+            FetchStatus::Canceled(FetchCancelReason::Timeout)
+        );
+        assert!(process.poll_timeout());
+        process.done(vec![42]);
+        assert_eq!(
+            reader.status(),
+            FetchStatus::Canceled(FetchCancelReason::Timeout)
+        );
+        assert!(reader.read().is_none());
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "web", feature = "parallel")))]
+    fn test_fetch_synchronous_read_never_times_out() {
+        let mut engine = engines::fs::FsFetchEngine::new(&".").timeout(Some(Duration::ZERO));
+        let reader = engine.fetch("Cargo.toml").unwrap();
+        assert_eq!(reader.status(), FetchStatus::Done);
+        assert!(!reader.read().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fetch_read_range() {
+        let reader = FetchProcess::new_done(vec![1, 2, 3, 4, 5]);
+        assert_eq!(reader.read_range(0..2), Some(vec![1, 2]));
+        assert_eq!(reader.read_range(3..10), Some(vec![4, 5]));
+        assert_eq!(reader.read_range(8..10), Some(vec![]));
+        assert_eq!(reader.status(), FetchStatus::Done);
+        assert_eq!(reader.read(), Some(vec![1, 2, 3, 4, 5]));
+        assert!(reader.read_range(0..2).is_none());
+    }
+
+    #[test]
+    fn test_fetch_callbacks() {
+        let progress = Arc::new(Mutex::new(vec![]));
+        let done = Arc::new(Mutex::new(vec![]));
+        let mut process = FetchProcess::new_start();
+        let reader = process.clone();
+        {
+            let progress = progress.clone();
+            process.on_progress(move |value| progress.lock().unwrap().push(value));
+        }
+        {
+            let done = done.clone();
+            process.on_done(move |bytes| done.lock().unwrap().push(bytes.to_vec()));
+        }
+        process.progress(0.5);
+        process.progress(0.75);
+        process.done(vec![1, 2]);
+        assert_eq!(*progress.lock().unwrap(), vec![0.5, 0.75]);
+        assert_eq!(*done.lock().unwrap(), vec![vec![1, 2]]);
+        {
+            let done = done.clone();
+            process.on_done(move |bytes| done.lock().unwrap().push(bytes.to_vec()));
+        }
+        assert_eq!(done.lock().unwrap().len(), 2);
+        assert_eq!(reader.read(), Some(vec![1, 2]));
+        {
+            let done = done.clone();
+            process.on_done(move |bytes| done.lock().unwrap().push(bytes.to_vec()));
+        }
+        assert_eq!(done.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_batch_fetch() {
+        let mut engine = engines::map::MapFetchEngine::default()
+            .insert("a", vec![1])
+            .insert("b", vec![2])
+            .insert("c", vec![3]);
+        let batch = engine.fetch_many(&["a", "b", "c"]);
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.progress(), 1.0);
+        assert_eq!(batch.status(), FetchStatus::Done);
+        assert_eq!(batch.read(), Some(vec![vec![1], vec![2], vec![3]]));
+        assert_eq!(batch.status(), FetchStatus::Read);
+        assert!(batch.read().is_none());
+
+        let batch = engine.fetch_many(&["a", "d"]);
+        assert_eq!(
+            batch.status(),
+            FetchStatus::Canceled(FetchCancelReason::Error)
+        );
+
+        let mut a = FetchProcess::new_start();
+        let mut b = FetchProcess::new_start();
+        let batch = BatchFetch::new(vec![a.clone(), b.clone(), FetchProcess::new_done(vec![3])]);
+        a.progress(0.5);
+        assert_eq!(batch.status(), FetchStatus::InProgress(0.5));
+        a.done(vec![1]);
+        b.progress(0.5);
+        assert_eq!(batch.status(), FetchStatus::InProgress(2.5 / 3.0));
+        b.done(vec![2]);
+        assert_eq!(batch.status(), FetchStatus::Done);
+        assert_eq!(batch.read(), Some(vec![vec![1], vec![2], vec![3]]));
+    }
+
+    #[test]
+    fn test_fetch_cancel_callback() {
+        let canceled = Arc::new(Mutex::new(vec![]));
+        let mut process = FetchProcess::new_start();
+        {
+            let canceled = canceled.clone();
+            process.on_cancel(move |reason| canceled.lock().unwrap().push(reason));
+        }
+        process.cancel(FetchCancelReason::User);
+        assert_eq!(*canceled.lock().unwrap(), vec![FetchCancelReason::User]);
+
+        let mut process = FetchProcess::new_done(vec![1]);
+        {
+            let canceled = canceled.clone();
+            process.on_cancel(move |reason| canceled.lock().unwrap().push(reason));
+        }
+        assert_eq!(canceled.lock().unwrap().len(), 1);
+    }
 }
@@ -15,6 +15,8 @@ pub mod state;
 pub mod localization;
 pub mod ecs;
 pub mod jobs;
+pub mod offload;
+pub mod replay;
 pub mod scripting;
 pub mod storage;
 pub mod utils;
@@ -54,14 +56,16 @@ pub mod prelude {
             *,
         },
         fetch::{
-            engines::{map::*, *},
+            engines::{caching::*, chain::*, map::*, *},
             *,
         },
         id::*,
         jobs::*,
         localization::*,
         log::*,
+        offload::{engines::*, *},
         prefab::*,
+        replay::*,
         scripting::*,
         state::*,
         storage::{
@@ -72,7 +76,7 @@ pub mod prelude {
         Scalar, *,
     };
     #[cfg(not(feature = "web"))]
-    pub use crate::{fetch::engines::fs::*, storage::engines::fs::*};
+    pub use crate::{fetch::engines::fs::*, offload::engines::thread::*, storage::engines::fs::*};
 }
 
 #[cfg(feature = "scalar64")]
@@ -1,30 +1,66 @@
-use std::sync::RwLock;
+use std::{fmt, sync::RwLock};
 
 lazy_static! {
     static ref LOGGER: RwLock<Option<Box<dyn Logger>>> = RwLock::new(None);
+    static ref LOG_LEVEL: RwLock<Log> = RwLock::new(Log::Trace);
+    static ref LOG_SINKS: RwLock<Vec<Box<dyn LogSink>>> = RwLock::new(vec![]);
 }
 
-#[derive(Debug, Copy, Clone)]
+/// Log level, ordered from the least to the most important one.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum Log {
+    Trace,
+    Debug,
     Info,
     Warning,
     Error,
-    Debug,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogRecord {
+    pub level: Log,
+    pub module_path: &'static str,
+    pub file: &'static str,
+    pub line: u32,
+    pub message: String,
+}
+
+impl fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}: {} | {}]:\n{}",
+            self.file, self.line, self.module_path, self.message
+        )
+    }
 }
 
 pub trait Logger: Send + Sync {
-    fn log(&mut self, mode: Log, message: String);
+    fn log(&mut self, record: &LogRecord);
+}
+
+/// Additional receiver of log records (on-screen console, ring buffer, file), that gets them
+/// next to the logger.
+pub trait LogSink: Send + Sync {
+    fn log(&self, record: &LogRecord);
 }
 
 pub struct DefaultLogger;
 
 impl Logger for DefaultLogger {
-    fn log(&mut self, mode: Log, message: String) {
-        match mode {
-            Log::Info => println!("[INFO] {}", message),
-            Log::Warning => eprintln!("[WARNING] {}", message),
-            Log::Error => eprintln!("[ERROR] {}", message),
-            Log::Debug => eprintln!("[DEBUG] {}", message),
+    fn log(&mut self, record: &LogRecord) {
+        LogSink::log(self, record);
+    }
+}
+
+impl LogSink for DefaultLogger {
+    fn log(&self, record: &LogRecord) {
+        match record.level {
+            Log::Trace => eprintln!("[TRACE] {}", record),
+            Log::Debug => eprintln!("[DEBUG] {}", record),
+            Log::Info => println!("[INFO] {}", record),
+            Log::Warning => eprintln!("[WARNING] {}", record),
+            Log::Error => eprintln!("[ERROR] {}", record),
         }
     }
 }
@@ -38,10 +74,46 @@ where
     }
 }
 
-pub fn logger_log(mode: Log, message: String) {
+pub fn add_log_sink(sink: Box<dyn LogSink>) {
+    if let Ok(mut sinks) = LOG_SINKS.write() {
+        sinks.push(sink);
+    }
+}
+
+pub fn clear_log_sinks() {
+    if let Ok(mut sinks) = LOG_SINKS.write() {
+        sinks.clear();
+    }
+}
+
+/// Minimal level of records that get logged.
+pub fn log_level() -> Log {
+    LOG_LEVEL.read().map(|level| *level).unwrap_or(Log::Trace)
+}
+
+/// Sets minimal level of records that get logged - less important ones are suppressed.
+pub fn set_log_level(level: Log) {
+    if let Ok(mut current) = LOG_LEVEL.write() {
+        *current = level;
+    }
+}
+
+pub fn log_enabled(level: Log) -> bool {
+    level >= log_level()
+}
+
+pub fn logger_log(record: LogRecord) {
+    if !log_enabled(record.level) {
+        return;
+    }
     if let Ok(mut logger) = LOGGER.write() {
         if let Some(ref mut logger) = *logger {
-            logger.log(mode, message);
+            logger.log(&record);
+        }
+    }
+    if let Ok(sinks) = LOG_SINKS.read() {
+        for sink in sinks.iter() {
+            sink.log(&record);
         }
     }
 }
@@ -49,64 +121,60 @@ pub fn logger_log(mode: Log, message: String) {
 #[macro_export]
 macro_rules! log {
     ($lvl:expr, $($arg:tt)*) => ({
-        $crate::log::logger_log($lvl, format!(
-            "[{}: {} | {}]:\n{}",
-            file!(),
-            line!(),
-            module_path!(),
-            format_args!($($arg)*)
-        ));
+        let level = $lvl;
+        if $crate::log::log_enabled(level) {
+            $crate::log::logger_log($crate::log::LogRecord {
+                level,
+                module_path: module_path!(),
+                file: file!(),
+                line: line!(),
+                message: format!($($arg)*),
+            });
+        }
     })
 }
 
+#[macro_export]
+macro_rules! log_trace {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Trace, $($arg)*))
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Debug, $($arg)*))
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Info, $($arg)*))
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Warning, $($arg)*))
+}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Error, $($arg)*))
+}
+
 #[macro_export]
 macro_rules! info {
-    ($($arg:tt)*) => ({
-        $crate::log::logger_log($crate::log::Log::Info, format!(
-            "[{}: {} | {}]:\n{}",
-            file!(),
-            line!(),
-            module_path!(),
-            format_args!($($arg)*)
-        ));
-    })
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Info, $($arg)*))
 }
 
 #[macro_export]
 macro_rules! warn {
-    ($($arg:tt)*) => ({
-        $crate::log::logger_log($crate::log::Log::Warning, format!(
-            "[{}: {} | {}]:\n{}",
-            file!(),
-            line!(),
-            module_path!(),
-            format_args!($($arg)*)
-        ));
-    })
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Warning, $($arg)*))
 }
 
 #[macro_export]
 macro_rules! error {
-    ($($arg:tt)*) => ({
-        $crate::log::logger_log($crate::log::Log::Error, format!(
-            "[{}: {} | {}]:\n{}",
-            file!(),
-            line!(),
-            module_path!(),
-            format_args!($($arg)*)
-        ));
-    })
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Error, $($arg)*))
 }
 
 #[macro_export]
 macro_rules! debug {
-    ($($arg:tt)*) => ({
-        $crate::log::logger_log($crate::log::Log::Debug, format!(
-            "[{}: {} | {}]:\n{}",
-            file!(),
-            line!(),
-            module_path!(),
-            format_args!($($arg)*)
-        ));
-    })
+    ($($arg:tt)*) => ($crate::log!($crate::log::Log::Debug, $($arg)*))
 }
@@ -0,0 +1,2 @@
+#[cfg(not(feature = "web"))]
+pub mod thread;
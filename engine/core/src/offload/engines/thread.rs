@@ -0,0 +1,38 @@
+#![cfg(not(feature = "web"))]
+
+use crate::{
+    fetch::{FetchCancelReason, FetchProcess, FetchStatus},
+    offload::{OffloadEngine, OffloadTaskRegistry},
+};
+
+#[derive(Default, Clone)]
+pub struct ThreadOffloadEngine {
+    pub registry: OffloadTaskRegistry,
+}
+
+impl ThreadOffloadEngine {
+    pub fn new(registry: OffloadTaskRegistry) -> Self {
+        Self { registry }
+    }
+}
+
+impl OffloadEngine for ThreadOffloadEngine {
+    fn offload(&mut self, task: &str, input: Vec<u8>) -> Result<Box<FetchProcess>, FetchStatus> {
+        let task = match self.registry.get(task) {
+            Some(task) => task,
+            None => return Err(FetchStatus::Canceled(FetchCancelReason::Error)),
+        };
+        let process = FetchProcess::new_start();
+        let mut p = process.clone();
+        let spawned = std::thread::Builder::new()
+            .name("oxygengine-offload".to_owned())
+            .spawn(move || match task(&input) {
+                Ok(bytes) => p.done(bytes),
+                Err(_) => p.cancel(FetchCancelReason::Error),
+            });
+        match spawned {
+            Ok(_) => Ok(Box::new(process)),
+            Err(_) => Err(FetchStatus::Canceled(FetchCancelReason::Error)),
+        }
+    }
+}
@@ -0,0 +1,111 @@
+pub mod engines;
+
+use crate::fetch::{FetchCancelReason, FetchProcess, FetchStatus};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+
+/// Task runs on serialized input and produces serialized output or error message.
+pub type OffloadTask = fn(&[u8]) -> Result<Vec<u8>, String>;
+
+#[derive(Default, Clone)]
+pub struct OffloadTaskRegistry {
+    tasks: HashMap<String, OffloadTask>,
+}
+
+impl OffloadTaskRegistry {
+    pub fn register(&mut self, name: &str, task: OffloadTask) {
+        self.tasks.insert(name.to_owned(), task);
+    }
+
+    pub fn with(mut self, name: &str, task: OffloadTask) -> Self {
+        self.register(name, task);
+        self
+    }
+
+    pub fn unregister(&mut self, name: &str) {
+        self.tasks.remove(name);
+    }
+
+    pub fn get(&self, name: &str) -> Option<OffloadTask> {
+        self.tasks.get(name).copied()
+    }
+
+    pub fn run(&self, name: &str, input: &[u8]) -> Result<Vec<u8>, String> {
+        match self.get(name) {
+            Some(task) => task(input),
+            None => Err(format!("There is no offload task registered: {}", name)),
+        }
+    }
+}
+
+/// Runs heavy tasks outside of main thread - task result arrives through
+/// returned process, the same way as fetched data does.
+pub trait OffloadEngine: Send + Sync {
+    fn offload(&mut self, task: &str, input: Vec<u8>) -> Result<Box<FetchProcess>, FetchStatus>;
+
+    fn offload_data<T>(&mut self, task: &str, input: &T) -> Result<Box<FetchProcess>, FetchStatus>
+    where
+        Self: Sized,
+        T: Serialize,
+    {
+        match offload_encode(input) {
+            Ok(input) => self.offload(task, input),
+            Err(_) => Err(FetchStatus::Canceled(FetchCancelReason::Error)),
+        }
+    }
+}
+
+pub fn offload_encode<T>(data: &T) -> Result<Vec<u8>, String>
+where
+    T: Serialize,
+{
+    bincode::serialize(data).map_err(|error| error.to_string())
+}
+
+pub fn offload_decode<T>(bytes: &[u8]) -> Result<T, String>
+where
+    T: DeserializeOwned,
+{
+    bincode::deserialize(bytes).map_err(|error| error.to_string())
+}
+
+/// Reads completed offload process result and decodes it.
+pub fn offload_read<T>(process: &FetchProcess) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    offload_decode(&process.read()?).ok()
+}
+
+#[cfg(all(test, not(feature = "web")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_offload() {
+        fn fib(input: &[u8]) -> Result<Vec<u8>, String> {
+            let n = offload_decode::<usize>(input)?;
+            let mut x = (1_usize, 1_usize);
+            for _ in 0..n {
+                x = (x.1, x.0 + x.1)
+            }
+            let background = std::thread::current().name() == Some("oxygengine-offload");
+            offload_encode(&(x.0, background))
+        }
+
+        let registry = OffloadTaskRegistry::default().with("fib", fib);
+        let mut engine = engines::thread::ThreadOffloadEngine::new(registry);
+        let process = engine.offload_data("fib", &50_usize).unwrap();
+        loop {
+            match process.status() {
+                FetchStatus::InProgress(_) => std::thread::yield_now(),
+                _ => break,
+            }
+        }
+        assert_eq!(process.status(), FetchStatus::Done);
+        let (result, background) = offload_read::<(usize, bool)>(&process).unwrap();
+        assert_eq!(result, 20365011074);
+        assert!(background);
+        assert!(engine.offload("unknown", vec![]).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
     app::{AppBuilder, AppLifeCycle},
-    assets::{asset::AssetId, database::AssetsDatabase, protocols::prefab::PrefabAsset},
+    assets::{
+        asset::AssetId,
+        database::{AssetsDatabase, LoadStatus},
+        protocols::prefab::PrefabAsset,
+    },
     ecs::{
         components::{Name, NonPersistent, NonPersistentPrefabProxy, Tag},
         hierarchy::{Parent, ParentPrefabProxy},
@@ -8,11 +12,16 @@ use crate::{
         pipeline::{PipelineBuilder, PipelineBuilderError},
         Universe,
     },
+    fetch::{FetchCancelReason, FetchStatus},
     state::StateToken,
+    Scalar,
 };
 use hecs::*;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex, RwLock},
+};
 
 pub use serde_json::Value as PrefabValue;
 
@@ -27,6 +36,14 @@ type ComponentFactory = Box<
         + Sync,
 >;
 
+type PrefabInstanceCallback = Box<dyn FnOnce(Result<&[Entity], FetchCancelReason>) + Send>;
+
+type ComponentSerializer = Box<
+    dyn Fn(EntityRef, &HashMap<Entity, String>) -> Option<Result<PrefabValue, PrefabError>>
+        + Send
+        + Sync,
+>;
+
 #[derive(Debug)]
 pub enum PrefabError {
     CouldNotSerialize(String),
@@ -91,6 +108,12 @@ where
         named_entities: &HashMap<String, Entity>,
         state_token: StateToken,
     ) -> Result<Self, PrefabError>;
+
+    /// Converts component back into its proxy when capturing world into prefab.
+    /// Returning `None` means component is not serializable and gets skipped.
+    fn to_proxy_with_extras(&self, _entity_names: &HashMap<Entity, String>) -> Option<P> {
+        None
+    }
 }
 
 impl Prefab for PrefabValue {}
@@ -131,10 +154,104 @@ pub struct PrefabSceneEntityData {
 
 impl Prefab for PrefabSceneEntityData {}
 
+/// Handle to prefab scene instance requested with `PrefabManager::instantiate_async`.
+/// Its status mirrors status of prefab asset fetch process until asset gets loaded, then
+/// it becomes `FetchStatus::Done` once entities are spawned.
+#[derive(Clone, Default)]
+pub struct PrefabInstanceHandle {
+    inner: Arc<RwLock<(FetchStatus, Vec<Entity>)>>,
+    callbacks: Arc<Mutex<Vec<PrefabInstanceCallback>>>,
+}
+
+impl PrefabInstanceHandle {
+    pub fn status(&self) -> FetchStatus {
+        self.inner.read().map(|meta| meta.0).unwrap_or_default()
+    }
+
+    /// Spawned scene entities, available once instance is done.
+    pub fn entities(&self) -> Option<Vec<Entity>> {
+        if let Ok(meta) = self.inner.read() {
+            if meta.0 == FetchStatus::Done {
+                return Some(meta.1.clone());
+            }
+        }
+        None
+    }
+
+    /// Registers callback called exactly once with spawned entities when instance is done or
+    /// with reason when it gets canceled - immediately if that already happened.
+    pub fn on_done<F>(&mut self, callback: F)
+    where
+        F: FnOnce(Result<&[Entity], FetchCancelReason>) + Send + 'static,
+    {
+        // callbacks are locked before reading status, so instance cannot finish in between and
+        // miss this callback. Locks are released before calling it.
+        let finished = {
+            let mut callbacks = match self.callbacks.lock() {
+                Ok(callbacks) => callbacks,
+                Err(_) => return,
+            };
+            let meta = match self.inner.read() {
+                Ok(meta) => meta,
+                Err(_) => return,
+            };
+            match meta.0 {
+                FetchStatus::Done => Ok(meta.1.clone()),
+                FetchStatus::Canceled(reason) => Err(reason),
+                _ => {
+                    callbacks.push(Box::new(callback));
+                    return;
+                }
+            }
+        };
+        match finished {
+            Ok(entities) => callback(Ok(&entities)),
+            Err(reason) => callback(Err(reason)),
+        }
+    }
+
+    fn progress(&mut self, value: Scalar) {
+        if let Ok(mut meta) = self.inner.write() {
+            meta.0 = FetchStatus::InProgress(value);
+        }
+    }
+
+    fn done(&mut self, entities: Vec<Entity>) {
+        if let Ok(mut meta) = self.inner.write() {
+            *meta = (FetchStatus::Done, entities.clone());
+        } else {
+            return;
+        }
+        self.finish_callbacks(Ok(&entities));
+    }
+
+    fn cancel(&mut self, reason: FetchCancelReason) {
+        if let Ok(mut meta) = self.inner.write() {
+            *meta = (FetchStatus::Canceled(reason), vec![]);
+        } else {
+            return;
+        }
+        self.finish_callbacks(Err(reason));
+    }
+
+    /// Called with no lock held, so callbacks can freely query this handle.
+    fn finish_callbacks(&self, result: Result<&[Entity], FetchCancelReason>) {
+        let callbacks = match self.callbacks.lock() {
+            Ok(mut callbacks) => std::mem::take(&mut *callbacks),
+            Err(_) => return,
+        };
+        for callback in callbacks {
+            callback(result);
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct PrefabManager {
     component_factory: HashMap<String, ComponentFactory>,
+    component_serializer: HashMap<String, ComponentSerializer>,
     templates: HashMap<String, PrefabScene>,
+    pending_instances: Vec<(String, PrefabInstanceHandle)>,
 }
 
 impl PrefabManager {
@@ -153,6 +270,10 @@ impl PrefabManager {
                 Ok(())
             }),
         );
+        self.component_serializer.insert(
+            name.to_owned(),
+            Box::new(|entity, _| entity.get::<&T>().map(|component| component.to_prefab())),
+        );
     }
 
     pub fn register_component_factory_proxy<T, P>(&mut self, name: &str)
@@ -168,10 +289,20 @@ impl PrefabManager {
                 Ok(())
             }),
         );
+        self.component_serializer.insert(
+            name.to_owned(),
+            Box::new(|entity, entity_names| {
+                entity
+                    .get::<&T>()
+                    .and_then(|component| component.to_proxy_with_extras(entity_names))
+                    .map(|proxy| proxy.to_prefab())
+            }),
+        );
     }
 
     pub fn unregister_component_factory(&mut self, name: &str) {
         self.component_factory.remove(name);
+        self.component_serializer.remove(name);
     }
 
     pub fn register_scene_template(&mut self, prefab: PrefabScene) -> Result<(), PrefabError> {
@@ -225,6 +356,114 @@ impl PrefabManager {
             .0)
     }
 
+    /// Requests prefab scene instance from prefab asset path (for example:
+    /// `prefab://enemy.yaml`). Asset gets loaded if it is not already and entities are spawned
+    /// by prefab system once it is ready, so handle should be polled or given callback.
+    pub fn instantiate_async(&mut self, path: &str) -> PrefabInstanceHandle {
+        let handle = PrefabInstanceHandle::default();
+        self.pending_instances
+            .push((path.to_owned(), handle.clone()));
+        handle
+    }
+
+    pub fn pending_instances_count(&self) -> usize {
+        self.pending_instances.len()
+    }
+
+    /// Instantiates template with sparse overrides merged onto its entities components data.
+    /// Overrides are mapping of entity uid to mapping of component name to component data, for
+    /// example: `{ "player": { "Health": 200 } }`. Mappings are merged deeply and any other
+    /// value replaces base one. Only data entities of given template can be overridden, nested
+    /// templates stay untouched.
+    pub fn instantiate_with_overrides(
+        &mut self,
+        name: &str,
+        overrides: &PrefabValue,
+        universe: &mut Universe,
+    ) -> Result<Vec<Entity>, PrefabError> {
+        let state_token = universe
+            .expect_resource::<AppLifeCycle>()
+            .current_state_token();
+        let mut world = universe.world_mut();
+        let mut changes = universe.expect_resource_mut::<EntityChanges>();
+        self.instantiate_with_overrides_direct(
+            name,
+            overrides,
+            &mut world,
+            &mut changes,
+            state_token,
+        )
+    }
+
+    pub fn instantiate_with_overrides_direct(
+        &mut self,
+        name: &str,
+        overrides: &PrefabValue,
+        world: &mut World,
+        changes: &mut EntityChanges,
+        state_token: StateToken,
+    ) -> Result<Vec<Entity>, PrefabError> {
+        let mut prefab = match self.templates.get(name) {
+            Some(prefab) => prefab.clone(),
+            None => {
+                return Err(PrefabError::Custom(format!(
+                    "There is no template registered: {}",
+                    name
+                )))
+            }
+        };
+        let overrides = match overrides {
+            PrefabValue::Object(overrides) => overrides,
+            PrefabValue::Null => return self.instantiate_direct(name, world, changes, state_token),
+            _ => {
+                return Err(PrefabError::Custom(
+                    "Prefab overrides must be a mapping of entity uids".to_owned(),
+                ))
+            }
+        };
+        for (uid, components) in overrides {
+            let data = prefab.entities.iter_mut().find_map(|entity| match entity {
+                PrefabSceneEntity::Data(data) if data.uid.as_ref() == Some(uid) => Some(data),
+                _ => None,
+            });
+            let data = match data {
+                Some(data) => data,
+                None => {
+                    return Err(PrefabError::Custom(format!(
+                        "There is no entity: {} in template: {}",
+                        uid, name
+                    )))
+                }
+            };
+            let components = match components {
+                PrefabValue::Object(components) => components,
+                _ => {
+                    return Err(PrefabError::Custom(format!(
+                        "Overrides of entity: {} must be a mapping of components",
+                        uid
+                    )))
+                }
+            };
+            for (key, value) in components {
+                merge_prefab_values(
+                    data.components
+                        .entry(key.to_owned())
+                        .or_insert(PrefabValue::Null),
+                    value,
+                );
+            }
+        }
+        Ok(self
+            .load_scene_from_prefab_inner(
+                &prefab,
+                world,
+                changes,
+                state_token,
+                &Default::default(),
+            )?
+            .0)
+    }
+
     pub fn load_scene_from_prefab(
         &mut self,
         prefab: &PrefabScene,
@@ -253,6 +492,85 @@ impl PrefabManager {
             .0)
     }
 
+    /// Captures world entities into prefab scene document (`PrefabScene` serialized to
+    /// `PrefabValue`), ready to be saved and loaded back with `load_scene_from_prefab`.
+    pub fn capture_world(&self, world: &World) -> Result<PrefabValue, PrefabError> {
+        self.capture_world_scene(world)?.to_prefab()
+    }
+
+    /// Captures world entities into prefab scene that can be loaded back with
+    /// `load_scene_from_prefab`. Only components with registered factories are
+    /// serialized and entities without any of them are skipped, unless they are
+    /// parents of captured entities.
+    pub fn capture_world_scene(&self, world: &World) -> Result<PrefabScene, PrefabError> {
+        let entity_names = world
+            .iter()
+            .map(|entity_ref| {
+                let entity = entity_ref.entity();
+                (entity, entity.id().to_string())
+            })
+            .collect::<HashMap<_, _>>();
+        let mut captured = HashMap::with_capacity(entity_names.len());
+        for entity_ref in world.iter() {
+            let mut components = HashMap::new();
+            for (key, serializer) in &self.component_serializer {
+                if let Some(data) = serializer(entity_ref, &entity_names) {
+                    components.insert(key.to_owned(), data?);
+                }
+            }
+            captured.insert(entity_ref.entity(), components);
+        }
+        let parents = world
+            .query::<&Parent>()
+            .iter()
+            .filter(|(_, parent)| entity_names.contains_key(&parent.0))
+            .map(|(entity, parent)| (entity, parent.0))
+            .collect::<HashMap<_, _>>();
+        let mut keep = captured
+            .iter()
+            .filter(|(_, components)| !components.is_empty())
+            .map(|(entity, _)| *entity)
+            .collect::<HashSet<_>>();
+        let mut stack = keep.iter().copied().collect::<Vec<_>>();
+        while let Some(entity) = stack.pop() {
+            if let Some(parent) = parents.get(&entity) {
+                if keep.insert(*parent) {
+                    stack.push(*parent);
+                }
+            }
+        }
+        // NOTE: parents have to be spawned before their children.
+        let depth = |mut entity| {
+            let mut result = 0;
+            while let Some(parent) = parents.get(&entity) {
+                entity = *parent;
+                result += 1;
+                if result > parents.len() {
+                    break;
+                }
+            }
+            result
+        };
+        let mut entities = keep
+            .into_iter()
+            .map(|entity| (depth(entity), entity))
+            .collect::<Vec<_>>();
+        entities.sort_by_key(|(depth, entity)| (*depth, entity.id()));
+        Ok(PrefabScene {
+            template_name: None,
+            dependencies: vec![],
+            entities: entities
+                .into_iter()
+                .map(|(_, entity)| {
+                    PrefabSceneEntity::Data(PrefabSceneEntityData {
+                        uid: entity_names.get(&entity).cloned(),
+                        components: captured.remove(&entity).unwrap_or_default(),
+                    })
+                })
+                .collect(),
+        })
+    }
+
     fn load_scene_from_prefab_inner(
         &mut self,
         prefab: &PrefabScene,
@@ -343,18 +661,34 @@ impl PrefabManager {
     }
 }
 
+/// Merges overrides onto base value: mappings are merged deeply, any other override value
+/// replaces base one.
+pub fn merge_prefab_values(base: &mut PrefabValue, overrides: &PrefabValue) {
+    match (base, overrides) {
+        (PrefabValue::Object(base), PrefabValue::Object(overrides)) => {
+            for (key, value) in overrides {
+                merge_prefab_values(
+                    base.entry(key.to_owned()).or_insert(PrefabValue::Null),
+                    value,
+                );
+            }
+        }
+        (base, overrides) => *base = overrides.clone(),
+    }
+}
+
 #[derive(Default)]
 pub struct PrefabSystemCache {
     templates_table: HashMap<AssetId, String>,
 }
 pub type PrefabSystemResources<'a> = (
-    &'a AssetsDatabase,
+    &'a mut AssetsDatabase,
     &'a mut PrefabManager,
     &'a mut PrefabSystemCache,
 );
 
 pub fn prefab_system(universe: &mut Universe) {
-    let (assets, mut prefabs, mut cache) = universe.query_resources::<PrefabSystemResources>();
+    let (mut assets, mut prefabs, mut cache) = universe.query_resources::<PrefabSystemResources>();
 
     for id in assets.lately_loaded_protocol("prefab") {
         let id = *id;
@@ -375,6 +709,67 @@ pub fn prefab_system(universe: &mut Universe) {
             prefabs.unregister_scene_template(&name);
         }
     }
+
+    if prefabs.pending_instances.is_empty() {
+        return;
+    }
+    let state_token = universe
+        .expect_resource::<AppLifeCycle>()
+        .current_state_token();
+    let mut world = universe.world_mut();
+    let mut changes = universe.expect_resource_mut::<EntityChanges>();
+    let pending = std::mem::take(&mut prefabs.pending_instances);
+    for (path, mut handle) in pending {
+        if let Some(asset) = assets.asset_by_path(&path) {
+            let prefab = match asset.get::<PrefabAsset>() {
+                Some(asset) => asset.get().clone(),
+                None => {
+                    error!("Trying to instantiate non-prefab asset: {}", path);
+                    handle.cancel(FetchCancelReason::Error);
+                    continue;
+                }
+            };
+            match prefabs.load_scene_from_prefab_direct(
+                &prefab,
+                &mut world,
+                &mut changes,
+                state_token,
+            ) {
+                Ok(entities) => handle.done(entities),
+                Err(error) => {
+                    error!("Could not instantiate prefab: {} - {:?}", path, error);
+                    handle.cancel(FetchCancelReason::Error);
+                }
+            }
+            continue;
+        }
+        match (handle.status(), assets.loading_status(&path)) {
+            (_, Some(FetchStatus::InProgress(value))) => handle.progress(value),
+            (_, Some(FetchStatus::Done)) => handle.progress(1.0),
+            (_, Some(FetchStatus::Canceled(reason))) => {
+                handle.cancel(reason);
+                continue;
+            }
+            (_, Some(_)) => {}
+            (FetchStatus::Empty, _) => match assets.load(&path) {
+                Ok(_) => handle.progress(0.0),
+                Err(LoadStatus::FetchError(FetchStatus::Canceled(reason))) => {
+                    handle.cancel(reason);
+                    continue;
+                }
+                Err(_) => {
+                    handle.cancel(FetchCancelReason::Error);
+                    continue;
+                }
+            },
+            // NOTE: asset is neither loaded nor being loaded anymore, so its loading has failed.
+            _ => {
+                handle.cancel(FetchCancelReason::Error);
+                continue;
+            }
+        }
+        prefabs.pending_instances.push((path, handle));
+    }
 }
 
 pub fn bundle_installer<PB, PMS>(
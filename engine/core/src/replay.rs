@@ -0,0 +1,280 @@
+use crate::{
+    app::{App, AppLifeCycle, AppTimer},
+    ecs::Universe,
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, fmt::Debug, time::Duration};
+
+/// Timer that advances by the same delta time on every tick.
+#[derive(Debug, Clone)]
+pub struct FixedAppTimer {
+    delta_time: Duration,
+    delta_time_seconds: Scalar,
+    time: Duration,
+    time_seconds: Scalar,
+    ticks: usize,
+}
+
+impl FixedAppTimer {
+    pub fn new(delta_time: Duration) -> Self {
+        #[cfg(feature = "scalar64")]
+        let delta_time_seconds = delta_time.as_secs_f64();
+        #[cfg(not(feature = "scalar64"))]
+        let delta_time_seconds = delta_time.as_secs_f32();
+        Self {
+            delta_time,
+            delta_time_seconds,
+            time: Duration::default(),
+            time_seconds: 0.0,
+            ticks: 0,
+        }
+    }
+}
+
+impl AppTimer for FixedAppTimer {
+    fn tick(&mut self) {
+        self.time += self.delta_time;
+        #[cfg(feature = "scalar64")]
+        let d = self.time.as_secs_f64();
+        #[cfg(not(feature = "scalar64"))]
+        let d = self.time.as_secs_f32();
+        self.time_seconds = d;
+        self.ticks = self.ticks.wrapping_add(1);
+    }
+
+    fn time(&self) -> Duration {
+        self.time
+    }
+
+    fn time_seconds(&self) -> Scalar {
+        self.time_seconds
+    }
+
+    fn delta_time(&self) -> Duration {
+        self.delta_time
+    }
+
+    fn delta_time_seconds(&self) -> Scalar {
+        self.delta_time_seconds
+    }
+
+    fn ticks(&self) -> usize {
+        self.ticks
+    }
+}
+
+/// Seeded pseudo-random numbers generator resource (SplitMix64), for gameplay code that has to
+/// stay deterministic during replays.
+#[derive(Debug, Clone)]
+pub struct ReplayRng {
+    seed: u64,
+    state: u64,
+}
+
+impl Default for ReplayRng {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl ReplayRng {
+    pub fn new(seed: u64) -> Self {
+        Self { seed, state: seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in range `[0; 1)`.
+    pub fn next_scalar(&mut self) -> Scalar {
+        (self.next_u64() >> 11) as Scalar / (1u64 << 53) as Scalar
+    }
+
+    pub fn range(&mut self, from: Scalar, to: Scalar) -> Scalar {
+        from + (to - from) * self.next_scalar()
+    }
+}
+
+/// Single input change recorded at given frame.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInputEvent {
+    pub frame: usize,
+    pub name: String,
+    pub value: Scalar,
+}
+
+/// Recorded input timeline.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTimeline {
+    #[serde(default)]
+    pub events: Vec<ReplayInputEvent>,
+}
+
+impl ReplayTimeline {
+    pub fn record(&mut self, frame: usize, name: impl ToString, value: Scalar) {
+        self.events.push(ReplayInputEvent {
+            frame,
+            name: name.to_string(),
+            value,
+        });
+    }
+
+    pub fn with(mut self, frame: usize, name: impl ToString, value: Scalar) -> Self {
+        self.record(frame, name, value);
+        self
+    }
+
+    pub fn events_at(&self, frame: usize) -> impl Iterator<Item = &ReplayInputEvent> {
+        self.events.iter().filter(move |event| event.frame == frame)
+    }
+}
+
+/// Resource with input state played back from replay timeline - values stay the same until
+/// timeline changes them.
+#[derive(Debug, Default, Clone)]
+pub struct ReplayInput {
+    frame: usize,
+    values: HashMap<String, Scalar>,
+}
+
+impl ReplayInput {
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    pub fn value(&self, name: &str) -> Scalar {
+        self.values.get(name).copied().unwrap_or_default()
+    }
+}
+
+/// Runs app with fixed timestep, seeded RNG and recorded input, producing snapshots of the
+/// universe after every frame.
+#[derive(Debug, Clone)]
+pub struct ReplayHarness {
+    pub seed: u64,
+    pub delta_time: Duration,
+    pub timeline: ReplayTimeline,
+}
+
+impl ReplayHarness {
+    pub fn new(seed: u64, delta_time: Duration, timeline: ReplayTimeline) -> Self {
+        Self {
+            seed,
+            delta_time,
+            timeline,
+        }
+    }
+
+    pub fn run<T, F>(&self, mut app: App, frames: usize, mut snapshot: F) -> Vec<T>
+    where
+        F: FnMut(&Universe) -> T,
+    {
+        {
+            let universe = app
+                .multiverse
+                .default_universe_mut()
+                .expect("Replayed app has no default universe");
+            universe.insert_resource(ReplayRng::new(self.seed));
+            universe.insert_resource(ReplayInput::default());
+            universe.expect_resource_mut::<AppLifeCycle>().timer =
+                Box::new(FixedAppTimer::new(self.delta_time));
+        }
+        (0..frames)
+            .map(|frame| {
+                {
+                    let universe = app.multiverse.default_universe_mut().unwrap();
+                    let mut input = universe.expect_resource_mut::<ReplayInput>();
+                    input.frame = frame;
+                    for event in self.timeline.events_at(frame) {
+                        input.values.insert(event.name.to_owned(), event.value);
+                    }
+                }
+                app.process();
+                snapshot(app.multiverse.default_universe().unwrap())
+            })
+            .collect()
+    }
+}
+
+/// Finds first frame at which two replays differ.
+pub fn replay_divergence<T: PartialEq>(a: &[T], b: &[T]) -> Option<usize> {
+    a.iter()
+        .zip(b.iter())
+        .position(|(a, b)| a != b)
+        .or_else(|| {
+            if a.len() != b.len() {
+                Some(a.len().min(b.len()))
+            } else {
+                None
+            }
+        })
+}
+
+pub fn assert_replays_match<T: PartialEq + Debug>(a: &[T], b: &[T]) {
+    if let Some(frame) = replay_divergence(a, b) {
+        panic!(
+            "Replays diverge at frame {}: {:?} != {:?}",
+            frame,
+            a.get(frame),
+            b.get(frame)
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ecs::pipeline::{engines::sequence::SequencePipelineEngine, LinearPipelineBuilder};
+
+    #[derive(Debug, Default)]
+    struct Position(Scalar);
+
+    fn movement_system(universe: &mut Universe) {
+        let dt = universe
+            .expect_resource::<AppLifeCycle>()
+            .delta_time_seconds();
+        let input = universe.expect_resource::<ReplayInput>().value("move");
+        let noise = universe.expect_resource_mut::<ReplayRng>().next_scalar();
+        universe.expect_resource_mut::<Position>().0 += input * dt + noise;
+    }
+
+    fn make_app() -> App {
+        App::build::<LinearPipelineBuilder>()
+            .with_resource(Position::default())
+            .with_system::<(&mut Position, &mut ReplayRng, &ReplayInput, &AppLifeCycle)>(
+                "movement",
+                movement_system,
+                &[],
+            )
+            .unwrap()
+            .build_empty::<SequencePipelineEngine, _>(FixedAppTimer::new(Duration::ZERO))
+    }
+
+    #[test]
+    fn test_replay() {
+        let timeline = ReplayTimeline::default()
+            .with(2, "move", 1.0)
+            .with(5, "move", -1.0);
+        let snapshot = |universe: &Universe| universe.expect_resource::<Position>().0;
+        let harness = ReplayHarness::new(42, Duration::from_millis(20), timeline);
+        let a = harness.run(make_app(), 10, snapshot);
+        let b = harness.run(make_app(), 10, snapshot);
+        assert_eq!(a.len(), 10);
+        assert_replays_match(&a, &b);
+
+        let mut harness = harness;
+        harness.seed = 7;
+        let c = harness.run(make_app(), 10, snapshot);
+        assert_eq!(replay_divergence(&a, &c), Some(0));
+    }
+}
@@ -1,10 +1,14 @@
-use crate::{ecs::Universe, id::ID};
+use crate::{ecs::Universe, id::ID, Scalar};
 use std::marker::PhantomData;
 
 pub enum StateChange {
     None,
+    /// Puts state on top of current one (pause menu, dialog), pausing current state until pushed
+    /// one gets popped. Entities of paused state stay alive.
     Push(Box<dyn State>),
+    /// Removes current state (despawning its non-persistent entities) and resumes the one below.
     Pop,
+    /// Replaces current state with new one.
     Swap(Box<dyn State>),
     Quit,
 }
@@ -14,14 +18,21 @@ pub trait State: Send + Sync {
 
     fn on_exit(&mut self, _universe: &mut Universe) {}
 
+    /// Called when other state gets pushed on top of this one.
     fn on_pause(&mut self, _universe: &mut Universe) {}
 
+    /// Called when state on top of this one gets popped.
     fn on_resume(&mut self, _universe: &mut Universe) {}
 
+    /// Called zero or more times per frame (before `on_process`) with constant delta time
+    /// configured in `AppFixedTimestep` resource - put frame rate independent simulation here.
+    fn on_fixed_process(&mut self, _universe: &mut Universe, _fixed_delta_time: Scalar) {}
+
     fn on_process(&mut self, _universe: &mut Universe) -> StateChange {
         StateChange::None
     }
 
+    /// Called instead of `on_process` while other state is on top of this one.
     fn on_process_background(&mut self, _universe: &mut Universe) {}
 }
 
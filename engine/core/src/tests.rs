@@ -1,25 +1,36 @@
 #![cfg(test)]
 
 use crate::{
-    app::{App, AppRunner, StandardAppRunner, StandardAppTimer},
+    app::{App, AppFixedTimestep, AppRunner, StandardAppRunner, StandardAppTimer},
     assets::{database::AssetsDatabase, protocols::prefab::PrefabAsset},
     ecs::{
         commands::{DespawnEntity, SpawnEntity, UniverseCommand},
-        components::Name,
-        hierarchy::{Hierarchy, Parent},
+        components::{Name, NonPersistent, NonPersistentPrefabProxy, Tag},
+        hierarchy::{Hierarchy, Parent, ParentPrefabProxy},
         life_cycle::EntityChanges,
         pipeline::{engines::sequence::SequencePipelineEngine, LinearPipelineBuilder},
-        Bundle, Entity, Universe,
+        Bundle, Entity, Universe, World,
     },
-    fetch::engines::map::MapFetchEngine,
+    fetch::{engines::map::MapFetchEngine, FetchCancelReason, FetchStatus},
     localization::Localization,
-    log::{logger_setup, DefaultLogger},
+    log::{
+        add_log_sink, clear_log_sinks, logger_setup, set_log_level, DefaultLogger, Log, LogRecord,
+        LogSink, Logger,
+    },
     prefab::{
-        Prefab, PrefabManager, PrefabScene, PrefabSceneEntity, PrefabSceneEntityData, PrefabValue,
+        merge_prefab_values, Prefab, PrefabComponent, PrefabManager, PrefabScene,
+        PrefabSceneEntity, PrefabSceneEntityData, PrefabValue,
     },
-    state::{State, StateChange},
+    replay::FixedAppTimer,
+    state::{State, StateChange, StateToken},
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
 };
-use std::collections::{HashMap, HashSet};
 
 #[derive(Default)]
 struct ExamplePrefab(bool);
@@ -117,6 +128,206 @@ fn test_prefabs() {
     let _ = AppRunner::new(app).run(StandardAppRunner::default());
 }
 
+#[test]
+fn test_prefab_capture_world() {
+    fn components_set(world: &World) -> HashSet<(Option<String>, Option<String>, bool, bool)> {
+        world
+            .query::<(
+                Option<&Name>,
+                Option<&Tag>,
+                Option<&Parent>,
+                Option<&NonPersistent>,
+            )>()
+            .iter()
+            .map(|(_, (name, tag, parent, non_persistent))| {
+                (
+                    name.map(|name| name.0.as_ref().to_owned()),
+                    tag.map(|tag| tag.0.as_ref().to_owned()),
+                    parent.is_some(),
+                    non_persistent.is_some(),
+                )
+            })
+            .collect()
+    }
+
+    let mut prefabs = PrefabManager::default();
+    prefabs.register_component_factory_proxy::<Parent, ParentPrefabProxy>("Parent");
+    prefabs.register_component_factory::<Tag>("Tag");
+    prefabs.register_component_factory::<Name>("Name");
+    prefabs.register_component_factory_proxy::<NonPersistent, NonPersistentPrefabProxy>(
+        "NonPersistent",
+    );
+    let prefab = PrefabScene::from_prefab_str(
+        r#"{
+            "entities": [
+                { "Data": { "uid": "root", "components": { "Name": "root", "Tag": "scene" } } },
+                { "Data": { "components": { "Name": "a", "Parent": "root" } } },
+                { "Data": { "components": { "Tag": "b", "Parent": "root", "NonPersistent": null } } }
+            ]
+        }"#,
+    )
+    .unwrap();
+    let mut world = World::new();
+    let mut changes = EntityChanges::default();
+    prefabs
+        .load_scene_from_prefab_direct(&prefab, &mut world, &mut changes, StateToken::new())
+        .unwrap();
+    let expected = components_set(&world);
+    world.spawn((0_u8,));
+    let captured = PrefabScene::from_prefab(&prefabs.capture_world(&world).unwrap()).unwrap();
+    assert_eq!(captured.entities.len(), 3);
+
+    let mut world2 = World::new();
+    prefabs
+        .load_scene_from_prefab_direct(&captured, &mut world2, &mut changes, StateToken::new())
+        .unwrap();
+    assert_eq!(world2.len(), 3);
+    assert_eq!(components_set(&world2), expected);
+    let root = world2
+        .query::<&Name>()
+        .iter()
+        .find(|(_, name)| name.0 == "root")
+        .map(|(entity, _)| entity)
+        .unwrap();
+    assert!(world2
+        .query::<&Parent>()
+        .iter()
+        .all(|(_, parent)| parent.0 == root));
+}
+
+#[test]
+fn test_prefab_instantiate_async() {
+    let mut files = HashMap::new();
+    files.insert(
+        "enemy.yaml".to_owned(),
+        br#"
+        entities:
+          - Data:
+              components:
+                Name: enemy
+        "#
+        .to_vec(),
+    );
+    let mut app = App::build::<LinearPipelineBuilder>()
+        .with_bundle(
+            crate::assets::bundle_installer,
+            (MapFetchEngine::new(files), |_| {}),
+        )
+        .unwrap()
+        .with_bundle(crate::prefab::bundle_installer, |_| {})
+        .unwrap()
+        .build::<SequencePipelineEngine, _, _>((), FixedAppTimer::new(Duration::from_millis(16)));
+
+    let completed = Arc::new(Mutex::new(None));
+    let (mut enemy, missing) = {
+        let universe = app.multiverse.default_universe().unwrap();
+        let mut prefabs = universe.expect_resource_mut::<PrefabManager>();
+        (
+            prefabs.instantiate_async("prefab://enemy.yaml"),
+            prefabs.instantiate_async("prefab://missing.yaml"),
+        )
+    };
+    assert_eq!(enemy.status(), FetchStatus::Empty);
+    let completed2 = completed.clone();
+    // callback can query handle it was registered on.
+    let handle = enemy.clone();
+    enemy.on_done(move |result| {
+        assert_eq!(handle.status(), FetchStatus::Done);
+        assert_eq!(handle.entities().map(|entities| entities.len()), Some(1));
+        *completed2.lock().unwrap() = Some(result.map(|entities| entities.len()));
+    });
+    for _ in 0..4 {
+        app.process();
+    }
+
+    assert_eq!(enemy.status(), FetchStatus::Done);
+    assert_eq!(*completed.lock().unwrap(), Some(Ok(1)));
+    let completed2 = completed.clone();
+    enemy.on_done(move |result| {
+        *completed2.lock().unwrap() = Some(result.map(|entities| entities.len() + 1));
+    });
+    assert_eq!(*completed.lock().unwrap(), Some(Ok(2)));
+    assert_eq!(
+        missing.status(),
+        FetchStatus::Canceled(FetchCancelReason::Error)
+    );
+    let universe = app.multiverse.default_universe().unwrap();
+    assert_eq!(
+        universe
+            .expect_resource::<PrefabManager>()
+            .pending_instances_count(),
+        0
+    );
+    let entity = enemy.entities().unwrap()[0];
+    assert_eq!(universe.world().get::<&Name>(entity).unwrap().0, "enemy");
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Health(usize);
+
+impl Prefab for Health {}
+impl PrefabComponent for Health {}
+
+#[test]
+fn test_prefab_overrides() {
+    let mut prefabs = PrefabManager::default();
+    prefabs.register_component_factory::<Name>("Name");
+    prefabs.register_component_factory::<Health>("Health");
+    prefabs
+        .register_scene_template(
+            PrefabScene::from_prefab_str(
+                r#"{
+                    "template_name": "enemy",
+                    "entities": [
+                        { "Data": { "uid": "body", "components": { "Name": "enemy", "Health": 10 } } }
+                    ]
+                }"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let overrides = PrefabValue::from_prefab_str(r#"{ "body": { "Health": 50 } }"#).unwrap();
+    let mut world = World::new();
+    let mut changes = EntityChanges::default();
+    let base = prefabs
+        .instantiate_direct("enemy", &mut world, &mut changes, StateToken::new())
+        .unwrap();
+    let variant = prefabs
+        .instantiate_with_overrides_direct(
+            "enemy",
+            &overrides,
+            &mut world,
+            &mut changes,
+            StateToken::new(),
+        )
+        .unwrap();
+    assert_eq!(world.get::<&Health>(base[0]).unwrap().0, 10);
+    assert_eq!(world.get::<&Health>(variant[0]).unwrap().0, 50);
+    assert_eq!(world.get::<&Name>(variant[0]).unwrap().0, "enemy");
+
+    let overrides = PrefabValue::from_prefab_str(r#"{ "head": { "Health": 50 } }"#).unwrap();
+    assert!(prefabs
+        .instantiate_with_overrides_direct(
+            "enemy",
+            &overrides,
+            &mut world,
+            &mut changes,
+            StateToken::new(),
+        )
+        .is_err());
+
+    let mut value =
+        PrefabValue::from_prefab_str(r#"{ "a": { "b": 1, "c": [1, 2] }, "d": 2 }"#).unwrap();
+    merge_prefab_values(
+        &mut value,
+        &PrefabValue::from_prefab_str(r#"{ "a": { "c": [3] }, "e": 4 }"#).unwrap(),
+    );
+    assert_eq!(
+        value,
+        PrefabValue::from_prefab_str(r#"{ "a": { "b": 1, "c": [3] }, "d": 2, "e": 4 }"#).unwrap()
+    );
+}
+
 #[test]
 fn test_hierarchy_find() {
     let mut app = App::build::<LinearPipelineBuilder>()
@@ -290,6 +501,64 @@ fn test_logger() {
     info!("my logger {}", "info");
     warn!("my logger {}", "warn");
     error!("my logger {}", "error");
+
+    struct CaptureLogger(Arc<Mutex<Vec<LogRecord>>>);
+
+    impl Logger for CaptureLogger {
+        fn log(&mut self, record: &LogRecord) {
+            if record.message.starts_with("captured") {
+                self.0.lock().unwrap().push(record.clone());
+            }
+        }
+    }
+
+    let records = Arc::new(Mutex::new(vec![]));
+    logger_setup(CaptureLogger(records.clone()));
+    set_log_level(Log::Warning);
+    log_info!("captured {}", "info");
+    log_error!("captured {}", "error");
+    set_log_level(Log::Trace);
+    logger_setup(DefaultLogger);
+    let records = records.lock().unwrap();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].level, Log::Error);
+    assert_eq!(records[0].message, "captured error");
+    assert_eq!(records[0].module_path, module_path!());
+}
+
+#[test]
+fn test_log_sinks() {
+    struct VecLogSink(Arc<Mutex<Vec<LogRecord>>>);
+
+    impl LogSink for VecLogSink {
+        fn log(&self, record: &LogRecord) {
+            if record.message.starts_with("sink") {
+                self.0.lock().unwrap().push(record.clone());
+            }
+        }
+    }
+
+    let records = Arc::new(Mutex::new(vec![]));
+    add_log_sink(Box::new(VecLogSink(records.clone())));
+    log_warn!("sink {}", 0);
+    log_error!("sink {}", 1);
+    log_warn!("sink {}", 2);
+    clear_log_sinks();
+    log_error!("sink {}", 3);
+    let records = records
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|record| (record.level, record.message.to_owned()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        records,
+        vec![
+            (Log::Warning, "sink 0".to_owned()),
+            (Log::Error, "sink 1".to_owned()),
+            (Log::Warning, "sink 2".to_owned()),
+        ]
+    );
 }
 
 #[test]
@@ -304,3 +573,159 @@ fn test_localization() {
     let text = localization_format_text!(loc, "hello", name => "Person", score => 42).unwrap();
     assert_eq!(text, "Hello Person, you've got 42 points! | {@bye}");
 }
+
+#[derive(Default)]
+struct StatesLog(Vec<&'static str>);
+
+#[derive(Default)]
+struct BaseState {
+    pushed: bool,
+}
+
+impl State for BaseState {
+    fn on_enter(&mut self, universe: &mut Universe) {
+        universe.world_mut().spawn((Name("base".into()),));
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("base enter");
+    }
+
+    fn on_pause(&mut self, universe: &mut Universe) {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("base pause");
+    }
+
+    fn on_resume(&mut self, universe: &mut Universe) {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("base resume");
+    }
+
+    fn on_process(&mut self, universe: &mut Universe) -> StateChange {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("base process");
+        if self.pushed {
+            StateChange::None
+        } else {
+            self.pushed = true;
+            StateChange::Push(Box::new(OverlayState))
+        }
+    }
+
+    fn on_process_background(&mut self, universe: &mut Universe) {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("base background");
+    }
+}
+
+struct OverlayState;
+
+impl State for OverlayState {
+    fn on_enter(&mut self, universe: &mut Universe) {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("overlay enter");
+    }
+
+    fn on_exit(&mut self, universe: &mut Universe) {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("overlay exit");
+    }
+
+    fn on_process(&mut self, universe: &mut Universe) -> StateChange {
+        universe
+            .expect_resource_mut::<StatesLog>()
+            .0
+            .push("overlay process");
+        StateChange::Pop
+    }
+}
+
+#[test]
+fn test_state_stack() {
+    let mut app = App::build::<LinearPipelineBuilder>()
+        .with_resource(StatesLog::default())
+        .build::<SequencePipelineEngine, _, _>(BaseState::default(), StandardAppTimer::default());
+    for _ in 0..3 {
+        app.process();
+    }
+    let universe = app.multiverse.default_universe().unwrap();
+    assert_eq!(
+        universe.expect_resource::<StatesLog>().0,
+        vec![
+            "base enter",
+            "base process",
+            "base pause",
+            "overlay enter",
+            "base background",
+            "overlay process",
+            "overlay exit",
+            "base resume",
+            "base process",
+        ]
+    );
+    assert_eq!(universe.world().len(), 1);
+}
+
+/// Number of fixed steps performed in every frame.
+#[derive(Default)]
+struct FixedStepsLog(Vec<usize>);
+
+#[derive(Default)]
+struct FixedStepsCounter(usize);
+
+impl State for FixedStepsCounter {
+    fn on_fixed_process(&mut self, _: &mut Universe, fixed_delta_time: Scalar) {
+        assert_eq!(fixed_delta_time, 1.0 / 32.0);
+        self.0 += 1;
+    }
+
+    fn on_process(&mut self, universe: &mut Universe) -> StateChange {
+        universe
+            .expect_resource_mut::<FixedStepsLog>()
+            .0
+            .push(self.0);
+        self.0 = 0;
+        StateChange::None
+    }
+}
+
+#[test]
+fn test_fixed_timestep() {
+    let run = |frame_time: Duration, frames: usize| {
+        let mut app = App::build::<LinearPipelineBuilder>()
+            .with_resource(FixedStepsLog::default())
+            .with_resource(AppFixedTimestep::new(1.0 / 32.0, 5))
+            .build::<SequencePipelineEngine, _, _>(
+                FixedStepsCounter::default(),
+                FixedAppTimer::new(frame_time),
+            );
+        for _ in 0..frames {
+            app.process();
+        }
+        let universe = app.multiverse.default_universe().unwrap();
+        let log = universe.expect_resource::<FixedStepsLog>().0.clone();
+        let substeps = universe.expect_resource::<AppFixedTimestep>().substeps();
+        (log, substeps)
+    };
+
+    // 3/64 of second per frame gives one and a half fixed step per frame.
+    let (log, substeps) = run(Duration::from_micros(46_875), 4);
+    assert_eq!(log, vec![1, 2, 1, 2]);
+    assert_eq!(substeps, 2);
+
+    // frame longer than max substeps gets clamped.
+    let (log, _) = run(Duration::from_millis(250), 2);
+    assert_eq!(log, vec![5, 5]);
+}
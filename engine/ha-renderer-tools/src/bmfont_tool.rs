@@ -21,6 +21,8 @@ pub struct Font {
     pub common: Common,
     pub pages: Pages,
     pub chars: Chars,
+    #[serde(default)]
+    pub kernings: Option<Kernings>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +74,23 @@ pub struct CharAttributes {
     pub page: usize,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kernings {
+    #[serde(rename = "$value", default)]
+    kernings: Vec<Kerning>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Kerning(pub KerningAttributes);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KerningAttributes {
+    pub first: usize,
+    pub second: usize,
+    pub amount: isize,
+}
+
 #[derive(Debug, Clone, Deserialize)]
 struct Params {
     #[serde(default)]
@@ -86,6 +105,9 @@ struct Params {
     pub padding: u32,
     #[serde(default)]
     pub force_line_height: Option<usize>,
+    /// Marks generated font as distance field one, rendered with distance field font material.
+    #[serde(default)]
+    pub distance_field: bool,
 }
 
 impl Params {
@@ -124,6 +146,7 @@ fn main() -> Result<(), Error> {
             params.max_height,
             params.padding,
             params.force_line_height,
+            params.distance_field,
         )?])
     })
 }
@@ -139,6 +162,7 @@ fn generate_sdf_font(
     max_height: u32,
     padding: u32,
     force_line_height: Option<usize>,
+    distance_field: bool,
 ) -> Result<String, Error> {
     let dirname = source.parent().map(|p| p.to_path_buf()).unwrap_or_default();
     let font = serde_xml_rs::from_str::<Font>(&read_to_string(source)?)
@@ -264,12 +288,27 @@ fn generate_sdf_font(
         })
         .collect();
 
+    let kerning = font
+        .kernings
+        .iter()
+        .flat_map(|kernings| kernings.kernings.iter())
+        .filter_map(|kerning| {
+            Some(FontAssetSourceKerning {
+                first: std::char::from_u32(kerning.0.first as _)?,
+                second: std::char::from_u32(kerning.0.second as _)?,
+                amount: kerning.0.amount,
+            })
+        })
+        .collect();
+
     let asset = FontAssetSource {
         line_height: (font.common.0.line_height as isize + diff_y).max(0) as usize,
         line_base: (font.common.0.base as isize + diff_y).max(0) as usize,
         sdf_resolution: generator.resolution,
+        distance_field,
         pages,
         filtering: image_filtering,
+        kerning,
     };
     let path = target.join("font.json");
     write(
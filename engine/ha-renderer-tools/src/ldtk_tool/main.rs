@@ -778,7 +778,7 @@ fn bake_project(
                             "HaMeshInstance".to_owned(),
                             HaMeshInstance {
                                 reference: MeshReference::Asset(name.to_owned()),
-                                override_draw_range: None,
+                                ..Default::default()
                             }
                             .to_prefab()
                             .unwrap_or_else(|_| {
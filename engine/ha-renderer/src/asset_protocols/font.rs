@@ -17,9 +17,23 @@ pub struct FontAssetSource {
     pub line_height: usize,
     pub line_base: usize,
     pub sdf_resolution: usize,
+    /// Tells if page images store distance fields instead of glyph coverage, so text gets
+    /// rendered with distance field font material.
+    #[serde(default)]
+    pub distance_field: bool,
     pub pages: Vec<FontAssetSourcePage>,
     #[serde(default)]
     pub filtering: ImageFiltering,
+    #[serde(default)]
+    pub kerning: Vec<FontAssetSourceKerning>,
+}
+
+/// Horizontal advance adjustment applied between pair of characters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FontAssetSourceKerning {
+    pub first: char,
+    pub second: char,
+    pub amount: isize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,10 +58,13 @@ pub struct FontAsset {
     pub line_height: usize,
     pub line_base: usize,
     pub sdf_resolution: usize,
+    pub distance_field: bool,
     pub characters: HashMap<char, FontAssetCharacter>,
     /// [ (page image size, asset id) ]
     pub pages_image_assets: Vec<(Vec2, AssetId)>,
     pub filtering: ImageFiltering,
+    /// { (first character, second character): advance adjustment }
+    pub kerning_pairs: HashMap<(char, char), Scalar>,
 }
 
 #[derive(Debug, Clone)]
@@ -60,6 +77,72 @@ pub struct FontAssetCharacter {
     pub line_advance: Scalar,
 }
 
+impl FontAsset {
+    /// Creates font from its source.
+    ///
+    /// # Arguments
+    /// * `source` - font source.
+    /// * `pages_image_assets` - size and asset id of image of every font page.
+    pub fn new(source: FontAssetSource, pages_image_assets: Vec<(Vec2, AssetId)>) -> Self {
+        let sdf_resolution = source.sdf_resolution;
+        let characters = source
+            .pages
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, page)| {
+                page.characters.into_iter().map(move |(id, character)| {
+                    (
+                        id,
+                        FontAssetCharacter {
+                            page: i,
+                            image_location: Vec2::new(character.x as f32, character.y as f32),
+                            image_size: Vec2::new(character.width as f32, character.height as f32),
+                            size: Vec2::new(
+                                character.width as f32
+                                    - sdf_resolution as f32
+                                    - sdf_resolution as f32,
+                                character.height as f32
+                                    - sdf_resolution as f32
+                                    - sdf_resolution as f32,
+                            ),
+                            offset: Vec2::new(
+                                character.xoffset as f32 - sdf_resolution as f32,
+                                character.yoffset as f32 - sdf_resolution as f32,
+                            ),
+                            line_advance: character.xadvance as f32,
+                        },
+                    )
+                })
+            })
+            .collect::<HashMap<_, _>>();
+
+        let kerning_pairs = source
+            .kerning
+            .iter()
+            .map(|pair| ((pair.first, pair.second), pair.amount as Scalar))
+            .collect();
+
+        Self {
+            line_height: source.line_height,
+            line_base: source.line_base,
+            sdf_resolution: source.sdf_resolution,
+            distance_field: source.distance_field,
+            characters,
+            pages_image_assets,
+            filtering: source.filtering,
+            kerning_pairs,
+        }
+    }
+
+    /// Advance adjustment between pair of characters, in font units.
+    pub fn kerning(&self, first: char, second: char) -> Scalar {
+        self.kerning_pairs
+            .get(&(first, second))
+            .copied()
+            .unwrap_or_default()
+    }
+}
+
 pub struct FontAssetProtocol;
 
 impl AssetProtocol for FontAssetProtocol {
@@ -111,46 +194,7 @@ impl AssetProtocol for FontAssetProtocol {
                 None
             })
             .collect::<Vec<_>>();
-        let sdf_resolution = source.sdf_resolution;
-        let characters = source
-            .pages
-            .into_iter()
-            .enumerate()
-            .flat_map(|(i, page)| {
-                page.characters.into_iter().map(move |(id, character)| {
-                    (
-                        id,
-                        FontAssetCharacter {
-                            page: i,
-                            image_location: Vec2::new(character.x as f32, character.y as f32),
-                            image_size: Vec2::new(character.width as f32, character.height as f32),
-                            size: Vec2::new(
-                                character.width as f32
-                                    - sdf_resolution as f32
-                                    - sdf_resolution as f32,
-                                character.height as f32
-                                    - sdf_resolution as f32
-                                    - sdf_resolution as f32,
-                            ),
-                            offset: Vec2::new(
-                                character.xoffset as f32 - sdf_resolution as f32,
-                                character.yoffset as f32 - sdf_resolution as f32,
-                            ),
-                            line_advance: character.xadvance as f32,
-                        },
-                    )
-                })
-            })
-            .collect::<HashMap<_, _>>();
-
-        AssetLoadResult::Data(Box::new(FontAsset {
-            line_height: source.line_height,
-            line_base: source.line_base,
-            sdf_resolution: source.sdf_resolution,
-            characters,
-            pages_image_assets,
-            filtering: source.filtering,
-        }))
+        AssetLoadResult::Data(Box::new(FontAsset::new(source, pages_image_assets)))
     }
 
     fn on_unload(&mut self, asset: &Asset) -> Option<Vec<AssetVariant>> {
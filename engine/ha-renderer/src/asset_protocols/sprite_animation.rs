@@ -162,6 +162,10 @@ pub struct SpriteAnimationState {
     pub frames: Vec<String>,
     #[serde(default)]
     pub signals: Vec<SpriteAnimationSignal>,
+    /// Frame markers: [(frame index, event name)]. Event is emitted every time playback enters
+    /// marked frame, in order of playback direction.
+    #[serde(default)]
+    pub events: Vec<(usize, String)>,
     #[serde(default = "default_speed")]
     pub speed: Scalar,
     #[serde(default)]
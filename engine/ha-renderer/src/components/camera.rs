@@ -180,7 +180,7 @@ impl HaStageCameraInfo {
 
     pub fn screen_to_render_target(&self, mut point: Vec2) -> Vec2 {
         point.x = (point.x + 1.0) * 0.5 * self.width as Scalar + self.x as Scalar;
-        point.y = (point.y + 1.0) * 0.5 * self.height as Scalar + self.y as Scalar;
+        point.y = (1.0 - point.y) * 0.5 * self.height as Scalar + self.y as Scalar;
         point
     }
 
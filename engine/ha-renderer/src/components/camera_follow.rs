@@ -0,0 +1,114 @@
+use crate::math::*;
+use core::{
+    prefab::{Prefab, PrefabComponent},
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+
+/// Makes camera follow named target entity.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HaCameraFollow {
+    /// Name of target entity in hierarchy.
+    #[serde(default)]
+    pub target: Option<String>,
+    /// Exponential smoothing rate - `None` snaps camera to target.
+    #[serde(default)]
+    pub smoothing: Option<Scalar>,
+    /// Time (in seconds) of target velocity to look ahead of target.
+    #[serde(default)]
+    pub look_ahead: Scalar,
+    /// Half extents of area around camera position in which target can move freely.
+    #[serde(default)]
+    pub dead_zone: Vec2,
+    /// World space area that camera view never leaves.
+    #[serde(default)]
+    pub bounds: Option<Rect>,
+    #[serde(skip)]
+    last_target: Option<Vec3>,
+}
+
+impl HaCameraFollow {
+    pub fn new(target: impl ToString) -> Self {
+        Self {
+            target: Some(target.to_string()),
+            ..Default::default()
+        }
+    }
+
+    pub fn smoothing(mut self, value: Option<Scalar>) -> Self {
+        self.smoothing = value;
+        self
+    }
+
+    pub fn look_ahead(mut self, value: Scalar) -> Self {
+        self.look_ahead = value;
+        self
+    }
+
+    pub fn dead_zone(mut self, value: Vec2) -> Self {
+        self.dead_zone = value;
+        self
+    }
+
+    pub fn bounds(mut self, value: Option<Rect>) -> Self {
+        self.bounds = value;
+        self
+    }
+
+    pub fn reset(&mut self) {
+        self.last_target = None;
+    }
+
+    /// Calculates next camera position, where `view_size` is world space size of camera view.
+    pub fn update(
+        &mut self,
+        from: Vec3,
+        target: Vec3,
+        view_size: Option<Vec2>,
+        delta_time: Scalar,
+    ) -> Vec3 {
+        let velocity = match self.last_target {
+            Some(last) if delta_time > 0.0 => (target - last) / delta_time,
+            _ => Vec3::zero(),
+        };
+        self.last_target = Some(target);
+        let mut to = target + velocity * self.look_ahead;
+        to.x = Self::apply_dead_zone(from.x, to.x, self.dead_zone.x);
+        to.y = Self::apply_dead_zone(from.y, to.y, self.dead_zone.y);
+        to.z = from.z;
+        let mut position = match self.smoothing {
+            Some(smoothing) => {
+                let f = 1.0 - (-smoothing.max(0.0) * delta_time).exp();
+                Vec3::lerp(from, to, f)
+            }
+            None => to,
+        };
+        if let Some(bounds) = self.bounds {
+            let view_size = view_size.unwrap_or_default();
+            position.x = Self::fit(position.x, bounds.x, bounds.w, view_size.x);
+            position.y = Self::fit(position.y, bounds.y, bounds.h, view_size.y);
+        }
+        position
+    }
+
+    fn apply_dead_zone(from: Scalar, to: Scalar, extent: Scalar) -> Scalar {
+        let offset = to - from;
+        if offset.abs() <= extent {
+            from
+        } else {
+            to - extent * offset.signum()
+        }
+    }
+
+    fn fit(value: Scalar, start: Scalar, length: Scalar, view: Scalar) -> Scalar {
+        if view > length {
+            start + length * 0.5
+        } else {
+            let half = view * 0.5;
+            value.max(start + half).min(start + length - half)
+        }
+    }
+}
+
+impl Prefab for HaCameraFollow {}
+impl PrefabComponent for HaCameraFollow {}
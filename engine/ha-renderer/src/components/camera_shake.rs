@@ -0,0 +1,112 @@
+use crate::math::*;
+use core::{
+    prefab::{Prefab, PrefabComponent},
+    Scalar,
+};
+use serde::{Deserialize, Serialize};
+
+/// Shakes camera with decaying noise offset applied on top of its position, so systems moving
+/// camera (like camera follow) keep working on its unshaken position.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct HaCameraShake {
+    /// Maximal offset (in world units) at the beginning of shake.
+    #[serde(default)]
+    pub amplitude: Scalar,
+    /// Number of noise samples per second.
+    #[serde(default)]
+    pub frequency: Scalar,
+    /// Time (in seconds) in which amplitude decays to zero.
+    #[serde(default)]
+    pub duration: Scalar,
+    /// Seed of noise - shakes with the same seed and parameters produce the same offsets.
+    #[serde(default)]
+    pub seed: u64,
+    #[serde(skip)]
+    time: Scalar,
+    #[serde(skip)]
+    pub(crate) applied: Vec3,
+}
+
+impl HaCameraShake {
+    pub fn new(amplitude: Scalar, frequency: Scalar, duration: Scalar) -> Self {
+        Self {
+            amplitude,
+            frequency,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    pub fn seed(mut self, value: u64) -> Self {
+        self.seed = value;
+        self
+    }
+
+    /// Starts shake from the beginning.
+    pub fn start(&mut self) {
+        self.time = 0.0;
+    }
+
+    pub fn stop(&mut self) {
+        self.time = self.duration;
+    }
+
+    pub fn time(&self) -> Scalar {
+        self.time
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.time < self.duration
+    }
+
+    /// Amplitude decayed at current time.
+    pub fn current_amplitude(&self) -> Scalar {
+        if self.duration <= 0.0 {
+            return 0.0;
+        }
+        let factor = (1.0 - self.time / self.duration).max(0.0).min(1.0);
+        self.amplitude * factor * factor
+    }
+
+    /// Offset currently applied to camera position.
+    pub fn offset(&self) -> Vec3 {
+        self.applied
+    }
+
+    /// Advances shake time and calculates camera offset for it.
+    pub fn update(&mut self, delta_time: Scalar) -> Vec3 {
+        self.time = (self.time + delta_time).min(self.duration.max(0.0));
+        let amplitude = self.current_amplitude();
+        if amplitude <= 0.0 {
+            return Vec3::zero();
+        }
+        let position = self.time * self.frequency;
+        let x = Self::noise(self.seed, position, 0);
+        let y = Self::noise(self.seed, position, 1);
+        Vec3::new(x, y, 0.0) * amplitude
+    }
+
+    /// Smooth value noise in range [-1; 1].
+    fn noise(seed: u64, position: Scalar, axis: u64) -> Scalar {
+        let index = position.floor();
+        let factor = position - index;
+        let factor = factor * factor * (3.0 - 2.0 * factor);
+        let index = index as i64;
+        let from = Self::hash(seed, index, axis);
+        let to = Self::hash(seed, index.wrapping_add(1), axis);
+        from + (to - from) * factor
+    }
+
+    fn hash(seed: u64, index: i64, axis: u64) -> Scalar {
+        let mut value = seed
+            ^ (index as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ axis.wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        value = (value ^ (value >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        value ^= value >> 31;
+        (value >> 40) as Scalar / (1u64 << 24) as Scalar * 2.0 - 1.0
+    }
+}
+
+impl Prefab for HaCameraShake {}
+impl PrefabComponent for HaCameraShake {}
@@ -1,14 +1,94 @@
-use crate::material::domains::surface::{immediate::SurfaceImmediateFactory, SurfaceDomain};
+use crate::{
+    components::mesh_instance::HaMeshInstanceBatch,
+    image::{ImageFiltering, ImageReference},
+    material::{
+        common::MaterialValue,
+        domains::surface::{immediate::SurfaceImmediateFactory, SurfaceDomain},
+    },
+};
 use core::prefab::{Prefab, PrefabComponent};
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+fn default_texture_uniform() -> String {
+    "mainImage".to_owned()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HaImmediateBatch<V>
 where
     V: SurfaceDomain + Default + Copy + Send + Sync + 'static,
 {
     #[serde(skip)]
     pub factory: SurfaceImmediateFactory<V>,
+    /// Max number of indices per draw call - batches exceeding it are automatically
+    /// flushed in multiple draw calls.
+    #[serde(default)]
+    pub draw_index_limit: Option<usize>,
+    /// Max number of vertices referenced per draw call - batches exceeding it are
+    /// automatically flushed in multiple draw calls.
+    #[serde(default)]
+    pub draw_vertex_limit: Option<usize>,
+    /// Sorts queued primitives by their render state before flushing.
+    #[serde(default)]
+    pub sort: bool,
+    /// Name of `sampler2D` uniform that textures of primitives render states get bound to.
+    #[serde(default = "default_texture_uniform")]
+    pub texture_uniform: String,
+    #[serde(default)]
+    pub texture_filtering: ImageFiltering,
+}
+
+impl<V> Default for HaImmediateBatch<V>
+where
+    V: SurfaceDomain + Default + Copy + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self {
+            factory: Default::default(),
+            draw_index_limit: None,
+            draw_vertex_limit: None,
+            sort: false,
+            texture_uniform: default_texture_uniform(),
+            texture_filtering: Default::default(),
+        }
+    }
+}
+
+impl<V> HaImmediateBatch<V>
+where
+    V: SurfaceDomain + Default + Copy + Send + Sync + 'static,
+{
+    /// Mesh batches drawing queued primitives with their render states - empty when none of
+    /// primitives has material or texture set, so entire mesh gets drawn with entity material.
+    pub fn mesh_batches(&self) -> Vec<HaMeshInstanceBatch> {
+        if self
+            .factory
+            .batches()
+            .all(|(state, _)| state.material.is_none() && state.texture.is_none())
+        {
+            return vec![];
+        }
+        self.factory
+            .batches()
+            .map(|(state, range)| HaMeshInstanceBatch {
+                range,
+                material: state.material,
+                values: state
+                    .texture
+                    .map(|id| {
+                        (
+                            self.texture_uniform.to_owned(),
+                            MaterialValue::sampler_2d_filter(
+                                ImageReference::Id(id),
+                                self.texture_filtering,
+                            ),
+                        )
+                    })
+                    .into_iter()
+                    .collect(),
+            })
+            .collect()
+    }
 }
 
 impl<V> Prefab for HaImmediateBatch<V> where
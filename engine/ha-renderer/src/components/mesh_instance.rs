@@ -1,6 +1,20 @@
-use crate::mesh::{MeshDrawRange, MeshReference, MeshResourceMapping};
+use crate::{
+    material::{common::MaterialValue, MaterialId},
+    mesh::{MeshDrawRange, MeshReference, MeshResourceMapping},
+};
 use core::prefab::{Prefab, PrefabComponent};
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, ops::Range};
+
+/// Range of mesh indices drawn in separate draw call, with its own render state.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct HaMeshInstanceBatch {
+    pub range: Range<usize>,
+    /// Material used instead of entity material.
+    pub material: Option<MaterialId>,
+    /// Uniforms applied over entity material instance values.
+    pub values: HashMap<String, MaterialValue>,
+}
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct HaMeshInstance {
@@ -8,6 +22,9 @@ pub struct HaMeshInstance {
     pub reference: MeshReference,
     #[serde(default)]
     pub override_draw_range: Option<MeshDrawRange>,
+    /// When not empty, mesh is drawn batch by batch instead of using draw range.
+    #[serde(skip)]
+    pub batches: Vec<HaMeshInstanceBatch>,
 }
 
 impl HaMeshInstance {
@@ -1,4 +1,6 @@
 pub mod camera;
+pub mod camera_follow;
+pub mod camera_shake;
 pub mod gizmo;
 pub mod immediate_batch;
 pub mod material_instance;
@@ -11,6 +11,7 @@ pub(crate) struct Active {
     pub state: String,
     pub frame: Scalar,
     pub bounced: bool,
+    pub entered: bool,
     pub cached_frame: Option<String>,
 }
 
@@ -32,6 +33,8 @@ pub struct HaSpriteAnimationInstance {
     pub(crate) frame_changed: bool,
     #[serde(skip)]
     pub(crate) signals: Vec<SpriteAnimationSignal>,
+    #[serde(skip)]
+    pub(crate) events: Vec<String>,
 }
 
 impl Default for HaSpriteAnimationInstance {
@@ -45,6 +48,7 @@ impl Default for HaSpriteAnimationInstance {
             active: None,
             frame_changed: false,
             signals: Default::default(),
+            events: Default::default(),
         }
     }
 }
@@ -77,12 +81,18 @@ impl HaSpriteAnimationInstance {
         &self.signals
     }
 
+    /// Frame events emitted during last update, in order of occurrence.
+    pub fn received_events(&self) -> &[String] {
+        &self.events
+    }
+
     pub fn play(&mut self, state: impl ToString) {
         self.playing = true;
         self.active = Some(Active {
             state: state.to_string(),
             frame: 0.0,
             bounced: false,
+            entered: false,
             cached_frame: None,
         });
         self.frame_changed = true;
@@ -349,6 +349,96 @@ impl HaTextWrapping {
     }
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HaTextDirection {
+    LeftToRight,
+    RightToLeft,
+}
+
+impl Default for HaTextDirection {
+    fn default() -> Self {
+        Self::LeftToRight
+    }
+}
+
+impl HaTextDirection {
+    /// Strong direction of character, `None` for neutral characters (whitespaces, punctuation).
+    pub fn of_character(character: char) -> Option<Self> {
+        match character as u32 {
+            0x0590..=0x08FF
+            | 0xFB1D..=0xFDFF
+            | 0xFE70..=0xFEFF
+            | 0x10800..=0x10FFF
+            | 0x1E800..=0x1EFFF => Some(Self::RightToLeft),
+            _ if character.is_alphanumeric() => Some(Self::LeftToRight),
+            _ => None,
+        }
+    }
+
+    /// Simplified Unicode bidi algorithm for single line: neutral characters take direction of
+    /// surrounding strong characters (or base direction when these differ), then runs get
+    /// reversed by their embedding levels.
+    ///
+    /// # Returns
+    /// Logical indices of characters in visual (left to right) order.
+    pub fn visual_order(characters: &[char], base: Self) -> Vec<usize> {
+        let strong = characters
+            .iter()
+            .map(|c| Self::of_character(*c))
+            .collect::<Vec<_>>();
+        let mut before = Vec::with_capacity(strong.len());
+        let mut last = base;
+        for direction in &strong {
+            before.push(last);
+            if let Some(direction) = direction {
+                last = *direction;
+            }
+        }
+        let mut after = vec![base; strong.len()];
+        let mut last = base;
+        for (index, direction) in strong.iter().enumerate().rev() {
+            after[index] = last;
+            if let Some(direction) = direction {
+                last = *direction;
+            }
+        }
+        let levels = strong
+            .iter()
+            .enumerate()
+            .map(|(index, direction)| {
+                let direction = direction.unwrap_or(if before[index] == after[index] {
+                    before[index]
+                } else {
+                    base
+                });
+                match (base, direction) {
+                    (Self::LeftToRight, Self::LeftToRight) => 0,
+                    (Self::LeftToRight, Self::RightToLeft) => 1,
+                    (Self::RightToLeft, Self::RightToLeft) => 1,
+                    (Self::RightToLeft, Self::LeftToRight) => 2,
+                }
+            })
+            .collect::<Vec<u8>>();
+        let mut order = (0..characters.len()).collect::<Vec<_>>();
+        let max_level = levels.iter().copied().max().unwrap_or_default();
+        for level in (1..=max_level).rev() {
+            let mut index = 0;
+            while index < order.len() {
+                if levels[order[index]] >= level {
+                    let start = index;
+                    while index < order.len() && levels[order[index]] >= level {
+                        index += 1;
+                    }
+                    order[start..index].reverse();
+                } else {
+                    index += 1;
+                }
+            }
+        }
+        order
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HaTextInstance {
     font: String,
@@ -377,6 +467,12 @@ pub struct HaTextInstance {
     wrapping: HaTextWrapping,
     #[serde(default)]
     lines_extra_space: Scalar,
+    #[serde(default = "HaTextInstance::default_line_height")]
+    line_height: Scalar,
+    #[serde(default = "HaTextInstance::default_kerning")]
+    kerning: bool,
+    #[serde(default)]
+    direction: HaTextDirection,
     #[serde(skip)]
     pub(crate) dirty: bool,
 }
@@ -398,6 +494,9 @@ impl Default for HaTextInstance {
             bounds_height: None,
             wrapping: Default::default(),
             lines_extra_space: 0.0,
+            line_height: Self::default_line_height(),
+            kerning: Self::default_kerning(),
+            direction: Default::default(),
             dirty: true,
         }
     }
@@ -412,6 +511,14 @@ impl HaTextInstance {
         32.0
     }
 
+    fn default_line_height() -> Scalar {
+        1.0
+    }
+
+    fn default_kerning() -> bool {
+        true
+    }
+
     pub fn lines_count(&self) -> usize {
         1 + self
             .content
@@ -554,6 +661,36 @@ impl HaTextInstance {
         self.dirty = true;
     }
 
+    /// Multiplier of font line height.
+    pub fn line_height(&self) -> Scalar {
+        self.line_height
+    }
+
+    pub fn set_line_height(&mut self, line_height: Scalar) {
+        self.line_height = line_height;
+        self.dirty = true;
+    }
+
+    /// Tells if kerning pairs of font adjust spacing between characters.
+    pub fn kerning(&self) -> bool {
+        self.kerning
+    }
+
+    pub fn set_kerning(&mut self, kerning: bool) {
+        self.kerning = kerning;
+        self.dirty = true;
+    }
+
+    pub fn direction(&self) -> HaTextDirection {
+        self.direction
+    }
+
+    /// Base text direction - for right-to-left text, horizontal alignment of 0 means right side.
+    pub fn set_direction(&mut self, direction: HaTextDirection) {
+        self.direction = direction;
+        self.dirty = true;
+    }
+
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
     }
@@ -735,4 +872,17 @@ mod tests {
         assert_eq!(a, b);
         assert_eq!(a, c);
     }
+
+    #[test]
+    fn test_bidi_visual_order() {
+        let characters = "abc \u{5d0}\u{5d1}\u{5d2}".chars().collect::<Vec<_>>();
+        assert_eq!(
+            HaTextDirection::visual_order(&characters, HaTextDirection::LeftToRight),
+            vec![0, 1, 2, 3, 6, 5, 4]
+        );
+        assert_eq!(
+            HaTextDirection::visual_order(&characters, HaTextDirection::RightToLeft),
+            vec![6, 5, 4, 3, 0, 1, 2]
+        );
+    }
 }
@@ -1,6 +1,10 @@
-use crate::{components::HaChangeFrequency, image::ImageFiltering, math::*};
+use crate::{components::HaChangeFrequency, image::ImageFiltering, math::*, mesh::MeshId};
 use core::prefab::{Prefab, PrefabComponent};
 use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// (col, row) of tilemap chunk.
+pub type HaTileMapChunk = (usize, usize);
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HaTileMapTile {
@@ -23,8 +27,18 @@ pub struct HaTileMapInstance {
     tiles: Vec<HaTileMapTile>,
     #[serde(default)]
     pivot: Vec2,
+    /// Number of cells along each side of chunk - every chunk gets its own mesh.
+    #[serde(default = "HaTileMapInstance::default_chunk_size")]
+    chunk_size: usize,
     #[serde(skip)]
     pub(crate) dirty: bool,
+    #[serde(skip)]
+    pub(crate) dirty_chunks: HashSet<HaTileMapChunk>,
+    #[serde(skip)]
+    pub(crate) chunk_meshes: HashMap<HaTileMapChunk, MeshId>,
+    /// {chunk: [index of tile]}
+    #[serde(skip)]
+    tiles_by_chunk: HashMap<HaTileMapChunk, Vec<usize>>,
 }
 
 impl Default for HaTileMapInstance {
@@ -38,12 +52,20 @@ impl Default for HaTileMapInstance {
             change_frequency: Default::default(),
             tiles: Default::default(),
             pivot: Default::default(),
+            chunk_size: Self::default_chunk_size(),
             dirty: true,
+            dirty_chunks: Default::default(),
+            chunk_meshes: Default::default(),
+            tiles_by_chunk: Default::default(),
         }
     }
 }
 
 impl HaTileMapInstance {
+    fn default_chunk_size() -> usize {
+        32
+    }
+
     pub fn atlas(&self) -> &str {
         &self.atlas
     }
@@ -95,6 +117,7 @@ impl HaTileMapInstance {
 
     pub fn set_tiles(&mut self, tiles: Vec<HaTileMapTile>) {
         self.tiles = tiles;
+        self.rebuild_tiles_by_chunk();
         self.dirty = true;
     }
 
@@ -107,14 +130,141 @@ impl HaTileMapInstance {
         self.dirty = true;
     }
 
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size.max(1);
+        self.rebuild_tiles_by_chunk();
+        self.dirty = true;
+    }
+
+    /// Number of chunks along columns and rows.
+    pub fn chunks(&self) -> (usize, usize) {
+        let chunk_size = self.chunk_size.max(1);
+        (
+            (self.cols + chunk_size - 1) / chunk_size,
+            (self.rows + chunk_size - 1) / chunk_size,
+        )
+    }
+
+    /// Chunk owning given cell.
+    pub fn chunk_of(&self, col: usize, row: usize) -> HaTileMapChunk {
+        let chunk_size = self.chunk_size.max(1);
+        (col / chunk_size, row / chunk_size)
+    }
+
+    /// Tiles placed in cells of given chunk.
+    pub fn chunk_tiles(&self, chunk: HaTileMapChunk) -> impl Iterator<Item = &HaTileMapTile> {
+        self.tiles_by_chunk
+            .get(&chunk)
+            .into_iter()
+            .flatten()
+            .map(move |index| &self.tiles[*index])
+    }
+
+    /// Chunks that have at least one tile placed in their cells.
+    pub fn occupied_chunks(&self) -> impl Iterator<Item = HaTileMapChunk> + '_ {
+        self.tiles_by_chunk.keys().copied()
+    }
+
+    pub fn tile(&self, col: usize, row: usize) -> Option<&HaTileMapTile> {
+        self.tile_index(col, row).map(|index| &self.tiles[index])
+    }
+
+    /// Puts atlas item in given cell (or clears it with `None`) and marks only its chunk dirty,
+    /// so only that chunk mesh gets rebuilt. Returns false if cell is outside of tilemap.
+    pub fn set_tile(&mut self, col: usize, row: usize, atlas_item: Option<String>) -> bool {
+        if col >= self.cols || row >= self.rows {
+            return false;
+        }
+        let chunk = self.chunk_of(col, row);
+        match (self.tile_index(col, row), atlas_item) {
+            (Some(index), Some(atlas_item)) => self.tiles[index].atlas_item = atlas_item,
+            (Some(index), None) => {
+                self.remove_tile_index(chunk, index);
+                // last tile takes place of removed one, so its index has to follow.
+                let last = self.tiles.len() - 1;
+                if index != last {
+                    let tile = &self.tiles[last];
+                    let last_chunk = self.chunk_of(tile.col, tile.row);
+                    if let Some(indices) = self.tiles_by_chunk.get_mut(&last_chunk) {
+                        for item in indices.iter_mut().filter(|item| **item == last) {
+                            *item = index;
+                        }
+                    }
+                }
+                self.tiles.swap_remove(index);
+            }
+            (None, Some(atlas_item)) => {
+                self.tiles_by_chunk
+                    .entry(chunk)
+                    .or_default()
+                    .push(self.tiles.len());
+                self.tiles.push(HaTileMapTile {
+                    col,
+                    row,
+                    atlas_item,
+                });
+            }
+            (None, None) => return true,
+        }
+        self.dirty_chunks.insert(chunk);
+        true
+    }
+
+    pub fn is_chunk_dirty(&self, chunk: HaTileMapChunk) -> bool {
+        self.dirty || self.dirty_chunks.contains(&chunk)
+    }
+
+    pub fn dirty_chunks(&self) -> impl Iterator<Item = HaTileMapChunk> + '_ {
+        self.dirty_chunks.iter().copied()
+    }
+
+    /// Meshes of chunks uploaded to renderer - chunks without tiles have no mesh.
+    pub fn chunk_meshes(&self) -> impl Iterator<Item = (HaTileMapChunk, MeshId)> + '_ {
+        self.chunk_meshes.iter().map(|(chunk, id)| (*chunk, *id))
+    }
+
     pub fn mark_dirty(&mut self) {
         self.dirty = true;
     }
+
+    fn tile_index(&self, col: usize, row: usize) -> Option<usize> {
+        self.tiles_by_chunk
+            .get(&self.chunk_of(col, row))?
+            .iter()
+            .copied()
+            .find(|index| {
+                let tile = &self.tiles[*index];
+                tile.col == col && tile.row == row
+            })
+    }
+
+    fn remove_tile_index(&mut self, chunk: HaTileMapChunk, index: usize) {
+        if let Some(indices) = self.tiles_by_chunk.get_mut(&chunk) {
+            indices.retain(|item| *item != index);
+            if indices.is_empty() {
+                self.tiles_by_chunk.remove(&chunk);
+            }
+        }
+    }
+
+    fn rebuild_tiles_by_chunk(&mut self) {
+        self.tiles_by_chunk.clear();
+        for (index, tile) in self.tiles.iter().enumerate() {
+            let chunk = self.chunk_of(tile.col, tile.row);
+            self.tiles_by_chunk.entry(chunk).or_default().push(index);
+        }
+    }
 }
 
 impl Prefab for HaTileMapInstance {
     fn post_from_prefab(&mut self) {
         self.pivot = Vec2::partial_max(Vec2::partial_min(self.pivot, 1.0), 0.0);
+        self.chunk_size = self.chunk_size.max(1);
+        self.rebuild_tiles_by_chunk();
         self.dirty = true;
     }
 }
@@ -1,5 +1,9 @@
 use crate::math::*;
 use core::{
+    ecs::{
+        hierarchy::{reparent, Parent, ReparentError},
+        Entity, World,
+    },
     prefab::{Prefab, PrefabComponent},
     Scalar,
 };
@@ -325,3 +329,101 @@ impl HaTransform {
 
 impl Prefab for HaTransform {}
 impl PrefabComponent for HaTransform {}
+
+/// Calculates entity world matrix from local matrices of its and its parents transforms
+/// (entities without `HaTransform` count as identity), so result does not depend on transform
+/// system being run after hierarchy changes.
+pub fn ha_world_matrix(world: &World, entity: Entity) -> Mat4 {
+    let mut result = Mat4::identity();
+    let mut current = Some(entity);
+    for _ in 0..=world.len() {
+        let entity = match current {
+            Some(entity) => entity,
+            None => break,
+        };
+        if let Ok(transform) = world.get::<&HaTransform>(entity) {
+            result = transform.local_matrix() * result;
+        }
+        current = world.get::<&Parent>(entity).ok().map(|parent| parent.0);
+    }
+    result
+}
+
+/// Moves entity under new parent (or makes it root when `new_parent` is `None`), recalculating
+/// its local transform so world transform stays the same.
+pub fn ha_reparent(
+    world: &mut World,
+    child: Entity,
+    new_parent: Option<Entity>,
+) -> Result<(), ReparentError> {
+    let world_matrix = ha_world_matrix(world, child);
+    reparent(world, child, new_parent)?;
+    let parent_matrix = match new_parent {
+        Some(parent) => ha_world_matrix(world, parent),
+        None => Mat4::identity(),
+    };
+    if let Ok(mut transform) = world.get::<&mut HaTransform>(child) {
+        let mut result = HaTransform::from_matrix(parent_matrix.inverted() * world_matrix);
+        result.cached_world_matrix = world_matrix;
+        result.cached_inverse_world_matrix = world_matrix.inverted();
+        *transform = result;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec_eq(a: Vec3, b: Vec3) {
+        assert!((a - b).magnitude() < 1.0e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_ha_reparent() {
+        let mut world = World::new();
+        let parent = world.spawn((HaTransform::new(
+            Vec3::new(10.0, 0.0, 0.0),
+            Rotator::default(),
+            Vec3::new(2.0, 2.0, 2.0),
+        ),));
+        let child = world.spawn((HaTransform::translation(Vec3::new(5.0, 5.0, 0.0)),));
+
+        // attach.
+        ha_reparent(&mut world, child, Some(parent)).unwrap();
+        assert_eq!(world.get::<&Parent>(child).unwrap().0, parent);
+        assert_vec_eq(
+            world.get::<&HaTransform>(child).unwrap().get_translation(),
+            Vec3::new(-2.5, 2.5, 0.0),
+        );
+        assert_vec_eq(
+            ha_world_matrix(&world, child).mul_point(Vec3::zero()),
+            Vec3::new(5.0, 5.0, 0.0),
+        );
+        assert_vec_eq(
+            world.get::<&HaTransform>(child).unwrap().get_world_origin(),
+            Vec3::new(5.0, 5.0, 0.0),
+        );
+
+        // cycle guard.
+        assert_eq!(
+            ha_reparent(&mut world, parent, Some(child)),
+            Err(ReparentError::Cycle {
+                child: parent,
+                parent: child
+            })
+        );
+        assert!(world.get::<&Parent>(parent).is_err());
+
+        // detach.
+        world
+            .get::<&mut HaTransform>(parent)
+            .unwrap()
+            .set_translation(Vec3::new(0.0, 10.0, 0.0));
+        ha_reparent(&mut world, child, None).unwrap();
+        assert!(world.get::<&Parent>(child).is_err());
+        let transform = world.get::<&HaTransform>(child).unwrap();
+        assert_vec_eq(transform.get_translation(), Vec3::new(-5.0, 15.0, 0.0));
+        assert_vec_eq(transform.get_scale(), Vec3::new(2.0, 2.0, 2.0));
+    }
+}
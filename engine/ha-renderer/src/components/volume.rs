@@ -14,6 +14,32 @@ pub enum HaVolume {
 }
 
 impl HaVolume {
+    /// Axis aligned world space bounds of this volume projected on XY plane.
+    pub fn world_space_rect(&self, matrix: &Mat4) -> Option<Rect> {
+        match self {
+            Self::Sphere(radius) => {
+                let origin = matrix.mul_point(Vec3::zero());
+                Some(Rect::new(
+                    origin.x - radius,
+                    origin.y - radius,
+                    radius * 2.0,
+                    radius * 2.0,
+                ))
+            }
+            Self::Box(half_extents) => {
+                let bbox =
+                    BoundsVolume::from_box(Vec3::zero(), *half_extents).transformed(*matrix)?;
+                let half_extents = bbox.half_extents();
+                Some(Rect::new(
+                    bbox.origin.x - half_extents.x,
+                    bbox.origin.y - half_extents.y,
+                    half_extents.x * 2.0,
+                    half_extents.y * 2.0,
+                ))
+            }
+        }
+    }
+
     pub fn world_space_contains(&self, matrix: &Mat4, position: Vec3) -> Option<Scalar> {
         match self {
             Self::Sphere(radius) => {
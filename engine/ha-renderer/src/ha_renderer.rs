@@ -55,6 +55,7 @@ pub struct RenderStats {
     pub uniform_changes: usize,
     pub sampler_changes: usize,
     pub state_changes: usize,
+    pub auto_flushes: usize,
 }
 
 pub struct RenderStageResources<'a> {
@@ -220,6 +221,7 @@ pub struct HaRenderer {
     pipeline_registry: HashMap<String, PipelineDescriptor>,
     pub(crate) pipelines: HashMap<PipelineId, Pipeline>,
     pub(crate) render_targets: Resources<RenderTarget>,
+    main_render_target_samples: usize,
     meshes: Resources<Mesh>,
     images: Resources<Image>,
     pub virtual_images: Resources<VirtualImage>,
@@ -239,6 +241,10 @@ impl std::fmt::Debug for HaRenderer {
             .field("pipeline_registry", &self.pipeline_registry)
             .field("pipelines", &self.pipelines)
             .field("render_targets", &self.render_targets)
+            .field(
+                "main_render_target_samples",
+                &self.main_render_target_samples,
+            )
             .field("meshes", &self.meshes)
             .field("images", &self.images)
             .field("virtual_meshes", &self.virtual_meshes)
@@ -267,6 +273,7 @@ impl HaRenderer {
             pipeline_registry: Default::default(),
             pipelines: Default::default(),
             render_targets: Default::default(),
+            main_render_target_samples: 1,
             images: Default::default(),
             virtual_images: Default::default(),
             meshes: Default::default(),
@@ -410,6 +417,36 @@ impl HaRenderer {
         self
     }
 
+    pub fn with_main_render_target_samples(mut self, samples: usize) -> Self {
+        self.set_main_render_target_samples(samples);
+        self
+    }
+
+    /// Requested number of MSAA samples of main render target.
+    pub fn main_render_target_samples(&self) -> usize {
+        self.main_render_target_samples
+    }
+
+    /// Requests MSAA (1, 2, 4 or 8 samples) for main render target of all pipelines - scene gets
+    /// rendered into multisampled buffers that are resolved into backbuffer after each stage.
+    /// Effective sample count is reported by `RenderTarget::samples`.
+    pub fn set_main_render_target_samples(&mut self, samples: usize) {
+        self.main_render_target_samples = samples;
+        let context = self.platform_interface.context();
+        for (id, render_target) in self.render_targets.iter_mut() {
+            if !render_target.is_backbuffer() {
+                continue;
+            }
+            render_target.set_requested_samples(samples);
+            if let Some(context) = context {
+                if let Err(error) = render_target.context_initialize(context) {
+                    self.error_reporter
+                        .on_report(Error::RenderTarget(id, error));
+                }
+            }
+        }
+    }
+
     pub fn stages(&self) -> impl Iterator<Item = (&str, TypeId, &str)> {
         self.stage_registry
             .iter()
@@ -464,7 +501,9 @@ impl HaRenderer {
         for (key, data) in data.render_targets {
             let render_target = match &data {
                 RenderTargetDescriptor::Main => match RenderTarget::main() {
-                    Ok(render_target) => render_target,
+                    Ok(render_target) => {
+                        render_target.with_samples(self.main_render_target_samples)
+                    }
                     Err(error) => return Err(PipelineError::CouldNotCreateRenderTarget(error)),
                 },
                 RenderTargetDescriptor::Custom {
@@ -485,6 +524,7 @@ impl HaRenderer {
             Pipeline {
                 stages,
                 render_targets,
+                order: data.order,
             },
         );
         self.dirty_signatures = true;
@@ -507,6 +547,17 @@ impl HaRenderer {
         self.pipelines.keys().copied()
     }
 
+    /// Pipelines sorted in order of execution.
+    pub fn pipelines_in_order(&self) -> Vec<PipelineId> {
+        let mut result = self
+            .pipelines
+            .iter()
+            .map(|(id, pipeline)| (*id, pipeline.order))
+            .collect::<Vec<_>>();
+        result.sort_by_key(|(_, order)| *order);
+        result.into_iter().map(|(id, _)| id).collect()
+    }
+
     pub fn pipeline(&self, id: PipelineId) -> Option<&Pipeline> {
         self.pipelines.get(&id)
     }
@@ -860,6 +911,26 @@ impl HaRenderer {
         self.added_materials.clear();
     }
 
+    pub(crate) fn reload_materials(
+        &mut self,
+        library: &mut MaterialLibrary,
+        fragment_high_precision_support: bool,
+    ) {
+        for (id, graph) in library.take_reloads() {
+            if let Some(material) = self.materials.get_mut(id) {
+                if let Err(error) = material.reload(
+                    self.platform_interface.context(),
+                    graph,
+                    &self.cached_signatures,
+                    library,
+                    fragment_high_precision_support,
+                ) {
+                    oxygengine_core::error!("Could not reload material {:?}: {:#?}", id, error);
+                }
+            }
+        }
+    }
+
     pub(crate) fn stage_resources(&self) -> RenderStageResources {
         RenderStageResources {
             render_targets: &self.render_targets,
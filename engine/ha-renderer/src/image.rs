@@ -1,8 +1,11 @@
 use crate::{
-    ha_renderer::RenderStageResources, math::Rect, render_target::RenderTargetId,
-    resources::resource_mapping::ResourceMapping, HasContextResources, ResourceReference,
+    ha_renderer::RenderStageResources,
+    math::{rect, Rect},
+    render_target::RenderTargetId,
+    resources::resource_mapping::ResourceMapping,
+    HasContextResources, ResourceReference,
 };
-use core::id::ID;
+use core::{id::ID, Scalar};
 use glow::*;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, hash::Hash};
@@ -423,12 +426,378 @@ impl VirtualImageSource {
     }
 }
 
+/// (column, row)
+pub type VirtualImageTile = (usize, usize);
+
+/// Residency state of streamed virtual image tile. Tiles not present in streaming are not
+/// resident and get `Requested` when region covering them is requested, then become `Resident`
+/// once uploaded into cache slot, until evicted to make room for other tiles.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VirtualImageTileState {
+    Requested,
+    Resident { slot: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualImageTileMapping {
+    pub tile: VirtualImageTile,
+    pub state: VirtualImageTileState,
+    /// Area of tile in source image UV space.
+    pub source_uvs: Rect,
+    /// Area of tile in cache image UV space, if tile is resident.
+    pub cache_uvs: Option<Rect>,
+}
+
+/// Pixels of resident tile copied out of source image, waiting to be written into cache slot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VirtualImageTileUpload {
+    pub tile: VirtualImageTile,
+    pub slot: usize,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct VirtualImageRegion {
+    pub tiles: Vec<VirtualImageTileMapping>,
+}
+
+impl VirtualImageRegion {
+    /// Tells if all tiles of region are resident - otherwise callers should sample placeholder.
+    pub fn is_resident(&self) -> bool {
+        self.tiles.iter().all(|tile| tile.cache_uvs.is_some())
+    }
+}
+
+/// Streaming of large source image in tiles, into cache image of limited size.
+///
+/// Tiles covering requested regions get queued for upload and virtual image streaming system
+/// copies their pixels from source image into cache image slots, marking them resident. When
+/// all slots are taken, least recently requested tile gets evicted.
+#[derive(Debug, Clone)]
+pub struct VirtualImageStreaming {
+    tile_size: usize,
+    columns: usize,
+    rows: usize,
+    slots: usize,
+    cache_columns: usize,
+    /// {tile: (state, last use)}
+    tiles: HashMap<VirtualImageTile, (VirtualImageTileState, usize)>,
+    pending: Vec<VirtualImageTile>,
+    free_slots: Vec<usize>,
+    clock: usize,
+    cache_image: Option<ImageId>,
+}
+
+impl VirtualImageStreaming {
+    /// Creates new streaming.
+    ///
+    /// # Arguments
+    /// * `width` - source image width in pixels.
+    /// * `height` - source image height in pixels.
+    /// * `tile_size` - tile width and height in pixels.
+    /// * `budget` - number of bytes of RGBA8 cache image that resident tiles are stored in.
+    pub fn new(width: usize, height: usize, tile_size: usize, budget: usize) -> Self {
+        let tile_size = tile_size.max(1);
+        let slots = budget / (tile_size * tile_size * 4);
+        let mut cache_columns = 1;
+        while cache_columns * cache_columns < slots {
+            cache_columns += 1;
+        }
+        Self {
+            tile_size,
+            columns: width.div_ceil(tile_size),
+            rows: height.div_ceil(tile_size),
+            slots,
+            cache_columns,
+            tiles: Default::default(),
+            pending: Default::default(),
+            free_slots: (0..slots).rev().collect(),
+            clock: 0,
+            cache_image: None,
+        }
+    }
+
+    pub fn tile_size(&self) -> usize {
+        self.tile_size
+    }
+
+    /// (columns, rows)
+    pub fn tiles_count(&self) -> (usize, usize) {
+        (self.columns, self.rows)
+    }
+
+    pub fn slots(&self) -> usize {
+        self.slots
+    }
+
+    /// Cache image size in pixels.
+    pub fn cache_size(&self) -> usize {
+        self.cache_columns * self.tile_size
+    }
+
+    /// Image that resident tiles are stored in - region cache UVs point to it.
+    pub fn cache_image(&self) -> Option<ImageId> {
+        self.cache_image
+    }
+
+    pub fn set_cache_image(&mut self, id: Option<ImageId>) {
+        self.cache_image = id;
+    }
+
+    /// Creates empty cache image of source image format.
+    pub fn create_cache_image(&self, format: ImageFormat) -> Result<Image, ImageError> {
+        let size = self.cache_size();
+        Image::new(
+            ImageDescriptor {
+                mode: ImageMode::Image2d,
+                format,
+                mipmap: ImageMipmap::None,
+            },
+            size,
+            size,
+            1,
+            vec![0; format.bytesize() * size * size],
+        )
+    }
+
+    pub fn has_pending_tiles(&self) -> bool {
+        !self.pending.is_empty()
+    }
+
+    pub fn tile_state(&self, tile: VirtualImageTile) -> Option<VirtualImageTileState> {
+        self.tiles.get(&tile).map(|(state, _)| *state)
+    }
+
+    pub fn resident_tiles(&self) -> impl Iterator<Item = (VirtualImageTile, usize)> + '_ {
+        self.tiles
+            .iter()
+            .filter_map(|(tile, (state, _))| match state {
+                VirtualImageTileState::Resident { slot } => Some((*tile, *slot)),
+                _ => None,
+            })
+    }
+
+    /// Requests tiles covering area of source image UV space to become resident.
+    pub fn request_region(&mut self, uvs: Rect) -> VirtualImageRegion {
+        self.clock += 1;
+        let tiles = self.region_tiles(uvs).collect::<Vec<_>>();
+        for tile in &tiles {
+            match self.tiles.get_mut(tile) {
+                Some((_, last_use)) => *last_use = self.clock,
+                None => {
+                    self.tiles
+                        .insert(*tile, (VirtualImageTileState::Requested, self.clock));
+                    self.pending.push(*tile);
+                }
+            }
+        }
+        self.region(tiles.into_iter())
+    }
+
+    /// Tells if tiles covering area of source image UV space are resident, without requesting
+    /// them.
+    pub fn is_region_resident(&self, uvs: Rect) -> bool {
+        self.region_tiles(uvs).all(|tile| {
+            matches!(
+                self.tile_state(tile),
+                Some(VirtualImageTileState::Resident { .. })
+            )
+        })
+    }
+
+    /// Takes requested tiles that wait for upload.
+    pub fn take_pending_tiles(&mut self) -> Vec<VirtualImageTile> {
+        std::mem::take(&mut self.pending)
+    }
+
+    /// Marks tile as resident, evicting least recently requested tile if there are no free slots.
+    ///
+    /// # Returns
+    /// `Some` with cache slot that tile pixels should be written into or `None` if tile was not
+    /// requested or there are no slots at all.
+    pub fn mark_tile_resident(&mut self, tile: VirtualImageTile) -> Option<usize> {
+        match self.tiles.get(&tile)?.0 {
+            VirtualImageTileState::Resident { slot } => return Some(slot),
+            VirtualImageTileState::Requested => {}
+        }
+        let slot = match self.free_slots.pop() {
+            Some(slot) => slot,
+            None => {
+                let (evicted, slot) = self
+                    .tiles
+                    .iter()
+                    .filter_map(|(tile, (state, last_use))| match state {
+                        VirtualImageTileState::Resident { slot } => Some((*tile, *slot, *last_use)),
+                        _ => None,
+                    })
+                    .min_by_key(|(_, _, last_use)| *last_use)
+                    .map(|(tile, slot, _)| (tile, slot))?;
+                self.tiles.remove(&evicted);
+                slot
+            }
+        };
+        if let Some((state, _)) = self.tiles.get_mut(&tile) {
+            *state = VirtualImageTileState::Resident { slot };
+        }
+        Some(slot)
+    }
+
+    /// Marks pending tiles resident and copies their pixels out of source image.
+    ///
+    /// # Returns
+    /// Uploads of tiles that are still resident after all pending tiles got their slots.
+    pub fn prepare_pending_uploads(&mut self, source: &Image) -> Vec<VirtualImageTileUpload> {
+        let bytesize = source.format().bytesize();
+        let mut result = vec![];
+        for tile in self.take_pending_tiles() {
+            let slot = match self.mark_tile_resident(tile) {
+                Some(slot) => slot,
+                None => continue,
+            };
+            let (x, y, width, height) = self.tile_pixels(tile);
+            let mut data = vec![0; width * height * bytesize];
+            // tiles at source image edges can stick out of it - missing pixels stay empty.
+            let cols = source.width().saturating_sub(x).min(width);
+            let rows = source.height().saturating_sub(y).min(height);
+            for row in 0..rows {
+                let from = ((y + row) * source.width() + x) * bytesize;
+                let to = row * width * bytesize;
+                data[to..(to + cols * bytesize)]
+                    .copy_from_slice(&source.data()[from..(from + cols * bytesize)]);
+            }
+            result.push(VirtualImageTileUpload { tile, slot, data });
+        }
+        result.retain(|upload| {
+            self.tile_state(upload.tile)
+                == Some(VirtualImageTileState::Resident { slot: upload.slot })
+        });
+        result
+    }
+
+    /// Writes pixels of uploaded tiles into their cache image slots.
+    pub fn write_uploads(
+        &self,
+        cache: &mut Image,
+        uploads: &[VirtualImageTileUpload],
+    ) -> Result<(), ImageError> {
+        let bytesize = cache.format().bytesize();
+        let cache_width = cache.width();
+        let size = self.cache_size();
+        if cache_width < size || cache.height() < size {
+            return Err(ImageError::InvalidSize(
+                cache_width * cache.height(),
+                size * size,
+            ));
+        }
+        let row_bytesize = self.tile_size * bytesize;
+        if let Some(upload) = uploads
+            .iter()
+            .find(|upload| upload.data.len() != row_bytesize * self.tile_size)
+        {
+            return Err(ImageError::InvalidSize(
+                upload.data.len(),
+                row_bytesize * self.tile_size,
+            ));
+        }
+        cache.with_data(|data| {
+            for upload in uploads {
+                let (x, y, _, height) = self.slot_pixels(upload.slot);
+                for row in 0..height {
+                    let to = ((y + row) * cache_width + x) * bytesize;
+                    let from = row * row_bytesize;
+                    data[to..(to + row_bytesize)]
+                        .copy_from_slice(&upload.data[from..(from + row_bytesize)]);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Evicts tile, making its cache slot free.
+    pub fn evict_tile(&mut self, tile: VirtualImageTile) -> bool {
+        match self.tiles.remove(&tile) {
+            Some((VirtualImageTileState::Resident { slot }, _)) => {
+                self.free_slots.push(slot);
+                true
+            }
+            Some((VirtualImageTileState::Requested, _)) => {
+                self.pending.retain(|item| item != &tile);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Area of tile in source image pixels: (x, y, width, height).
+    pub fn tile_pixels(&self, tile: VirtualImageTile) -> (usize, usize, usize, usize) {
+        (
+            tile.0 * self.tile_size,
+            tile.1 * self.tile_size,
+            self.tile_size,
+            self.tile_size,
+        )
+    }
+
+    /// Area of cache slot in cache image pixels: (x, y, width, height).
+    pub fn slot_pixels(&self, slot: usize) -> (usize, usize, usize, usize) {
+        (
+            (slot % self.cache_columns) * self.tile_size,
+            (slot / self.cache_columns) * self.tile_size,
+            self.tile_size,
+            self.tile_size,
+        )
+    }
+
+    fn region_tiles(&self, uvs: Rect) -> impl Iterator<Item = VirtualImageTile> {
+        let columns = self.columns as Scalar;
+        let rows = self.rows as Scalar;
+        let col_from = (uvs.x * columns).floor().max(0.0) as usize;
+        let col_to = ((uvs.x + uvs.w) * columns).ceil().min(columns) as usize;
+        let row_from = (uvs.y * rows).floor().max(0.0) as usize;
+        let row_to = ((uvs.y + uvs.h) * rows).ceil().min(rows) as usize;
+        (row_from..row_to).flat_map(move |row| (col_from..col_to).map(move |col| (col, row)))
+    }
+
+    fn region(&self, tiles: impl Iterator<Item = VirtualImageTile>) -> VirtualImageRegion {
+        let columns = self.columns as Scalar;
+        let rows = self.rows as Scalar;
+        let cache_columns = self.cache_columns as Scalar;
+        let tiles = tiles
+            .filter_map(|tile| {
+                let state = self.tile_state(tile)?;
+                let cache_uvs = match state {
+                    VirtualImageTileState::Resident { slot } => Some(rect(
+                        (slot % self.cache_columns) as Scalar / cache_columns,
+                        (slot / self.cache_columns) as Scalar / cache_columns,
+                        1.0 / cache_columns,
+                        1.0 / cache_columns,
+                    )),
+                    VirtualImageTileState::Requested => None,
+                };
+                Some(VirtualImageTileMapping {
+                    tile,
+                    state,
+                    source_uvs: rect(
+                        tile.0 as Scalar / columns,
+                        tile.1 as Scalar / rows,
+                        1.0 / columns,
+                        1.0 / rows,
+                    ),
+                    cache_uvs,
+                })
+            })
+            .collect();
+        VirtualImageRegion { tiles }
+    }
+}
+
 #[derive(Debug)]
 pub struct VirtualImage {
     source: VirtualImageSource,
     uvs: HashMap<ImageId, (Rect, usize)>,
     map: HashMap<String, ImageId>,
     table: HashMap<ImageId, String>,
+    streaming: Option<VirtualImageStreaming>,
 }
 
 impl VirtualImage {
@@ -438,13 +807,46 @@ impl VirtualImage {
             uvs: Default::default(),
             map: Default::default(),
             table: Default::default(),
+            streaming: None,
         }
     }
 
+    pub fn with_streaming(mut self, streaming: VirtualImageStreaming) -> Self {
+        self.streaming = Some(streaming);
+        self
+    }
+
     pub fn source(&self) -> &VirtualImageSource {
         &self.source
     }
 
+    pub fn streaming(&self) -> Option<&VirtualImageStreaming> {
+        self.streaming.as_ref()
+    }
+
+    pub fn streaming_mut(&mut self) -> Option<&mut VirtualImageStreaming> {
+        self.streaming.as_mut()
+    }
+
+    /// Requests area of source image UV space to become resident.
+    ///
+    /// # Returns
+    /// `Some` with UV remap of region tiles, or `None` if image is not streamed.
+    pub fn request_region(&mut self, uvs: Rect) -> Option<VirtualImageRegion> {
+        self.streaming
+            .as_mut()
+            .map(|streaming| streaming.request_region(uvs))
+    }
+
+    /// Tells if area of source image UV space is resident - images that are not streamed are
+    /// always resident.
+    pub fn is_region_resident(&self, uvs: Rect) -> bool {
+        self.streaming
+            .as_ref()
+            .map(|streaming| streaming.is_region_resident(uvs))
+            .unwrap_or(true)
+    }
+
     pub fn register_image_uvs(&mut self, uvs: Rect, page: usize) -> ImageId {
         let id = ImageId::new();
         self.uvs.insert(id, (uvs, page));
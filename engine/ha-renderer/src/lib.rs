@@ -34,10 +34,11 @@ pub mod prelude {
         builtin_material_function, builtin_material_functions, code_material_function,
         code_material_functions,
         components::{
-            camera::*, gizmo::*, immediate_batch::*, material_instance::*, mesh_instance::*,
-            postprocess::*, rig_instance::*, sprite_animation_instance::*, text_instance::*,
-            tilemap_instance::*, transform::*, virtual_image_uniforms::*, visibility::*, volume::*,
-            volume_overlap::*, volume_visibility::*, *,
+            camera::*, camera_follow::*, camera_shake::*, gizmo::*, immediate_batch::*,
+            material_instance::*, mesh_instance::*, postprocess::*, rig_instance::*,
+            sprite_animation_instance::*, text_instance::*, tilemap_instance::*, transform::*,
+            virtual_image_uniforms::*, visibility::*, volume::*, volume_overlap::*,
+            volume_visibility::*, *,
         },
         constants::material_uniforms::*,
         graph_material_function,
@@ -46,11 +47,14 @@ pub mod prelude {
         material::{
             common::*,
             domains::{
+                bloom::*,
+                font::*,
                 gizmo::*,
+                lighting::*,
                 screenspace::*,
                 surface::{
-                    circle::*, grid::*, immediate::*, quad::*, rig2d::*, text::*, tilemap::*,
-                    triangles2d::*, *,
+                    circle::*, grid::*, immediate::*, instanced::*, quad::*, rig2d::*, text::*,
+                    tilemap::*, triangles2d::*, *,
                 },
                 *,
             },
@@ -70,14 +74,18 @@ pub mod prelude {
         pipeline::{render_queue::*, stage::*, *},
         platform::*,
         render_target::*,
-        resources::{camera_cache::*, gizmos::*, material_library::*, resource_mapping::*, *},
+        resources::{
+            atlas_builder::*, bloom::*, camera_cache::*, debug_draw::*, gizmos::*,
+            material_hot_reload::*, material_library::*, resource_mapping::*, spatial_index::*, *,
+        },
         rich_text,
         systems::{
-            apply_sprite_animation_to_material::*, atlas::*, camera_cache::*, font::*,
-            immediate_batch::*, mesh_bounds_gizmo::*, render_forward_stage::*,
-            render_gizmo_stage::*, render_postprocess_stage::*, renderer::*, sprite_animation::*,
-            tilemap::*, transform::*, virtual_image_uniforms::*, volume_overlap::*,
-            volume_visibility::*, *,
+            apply_sprite_animation_to_material::*, atlas::*, atlas_builder::*, bloom::*,
+            camera_cache::*, camera_follow::*, camera_shake::*, font::*, immediate_batch::*,
+            material_hot_reload::*, mesh_bounds_gizmo::*, render_forward_stage::*,
+            render_gizmo_stage::*, render_postprocess_stage::*, renderer::*, spatial_index::*,
+            sprite_animation::*, tilemap::*, transform::*, virtual_image_streaming::*,
+            virtual_image_uniforms::*, volume_overlap::*, volume_visibility::*, *,
         },
         Error, HaRendererBundleSetup, HasContextResources, ResourceReference, Resources,
     };
@@ -100,6 +108,8 @@ use crate::{
     },
     components::{
         camera::{HaCamera, HaDefaultCamera},
+        camera_follow::HaCameraFollow,
+        camera_shake::HaCameraShake,
         gizmo::HaGizmo,
         immediate_batch::HaImmediateBatch,
         material_instance::HaMaterialInstance,
@@ -120,6 +130,11 @@ use crate::{
     image::{ImageError, ImageId, ImageMode, ImageResourceMapping},
     material::{
         domains::{
+            bloom::{
+                bloom_blur_material_graph, bloom_bright_pass_material_graph,
+                bloom_composite_material_graph,
+            },
+            font::{default_sdf_font_material_graph, SDF_FONT_MATERIAL_NAME},
             gizmo::{default_gizmo_color_material_graph, gizmo_domain_graph},
             screenspace::{
                 default_screenspace_color_material_graph,
@@ -146,20 +161,34 @@ use crate::{
     },
     mesh::{controls::animation::AnimationRigControl, MeshError, MeshId, MeshResourceMapping},
     render_target::{RenderTargetError, RenderTargetId},
-    resources::{camera_cache::CameraCache, gizmos::Gizmos, material_library::MaterialLibrary},
+    resources::{
+        atlas_builder::AtlasBuilder, bloom::BloomSettings, camera_cache::CameraCache,
+        debug_draw::DebugDraw, gizmos::Gizmos, material_hot_reload::MaterialHotReload,
+        material_library::MaterialLibrary, spatial_index::HaSpatialIndex,
+    },
     systems::{
         apply_sprite_animation_to_material::{
             ha_apply_sprite_animation_to_material, HaApplySpriteAnimationToMaterialSystemResources,
         },
         atlas::{ha_atlas_system, HaAtlasSystemCache, HaAtlasSystemResources},
+        atlas_builder::{
+            ha_atlas_builder_system, HaAtlasBuilderSystemCache, HaAtlasBuilderSystemResources,
+        },
+        bloom::{ha_bloom_system, HaBloomSystemResources},
         camera_cache::{ha_camera_cache_system, HaCameraCacheSystemResources},
+        camera_follow::{ha_camera_follow_system, HaCameraFollowSystemResources},
+        camera_shake::{
+            ha_camera_shake_restore_system, ha_camera_shake_system, HaCameraShakeSystemResources,
+        },
         font::{ha_font_system, HaFontSystemCache, HaFontSystemResources},
         immediate_batch::{
             ha_immediate_batch_system, HaImmediateBatchSystemCache, HaImmediateBatchSystemResources,
         },
+        material_hot_reload::{ha_material_hot_reload_system, HaMaterialHotReloadSystemResources},
         mesh_bounds_gizmo::{ha_mesh_bounds_gizmo_system, HaMeshBoundsGizmoSystemResources},
         render_forward_stage::{
-            ha_render_forward_stage_system, HaRenderForwardStageSystemResources,
+            ha_render_forward_stage_system, HaRenderForwardStageCullingStats,
+            HaRenderForwardStageSystemResources,
         },
         render_gizmo_stage::{
             ha_render_gizmo_stage_system, HaRenderGizmoStageSystemCache,
@@ -175,11 +204,15 @@ use crate::{
             HaRendererMaintenanceSystemResources,
         },
         rig::{ha_rig_system, HaRigSystemCache, HaRigSystemResources},
+        spatial_index::{ha_spatial_index_system, HaSpatialIndexSystemResources},
         sprite_animation::{
             ha_sprite_animation, HaSpriteAnimationSystemCache, HaSpriteAnimationSystemResources,
         },
         tilemap::{ha_tilemap_system, HaTileMapSystemCache, HaTileMapSystemResources},
         transform::{ha_transform_system, HaTransformSystemResources},
+        virtual_image_streaming::{
+            ha_virtual_image_streaming_system, HaVirtualImageStreamingSystemResources,
+        },
         virtual_image_uniforms::{
             ha_virtual_image_uniforms, HaVirtualImageUniformsSystemResources,
         },
@@ -426,6 +459,7 @@ where
     builder.install_resource(setup.renderer);
     builder.install_resource(HaRendererMaintenanceSystemCache::default());
     builder.install_resource(HaAtlasSystemCache::default());
+    builder.install_resource(HaAtlasBuilderSystemCache::default());
     builder.install_resource(HaFontSystemCache::default());
     builder.install_resource(HaTileMapSystemCache::default());
     builder.install_resource(HaSpriteAnimationSystemCache::default());
@@ -434,19 +468,49 @@ where
     builder.install_resource(HaVolumeOverlapSystemCache::default());
     builder.install_resource(HaRenderGizmoStageSystemCache::default());
     builder.install_resource(HaRenderPostProcessStageSystemCache::default());
+    builder.install_resource(HaRenderForwardStageCullingStats::default());
     builder.install_resource(HaImmediateBatchSystemCache::default());
     builder.install_resource(MaterialLibrary::default());
+    builder.install_resource(MaterialHotReload::default());
     builder.install_resource(ImageResourceMapping::default());
     builder.install_resource(MeshResourceMapping::default());
     builder.install_resource(MaterialResourceMapping::default());
     builder.install_resource(CameraCache::default());
+    builder.install_resource(HaSpatialIndex::default());
+    builder.install_resource(AtlasBuilder::default());
+    builder.install_resource(BloomSettings::default());
+    builder.install_resource(DebugDraw::default());
     builder.install_resource(setup.gizmos);
 
     // NOTE: ORDER MATTERS! transform first, renderer second, then the others - dependencies always first.
+    // camera follow goes before transform so camera matrices use its final position.
+    // camera shake offset is removed before camera follow and applied again after it, so
+    // follow works on unshaken camera position.
+    builder.install_system_on_layer::<HaCameraShakeSystemResources>(
+        "camera-shake-restore",
+        ha_camera_shake_restore_system,
+        &[],
+        PipelineLayer::Pre,
+        false,
+    )?;
+    builder.install_system_on_layer::<HaCameraFollowSystemResources>(
+        "camera-follow",
+        ha_camera_follow_system,
+        &["camera-shake-restore"],
+        PipelineLayer::Pre,
+        false,
+    )?;
+    builder.install_system_on_layer::<HaCameraShakeSystemResources>(
+        "camera-shake",
+        ha_camera_shake_system,
+        &["camera-follow"],
+        PipelineLayer::Pre,
+        false,
+    )?;
     builder.install_system_on_layer::<HaTransformSystemResources>(
         "transform",
         ha_transform_system,
-        &[],
+        &["camera-shake"],
         PipelineLayer::Pre,
         false,
     )?;
@@ -474,17 +538,28 @@ where
         ha_render_forward_stage_system,
         &[],
     )?;
+    builder.install_system::<HaBloomSystemResources>("bloom", ha_bloom_system, &[])?;
     builder.install_system::<HaRenderPostProcessStageSystemResources>(
         "renderer-postprocess-stage",
         ha_render_postprocess_stage_system,
-        &[],
+        &["bloom"],
     )?;
     builder.install_system::<HaRenderGizmoStageSystemResources>(
         "renderer-gizmo-stage",
         ha_render_gizmo_stage_system,
         &[],
     )?;
+    builder.install_system::<HaMaterialHotReloadSystemResources>(
+        "material-hot-reload",
+        ha_material_hot_reload_system,
+        &[],
+    )?;
     builder.install_system::<HaAtlasSystemResources>("atlas", ha_atlas_system, &[])?;
+    builder.install_system::<HaAtlasBuilderSystemResources>(
+        "atlas-builder",
+        ha_atlas_builder_system,
+        &[],
+    )?;
     builder.install_system::<HaFontSystemResources>("font", ha_font_system, &[])?;
     builder.install_system::<HaTileMapSystemResources>("tilemap", ha_tilemap_system, &[])?;
     builder.install_system::<HaRigSystemResources>("rig", ha_rig_system, &[])?;
@@ -503,6 +578,11 @@ where
         ha_virtual_image_uniforms,
         &["apply-sprite-animation-to-material"],
     )?;
+    builder.install_system::<HaVirtualImageStreamingSystemResources>(
+        "virtual-image-streaming",
+        ha_virtual_image_streaming_system,
+        &[],
+    )?;
     builder.install_system::<HaVolumeVisibilitySystemResources>(
         "volume-visibility",
         ha_volume_visibility_system,
@@ -513,6 +593,11 @@ where
         ha_volume_overlap_system,
         &[],
     )?;
+    builder.install_system::<HaSpatialIndexSystemResources>(
+        "spatial-index",
+        ha_spatial_index_system,
+        &[],
+    )?;
     builder.install_system::<HaMeshBoundsGizmoSystemResources>(
         "mesh-bounds-gizmo",
         ha_mesh_bounds_gizmo_system,
@@ -819,6 +904,15 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
             content: default_surface_flat_sdf_text_material_graph(),
         },
     ));
+    database.insert(Asset::new(
+        "material",
+        SDF_FONT_MATERIAL_NAME,
+        MaterialAsset::Graph {
+            default_values: Default::default(),
+            draw_options: MaterialDrawOptions::transparent(),
+            content: default_sdf_font_material_graph(),
+        },
+    ));
     database.insert(Asset::new(
         "material",
         "@material/graph/screenspace/color",
@@ -837,6 +931,33 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
             content: default_screenspace_texture_material_graph(),
         },
     ));
+    database.insert(Asset::new(
+        "material",
+        "@material/graph/screenspace/bloom/bright-pass",
+        MaterialAsset::Graph {
+            default_values: Default::default(),
+            draw_options: Default::default(),
+            content: bloom_bright_pass_material_graph(),
+        },
+    ));
+    database.insert(Asset::new(
+        "material",
+        "@material/graph/screenspace/bloom/blur",
+        MaterialAsset::Graph {
+            default_values: Default::default(),
+            draw_options: Default::default(),
+            content: bloom_blur_material_graph(),
+        },
+    ));
+    database.insert(Asset::new(
+        "material",
+        "@material/graph/screenspace/bloom/composite",
+        MaterialAsset::Graph {
+            default_values: Default::default(),
+            draw_options: Default::default(),
+            content: bloom_composite_material_graph(),
+        },
+    ));
     database.insert(Asset::new(
         "material",
         "@material/graph/gizmo/color",
@@ -851,6 +972,8 @@ pub fn protocols_installer(database: &mut AssetsDatabase) {
 pub fn prefabs_installer(prefabs: &mut PrefabManager) {
     prefabs.register_component_factory::<HaCamera>("HaCamera");
     prefabs.register_component_factory::<HaDefaultCamera>("HaDefaultCamera");
+    prefabs.register_component_factory::<HaCameraFollow>("HaCameraFollow");
+    prefabs.register_component_factory::<HaCameraShake>("HaCameraShake");
     prefabs.register_component_factory::<HaMaterialInstance>("HaMaterialInstance");
     prefabs.register_component_factory::<HaMeshInstance>("HaMeshInstance");
     prefabs.register_component_factory::<HaSpriteAnimationInstance>("HaSpriteAnimationInstance");
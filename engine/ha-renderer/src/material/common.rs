@@ -622,6 +622,21 @@ impl_value_from! {
     Array(Vec<MaterialValue>),
 }
 
+/// Interpolation of value passed from vertex to fragment shader across primitive surface.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum MaterialInterpolation {
+    /// Value gets perspective-correct interpolated between vertices.
+    Smooth,
+    /// Value of provoking vertex is used for whole primitive (always used for integers).
+    Flat,
+}
+
+impl Default for MaterialInterpolation {
+    fn default() -> Self {
+        Self::Smooth
+    }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum MaterialShaderType {
     Undefined,
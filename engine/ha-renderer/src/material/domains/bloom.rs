@@ -0,0 +1,129 @@
+use crate::{material::graph::MaterialGraph, material_graph, math::*};
+
+/// Extracts parts of image brighter than `bloomThreshold`, scaling them by how much they exceed
+/// it, so bright areas fade in smoothly instead of popping.
+pub fn bloom_bright_pass_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout TextureCoord: vec2 = {vec2(0.0, 0.0)};
+
+            [fragment] uniform mainImage: sampler2D;
+            [fragment] uniform bloomThreshold: float = {0.8};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [color = (texture2d, sampler: mainImage, coord: [TextureCoord => vTexCoord])]
+        [rgb = (truncate_vec4, v: color)]
+        [brightness = (dot_vec3, x: rgb, y: {vec3(0.2126, 0.7152, 0.0722)})]
+        [factor = (div_float,
+            a: (max_float, x: (sub_float, a: brightness, b: bloomThreshold), y: {0.0}),
+            b: (max_float, x: brightness, y: {0.0001})
+        )]
+        [(append_vec4, a: (mul_vec3, a: rgb, b: (fill_vec3, v: factor)), b: {1.0}) -> BaseColor]
+    }
+}
+
+/// One direction of separable 9-tap Gaussian blur, sampled with 5 fetches thanks to linear
+/// filtering. `blurDirection` is measured in texels: `(1, 0)` blurs horizontally and `(0, 1)`
+/// vertically.
+pub fn bloom_blur_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout TextureCoord: vec2 = {vec2(0.0, 0.0)};
+
+            [fragment] uniform mainImage: sampler2D;
+            [fragment] uniform blurDirection: vec2 = {vec2(1.0, 0.0)};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [coord = [TextureCoord => vTexCoord]]
+        [size = (cast_ivec2_vec2, v: (textureSize2d, sampler: mainImage, lod: {0_i32}))]
+        [texel = (div_vec2, a: blurDirection, b: size)]
+        [near = (mul_vec2, a: texel, b: {vec2(1.384_615, 1.384_615)})]
+        [far = (mul_vec2, a: texel, b: {vec2(3.230_769, 3.230_769)})]
+        [center = (texture2d, sampler: mainImage, coord: coord)]
+        [near = (add_vec4,
+            a: (texture2d, sampler: mainImage, coord: (add_vec2, a: coord, b: near)),
+            b: (texture2d, sampler: mainImage, coord: (sub_vec2, a: coord, b: near))
+        )]
+        [far = (add_vec4,
+            a: (texture2d, sampler: mainImage, coord: (add_vec2, a: coord, b: far)),
+            b: (texture2d, sampler: mainImage, coord: (sub_vec2, a: coord, b: far))
+        )]
+        [(add_vec4,
+            a: (mul_vec4, a: center, b: (fill_vec4, v: {0.227_027})),
+            b: (add_vec4,
+                a: (mul_vec4, a: near, b: (fill_vec4, v: {0.316_216})),
+                b: (mul_vec4, a: far, b: (fill_vec4, v: {0.070_27}))
+            )
+        ) -> BaseColor]
+    }
+}
+
+/// Adds blurred bright parts of both bloom mip levels (`bloomImage0` and `bloomImage1`) on top
+/// of `mainImage`, scaled by `bloomIntensity`.
+pub fn bloom_composite_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout TextureCoord: vec2 = {vec2(0.0, 0.0)};
+
+            [fragment] uniform mainImage: sampler2D;
+            [fragment] uniform bloomImage0: sampler2D;
+            [fragment] uniform bloomImage1: sampler2D;
+            [fragment] uniform bloomIntensity: float = {1.0};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [coord = [TextureCoord => vTexCoord]]
+        [scene = (texture2d, sampler: mainImage, coord: coord)]
+        [bloom = (add_vec4,
+            a: (texture2d, sampler: bloomImage0, coord: coord),
+            b: (texture2d, sampler: bloomImage1, coord: coord)
+        )]
+        [bloom = (mul_vec3, a: (truncate_vec4, v: bloom), b: (fill_vec3, v: bloomIntensity))]
+        [(append_vec4,
+            a: (add_vec3, a: (truncate_vec4, v: scene), b: bloom),
+            b: (maskW_vec4, v: scene)
+        ) -> BaseColor]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_bloom_materials() {
+        let target = RenderTargetDescriptor::simple("finalColor").unwrap();
+
+        MaterialLibrary::assert_material_compilation(
+            &ScreenSpaceVertex::vertex_layout().unwrap(),
+            target.to_owned(),
+            &screenspace_domain_graph(),
+            &bloom_bright_pass_material_graph(),
+        );
+
+        MaterialLibrary::assert_material_compilation(
+            &ScreenSpaceVertex::vertex_layout().unwrap(),
+            target,
+            &screenspace_domain_graph(),
+            &bloom_blur_material_graph(),
+        );
+
+        MaterialLibrary::assert_material_compilation(
+            &ScreenSpaceVertex::vertex_layout().unwrap(),
+            RenderTargetDescriptor::Main,
+            &screenspace_domain_graph(),
+            &bloom_composite_material_graph(),
+        );
+    }
+}
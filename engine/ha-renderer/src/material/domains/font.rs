@@ -0,0 +1,91 @@
+use crate::{material::graph::MaterialGraph, material_graph, math::*};
+
+/// Name of material asset that text of distance field fonts gets rendered with, unless its
+/// material instance points to another one.
+pub const SDF_FONT_MATERIAL_NAME: &str = "@material/graph/font/sdf";
+
+/// Text material for distance field font atlases (`FontAsset::distance_field`).
+///
+/// Distance is read from red channel of `mainImage` and turned into coverage with smoothstep
+/// around `sdfThreshold`, over width of single screen pixel widened by `sdfSmoothing`, so
+/// glyph edges stay crisp at any scale. Fill gets composited over `outlineColor` covering
+/// distances above `outlineThreshold` and `glowColor` fading in from `glowThreshold` - both
+/// are transparent by default. Thresholds are expected to satisfy
+/// `glowThreshold <= outlineThreshold <= sdfThreshold`.
+pub fn default_sdf_font_material_graph() -> MaterialGraph {
+    material_graph! {
+        inputs {
+            [vertex] inout TextureCoord: vec3 = {vec3(0.0, 0.0, 0.0)};
+            [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+
+            [fragment] uniform mainImage: sampler2DArray;
+            [fragment] uniform sdfThreshold: float = {0.5};
+            [fragment] uniform sdfSmoothing: float = {0.0};
+            [fragment] uniform outlineColor: vec4 = {vec4(0.0, 0.0, 0.0, 0.0)};
+            [fragment] uniform outlineThreshold: float = {0.5};
+            [fragment] uniform glowColor: vec4 = {vec4(0.0, 0.0, 0.0, 0.0)};
+            [fragment] uniform glowThreshold: float = {0.5};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [sdf = (texture2dArray, sampler: mainImage, coord: [TextureCoord => vTexCoord])]
+        [distance = (maskX_vec4, v: sdf)]
+        [width = (max_float,
+            x: (add_float, a: (fwidth_float, p: distance), b: sdfSmoothing),
+            y: {0.0001}
+        )]
+        [fill = (smoothstep_float,
+            edge0: (sub_float, a: sdfThreshold, b: width),
+            edge1: (add_float, a: sdfThreshold, b: width),
+            x: distance
+        )]
+        [outline = (smoothstep_float,
+            edge0: (sub_float, a: outlineThreshold, b: width),
+            edge1: (add_float, a: outlineThreshold, b: width),
+            x: distance
+        )]
+        [glow = (smoothstep_float,
+            edge0: (sub_float, a: glowThreshold, b: width),
+            edge1: (add_float, a: outlineThreshold, b: width),
+            x: distance
+        )]
+        [tint = [TintColor => vColor]]
+        // layers are composited with premultiplied alpha: glow, then outline, then fill.
+        [alpha = (mul_float, a: (maskW_vec4, v: glowColor), b: glow)]
+        [rgb = (mul_vec3, a: (truncate_vec4, v: glowColor), b: (fill_vec3, v: alpha))]
+        [coverage = (mul_float, a: (maskW_vec4, v: outlineColor), b: outline)]
+        [rest = (sub_float, a: {1.0}, b: coverage)]
+        [rgb = (add_vec3,
+            a: (mul_vec3, a: (truncate_vec4, v: outlineColor), b: (fill_vec3, v: coverage)),
+            b: (mul_vec3, a: rgb, b: (fill_vec3, v: rest))
+        )]
+        [alpha = (add_float, a: coverage, b: (mul_float, a: alpha, b: rest))]
+        [coverage = (mul_float, a: (maskW_vec4, v: tint), b: fill)]
+        [rest = (sub_float, a: {1.0}, b: coverage)]
+        [rgb = (add_vec3,
+            a: (mul_vec3, a: (truncate_vec4, v: tint), b: (fill_vec3, v: coverage)),
+            b: (mul_vec3, a: rgb, b: (fill_vec3, v: rest))
+        )]
+        [alpha = (add_float, a: coverage, b: (mul_float, a: alpha, b: rest))]
+        [rgb = (div_vec3, a: rgb, b: (fill_vec3, v: (max_float, x: alpha, y: {0.0001})))]
+        [(append_vec4, a: rgb, b: alpha) -> BaseColor]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_sdf_font_materials() {
+        MaterialLibrary::assert_material_compilation(
+            &SurfaceVertexText::vertex_layout().unwrap(),
+            RenderTargetDescriptor::Main,
+            &surface_flat_domain_graph(),
+            &default_sdf_font_material_graph(),
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{
+    material::{
+        common::{MaterialDataType, MaterialShaderType, MaterialValue, MaterialValueType},
+        graph::{
+            node::{
+                MaterialGraphInput, MaterialGraphNodeId, MaterialGraphOperation,
+                MaterialGraphTransfer,
+            },
+            MaterialGraph,
+        },
+    },
+    material_graph_input, material_graph_output,
+    math::*,
+};
+
+/// Name of uniform holding given property (`Position`, `Color` or `Radius`) of point light at
+/// given index, used by lit material graph.
+pub fn point_light_uniform_name(index: usize, property: &str) -> String {
+    format!("pointLight{}{}", index, property)
+}
+
+/// Surface material lit by `LIGHTS` point lights.
+///
+/// Normals are read from `normalImage` (expected to face +Z, like sprite normal maps) and
+/// each light is described by `pointLight<N>Position: vec3`, `pointLight<N>Color: vec3` and
+/// `pointLight<N>Radius: float` uniforms, contributing linearly fading diffuse light within
+/// its radius. `ambientColor` gets added to light of all fragments.
+pub fn default_lit_material_graph<const LIGHTS: usize>() -> MaterialGraph {
+    let mut graph = MaterialGraph::default();
+    let texture_coord = graph.add_node(material_graph_input! {
+        [vertex] inout TextureCoord: vec3 = {vec3(0.0, 0.0, 0.0)}
+    });
+    let tint_color = graph.add_node(material_graph_input! {
+        [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)}
+    });
+    let world_position = graph.add_node(material_graph_input! {
+        [vertex] inout WorldPosition: vec3 = {vec3(0.0, 0.0, 0.0)}
+    });
+    let main_image = graph.add_node(material_graph_input! {
+        [fragment] uniform mainImage: sampler2D
+    });
+    let normal_image = graph.add_node(material_graph_input! {
+        [fragment] uniform normalImage: sampler2D
+    });
+    let ambient_color = graph.add_node(material_graph_input! {
+        [fragment] uniform ambientColor: vec3 = {vec3(0.0, 0.0, 0.0)}
+    });
+    let base_color = graph.add_node(material_graph_output! {
+        [fragment] inout BaseColor: vec4
+    });
+
+    let texture_coord = transfer(&mut graph, "vTexCoord", texture_coord);
+    let coord = operation(&mut graph, "truncate_vec3", &[("v", texture_coord)]);
+    let tint_color = transfer(&mut graph, "vColor", tint_color);
+    let world_position = transfer(&mut graph, "vWorldPosition", world_position);
+    let color = operation(
+        &mut graph,
+        "texture2d",
+        &[("sampler", main_image), ("coord", coord)],
+    );
+    let color = operation(&mut graph, "mul_vec4", &[("a", color), ("b", tint_color)]);
+    let normal = operation(
+        &mut graph,
+        "texture2d",
+        &[("sampler", normal_image), ("coord", coord)],
+    );
+    let normal = operation(&mut graph, "truncate_vec4", &[("v", normal)]);
+    let twos = value(&mut graph, vec3(2.0, 2.0, 2.0));
+    let ones = value(&mut graph, vec3(1.0, 1.0, 1.0));
+    let normal = operation(&mut graph, "mul_vec3", &[("a", normal), ("b", twos)]);
+    let normal = operation(&mut graph, "sub_vec3", &[("a", normal), ("b", ones)]);
+    let normal = operation(&mut graph, "normalize_vec3", &[("x", normal)]);
+    let zero = value(&mut graph, 0.0);
+    let one = value(&mut graph, 1.0);
+
+    let mut light = ambient_color;
+    for index in 0..LIGHTS {
+        let position = uniform(&mut graph, index, "Position", MaterialValueType::Vec3F);
+        let light_color = uniform(&mut graph, index, "Color", MaterialValueType::Vec3F);
+        let radius = uniform(&mut graph, index, "Radius", MaterialValueType::Scalar);
+        let direction = operation(
+            &mut graph,
+            "sub_vec3",
+            &[("a", position), ("b", world_position)],
+        );
+        let distance = operation(&mut graph, "length_vec3", &[("x", direction)]);
+        let direction = operation(&mut graph, "normalize_vec3", &[("x", direction)]);
+        let diffuse = operation(&mut graph, "dot_vec3", &[("x", normal), ("y", direction)]);
+        let diffuse = operation(&mut graph, "max_float", &[("x", diffuse), ("y", zero)]);
+        let falloff = operation(&mut graph, "div_float", &[("a", distance), ("b", radius)]);
+        let falloff = operation(&mut graph, "sub_float", &[("a", one), ("b", falloff)]);
+        let falloff = operation(
+            &mut graph,
+            "clamp_float",
+            &[("x", falloff), ("min", zero), ("max", one)],
+        );
+        let intensity = operation(&mut graph, "mul_float", &[("a", diffuse), ("b", falloff)]);
+        let intensity = operation(&mut graph, "fill_vec3", &[("v", intensity)]);
+        let contribution = operation(
+            &mut graph,
+            "mul_vec3",
+            &[("a", light_color), ("b", intensity)],
+        );
+        light = operation(&mut graph, "add_vec3", &[("a", light), ("b", contribution)]);
+    }
+
+    let light = operation(&mut graph, "append_vec4", &[("a", light), ("b", one)]);
+    let result = operation(&mut graph, "mul_vec4", &[("a", color), ("b", light)]);
+    let _ = graph.connect(result, base_color, None);
+    graph
+}
+
+fn uniform(
+    graph: &mut MaterialGraph,
+    index: usize,
+    property: &str,
+    value_type: MaterialValueType,
+) -> MaterialGraphNodeId {
+    graph.add_node(
+        MaterialGraphInput {
+            name: point_light_uniform_name(index, property),
+            undirected: false,
+            data_precision: Default::default(),
+            data_type: MaterialDataType::Uniform,
+            value_type,
+            shader_type: MaterialShaderType::Fragment,
+            default_value: None,
+        }
+        .into(),
+    )
+}
+
+fn value(graph: &mut MaterialGraph, value: impl Into<MaterialValue>) -> MaterialGraphNodeId {
+    graph.add_node(value.into().into())
+}
+
+fn transfer(
+    graph: &mut MaterialGraph,
+    name: &str,
+    from: MaterialGraphNodeId,
+) -> MaterialGraphNodeId {
+    graph.add_node(MaterialGraphTransfer::new_connected(name.to_owned(), from).into())
+}
+
+fn operation(
+    graph: &mut MaterialGraph,
+    name: &str,
+    params: &[(&str, MaterialGraphNodeId)],
+) -> MaterialGraphNodeId {
+    let connections = params
+        .iter()
+        .map(|(param, from)| (param.to_string(), *from))
+        .collect();
+    graph.add_node(MaterialGraphOperation::new_connected(name.to_owned(), connections).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[test]
+    fn test_lit_materials() {
+        MaterialLibrary::assert_material_compilation(
+            &SurfaceVertexPT::vertex_layout().unwrap(),
+            RenderTargetDescriptor::Main,
+            &surface_flat_domain_graph(),
+            &default_lit_material_graph::<0>(),
+        );
+
+        MaterialLibrary::assert_material_compilation(
+            &SurfaceVertexPT::vertex_layout().unwrap(),
+            RenderTargetDescriptor::Main,
+            &surface_flat_domain_graph(),
+            &default_lit_material_graph::<4>(),
+        );
+
+        let graph = default_lit_material_graph::<2>();
+        for index in 0..2 {
+            for property in ["Position", "Color", "Radius"] {
+                let name = point_light_uniform_name(index, property);
+                assert!(graph.inputs().any(|(_, input)| input.name == name));
+            }
+        }
+        assert!(!graph
+            .inputs()
+            .any(|(_, input)| input.name == point_light_uniform_name(2, "Position")));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod bloom;
+pub mod font;
 pub mod gizmo;
+pub mod lighting;
 pub mod screenspace;
 pub mod surface;
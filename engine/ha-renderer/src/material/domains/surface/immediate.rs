@@ -1,8 +1,31 @@
 use crate::{
-    material::domains::surface::SurfaceDomain,
+    image::ImageId,
+    material::{domains::surface::SurfaceDomain, MaterialId},
     mesh::{vertex_factory::StaticVertexFactory, MeshDrawMode, MeshError},
 };
 use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, ops::Range};
+
+/// Render state that queued primitives are drawn with.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SurfaceImmediateState {
+    /// Layers are drawn in ascending order.
+    pub layer: i32,
+    /// Material used instead of batch entity material.
+    pub material: Option<MaterialId>,
+    /// Image bound to batch texture uniform.
+    pub texture: Option<ImageId>,
+}
+
+/// Tells how primitives of given layer can be sorted.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SurfaceImmediateLayerKind {
+    /// Primitives can be reordered to group the ones sharing material and texture.
+    Opaque,
+    /// Primitives keep order they were queued in (back-to-front).
+    #[default]
+    Transparent,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SurfaceImmediateFactory<V>
@@ -11,6 +34,10 @@ where
 {
     vertices: Vec<V>,
     triangles: Vec<(u32, u32, u32)>,
+    /// [(state, triangles range)]
+    items: Vec<(SurfaceImmediateState, Range<usize>)>,
+    state: SurfaceImmediateState,
+    layers: HashMap<i32, SurfaceImmediateLayerKind>,
 }
 
 impl<V> Default for SurfaceImmediateFactory<V>
@@ -21,6 +48,9 @@ where
         Self {
             vertices: Default::default(),
             triangles: Default::default(),
+            items: Default::default(),
+            state: Default::default(),
+            layers: Default::default(),
         }
     }
 }
@@ -33,9 +63,29 @@ where
         Self {
             vertices: Vec::with_capacity(vertex_capacity),
             triangles: Vec::with_capacity(triangle_capacity),
+            items: Default::default(),
+            state: Default::default(),
+            layers: Default::default(),
         }
     }
 
+    pub fn state(&self) -> &SurfaceImmediateState {
+        &self.state
+    }
+
+    /// Sets render state of primitives queued from now on.
+    pub fn set_state(&mut self, state: SurfaceImmediateState) {
+        self.state = state;
+    }
+
+    pub fn layer_kind(&self, layer: i32) -> SurfaceImmediateLayerKind {
+        self.layers.get(&layer).copied().unwrap_or_default()
+    }
+
+    pub fn set_layer_kind(&mut self, layer: i32, kind: SurfaceImmediateLayerKind) {
+        self.layers.insert(layer, kind);
+    }
+
     pub fn is_empty(&self) -> bool {
         self.triangles.is_empty()
     }
@@ -43,6 +93,8 @@ where
     pub fn clear(&mut self) {
         self.vertices.clear();
         self.triangles.clear();
+        self.items.clear();
+        self.state = Default::default();
     }
 
     pub fn reserve(&mut self, vertex_count: usize, triangle_count: usize) {
@@ -52,6 +104,7 @@ where
 
     pub fn triangles(&mut self, vertices: &[V], triangles: &[(u32, u32, u32)]) {
         let offset = self.vertices.len() as u32;
+        let start = self.triangles.len();
         self.vertices.extend(vertices.iter().copied());
         self.triangles.extend(
             triangles
@@ -59,6 +112,7 @@ where
                 .copied()
                 .map(|(a, b, c)| (a + offset, b + offset, c + offset)),
         );
+        self.push_item(start);
     }
 
     pub fn triangle(&mut self, vertices: [V; 3]) {
@@ -77,13 +131,60 @@ where
         let triangles = vertices.len() - 2;
         self.vertices.extend(vertices.iter().copied());
         self.triangles.reserve(triangles);
+        let start = self.triangles.len();
         for i in 0..(triangles as u32) {
             self.triangles
                 .push((offset, offset + i + 1, offset + i + 2));
         }
+        self.push_item(start);
         true
     }
 
+    /// Sorts queued primitives by layer and groups primitives of opaque layers sharing material
+    /// and texture, so they can be flushed with less state changes. Primitives of transparent
+    /// layers keep their order.
+    pub fn sort(&mut self) {
+        let items = std::mem::take(&mut self.items);
+        // { (layer, material, texture): group }
+        let mut groups = HashMap::<_, usize>::new();
+        let mut keys = Vec::with_capacity(items.len());
+        for (state, _) in &items {
+            let group = match self.layer_kind(state.layer) {
+                SurfaceImmediateLayerKind::Opaque => {
+                    let count = groups.len();
+                    *groups
+                        .entry((state.layer, state.material, state.texture))
+                        .or_insert(count)
+                }
+                SurfaceImmediateLayerKind::Transparent => 0,
+            };
+            keys.push((state.layer, group));
+        }
+        let mut order = (0..items.len()).collect::<Vec<_>>();
+        // stable sort keeps queue order of primitives with the same key.
+        order.sort_by_key(|index| keys[*index]);
+        let mut triangles = Vec::with_capacity(self.triangles.len());
+        for index in order {
+            let (state, range) = &items[index];
+            let start = triangles.len();
+            triangles.extend_from_slice(&self.triangles[range.to_owned()]);
+            push_item(&mut self.items, state, start..triangles.len());
+        }
+        self.triangles = triangles;
+    }
+
+    /// Ranges of indices of consecutive primitives sharing render state, in order they get
+    /// flushed - each one needs single draw call.
+    pub fn batches(&self) -> impl Iterator<Item = (&SurfaceImmediateState, Range<usize>)> {
+        self.items
+            .iter()
+            .map(|(state, range)| (state, (range.start * 3)..(range.end * 3)))
+    }
+
+    fn push_item(&mut self, start: usize) {
+        push_item(&mut self.items, &self.state, start..self.triangles.len());
+    }
+
     pub fn factory(&self) -> Result<StaticVertexFactory, MeshError> {
         let mut result = StaticVertexFactory::new(
             V::vertex_layout()?,
@@ -96,3 +197,20 @@ where
         Ok(result)
     }
 }
+
+fn push_item(
+    items: &mut Vec<(SurfaceImmediateState, Range<usize>)>,
+    state: &SurfaceImmediateState,
+    range: Range<usize>,
+) {
+    if range.is_empty() {
+        return;
+    }
+    if let Some((last_state, last_range)) = items.last_mut() {
+        if last_state == state && last_range.end == range.start {
+            last_range.end = range.end;
+            return;
+        }
+    }
+    items.push((state.to_owned(), range));
+}
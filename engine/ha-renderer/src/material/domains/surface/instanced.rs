@@ -0,0 +1,199 @@
+use crate::mesh::{
+    vertex_factory::{StaticVertexFactory, VertexType},
+    Mesh, MeshDrawMode, MeshError, VertexLayout,
+};
+
+/// Accumulates instances of single mesh, so all of them get rendered in one draw call.
+///
+/// Geometry goes into per-vertex buffers and instances data into per-instance buffers appended
+/// after them, so meshes receiving this batch must use `InstancedBatch::layout()`.
+#[derive(Debug, Clone)]
+pub struct InstancedBatch<I>
+where
+    I: VertexType,
+{
+    geometry: StaticVertexFactory,
+    layout: VertexLayout,
+    instances: Vec<I>,
+}
+
+impl<I> InstancedBatch<I>
+where
+    I: VertexType + Copy,
+{
+    pub fn new(geometry: StaticVertexFactory) -> Result<Self, MeshError> {
+        let layout = geometry
+            .layout()
+            .to_owned()
+            .with_instanced(I::vertex_layout()?)?;
+        Ok(Self {
+            geometry,
+            layout,
+            instances: vec![],
+        })
+    }
+
+    pub fn layout(&self) -> &VertexLayout {
+        &self.layout
+    }
+
+    pub fn geometry(&self) -> &StaticVertexFactory {
+        &self.geometry
+    }
+
+    pub fn instances(&self) -> &[I] {
+        &self.instances
+    }
+
+    pub fn len(&self) -> usize {
+        self.instances.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instances.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.instances.clear();
+    }
+
+    pub fn push(&mut self, instance: I) {
+        self.instances.push(instance);
+    }
+
+    pub fn extend(&mut self, instances: impl IntoIterator<Item = I>) {
+        self.instances.extend(instances);
+    }
+
+    /// Bytes of per-instance buffers, in order of their appearance in layout.
+    pub fn instance_buffers(&self) -> Result<Vec<Vec<u8>>, MeshError> {
+        let mut factory = StaticVertexFactory::new(
+            I::vertex_layout()?,
+            self.instances.len(),
+            0,
+            MeshDrawMode::Triangles,
+        );
+        factory.vertices(&self.instances, None)?;
+        Ok(factory.into_inner().1)
+    }
+
+    /// Writes geometry and instances into mesh - it then gets rendered with single draw call.
+    pub fn write_into(&self, mesh: &mut Mesh) -> Result<(), MeshError> {
+        if mesh.layout() != &self.layout {
+            return Err(MeshError::LayoutsMismatch(
+                Box::new(self.layout.to_owned()),
+                Box::new(mesh.layout().to_owned()),
+            ));
+        }
+        let (_, buffers, indices, _, draw_mode) = self.geometry.to_owned().into_inner();
+        for (index, data) in buffers
+            .into_iter()
+            .chain(self.instance_buffers()?.into_iter())
+            .enumerate()
+        {
+            mesh.set_vertex_data(index, data)?;
+        }
+        mesh.set_index_data(indices, draw_mode);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    fn quad() -> StaticVertexFactory {
+        let mut factory = StaticVertexFactory::new(
+            SurfaceVertexPT::vertex_layout().unwrap(),
+            4,
+            2,
+            MeshDrawMode::Triangles,
+        );
+        factory
+            .vertices_vec3f(
+                "position",
+                &[
+                    vec3(0.0, 0.0, 0.0),
+                    vec3(1.0, 0.0, 0.0),
+                    vec3(1.0, 1.0, 0.0),
+                    vec3(0.0, 1.0, 0.0),
+                ],
+                None,
+            )
+            .unwrap();
+        factory.triangles(&[(0, 1, 2), (2, 3, 0)], None).unwrap();
+        factory
+    }
+
+    #[test]
+    fn test_instanced_materials() {
+        let batch = InstancedBatch::<SurfaceInstanceFragment>::new(quad()).unwrap();
+        assert!(batch.layout().is_instanced());
+        assert_eq!(batch.layout().middlewares(), &["instancing".to_owned()]);
+
+        MaterialLibrary::assert_material_compilation(
+            batch.layout(),
+            RenderTargetDescriptor::Main,
+            &surface_flat_domain_graph(),
+            &default_surface_flat_texture_2d_material_graph(),
+        );
+    }
+
+    #[test]
+    fn test_instanced_batch_layout() {
+        let mut batch = InstancedBatch::<SurfaceInstanceFragment>::new(quad()).unwrap();
+        let layout = batch.layout();
+        assert_eq!(layout.buffers().len(), 2);
+        assert_eq!(layout.buffers()[0].divisor(), 0);
+        assert_eq!(layout.buffers()[1].divisor(), 1);
+        let stride = layout.buffers()[1].bytesize();
+        assert_eq!(stride, 6 * 4 * std::mem::size_of::<f32>());
+        let attribs = layout
+            .vertex_attribs()
+            .map(|(index, name, chunk)| (index, name.to_owned(), chunk.location(), chunk.offset()))
+            .collect::<Vec<_>>();
+        let (_, _, base_location, _) = attribs
+            .iter()
+            .find(|(_, name, _, _)| name == "instanceMatrixA")
+            .unwrap();
+        assert!(attribs
+            .iter()
+            .filter(|(index, _, _, _)| *index == 0)
+            .all(|(_, _, location, _)| location < base_location));
+        let color_offset = attribs
+            .iter()
+            .find(|(_, name, _, _)| name == "instanceColor")
+            .map(|(_, _, _, offset)| *offset)
+            .unwrap();
+
+        batch.push(SurfaceInstanceFragment::default());
+        batch.push(SurfaceInstanceFragment::new(
+            Mat4::translation_3d(vec3(2.0, 3.0, 4.0)),
+            vec4(0.5, 0.25, 1.0, 1.0),
+            rect(0.5, 0.0, 0.5, 0.5),
+        ));
+        let buffers = batch.instance_buffers().unwrap();
+        assert_eq!(buffers.len(), 1);
+        assert_eq!(buffers[0].len(), stride * 2);
+        let floats = |offset: usize| {
+            buffers[0][offset..(offset + 16)]
+                .chunks_exact(4)
+                .map(|bytes| f32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(floats(0), vec![1.0, 0.0, 0.0, 0.0]);
+        assert_eq!(floats(stride + 48), vec![2.0, 3.0, 4.0, 1.0]);
+        assert_eq!(floats(stride + color_offset), vec![0.5, 0.25, 1.0, 1.0]);
+        assert_eq!(floats(stride + color_offset + 16), vec![0.5, 0.0, 0.5, 0.5]);
+
+        let mut mesh = Mesh::new(batch.layout().to_owned());
+        batch.write_into(&mut mesh).unwrap();
+        assert_eq!(mesh.instances_count(), Some(2));
+        assert_eq!(mesh.index_data().len(), 6);
+        assert_eq!(mesh.vertex_data(1).unwrap().len(), stride * 2);
+
+        batch.clear();
+        batch.write_into(&mut mesh).unwrap();
+        assert_eq!(mesh.instances_count(), Some(0));
+    }
+}
@@ -1,6 +1,7 @@
 pub mod circle;
 pub mod grid;
 pub mod immediate;
+pub mod instanced;
 pub mod quad;
 pub mod rig2d;
 pub mod text;
@@ -411,6 +412,26 @@ fn default_bone_weights() -> vek::Vec4<f32> {
     vec4(0.0, 0.0, 0.0, 0.0)
 }
 
+fn default_instance_matrix_a() -> vek::Vec4<f32> {
+    vec4(1.0, 0.0, 0.0, 0.0)
+}
+
+fn default_instance_matrix_b() -> vek::Vec4<f32> {
+    vec4(0.0, 1.0, 0.0, 0.0)
+}
+
+fn default_instance_matrix_c() -> vek::Vec4<f32> {
+    vec4(0.0, 0.0, 1.0, 0.0)
+}
+
+fn default_instance_matrix_d() -> vek::Vec4<f32> {
+    vec4(0.0, 0.0, 0.0, 1.0)
+}
+
+fn default_instance_texture_rect() -> vek::Vec4<f32> {
+    vec4(0.0, 0.0, 1.0, 1.0)
+}
+
 pub trait SurfaceDomain: VertexType {}
 pub trait SurfaceColoredDomain: SurfaceDomain {}
 pub trait SurfaceTexturedDomain: SurfaceDomain {}
@@ -449,6 +470,66 @@ vertex_type! {
     }
 }
 
+vertex_type! {
+    /// Per-instance data of instanced surface meshes, see `InstancedBatch`.
+    #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
+    @middlewares(instancing)
+    pub struct SurfaceInstanceFragment {
+        #[serde(default = "default_instance_matrix_a")]
+        pub matrix_a: vec4 = instanceMatrixA(0),
+        #[serde(default = "default_instance_matrix_b")]
+        pub matrix_b: vec4 = instanceMatrixB(0),
+        #[serde(default = "default_instance_matrix_c")]
+        pub matrix_c: vec4 = instanceMatrixC(0),
+        #[serde(default = "default_instance_matrix_d")]
+        pub matrix_d: vec4 = instanceMatrixD(0),
+        #[serde(default = "default_color")]
+        pub color: vec4 = instanceColor(0),
+        #[serde(default = "default_instance_texture_rect")]
+        pub texture_rect: vec4 = instanceTextureRect(0),
+    }
+}
+
+impl Default for SurfaceInstanceFragment {
+    fn default() -> Self {
+        Self {
+            matrix_a: default_instance_matrix_a(),
+            matrix_b: default_instance_matrix_b(),
+            matrix_c: default_instance_matrix_c(),
+            matrix_d: default_instance_matrix_d(),
+            color: default_color(),
+            texture_rect: default_instance_texture_rect(),
+        }
+    }
+}
+
+impl SurfaceInstanceFragment {
+    pub fn new(transform: vek::Mat4<f32>, color: vek::Vec4<f32>, texture_rect: Rect) -> Self {
+        Self {
+            matrix_a: transform.cols.x,
+            matrix_b: transform.cols.y,
+            matrix_c: transform.cols.z,
+            matrix_d: transform.cols.w,
+            color,
+            texture_rect: vec4(
+                texture_rect.x as _,
+                texture_rect.y as _,
+                texture_rect.w as _,
+                texture_rect.h as _,
+            ),
+        }
+    }
+
+    pub fn transform(&self) -> vek::Mat4<f32> {
+        vek::Mat4::from_col_arrays([
+            self.matrix_a.into_array(),
+            self.matrix_b.into_array(),
+            self.matrix_c.into_array(),
+            self.matrix_d.into_array(),
+        ])
+    }
+}
+
 vertex_type! {
     #[derive(Debug, Default, Copy, Clone, Serialize, Deserialize)]
     @tags(SurfaceDomain)
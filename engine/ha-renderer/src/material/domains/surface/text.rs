@@ -1,6 +1,6 @@
 use crate::{
     asset_protocols::font::FontAsset,
-    components::text_instance::{HaTextElement, HaTextInstance},
+    components::text_instance::{HaTextDirection, HaTextElement, HaTextInstance},
     material::domains::surface::SurfaceTextDomain,
     math::*,
     mesh::{
@@ -17,6 +17,8 @@ struct TextGlyph {
     character: char,
     page: usize,
     position: Vec2,
+    x_offset: f32,
+    advance: f32,
     uvs: Rect,
     size: Vec2,
     color: Rgba,
@@ -30,15 +32,25 @@ struct TextGlyph {
 pub struct SurfaceTextFactory;
 
 impl SurfaceTextFactory {
-    pub fn geometry(
+    /// Measures size of laid out text - bounds of text instance, when set, are its minimal size.
+    pub fn measure(text: &HaTextInstance, font: &FontAsset) -> Vec2 {
+        Self::layout_with_size(text, font).1
+    }
+
+    fn layout(text: &HaTextInstance, font: &FontAsset) -> Vec<(f32, Vec<TextGlyph>)> {
+        Self::layout_with_size(text, font).0
+    }
+
+    fn layout_with_size(
         text: &HaTextInstance,
         font: &FontAsset,
-        meta: bool,
-    ) -> Result<Geometry, MeshError> {
+    ) -> (Vec<(f32, Vec<TextGlyph>)>, Vec2) {
         let count = text.glyphs_count();
         let bounds_width = text.bounds_width().unwrap_or(f32::INFINITY);
         let bounds_height = text.bounds_height().unwrap_or(f32::INFINITY);
-        let extra_y = text.lines_extra_space();
+        // line height multiplier adds space the same way extra lines space does.
+        let extra_y = text.lines_extra_space()
+            + font.line_height as f32 * (text.line_height().max(0.0) - 1.0);
         let mut line_cache = Vec::<TextGlyph>::with_capacity(count);
         let mut lines = Vec::with_capacity(text.lines_count());
         let mut x = 0.0;
@@ -48,6 +60,7 @@ impl SurfaceTextFactory {
         let mut line_width: f32 = 0.0;
         let mut line_height: f32 = 0.0;
         let mut line_base: f32 = 0.0;
+        let mut previous = None;
 
         macro_rules! move_to_new_line {
             (@push) => {
@@ -55,6 +68,7 @@ impl SurfaceTextFactory {
                     for glyph in &mut line_cache {
                         glyph.position.y += line_base - glyph.baseline;
                     }
+                    Self::reorder_line(&mut line_cache, text.direction());
                     lines.push((
                         line_width,
                         std::mem::replace(&mut line_cache, Vec::with_capacity(count)),
@@ -69,6 +83,7 @@ impl SurfaceTextFactory {
                     line_width = 0.0;
                     line_height = 0.0;
                     line_base = 0.0;
+                    previous = None;
                 }
             };
             () => {
@@ -97,6 +112,10 @@ impl SurfaceTextFactory {
                     if let Some(c) = font.characters.get(&character) {
                         if let Some((page_size, _)) = font.pages_image_assets.get(c.page) {
                             let scale = size / font.line_height as f32;
+                            if let (true, Some(previous)) = (text.kerning(), previous) {
+                                x += font.kerning(previous, character) * scale;
+                            }
+                            previous = Some(character);
                             let xadvance = c.line_advance * scale;
                             let yadvance = (font.line_height as f32 + extra_y) * scale;
                             if x + xadvance > bounds_width {
@@ -115,6 +134,8 @@ impl SurfaceTextFactory {
                                 character,
                                 page: c.page as _,
                                 position: Vec2::new(x, y) + offset,
+                                x_offset: offset.x,
+                                advance: xadvance,
                                 uvs: Rect::new(
                                     c.image_location.x / page_size.x,
                                     c.image_location.y / page_size.y,
@@ -142,14 +163,73 @@ impl SurfaceTextFactory {
         let yalign = (height - y) * text.alignment().y;
         let xpivot = width * text.pivot().x;
         let ypivot = height * text.pivot().y;
+        let xalignment = match text.direction() {
+            HaTextDirection::LeftToRight => text.alignment().x,
+            HaTextDirection::RightToLeft => 1.0 - text.alignment().x,
+        };
         for (line_width, line) in &mut lines {
-            let xalign = (width - *line_width) * text.alignment().x;
+            let xalign = (width - *line_width) * xalignment;
             for glyph in line {
                 glyph.position.x += xalign - xpivot;
                 glyph.position.y += yalign - ypivot;
             }
         }
+        (lines, Vec2::new(width, height))
+    }
 
+    fn reorder_line(glyphs: &mut Vec<TextGlyph>, direction: HaTextDirection) {
+        if direction == HaTextDirection::LeftToRight
+            && !glyphs.iter().any(|glyph| {
+                HaTextDirection::of_character(glyph.character) == Some(HaTextDirection::RightToLeft)
+            })
+        {
+            return;
+        }
+        let characters = glyphs
+            .iter()
+            .map(|glyph| glyph.character)
+            .collect::<Vec<_>>();
+        let order = HaTextDirection::visual_order(&characters, direction);
+        // kerning applied in front of each glyph in logical order.
+        let mut pen = 0.0;
+        let kernings = glyphs
+            .iter()
+            .map(|glyph| {
+                let start = glyph.position.x - glyph.x_offset;
+                let kerning = start - pen;
+                pen = start + glyph.advance;
+                kerning
+            })
+            .collect::<Vec<_>>();
+        let mut source = std::mem::take(glyphs)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        let mut x = 0.0;
+        let mut last = None;
+        for index in order {
+            if let Some(mut glyph) = source[index].take() {
+                // glyphs that stay next to each other keep their kerning, so reversed run gets
+                // mirrored kerned positions.
+                x += match last {
+                    Some(last) if index == last + 1 => kernings[index],
+                    Some(last) if index + 1 == last => kernings[last],
+                    _ => 0.0,
+                };
+                glyph.position.x = x + glyph.x_offset;
+                x += glyph.advance;
+                last = Some(index);
+                glyphs.push(glyph);
+            }
+        }
+    }
+
+    pub fn geometry(
+        text: &HaTextInstance,
+        font: &FontAsset,
+        meta: bool,
+    ) -> Result<Geometry, MeshError> {
+        let lines = Self::layout(text, font);
         Ok(Geometry::new(
             GeometryVertices::default().with_columns([
                 GeometryVerticesColumn::new(
@@ -258,3 +338,200 @@ impl SurfaceTextFactory {
         Self::geometry(text, font, false)?.factory::<T>()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asset_protocols::font::FontAssetCharacter;
+    use core::assets::asset::AssetId;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_bidi_text_layout() {
+        let characters = ['a', 'b', ' ', '\u{5d0}', '\u{5d1}']
+            .into_iter()
+            .map(|character| {
+                (
+                    character,
+                    FontAssetCharacter {
+                        page: 0,
+                        image_location: Vec2::zero(),
+                        image_size: Vec2::new(10.0, 10.0),
+                        size: Vec2::new(10.0, 10.0),
+                        offset: Vec2::zero(),
+                        line_advance: 10.0,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let font = FontAsset {
+            line_height: 10,
+            line_base: 8,
+            sdf_resolution: 0,
+            distance_field: false,
+            characters,
+            pages_image_assets: vec![(Vec2::new(100.0, 100.0), AssetId::new())],
+            filtering: Default::default(),
+            kerning_pairs: Default::default(),
+        };
+        let mut text = HaTextInstance::default();
+        text.set_size(10.0);
+        text.set_bounds_width(Some(100.0));
+        text.set_content("ab \u{5d0}\u{5d1}");
+
+        let lines = SurfaceTextFactory::layout(&text, &font);
+        let glyphs = lines[0]
+            .1
+            .iter()
+            .map(|glyph| (glyph.character, glyph.position.x))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            glyphs,
+            vec![
+                ('a', 0.0),
+                ('b', 10.0),
+                (' ', 20.0),
+                ('\u{5d1}', 30.0),
+                ('\u{5d0}', 40.0),
+            ]
+        );
+
+        text.set_direction(HaTextDirection::RightToLeft);
+        let lines = SurfaceTextFactory::layout(&text, &font);
+        let glyphs = lines[0]
+            .1
+            .iter()
+            .map(|glyph| (glyph.character, glyph.position.x))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            glyphs,
+            vec![
+                ('\u{5d1}', 50.0),
+                ('\u{5d0}', 60.0),
+                (' ', 70.0),
+                ('a', 80.0),
+                ('b', 90.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_measure_text() {
+        let source = serde_json::from_str(include_str!(
+            "../../../../../../templates/prototype/assets/fonts/roboto.json"
+        ))
+        .unwrap();
+        let font = FontAsset::new(source, vec![(Vec2::new(512.0, 512.0), AssetId::new())]);
+        let mut text = HaTextInstance::default();
+        text.set_size(32.0);
+        text.set_content("Hello");
+        let short = SurfaceTextFactory::measure(&text, &font);
+        text.set_content("Hello, world");
+        let long = SurfaceTextFactory::measure(&text, &font);
+        assert!(long.x > short.x);
+        assert_eq!(long.y, short.y);
+        assert_eq!(short.y, 32.0);
+
+        text.set_content("Hello,\nworld");
+        let multiline = SurfaceTextFactory::measure(&text, &font);
+        assert!(multiline.x < long.x);
+        assert_eq!(multiline.y, 64.0);
+
+        text.set_size(64.0);
+        let scaled = SurfaceTextFactory::measure(&text, &font);
+        assert!((scaled.x - multiline.x * 2.0).abs() < 1.0e-3);
+        assert_eq!(scaled.y, 128.0);
+    }
+
+    #[test]
+    fn test_text_kerning() {
+        let characters = ['A', 'V']
+            .into_iter()
+            .map(|character| {
+                (
+                    character,
+                    FontAssetCharacter {
+                        page: 0,
+                        image_location: Vec2::zero(),
+                        image_size: Vec2::new(10.0, 10.0),
+                        size: Vec2::new(10.0, 10.0),
+                        offset: Vec2::zero(),
+                        line_advance: 10.0,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let font = FontAsset {
+            line_height: 10,
+            line_base: 8,
+            sdf_resolution: 0,
+            distance_field: false,
+            characters,
+            pages_image_assets: vec![(Vec2::new(100.0, 100.0), AssetId::new())],
+            filtering: Default::default(),
+            kerning_pairs: [(('A', 'V'), -2.0)].into_iter().collect(),
+        };
+        let mut text = HaTextInstance::default();
+        text.set_size(10.0);
+        text.set_content("AV");
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).x, 18.0);
+        text.set_content("VA");
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).x, 20.0);
+
+        text.set_content("AV");
+        text.set_kerning(false);
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).x, 20.0);
+
+        text.set_content("A\nV");
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).y, 20.0);
+        text.set_line_height(1.5);
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).y, 30.0);
+    }
+
+    #[test]
+    fn test_right_to_left_text_kerning() {
+        let characters = ['\u{5d0}', '\u{5d1}', '\u{5d2}']
+            .into_iter()
+            .map(|character| {
+                (
+                    character,
+                    FontAssetCharacter {
+                        page: 0,
+                        image_location: Vec2::zero(),
+                        image_size: Vec2::new(10.0, 10.0),
+                        size: Vec2::new(10.0, 10.0),
+                        offset: Vec2::zero(),
+                        line_advance: 10.0,
+                    },
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        let font = FontAsset {
+            line_height: 10,
+            line_base: 8,
+            sdf_resolution: 0,
+            distance_field: false,
+            characters,
+            pages_image_assets: vec![(Vec2::new(100.0, 100.0), AssetId::new())],
+            filtering: Default::default(),
+            kerning_pairs: [(('\u{5d0}', '\u{5d1}'), -2.0)].into_iter().collect(),
+        };
+        let mut text = HaTextInstance::default();
+        text.set_size(10.0);
+        text.set_direction(HaTextDirection::RightToLeft);
+        text.set_content("\u{5d0}\u{5d1}\u{5d2}");
+        assert_eq!(SurfaceTextFactory::measure(&text, &font).x, 28.0);
+
+        let lines = SurfaceTextFactory::layout(&text, &font);
+        let glyphs = &lines[0].1;
+        let start = glyphs[0].position.x;
+        let glyphs = glyphs
+            .iter()
+            .map(|glyph| (glyph.character, glyph.position.x - start))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            glyphs,
+            vec![('\u{5d2}', 0.0), ('\u{5d1}', 10.0), ('\u{5d0}', 18.0)]
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    components::tilemap_instance::HaTileMapInstance,
+    components::tilemap_instance::{HaTileMapChunk, HaTileMapInstance, HaTileMapTile},
     image::VirtualImage,
     material::domains::surface::SurfaceTexturedDomain,
     math::*,
@@ -24,7 +24,27 @@ impl SurfaceTileMapFactory {
         resources: &Resources<VirtualImage>,
         meta: bool,
     ) -> Result<Geometry, MeshError> {
-        if tilemap.cols() == 0 || tilemap.rows() == 0 || tilemap.tiles().is_empty() {
+        Self::tiles_geometry(tilemap, tilemap.tiles().iter(), resources, meta)
+    }
+
+    /// Geometry of tiles of single chunk, positioned the same as in whole tilemap geometry.
+    pub fn chunk_geometry(
+        tilemap: &HaTileMapInstance,
+        chunk: HaTileMapChunk,
+        resources: &Resources<VirtualImage>,
+        meta: bool,
+    ) -> Result<Geometry, MeshError> {
+        Self::tiles_geometry(tilemap, tilemap.chunk_tiles(chunk), resources, meta)
+    }
+
+    fn tiles_geometry<'a>(
+        tilemap: &HaTileMapInstance,
+        tiles: impl Iterator<Item = &'a HaTileMapTile>,
+        resources: &Resources<VirtualImage>,
+        meta: bool,
+    ) -> Result<Geometry, MeshError> {
+        let tiles = tiles.collect::<Vec<_>>();
+        if tilemap.cols() == 0 || tilemap.rows() == 0 || tiles.is_empty() {
             return Err(MeshError::ZeroSize);
         }
         let virtual_image = match resources.get_named(tilemap.atlas()) {
@@ -36,9 +56,8 @@ impl SurfaceTileMapFactory {
                 )))
             }
         };
-        let tiles = tilemap
-            .tiles()
-            .iter()
+        let tiles = tiles
+            .into_iter()
             .filter_map(|tile| {
                 virtual_image
                     .named_image_uvs(&tile.atlas_item)
@@ -124,4 +143,15 @@ impl SurfaceTileMapFactory {
     {
         Self::geometry(tilemap, resources, false)?.factory::<T>()
     }
+
+    pub fn chunk_factory<T>(
+        tilemap: &HaTileMapInstance,
+        chunk: HaTileMapChunk,
+        resources: &Resources<VirtualImage>,
+    ) -> Result<StaticVertexFactory, MeshError>
+    where
+        T: SurfaceTexturedDomain,
+    {
+        Self::chunk_geometry(tilemap, chunk, resources, false)?.factory::<T>()
+    }
 }
@@ -52,6 +52,7 @@ impl MaterialFunction {
     }
 
     pub fn validate(&self, library: &MaterialLibrary) -> Result<(), MaterialError> {
+        self.validate_signature()?;
         if let MaterialFunctionContent::Graph(graph) = &self.content {
             graph.validate(library)?;
             if !graph.outputs().any(|(_, node)| {
@@ -76,6 +77,27 @@ impl MaterialFunction {
         Ok(())
     }
 
+    /// Checks if function and its inputs have names that can be used in shader code and that
+    /// no input name is repeated.
+    pub fn validate_signature(&self) -> Result<(), MaterialError> {
+        if !is_valid_identifier(&self.name) {
+            return Err(MaterialError::InvalidFunctionName(self.name.to_owned()));
+        }
+        for (index, input) in self.inputs.iter().enumerate() {
+            if !is_valid_identifier(&input.name)
+                || self.inputs[..index]
+                    .iter()
+                    .any(|item| item.name == input.name)
+            {
+                return Err(MaterialError::InvalidFunctionInput {
+                    function: self.name.to_owned(),
+                    input: input.name.to_owned(),
+                });
+            }
+        }
+        Ok(())
+    }
+
     pub fn can_be_compiled(&self) -> bool {
         !matches!(self.content, MaterialFunctionContent::BuiltIn(_))
     }
@@ -148,6 +170,11 @@ impl MaterialFunction {
     }
 }
 
+fn is_valid_identifier(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 impl MaterialCompile<StringBuffer, String, MaterialCompilationState<'_>> for MaterialFunction {
     fn compile_to(
         &self,
@@ -5,8 +5,8 @@ use crate::{
     material::{
         common::{
             BakedMaterialShaders, MaterialCompilationState, MaterialCompile, MaterialDataType,
-            MaterialShaderType, MaterialSignature, MaterialValue, MaterialValueCategory,
-            MaterialValueType,
+            MaterialInterpolation, MaterialShaderType, MaterialSignature, MaterialValue,
+            MaterialValueCategory, MaterialValueType,
         },
         graph::{
             function::{MaterialFunctionContent, MaterialFunctionInput},
@@ -18,6 +18,7 @@ use crate::{
         MaterialError,
     },
     math::vek::*,
+    mesh::VertexLayout,
     resources::material_library::MaterialLibrary,
 };
 use core::utils::StringBuffer;
@@ -93,6 +94,61 @@ impl MaterialGraph {
         self.nodes.get(&id)
     }
 
+    /// Kind and name of node, used in error messages.
+    pub fn node_description(&self, id: MaterialGraphNodeId) -> String {
+        match self.nodes.get(&id) {
+            Some(MaterialGraphNode::Value(value)) => format!("value {:?}", value),
+            Some(MaterialGraphNode::Input(node)) => format!("input {}", node.name),
+            Some(MaterialGraphNode::Operation(node)) => format!("operation {}", node.name),
+            Some(MaterialGraphNode::Transfer(node)) => format!("transfer {}", node.name),
+            Some(MaterialGraphNode::Output(node)) => format!("output {}", node.name),
+            None => format!("{:?}", id),
+        }
+    }
+
+    /// Shader that node gets compiled into - nodes feeding transfer nodes end up in vertex
+    /// shader, other ones in shader of output they lead to.
+    pub fn node_shader_type(&self, id: MaterialGraphNodeId) -> MaterialShaderType {
+        match self.nodes.get(&id) {
+            Some(MaterialGraphNode::Input(node)) => return node.shader_type,
+            Some(MaterialGraphNode::Output(node)) => return node.shader_type,
+            Some(MaterialGraphNode::Transfer(_)) => return MaterialShaderType::Fragment,
+            _ => {}
+        }
+        let mut visited = HashSet::with_capacity(self.nodes.len());
+        let mut queue = vec![id];
+        while let Some(current) = queue.pop() {
+            if !visited.insert(current) {
+                continue;
+            }
+            for (next, node) in &self.nodes {
+                if !node.has_input(current) {
+                    continue;
+                }
+                match node {
+                    MaterialGraphNode::Transfer(_) => return MaterialShaderType::Vertex,
+                    MaterialGraphNode::Output(node) => return node.shader_type,
+                    _ => queue.push(*next),
+                }
+            }
+        }
+        MaterialShaderType::Undefined
+    }
+
+    /// Adds vertex shader inputs of all attributes of vertex layout.
+    ///
+    /// # Returns
+    /// Map of attribute names to their input nodes.
+    pub fn add_vertex_inputs(
+        &mut self,
+        layout: &VertexLayout,
+    ) -> HashMap<String, MaterialGraphNodeId> {
+        layout
+            .material_graph_inputs()
+            .map(|input| (input.name.to_owned(), self.add_node(input.into())))
+            .collect()
+    }
+
     pub fn nodes(&self) -> impl Iterator<Item = (MaterialGraphNodeId, &MaterialGraphNode)> {
         self.nodes.iter().map(|(k, v)| (*k, v))
     }
@@ -310,6 +366,14 @@ impl MaterialGraph {
                                 });
                             }
                         }
+                        for name in node.input_connections.keys() {
+                            if !function.inputs.iter().any(|item| &item.name == name) {
+                                return Err(MaterialError::InvalidConnectionParam {
+                                    target: *id,
+                                    name: name.to_owned(),
+                                });
+                            }
+                        }
                     }
                 }
                 MaterialGraphNode::Transfer(node) => {
@@ -1109,16 +1173,18 @@ impl MaterialGraph {
                 _ => {}
             }
         }
-        for (name, value_type) in transfers {
+        for (name, (value_type, interpolation)) in transfers {
+            let flat = interpolation == MaterialInterpolation::Flat
+                || value_type.category() == MaterialValueCategory::Integer;
             match shader_type {
                 MaterialShaderType::Vertex => {
-                    if value_type.category() == MaterialValueCategory::Integer {
+                    if flat {
                         output.write_str("flat ")?
                     }
                     output.write_str("out ")?
                 }
                 MaterialShaderType::Fragment => {
-                    if value_type.category() == MaterialValueCategory::Integer {
+                    if flat {
                         output.write_str("flat ")?
                     }
                     output.write_str("in ")?
@@ -1139,7 +1205,7 @@ impl MaterialGraph {
         node: &'a MaterialGraphNode,
         value_type: &'a MaterialValueType,
         library: &'a MaterialLibrary,
-        output: &mut HashMap<String, &'a MaterialValueType>,
+        output: &mut HashMap<String, (&'a MaterialValueType, MaterialInterpolation)>,
     ) {
         match node {
             MaterialGraphNode::Operation(n) => {
@@ -1155,7 +1221,7 @@ impl MaterialGraph {
                 }
             }
             MaterialGraphNode::Transfer(n) => {
-                output.insert(n.name.to_owned(), value_type);
+                output.insert(n.name.to_owned(), (value_type, n.interpolation));
             }
             MaterialGraphNode::Output(n) => {
                 if let Some(from) = n.input_connection {
@@ -1,7 +1,7 @@
 use crate::{
     material::common::{
-        MaterialDataPrecision, MaterialDataType, MaterialShaderType, MaterialValue,
-        MaterialValueType,
+        MaterialDataPrecision, MaterialDataType, MaterialInterpolation, MaterialShaderType,
+        MaterialValue, MaterialValueType,
     },
     math::vek::*,
 };
@@ -66,6 +66,23 @@ impl MaterialGraphInput {
         }
     }
 
+    /// Vertex shader input reading mesh vertex attribute of given name.
+    pub fn vs_attribute(
+        name: String,
+        value_type: MaterialValueType,
+        default_value: MaterialValue,
+    ) -> Self {
+        Self {
+            name,
+            undirected: false,
+            data_precision: MaterialDataPrecision::Default,
+            data_type: MaterialDataType::Attribute,
+            value_type,
+            shader_type: MaterialShaderType::Vertex,
+            default_value: Some(default_value),
+        }
+    }
+
     pub fn fs_front_facing() -> Self {
         Self {
             name: "gl_FrontFacing".to_owned(),
@@ -280,6 +297,8 @@ pub struct MaterialGraphTransfer {
     #[serde(default)]
     pub name: String,
     #[serde(default)]
+    pub interpolation: MaterialInterpolation,
+    #[serde(default)]
     pub(crate) input_connection: Option<MaterialGraphNodeId>,
 }
 
@@ -287,6 +306,7 @@ impl MaterialGraphTransfer {
     pub fn new(name: String) -> Self {
         Self {
             name,
+            interpolation: Default::default(),
             input_connection: None,
         }
     }
@@ -294,10 +314,16 @@ impl MaterialGraphTransfer {
     pub fn new_connected(name: String, node: MaterialGraphNodeId) -> Self {
         Self {
             name,
+            interpolation: Default::default(),
             input_connection: Some(node),
         }
     }
 
+    pub fn with_interpolation(mut self, interpolation: MaterialInterpolation) -> Self {
+        self.interpolation = interpolation;
+        self
+    }
+
     pub fn has_input(&self, id: MaterialGraphNodeId) -> bool {
         self.input_connection == Some(id)
     }
@@ -5,17 +5,23 @@ pub mod graph;
 use crate::{
     ha_renderer::{RenderStageResources, RenderStats},
     material::{
-        common::{BakedMaterialShaders, MaterialSignature, MaterialValue, MaterialValueType},
+        common::{
+            BakedMaterialShaders, MaterialShaderType, MaterialSignature, MaterialValue,
+            MaterialValueType,
+        },
         graph::{node::MaterialGraphNodeId, MaterialGraph},
     },
     render_target::RenderTargetError,
-    resources::resource_mapping::ResourceMapping,
+    resources::{material_library::MaterialLibrary, resource_mapping::ResourceMapping},
     HasContextResources, ResourceReference,
 };
 use core::id::ID;
 use glow::*;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
 
 pub type MaterialId = ID<Material>;
 pub type MaterialReference = ResourceReference<MaterialId>;
@@ -89,9 +95,92 @@ pub enum MaterialError {
     CouldNotCreateRenderTarget(Box<RenderTargetError>),
     FunctionIsNotValidMiddleware(String),
     FunctionDoesNotExists(String),
+    FunctionAlreadyExists(String),
+    InvalidFunctionName(String),
+    InvalidFunctionInput {
+        function: String,
+        input: String,
+    },
     MiddlewareDoesNotExists(String),
 }
 
+impl MaterialError {
+    /// Details of error pointing at graph node that caused it, available for errors of graph
+    /// validation reported while baking material.
+    pub fn compile_error(&self) -> Option<MaterialCompileError> {
+        let (graph, error) = match self {
+            Self::Baking(graph, error) => (graph, &**error),
+            _ => return None,
+        };
+        let (node, param, expected_type, actual_type) = match error {
+            Self::MismatchingConnectionTypes {
+                from_value_type,
+                to,
+                to_value_type,
+                param,
+                ..
+            } => (
+                *to,
+                param.to_owned(),
+                to_value_type.to_owned(),
+                from_value_type.to_owned(),
+            ),
+            Self::MissingConnection { id, param } => (*id, param.to_owned(), None, None),
+            Self::InvalidConnectionParam { target, name } => {
+                (*target, Some(name.to_owned()), None, None)
+            }
+            Self::InvalidConnectionSource { target, .. } => (*target, None, None, None),
+            Self::FunctionNotFoundInLibrary { node, .. }
+            | Self::InvalidName { node, .. }
+            | Self::AttributeInputHasNoDefaultValue { node, .. } => (*node, None, None, None),
+            Self::NoTransferFound(id) => (*id, None, None, None),
+            _ => return None,
+        };
+        Some(MaterialCompileError {
+            node,
+            node_name: graph.node_description(node),
+            shader_type: graph.node_shader_type(node),
+            param,
+            expected_type,
+            actual_type,
+            error: error.to_owned(),
+        })
+    }
+}
+
+/// Material graph error together with description of node that caused it.
+#[derive(Debug, Clone)]
+pub struct MaterialCompileError {
+    pub node: MaterialGraphNodeId,
+    /// Kind and name of node, for example: `operation add_vec4`.
+    pub node_name: String,
+    /// Shader that node ends up in, `Undefined` if it does not reach any output.
+    pub shader_type: MaterialShaderType,
+    /// Name of node parameter that caused error, if any.
+    pub param: Option<String>,
+    pub expected_type: Option<MaterialValueType>,
+    pub actual_type: Option<MaterialValueType>,
+    pub error: MaterialError,
+}
+
+impl fmt::Display for MaterialCompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} shader node {}", self.shader_type, self.node_name)?;
+        if let Some(param) = &self.param {
+            write!(f, " (param: {})", param)?;
+        }
+        match (&self.expected_type, &self.actual_type) {
+            (Some(expected), Some(actual)) => write!(
+                f,
+                " expects {} but got {}",
+                expected.to_string(),
+                actual.to_string()
+            ),
+            _ => write!(f, ": {:?}", self.error),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct MaterialResourceHandles {
     pub program: <Context as HasContext>::Program,
@@ -645,6 +734,72 @@ impl Material {
         Ok(())
     }
 
+    /// Bakes and compiles new graph for given signatures and swaps it with current one only
+    /// when all of them succeed, otherwise material keeps its previous graph and programs.
+    /// Uniform values already set on material are kept.
+    pub(crate) fn reload(
+        &mut self,
+        context: Option<&Context>,
+        graph: MaterialGraph,
+        signatures: &HashSet<MaterialSignature>,
+        library: &MaterialLibrary,
+        fragment_high_precision_support: bool,
+    ) -> Result<(), MaterialError> {
+        if matches!(self.content, MaterialContent::Baked) {
+            return Ok(());
+        }
+        let mut versions = HashMap::with_capacity(signatures.len());
+        for signature in signatures {
+            let domain = signature.domain().and_then(|domain| library.domain(domain));
+            let baked = graph.bake(signature, domain, library, fragment_high_precision_support)?;
+            if let Some(baked) = baked {
+                versions.insert(signature.to_owned(), baked);
+            }
+        }
+        if let (Some(context), Some(resources)) = (context, &mut self.resources) {
+            let mut handles = HashMap::with_capacity(versions.len());
+            for (signature, baked) in &versions {
+                match Self::compile_program(context, baked) {
+                    Ok((program, uniforms, samplers)) => {
+                        handles.insert(
+                            signature.to_owned(),
+                            MaterialResourceHandles {
+                                program,
+                                uniforms,
+                                samplers,
+                            },
+                        );
+                    }
+                    Err(error) => {
+                        unsafe {
+                            for handles in handles.values() {
+                                context.delete_program(handles.program);
+                            }
+                        }
+                        return Err(error);
+                    }
+                }
+            }
+            let old = std::mem::replace(&mut resources.0, handles);
+            unsafe {
+                for handles in old.values() {
+                    context.delete_program(handles.program);
+                    for handle in handles.samplers.values() {
+                        context.delete_sampler(*handle);
+                    }
+                }
+            }
+        }
+        for (name, value) in graph.default_uniform_values() {
+            self.default_values
+                .entry(name.to_owned())
+                .or_insert_with(|| value.to_owned());
+        }
+        self.content = MaterialContent::Graph(graph);
+        self.versions = versions;
+        Ok(())
+    }
+
     pub(crate) fn remove_version(
         &mut self,
         context: &Context,
@@ -1086,6 +1241,17 @@ macro_rules! material_graph {
             $graph.add_node(___temp)
         }
     };
+    ( @expression [ $node:tt => flat $name:ident ], $graph:expr ) => {
+        {
+            let ___temp = $crate::material::graph::node::MaterialGraphTransfer::new_connected(
+                stringify!($name).to_owned(),
+                $crate::material_graph!(@expression $node, $graph)
+            )
+            .with_interpolation($crate::material::common::MaterialInterpolation::Flat)
+            .into();
+            $graph.add_node(___temp)
+        }
+    };
     ( @expression [ $node:tt => $name:ident ], $graph:expr ) => {
         {
             let ___temp = $crate::material::graph::node::MaterialGraphTransfer::new_connected(
@@ -6,12 +6,16 @@ pub mod vertex_factory;
 
 use crate::{
     ha_renderer::{RenderStageResources, RenderStats},
+    material::{
+        common::{MaterialValue, MaterialValueType},
+        graph::node::MaterialGraphInput,
+    },
     math::*,
     mesh::{geometry::GeometryValueType, vertex_factory::VertexType},
     resources::resource_mapping::ResourceMapping,
     HasContextResources, ResourceReference,
 };
-use core::id::ID;
+use core::{id::ID, Scalar};
 use glow::*;
 use serde::{Deserialize, Serialize};
 use std::{collections::HashMap, ops::Range};
@@ -106,6 +110,44 @@ impl VertexValueType {
         )
     }
 
+    pub fn material_value_type(self) -> MaterialValueType {
+        match self {
+            Self::Scalar => MaterialValueType::Scalar,
+            Self::Vec2F => MaterialValueType::Vec2F,
+            Self::Vec3F => MaterialValueType::Vec3F,
+            Self::Vec4F => MaterialValueType::Vec4F,
+            Self::Mat2F => MaterialValueType::Mat2F,
+            Self::Mat3F => MaterialValueType::Mat3F,
+            Self::Mat4F => MaterialValueType::Mat4F,
+            Self::Integer => MaterialValueType::Integer,
+            Self::Vec2I => MaterialValueType::Vec2I,
+            Self::Vec3I => MaterialValueType::Vec3I,
+            Self::Vec4I => MaterialValueType::Vec4I,
+            Self::Mat2I => MaterialValueType::Mat2I,
+            Self::Mat3I => MaterialValueType::Mat3I,
+            Self::Mat4I => MaterialValueType::Mat4I,
+        }
+    }
+
+    pub fn material_zero_value(self) -> MaterialValue {
+        match self {
+            Self::Scalar => MaterialValue::Scalar(0.0),
+            Self::Vec2F => MaterialValue::Vec2F(vek::Vec2::zero()),
+            Self::Vec3F => MaterialValue::Vec3F(vek::Vec3::zero()),
+            Self::Vec4F => MaterialValue::Vec4F(vek::Vec4::zero()),
+            Self::Mat2F => MaterialValue::Mat2F(vek::Mat2::zero()),
+            Self::Mat3F => MaterialValue::Mat3F(vek::Mat3::zero()),
+            Self::Mat4F => MaterialValue::Mat4F(vek::Mat4::zero()),
+            Self::Integer => MaterialValue::Integer(0),
+            Self::Vec2I => MaterialValue::Vec2I(vek::Vec2::zero()),
+            Self::Vec3I => MaterialValue::Vec3I(vek::Vec3::zero()),
+            Self::Vec4I => MaterialValue::Vec4I(vek::Vec4::zero()),
+            Self::Mat2I => MaterialValue::Mat2I(vek::Mat2::zero()),
+            Self::Mat3I => MaterialValue::Mat3I(vek::Mat3::zero()),
+            Self::Mat4I => MaterialValue::Mat4I(vek::Mat4::zero()),
+        }
+    }
+
     pub fn channels(self) -> usize {
         match self {
             Self::Scalar | Self::Integer => 1,
@@ -235,6 +277,26 @@ impl VertexAttribute {
         self.count * self.value_type.locations()
     }
 
+    /// Vertex shader `in` input that material graphs use to read this attribute, defaulting
+    /// to zero for meshes without it.
+    pub fn material_graph_input(&self) -> MaterialGraphInput {
+        let (value_type, default_value) = if self.count > 1 {
+            (
+                MaterialValueType::Array(
+                    Box::new(self.value_type.material_value_type()),
+                    Some(self.count),
+                ),
+                MaterialValue::Array(vec![self.value_type.material_zero_value(); self.count]),
+            )
+        } else {
+            (
+                self.value_type.material_value_type(),
+                self.value_type.material_zero_value(),
+            )
+        };
+        MaterialGraphInput::vs_attribute(self.id.to_owned(), value_type, default_value)
+    }
+
     pub fn bytesize(&self) -> usize {
         self.count * self.value_type.bytesize()
     }
@@ -250,6 +312,10 @@ pub struct VertexBufferLayout {
     attributes: Vec<(VertexAttribute, usize)>,
     base_location: usize,
     bytesize: usize,
+    /// Number of instances that share single element of this buffer - 0 means buffer holds
+    /// per-vertex data.
+    #[serde(default)]
+    divisor: usize,
 }
 
 impl VertexBufferLayout {
@@ -268,10 +334,23 @@ impl VertexBufferLayout {
         Ok(self)
     }
 
+    pub fn with_divisor(mut self, divisor: usize) -> Self {
+        self.divisor = divisor;
+        self
+    }
+
     pub fn bytesize(&self) -> usize {
         self.bytesize
     }
 
+    pub fn divisor(&self) -> usize {
+        self.divisor
+    }
+
+    pub fn is_instanced(&self) -> bool {
+        self.divisor > 0
+    }
+
     fn attributes(&self) -> impl Iterator<Item = &VertexAttribute> + '_ {
         self.attributes.iter().map(|(item, _)| item)
     }
@@ -424,6 +503,17 @@ impl VertexLayout {
         Ok(result)
     }
 
+    /// Appends buffers of other layout as separate per-instance buffers.
+    pub fn with_instanced(mut self, other: Self) -> Result<Self, MeshError> {
+        self = self.with_middlewares(other.middlewares);
+        for mut buffer in other.buffers {
+            buffer.base_location = 0;
+            buffer.divisor = buffer.divisor.max(1);
+            self = self.with_buffer(buffer)?;
+        }
+        Ok(self)
+    }
+
     pub fn buffers(&self) -> &[VertexBufferLayout] {
         &self.buffers
     }
@@ -453,6 +543,12 @@ impl VertexLayout {
         self.buffers.iter().flat_map(|buffer| buffer.attributes())
     }
 
+    /// Material graph inputs of all attributes of this layout.
+    pub fn material_graph_inputs(&self) -> impl Iterator<Item = MaterialGraphInput> + '_ {
+        self.attributes()
+            .map(|attribute| attribute.material_graph_input())
+    }
+
     pub fn vertex_attribs(&self) -> impl Iterator<Item = (usize, &'_ str, VertexAttribChunk)> + '_ {
         self.buffers.iter().enumerate().flat_map(|(index, buffer)| {
             buffer
@@ -474,6 +570,10 @@ impl VertexLayout {
     pub fn is_compact(&self) -> bool {
         self.buffers.len() == 1
     }
+
+    pub fn is_instanced(&self) -> bool {
+        self.buffers.iter().any(|buffer| buffer.is_instanced())
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -518,6 +618,91 @@ impl MeshDrawMode {
             Self::Points => 1,
         }
     }
+
+    /// Splits index range into consecutive ranges of at most `limit` indices,
+    /// aligned to primitive boundaries so no primitive gets cut in half.
+    pub fn split_range(self, range: Range<usize>, limit: usize) -> Vec<Range<usize>> {
+        let primitive = self.indices_count();
+        let limit = (limit - limit % primitive).max(primitive);
+        let mut result = Vec::with_capacity((range.end - range.start) / limit + 1);
+        let mut start = range.start;
+        while start < range.end {
+            let end = (start + limit).min(range.end);
+            result.push(start..end);
+            start = end;
+        }
+        result
+    }
+
+    /// Splits index range into consecutive ranges, aligned to primitive boundaries, where
+    /// indices of each range span at most `limit` vertices. Primitive spanning more vertices
+    /// than that gets its own range.
+    pub fn split_range_by_vertices(
+        self,
+        indices: &[u32],
+        range: Range<usize>,
+        limit: usize,
+    ) -> Vec<Range<usize>> {
+        let primitive = self.indices_count();
+        let end = range.end.min(indices.len());
+        let mut result = vec![];
+        let mut start = range.start;
+        let mut bounds: Option<(u32, u32)> = None;
+        let mut index = range.start;
+        while index < end {
+            let next = (index + primitive).min(end);
+            let primitive_bounds = indices[index..next]
+                .iter()
+                .fold((u32::MAX, 0), |(min, max), v| (min.min(*v), max.max(*v)));
+            bounds = match bounds {
+                Some((min, max)) => {
+                    let min = min.min(primitive_bounds.0);
+                    let max = max.max(primitive_bounds.1);
+                    if (max - min) as usize >= limit {
+                        result.push(start..index);
+                        start = index;
+                        Some(primitive_bounds)
+                    } else {
+                        Some((min, max))
+                    }
+                }
+                None => Some(primitive_bounds),
+            };
+            index = next;
+        }
+        if start < range.end {
+            result.push(start..range.end);
+        }
+        result
+    }
+}
+
+/// Size of GPU buffer storage, tracked on CPU side so data that fits in it gets written in place
+/// instead of reallocating storage.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct MeshBufferAllocation {
+    /// Number of bytes allocated.
+    pub capacity: usize,
+    /// Number of bytes in use.
+    pub length: usize,
+    /// Changes every time buffer storage gets reallocated.
+    pub generation: usize,
+    reallocate: bool,
+}
+
+impl MeshBufferAllocation {
+    fn resize(&mut self, length: usize) {
+        self.length = length;
+        if length > self.capacity {
+            self.capacity = length;
+            self.invalidate();
+        }
+    }
+
+    fn invalidate(&mut self) {
+        self.generation += 1;
+        self.reallocate = true;
+    }
 }
 
 #[derive(Debug)]
@@ -542,10 +727,14 @@ pub struct Mesh {
     vertex_data: Vec<(Vec<u8>, BufferStorage, bool)>,
     /// [(indices, storage, dirty)]
     index_data: (Vec<u32>, BufferStorage, bool),
+    vertex_allocations: Vec<MeshBufferAllocation>,
+    index_allocation: MeshBufferAllocation,
     draw_mode: MeshDrawMode,
     resources: Option<MeshResources>,
     bounds: Option<BoundsVolume>,
     regenerate_bounds: bool,
+    draw_index_limit: Option<usize>,
+    draw_vertex_limit: Option<usize>,
 }
 
 impl Drop for Mesh {
@@ -608,6 +797,9 @@ impl HasContextResources<Context> for Mesh {
                                 offset as _,
                             );
                             context.enable_vertex_attrib_array(location as _);
+                            if buffer.is_instanced() {
+                                context.vertex_attrib_divisor(location as _, buffer.divisor as _);
+                            }
                         }
                         VertexAttribChunk::Integer {
                             location,
@@ -623,6 +815,9 @@ impl HasContextResources<Context> for Mesh {
                                 offset as _,
                             );
                             context.enable_vertex_attrib_array(location as _);
+                            if buffer.is_instanced() {
+                                context.vertex_attrib_divisor(location as _, buffer.divisor as _);
+                            }
                         }
                     }
                 }
@@ -635,6 +830,14 @@ impl HasContextResources<Context> for Mesh {
             indices_handle,
             array_handle,
         });
+        for allocation in &mut self.vertex_allocations {
+            allocation.invalidate();
+        }
+        for (_, _, dirty) in &mut self.vertex_data {
+            *dirty = true;
+        }
+        self.index_allocation.invalidate();
+        self.index_data.2 = true;
         self.maintain(context)
     }
 
@@ -661,10 +864,14 @@ impl Mesh {
             layout,
             vertex_data,
             index_data: (vec![], BufferStorage::default(), false),
+            vertex_allocations: vec![Default::default(); layout.buffers.len()],
+            index_allocation: Default::default(),
             draw_mode: MeshDrawMode::default(),
             resources: None,
             bounds: None,
             regenerate_bounds: true,
+            draw_index_limit: None,
+            draw_vertex_limit: None,
         }
     }
 
@@ -712,15 +919,36 @@ impl Mesh {
 
     pub fn set_regenerate_bounds(&mut self, mode: bool) {
         self.regenerate_bounds = mode;
-        if !mode {
-            self.bounds = None;
-        }
+        self.update_bounds();
+    }
+
+    pub fn draw_index_limit(&self) -> Option<usize> {
+        self.draw_index_limit
+    }
+
+    /// Limits number of indices sent in single draw call - bigger ranges are
+    /// automatically flushed in multiple draw calls, preserving their order.
+    pub fn set_draw_index_limit(&mut self, limit: Option<usize>) {
+        self.draw_index_limit = limit.filter(|limit| *limit > 0);
+    }
+
+    pub fn draw_vertex_limit(&self) -> Option<usize> {
+        self.draw_vertex_limit
+    }
+
+    /// Limits number of vertices referenced by single draw call - ranges whose indices span
+    /// more vertices are automatically flushed in multiple draw calls, preserving their order.
+    pub fn set_draw_vertex_limit(&mut self, limit: Option<usize>) {
+        self.draw_vertex_limit = limit.filter(|limit| *limit > 0);
     }
 
     pub fn layout(&self) -> &VertexLayout {
         &self.layout
     }
 
+    /// Box containing all vertices of layout bounds attribute (usually `position`), computed
+    /// every time vertex data changes. `None` for empty meshes, layouts without bounds attribute
+    /// or when bounds regeneration is disabled.
     pub fn bounds(&self) -> Option<&BoundsVolume> {
         self.bounds.as_ref()
     }
@@ -741,6 +969,7 @@ impl Mesh {
         if let Some((_, s, d)) = self.vertex_data.get_mut(buffer) {
             *s = storage;
             *d = true;
+            self.vertex_allocations[buffer].invalidate();
             return Ok(());
         }
         Err(MeshError::NoBuffer(buffer, self.vertex_data.len()))
@@ -751,11 +980,15 @@ impl Mesh {
             *s = storage;
             *d = true;
         }
+        for allocation in &mut self.vertex_allocations {
+            allocation.invalidate();
+        }
     }
 
     pub fn set_index_storage(&mut self, storage: BufferStorage) {
         self.index_data.1 = storage;
         self.index_data.2 = true;
+        self.index_allocation.invalidate();
     }
 
     pub fn vertex_data(&self, buffer: usize) -> Option<&[u8]> {
@@ -764,38 +997,69 @@ impl Mesh {
             .map(|(data, _, _)| data.as_slice())
     }
 
+    /// Number of instances drawn in single draw call, `None` for meshes without per-instance
+    /// buffers.
+    pub fn instances_count(&self) -> Option<usize> {
+        self.layout
+            .buffers
+            .iter()
+            .zip(self.vertex_data.iter())
+            .filter(|(buffer, _)| buffer.is_instanced() && buffer.bytesize() > 0)
+            .map(|(buffer, (data, _, _))| data.len() / buffer.bytesize() * buffer.divisor())
+            .min()
+    }
+
     pub fn index_data(&self) -> &[u32] {
         &self.index_data.0
     }
 
+    pub fn vertex_allocation(&self, buffer: usize) -> Option<MeshBufferAllocation> {
+        self.vertex_allocations.get(buffer).copied()
+    }
+
+    pub fn index_allocation(&self) -> MeshBufferAllocation {
+        self.index_allocation
+    }
+
     pub fn set_vertex_data(&mut self, buffer: usize, data: Vec<u8>) -> Result<(), MeshError> {
-        let (vertex_data, dirty) = match self.vertex_data.get_mut(buffer) {
-            Some((vertex_data, _, dirty)) => (vertex_data, dirty),
-            None => return Err(MeshError::NoBuffer(buffer, self.vertex_data.len())),
-        };
-        let buffer = match self.layout.buffers.get(buffer) {
-            Some(buffer) => buffer,
-            None => return Err(MeshError::NoBuffer(buffer, self.layout.buffers.len())),
-        };
-        let bytesize = buffer.bytesize();
-        if bytesize == 0 {
-            return Err(MeshError::ZeroSize);
-        }
-        let size = data.len();
-        let count = size / bytesize;
-        let expected_bytesize = count * bytesize;
-        if size != expected_bytesize {
-            return Err(MeshError::InvalidSize(size, expected_bytesize));
-        }
+        self.validate_vertex_data_size(buffer, data.len())?;
+        let (vertex_data, _, dirty) = &mut self.vertex_data[buffer];
         *vertex_data = data;
         *dirty = true;
-        self.bounds = None;
+        self.vertex_allocations[buffer].resize(vertex_data.len());
+        self.update_bounds();
+        Ok(())
+    }
+
+    /// Replaces vertex data reusing already allocated CPU and GPU memory - GPU buffer storage
+    /// gets reallocated only when new data does not fit its capacity.
+    pub fn update_vertices(&mut self, buffer: usize, data: &[u8]) -> Result<(), MeshError> {
+        self.validate_vertex_data_size(buffer, data.len())?;
+        let (vertex_data, _, dirty) = &mut self.vertex_data[buffer];
+        vertex_data.clear();
+        vertex_data.extend_from_slice(data);
+        *dirty = true;
+        self.vertex_allocations[buffer].resize(vertex_data.len());
+        self.update_bounds();
         Ok(())
     }
 
     pub fn set_index_data(&mut self, data: Vec<u32>, draw_mode: MeshDrawMode) {
         self.index_data.0 = data;
         self.index_data.2 = true;
+        self.index_allocation
+            .resize(self.index_data.0.len() * std::mem::size_of::<u32>());
+        self.draw_mode = draw_mode;
+    }
+
+    /// Replaces index data reusing already allocated CPU and GPU memory - GPU buffer storage
+    /// gets reallocated only when new data does not fit its capacity.
+    pub fn update_indices(&mut self, data: &[u32], draw_mode: MeshDrawMode) {
+        self.index_data.0.clear();
+        self.index_data.0.extend_from_slice(data);
+        self.index_data.2 = true;
+        self.index_allocation
+            .resize(self.index_data.0.len() * std::mem::size_of::<u32>());
         self.draw_mode = draw_mode;
     }
 
@@ -831,7 +1095,7 @@ impl Mesh {
         let start = start * bytesize;
         vertex_data[start..limit].copy_from_slice(&data);
         *dirty = true;
-        self.bounds = None;
+        self.update_bounds();
         Ok(())
     }
 
@@ -861,7 +1125,7 @@ impl Mesh {
         };
         f(vertex_data);
         *dirty = true;
-        self.bounds = None;
+        self.update_bounds();
         Ok(())
     }
 
@@ -940,23 +1204,133 @@ impl Mesh {
         context: &Context,
         render_stats: &mut RenderStats,
     ) -> Result<(), MeshError> {
-        let count = range.end - range.start;
-        if count == 0 || range.end > self.index_data.0.len() {
+        if range.start == range.end || range.end > self.index_data.0.len() {
             return Ok(());
         }
+        let ranges = self.flush_ranges(range);
+        render_stats.auto_flushes += ranges.len().saturating_sub(1);
+        for range in ranges {
+            self.draw_elements(range, context, render_stats);
+        }
+        Ok(())
+    }
+
+    /// Ranges of indices that will be sent in separate draw calls when drawing given range.
+    pub fn flush_ranges(&self, range: Range<usize>) -> Vec<Range<usize>> {
+        let ranges = match self.draw_index_limit {
+            Some(limit) if range.end - range.start > limit => {
+                self.draw_mode.split_range(range, limit)
+            }
+            _ => vec![range],
+        };
+        match self.draw_vertex_limit {
+            Some(limit) => ranges
+                .into_iter()
+                .flat_map(|range| {
+                    self.draw_mode
+                        .split_range_by_vertices(&self.index_data.0, range, limit)
+                })
+                .collect(),
+            None => ranges,
+        }
+    }
+
+    fn draw_elements(
+        &self,
+        range: Range<usize>,
+        context: &Context,
+        render_stats: &mut RenderStats,
+    ) {
+        let count = range.end - range.start;
         let offset = range.start;
+        let instances = self.instances_count();
+        if instances == Some(0) {
+            return;
+        }
         unsafe {
-            context.draw_elements(
-                self.draw_mode.as_gl(),
-                count as i32,
-                UNSIGNED_INT,
-                (offset * std::mem::size_of::<u32>()) as i32,
-            );
+            if let Some(instances) = instances {
+                context.draw_elements_instanced(
+                    self.draw_mode.as_gl(),
+                    count as i32,
+                    UNSIGNED_INT,
+                    (offset * std::mem::size_of::<u32>()) as i32,
+                    instances as i32,
+                );
+            } else {
+                context.draw_elements(
+                    self.draw_mode.as_gl(),
+                    count as i32,
+                    UNSIGNED_INT,
+                    (offset * std::mem::size_of::<u32>()) as i32,
+                );
+            }
             render_stats.draw_calls += 1;
         }
+    }
+
+    fn validate_vertex_data_size(&self, buffer: usize, size: usize) -> Result<(), MeshError> {
+        if buffer >= self.vertex_data.len() {
+            return Err(MeshError::NoBuffer(buffer, self.vertex_data.len()));
+        }
+        let buffer = match self.layout.buffers.get(buffer) {
+            Some(buffer) => buffer,
+            None => return Err(MeshError::NoBuffer(buffer, self.layout.buffers.len())),
+        };
+        let bytesize = buffer.bytesize();
+        if bytesize == 0 {
+            return Err(MeshError::ZeroSize);
+        }
+        let count = size / bytesize;
+        let expected_bytesize = count * bytesize;
+        if size != expected_bytesize {
+            return Err(MeshError::InvalidSize(size, expected_bytesize));
+        }
         Ok(())
     }
 
+    fn update_bounds(&mut self) {
+        self.bounds = None;
+        if !self.regenerate_bounds {
+            return;
+        }
+        let (buffer, channels, offset, stride) = match self.layout.bounds_vertex_attrib() {
+            Some(result) => result,
+            None => return,
+        };
+        let buffer = match self.vertex_data.get(buffer) {
+            Some((buffer, _, _)) => buffer,
+            None => return,
+        };
+        let channels = channels.min(3);
+        let size = std::mem::size_of::<f32>();
+        self.bounds = BoundsVolume::from_points_cloud(buffer.chunks_exact(stride).map(|bytes| {
+            let mut result = Vec3::zero();
+            for (index, bytes) in bytes[offset..(offset + channels * size)]
+                .chunks_exact(size)
+                .enumerate()
+            {
+                result[index] = f32::from_ne_bytes(bytes.try_into().unwrap()) as Scalar;
+            }
+            result
+        }));
+    }
+
+    unsafe fn upload_buffer(
+        context: &Context,
+        target: u32,
+        data: &[u8],
+        storage: BufferStorage,
+        allocation: &mut MeshBufferAllocation,
+    ) {
+        if allocation.reallocate {
+            context.buffer_data_size(target, allocation.capacity as _, storage.as_gl());
+            allocation.reallocate = false;
+        }
+        if !data.is_empty() {
+            context.buffer_sub_data_u8_slice(target, 0, data);
+        }
+    }
+
     pub(crate) fn maintain(&mut self, context: &Context) -> Result<(), MeshError> {
         let resources = match &self.resources {
             Some(resources) => resources,
@@ -965,15 +1339,13 @@ impl Mesh {
         if self.index_data.2 {
             unsafe {
                 context.bind_buffer(ELEMENT_ARRAY_BUFFER, Some(resources.indices_handle));
-                if self.index_data.0.is_empty() {
-                    context.buffer_data_size(ELEMENT_ARRAY_BUFFER, 0, self.index_data.1.as_gl());
-                } else {
-                    context.buffer_data_u8_slice(
-                        ELEMENT_ARRAY_BUFFER,
-                        self.index_data.0.align_to().1,
-                        self.index_data.1.as_gl(),
-                    );
-                }
+                Self::upload_buffer(
+                    context,
+                    ELEMENT_ARRAY_BUFFER,
+                    self.index_data.0.align_to().1,
+                    self.index_data.1,
+                    &mut self.index_allocation,
+                );
             }
             self.index_data.2 = false;
         }
@@ -981,34 +1353,15 @@ impl Mesh {
             if *dirty {
                 unsafe {
                     context.bind_buffer(ARRAY_BUFFER, Some(resources.vertices_handles[index]));
-                    if vertex_data.is_empty() {
-                        context.buffer_data_size(ARRAY_BUFFER, 0, storage.as_gl());
-                    } else {
-                        context.buffer_data_u8_slice(ARRAY_BUFFER, vertex_data, storage.as_gl());
-                    }
-                }
-                *dirty = false;
-            }
-        }
-        if self.regenerate_bounds && self.bounds.is_none() {
-            if let Some((buffer, channels, offset, stride)) = self.layout().bounds_vertex_attrib() {
-                if let Some((buffer, _, _)) = self.vertex_data.get(buffer) {
-                    self.bounds = BoundsVolume::from_points_cloud(
-                        buffer.chunks_exact(stride).filter_map(|bytes| unsafe {
-                            let bytes =
-                                &bytes[offset..(offset + channels * std::mem::size_of::<f32>())];
-                            let values = bytes.as_ptr() as *const f32;
-                            match channels {
-                                1 => Some(Vec3::new(*values, 0.0, 0.0)),
-                                2 => Some(Vec3::new(*values, *values.offset(1), 0.0)),
-                                3 | 4 => {
-                                    Some(Vec3::new(*values, *values.offset(1), *values.offset(2)))
-                                }
-                                _ => None,
-                            }
-                        }),
+                    Self::upload_buffer(
+                        context,
+                        ARRAY_BUFFER,
+                        vertex_data,
+                        *storage,
+                        &mut self.vertex_allocations[index],
                     );
                 }
+                *dirty = false;
             }
         }
         Ok(())
@@ -1,4 +1,4 @@
-use crate::mesh::*;
+use crate::{material::graph::node::MaterialGraphInput, mesh::*};
 
 #[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct StreamingVertexFactory {
@@ -544,6 +544,11 @@ pub trait VertexType {
     fn mat3i(&self, name: &str) -> Option<vek::Mat3<i32>>;
     fn mat4i(&self, name: &str) -> Option<vek::Mat4<i32>>;
     fn transform(&mut self, _matrix: &vek::Mat4<f32>) {}
+
+    /// Material graph inputs of all attributes of this vertex type.
+    fn material_graph_inputs() -> Result<Vec<MaterialGraphInput>, MeshError> {
+        Ok(Self::vertex_layout()?.material_graph_inputs().collect())
+    }
 }
 
 #[macro_export]
@@ -634,7 +639,7 @@ macro_rules! vertex_type {
     };
     (@data(imat3, $ignore:ident), $field_name:ident, $field_mapping:ident, $name:expr, $this:expr) => ();
     (@data(imat4, imat4), $field_name:ident, $field_mapping:ident, $name:expr, $this:expr) => {
-        $crate::vertex_type!(@return $field_name, $field_mapping, $field_mapping, $name, $this);
+        $crate::vertex_type!(@return $field_name, $field_mapping, $name, $this);
     };
     (@data(imat4, $ignore:ident), $field_name:ident, $field_mapping:ident, $name:expr, $this:expr) => ();
     (
@@ -28,6 +28,8 @@ pub struct PipelineDescriptor {
     pub(crate) stages: Vec<StageDescriptor>,
     #[serde(default)]
     pub(crate) render_targets: HashMap<String, RenderTargetDescriptor>,
+    #[serde(default)]
+    pub(crate) order: i32,
 }
 
 impl PipelineDescriptor {
@@ -58,6 +60,13 @@ impl PipelineDescriptor {
         self.render_targets.insert(name.to_string(), data);
         self
     }
+
+    /// Pipelines with lower order are executed first - use it to render into offscreen targets
+    /// before other pipelines sample them.
+    pub fn order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -70,6 +79,7 @@ pub struct PipelineDetailedInfo {
 pub struct Pipeline {
     pub(crate) stages: Vec<Stage>,
     pub(crate) render_targets: HashMap<String, (RenderTargetDescriptor, RenderTargetId)>,
+    pub(crate) order: i32,
 }
 
 impl Pipeline {
@@ -84,6 +94,10 @@ impl Pipeline {
         self.render_targets.values().map(|(_, id)| *id)
     }
 
+    pub fn order(&self) -> i32 {
+        self.order
+    }
+
     pub fn stages_count(&self) -> usize {
         self.stages.len()
     }
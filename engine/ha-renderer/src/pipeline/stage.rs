@@ -37,6 +37,19 @@ impl StageProcessInfo {
             vertex_layout.middlewares().into(),
         )
     }
+
+    /// Tells if world space bounds are at least partially inside camera frustum. Only side
+    /// planes are tested, so objects never get rejected because of camera depth range.
+    pub fn is_world_bounds_visible(&self, bounds: &BoundsVolume) -> bool {
+        let matrix = self.projection_matrix * self.view_matrix;
+        let points = bounds
+            .box_vertices()
+            .map(|point| matrix * Vec4::from_point(point));
+        !(points.iter().all(|point| point.x < -point.w)
+            || points.iter().all(|point| point.x > point.w)
+            || points.iter().all(|point| point.y < -point.w)
+            || points.iter().all(|point| point.y > point.w))
+    }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
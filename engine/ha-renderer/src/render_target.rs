@@ -6,6 +6,12 @@ use core::{id::ID, Scalar};
 use glow::*;
 use serde::{Deserialize, Serialize};
 
+/// Name of virtual image that exposes buffer of render target used by pipeline of named camera,
+/// so it can be sampled as `sampler2D` uniform of materials (mirrors, minimaps, portals).
+pub fn render_target_virtual_image_name(camera: &str, render_target: &str, buffer: &str) -> String {
+    format!("@render-target/{}/{}/{}", camera, render_target, buffer)
+}
+
 #[derive(Debug, Clone)]
 pub enum RenderTargetError {
     InvalidId(String),
@@ -67,9 +73,19 @@ pub struct TargetBuffers {
     pub depth_stencil: Option<String>,
     #[serde(default)]
     pub colors: Vec<TargetBuffer>,
+    /// Requested number of MSAA samples (1, 2, 4 or 8) - 0 and 1 mean no multisampling.
+    /// Multisampled buffers get resolved into regular buffers after each render, so they can
+    /// be sampled by later stages.
+    #[serde(default)]
+    pub samples: usize,
 }
 
 impl TargetBuffers {
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.samples = samples;
+        self
+    }
+
     pub fn with_depth_stencil(mut self, id: String) -> Result<Self, RenderTargetError> {
         if let Some(ds) = &self.depth_stencil {
             if ds == &id {
@@ -97,6 +113,8 @@ impl TargetBuffers {
     }
 }
 
+/// Levels scale size by powers of two: positive levels upscale and negative levels downscale
+/// (`-1` gives half of size), which is useful for postprocess mip chains.
 #[derive(Debug, Copy, Clone, Serialize, Deserialize)]
 pub enum RenderTargetSizeValue {
     Screen {
@@ -127,9 +145,17 @@ impl Default for RenderTargetSizeValue {
 }
 
 impl RenderTargetSizeValue {
+    fn apply_level(value: usize, level: i8) -> usize {
+        if level >= 0 {
+            value << level
+        } else {
+            (value >> level.unsigned_abs()).max(1)
+        }
+    }
+
     pub fn width(self, width: usize, height: usize) -> usize {
         match self {
-            Self::Screen { level } => width << level,
+            Self::Screen { level } => Self::apply_level(width, level),
             Self::ScreenAspectWidth { value, .. } => value,
             Self::ScreenAspectHeight { value, round_up } => {
                 let value = (value as Scalar * width as Scalar) / height as Scalar;
@@ -139,13 +165,13 @@ impl RenderTargetSizeValue {
                     value.floor() as _
                 }
             }
-            Self::Exact { value, level } => value << level,
+            Self::Exact { value, level } => Self::apply_level(value, level),
         }
     }
 
     pub fn height(self, width: usize, height: usize) -> usize {
         match self {
-            Self::Screen { level } => height << level,
+            Self::Screen { level } => Self::apply_level(height, level),
             Self::ScreenAspectWidth { value, round_up } => {
                 let value = (value as Scalar * height as Scalar) / width as Scalar;
                 if round_up {
@@ -155,7 +181,7 @@ impl RenderTargetSizeValue {
                 }
             }
             Self::ScreenAspectHeight { value, .. } => value,
-            Self::Exact { value, level } => value << level,
+            Self::Exact { value, level } => Self::apply_level(value, level),
         }
     }
 }
@@ -284,6 +310,28 @@ impl RenderTargetDescriptor {
             height: RenderTargetSizeValue::default(),
         })
     }
+
+    /// Sets size of custom render target - use `RenderTargetSizeValue::Exact` to pin it to fixed
+    /// resolution, other values make it follow screen size.
+    pub fn with_size(self, width: RenderTargetSizeValue, height: RenderTargetSizeValue) -> Self {
+        match self {
+            Self::Main => Self::Main,
+            Self::Custom { buffers, .. } => Self::Custom {
+                buffers,
+                width,
+                height,
+            },
+        }
+    }
+}
+
+/// Finds highest sample count supported by context (with given max samples) that does not
+/// exceed requested one.
+pub fn supported_render_target_samples(requested: usize, max_samples: usize) -> usize {
+    [8, 4, 2]
+        .into_iter()
+        .find(|samples| *samples <= requested && *samples <= max_samples)
+        .unwrap_or(1)
 }
 
 #[derive(Debug)]
@@ -291,6 +339,15 @@ pub struct RenderTargetResources {
     pub buffer_handle: <Context as HasContext>::Framebuffer,
     pub depth_stencil_handle: Option<<Context as HasContext>::Texture>,
     pub color_handles: Vec<<Context as HasContext>::Texture>,
+    /// Multisampled buffers rendered into, that get resolved into the ones above.
+    pub multisample: Option<RenderTargetMultisampleResources>,
+}
+
+#[derive(Debug)]
+pub struct RenderTargetMultisampleResources {
+    pub buffer_handle: <Context as HasContext>::Framebuffer,
+    pub depth_stencil_handle: Option<<Context as HasContext>::Renderbuffer>,
+    pub color_handles: Vec<<Context as HasContext>::Renderbuffer>,
 }
 
 pub type RenderTargetId = ID<RenderTarget>;
@@ -303,6 +360,7 @@ pub struct RenderTargetDetailedInfo {
     pub preferred_width: RenderTargetSizeValue,
     pub preferred_height: RenderTargetSizeValue,
     pub backbuffer: bool,
+    pub samples: usize,
 }
 
 #[derive(Debug)]
@@ -313,12 +371,16 @@ pub struct RenderTarget {
     preferred_width: RenderTargetSizeValue,
     preferred_height: RenderTargetSizeValue,
     backbuffer: bool,
+    samples: usize,
     resources: Option<RenderTargetResources>,
+    /// Multisampled buffers that main target renders into before they get resolved into
+    /// backbuffer.
+    backbuffer_multisample: Option<RenderTargetMultisampleResources>,
 }
 
 impl Drop for RenderTarget {
     fn drop(&mut self) {
-        if self.resources.is_some() {
+        if self.resources.is_some() || self.backbuffer_multisample.is_some() {
             panic!(
                 "Dropping {} without calling `context_release` to release resources first!",
                 std::any::type_name::<Self>()
@@ -335,12 +397,16 @@ impl HasContextResources<Context> for RenderTarget {
     }
 
     fn context_initialize(&mut self, context: &Context) -> Result<(), Self::Error> {
+        self.context_release(context)?;
+
         if self.backbuffer {
+            self.update_samples(context);
+            if self.samples > 1 {
+                self.backbuffer_multisample = Some(self.initialize_multisample(context)?);
+            }
             return Ok(());
         }
 
-        self.context_release(context)?;
-
         let buffer_handle = match unsafe { context.create_framebuffer() } {
             Ok(handle) => handle,
             Err(error) => return Err(RenderTargetError::Internal(error)),
@@ -443,20 +509,34 @@ impl HasContextResources<Context> for RenderTarget {
             }
         }
 
+        self.update_samples(context);
+        let multisample = if self.samples > 1 {
+            Some(self.initialize_multisample(context)?)
+        } else {
+            None
+        };
+
         self.resources = Some(RenderTargetResources {
             buffer_handle,
             depth_stencil_handle,
             color_handles,
+            multisample,
         });
         Ok(())
     }
 
     fn context_release(&mut self, context: &Context) -> Result<(), Self::Error> {
+        if let Some(multisample) = self.backbuffer_multisample.take() {
+            Self::release_multisample(context, multisample);
+        }
         if self.backbuffer {
             return Ok(());
         }
 
         if let Some(resources) = std::mem::take(&mut self.resources) {
+            if let Some(multisample) = resources.multisample {
+                Self::release_multisample(context, multisample);
+            }
             unsafe {
                 context.delete_framebuffer(resources.buffer_handle);
                 if let Some(handle) = resources.depth_stencil_handle {
@@ -484,7 +564,9 @@ impl RenderTarget {
             preferred_width,
             preferred_height,
             backbuffer: false,
+            samples: 1,
             resources: None,
+            backbuffer_multisample: None,
         }
     }
 
@@ -502,10 +584,24 @@ impl RenderTarget {
             preferred_width: RenderTargetSizeValue::default(),
             preferred_height: RenderTargetSizeValue::default(),
             backbuffer: true,
+            samples: 1,
             resources: None,
+            backbuffer_multisample: None,
         })
     }
 
+    /// Sets requested number of MSAA samples (1, 2, 4 or 8) - it gets applied when target gets
+    /// its context resources initialized. Multisampled main target gets resolved into
+    /// backbuffer after each render.
+    pub fn with_samples(mut self, samples: usize) -> Self {
+        self.buffers.samples = samples;
+        self
+    }
+
+    pub(crate) fn set_requested_samples(&mut self, samples: usize) {
+        self.buffers.samples = samples;
+    }
+
     pub fn detailed_info(&self) -> RenderTargetDetailedInfo {
         RenderTargetDetailedInfo {
             buffers: self.buffers.clone(),
@@ -514,6 +610,7 @@ impl RenderTarget {
             preferred_width: self.preferred_width,
             preferred_height: self.preferred_height,
             backbuffer: self.backbuffer,
+            samples: self.samples,
         }
     }
 
@@ -521,6 +618,10 @@ impl RenderTarget {
         &self.buffers
     }
 
+    pub fn is_backbuffer(&self) -> bool {
+        self.backbuffer
+    }
+
     pub fn width(&self) -> usize {
         self.cached_width
     }
@@ -533,6 +634,190 @@ impl RenderTarget {
         (self.cached_width, self.cached_height)
     }
 
+    /// Effective number of MSAA samples - it might be lower than requested one when context
+    /// does not support it.
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    fn update_samples(&mut self, context: &Context) {
+        let max_samples = unsafe { context.get_parameter_i32(MAX_SAMPLES) }.max(1) as usize;
+        let requested = self.buffers.samples.max(1);
+        self.samples = supported_render_target_samples(requested, max_samples);
+        if self.samples != requested {
+            oxygengine_core::warn!(
+                "Render target requested {} samples but context supports {} - using {} samples",
+                requested,
+                max_samples,
+                self.samples
+            );
+        }
+    }
+
+    fn release_multisample(context: &Context, multisample: RenderTargetMultisampleResources) {
+        unsafe {
+            context.delete_framebuffer(multisample.buffer_handle);
+            if let Some(handle) = multisample.depth_stencil_handle {
+                context.delete_renderbuffer(handle);
+            }
+            for handle in multisample.color_handles {
+                context.delete_renderbuffer(handle);
+            }
+        }
+    }
+
+    fn initialize_multisample(
+        &self,
+        context: &Context,
+    ) -> Result<RenderTargetMultisampleResources, RenderTargetError> {
+        let buffer_handle = match unsafe { context.create_framebuffer() } {
+            Ok(handle) => handle,
+            Err(error) => return Err(RenderTargetError::Internal(error)),
+        };
+        let create_renderbuffer = |internal_format: u32| -> Result<_, RenderTargetError> {
+            let handle = match unsafe { context.create_renderbuffer() } {
+                Ok(handle) => handle,
+                Err(error) => return Err(RenderTargetError::Internal(error)),
+            };
+            unsafe {
+                context.bind_renderbuffer(RENDERBUFFER, Some(handle));
+                context.renderbuffer_storage_multisample(
+                    RENDERBUFFER,
+                    self.samples as _,
+                    internal_format,
+                    self.cached_width as _,
+                    self.cached_height as _,
+                );
+            }
+            Ok(handle)
+        };
+        let depth_stencil_handle = if self.buffers.depth_stencil.is_some() {
+            Some(create_renderbuffer(DEPTH24_STENCIL8)?)
+        } else {
+            None
+        };
+        let color_handles = self
+            .buffers
+            .colors
+            .iter()
+            .map(|buffer| match buffer.value_type {
+                TargetValueType::Color => create_renderbuffer(RGBA8),
+                TargetValueType::FloatColor => create_renderbuffer(RGBA32F),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        unsafe {
+            context.bind_renderbuffer(RENDERBUFFER, None);
+            context.bind_framebuffer(FRAMEBUFFER, Some(buffer_handle));
+            context.framebuffer_renderbuffer(
+                FRAMEBUFFER,
+                DEPTH_STENCIL_ATTACHMENT,
+                RENDERBUFFER,
+                depth_stencil_handle,
+            );
+            for (i, handle) in color_handles.iter().enumerate() {
+                context.framebuffer_renderbuffer(
+                    FRAMEBUFFER,
+                    COLOR_ATTACHMENT0 + i as u32,
+                    RENDERBUFFER,
+                    Some(*handle),
+                );
+            }
+            context.draw_buffers(&self.color_attachments());
+        }
+
+        Ok(RenderTargetMultisampleResources {
+            buffer_handle,
+            depth_stencil_handle,
+            color_handles,
+        })
+    }
+
+    fn color_attachments(&self) -> Vec<u32> {
+        (0..self.buffers.colors.len())
+            .map(|i| COLOR_ATTACHMENT0 + i as u32)
+            .collect()
+    }
+
+    /// Copies multisampled color buffer into backbuffer, averaging samples.
+    fn resolve_backbuffer_multisample(&self, context: &Context) {
+        let multisample = match &self.backbuffer_multisample {
+            Some(multisample) => multisample,
+            None => return,
+        };
+        let width = self.cached_width as i32;
+        let height = self.cached_height as i32;
+        unsafe {
+            context.bind_framebuffer(READ_FRAMEBUFFER, Some(multisample.buffer_handle));
+            context.bind_framebuffer(DRAW_FRAMEBUFFER, None);
+            context.read_buffer(COLOR_ATTACHMENT0);
+            context.blit_framebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                COLOR_BUFFER_BIT,
+                NEAREST,
+            );
+            context.bind_framebuffer(READ_FRAMEBUFFER, None);
+        }
+    }
+
+    /// Copies multisampled buffers into regular ones, averaging samples.
+    fn resolve_multisample(&self, context: &Context, resources: &RenderTargetResources) {
+        let multisample = match &resources.multisample {
+            Some(multisample) => multisample,
+            None => return,
+        };
+        let width = self.cached_width as i32;
+        let height = self.cached_height as i32;
+        unsafe {
+            context.bind_framebuffer(READ_FRAMEBUFFER, Some(multisample.buffer_handle));
+            context.bind_framebuffer(DRAW_FRAMEBUFFER, Some(resources.buffer_handle));
+            let count = self.buffers.colors.len();
+            for i in 0..count {
+                let attachment = COLOR_ATTACHMENT0 + i as u32;
+                let mut draw_buffers = vec![NONE; count];
+                draw_buffers[i] = attachment;
+                context.read_buffer(attachment);
+                context.draw_buffers(&draw_buffers);
+                context.blit_framebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    COLOR_BUFFER_BIT,
+                    NEAREST,
+                );
+            }
+            if self.buffers.depth_stencil.is_some() {
+                context.blit_framebuffer(
+                    0,
+                    0,
+                    width,
+                    height,
+                    0,
+                    0,
+                    width,
+                    height,
+                    DEPTH_BUFFER_BIT | STENCIL_BUFFER_BIT,
+                    NEAREST,
+                );
+            }
+            context.draw_buffers(&self.color_attachments());
+            context.bind_framebuffer(READ_FRAMEBUFFER, None);
+            context.bind_framebuffer(DRAW_FRAMEBUFFER, None);
+        }
+    }
+
     pub fn resources(&self, _: &RenderStageResources<'_>) -> Option<&RenderTargetResources> {
         self.resources.as_ref()
     }
@@ -600,14 +885,21 @@ impl RenderTarget {
         })
     }
 
+    /// Size of render target for given screen size.
+    pub fn preferred_size(&self, screen_width: usize, screen_height: usize) -> (usize, usize) {
+        (
+            self.preferred_width.width(screen_width, screen_height),
+            self.preferred_height.height(screen_width, screen_height),
+        )
+    }
+
     pub(crate) fn screen_resize(
         &mut self,
         context: &Context,
         width: usize,
         height: usize,
     ) -> Result<(), RenderTargetError> {
-        let width = self.preferred_width.width(width, height);
-        let height = self.preferred_height.height(width, height);
+        let (width, height) = self.preferred_size(width, height);
         if width != self.cached_width || height != self.cached_height {
             self.cached_width = width;
             self.cached_height = height;
@@ -626,9 +918,18 @@ impl RenderTarget {
         F: FnOnce(&Context),
     {
         if let Some(resources) = &self.resources {
-            unsafe { context.bind_framebuffer(FRAMEBUFFER, Some(resources.buffer_handle)) };
+            let handle = resources
+                .multisample
+                .as_ref()
+                .map(|multisample| multisample.buffer_handle)
+                .unwrap_or(resources.buffer_handle);
+            unsafe { context.bind_framebuffer(FRAMEBUFFER, Some(handle)) };
         } else if self.backbuffer {
-            unsafe { context.bind_framebuffer(FRAMEBUFFER, None) };
+            let handle = self
+                .backbuffer_multisample
+                .as_ref()
+                .map(|multisample| multisample.buffer_handle);
+            unsafe { context.bind_framebuffer(FRAMEBUFFER, handle) };
         } else {
             return Err(RenderTargetError::NoResources);
         }
@@ -654,7 +955,9 @@ impl RenderTarget {
             }
         }
         f(context);
+        self.resolve_backbuffer_multisample(context);
         if let Some(resources) = &self.resources {
+            self.resolve_multisample(context, resources);
             for (handle, buffer) in resources
                 .color_handles
                 .iter()
@@ -0,0 +1,261 @@
+use crate::{image::ImageFormat, math::*};
+use core::Scalar;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AtlasBuilderError {
+    DuplicateId(String),
+    /// (id, width, height, page width, page height)
+    TooLarge(String, usize, usize, usize, usize),
+    /// (id, provided, expected)
+    InvalidDataSize(String, usize, usize),
+}
+
+/// Area of atlas page occupied by inserted image.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct AtlasBuilderRegion {
+    pub page: usize,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    /// Normalized texture coordinates of region within its page.
+    pub uvs: Rect,
+}
+
+impl AtlasBuilderRegion {
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.page == other.page
+            && self.x < other.x + other.width
+            && other.x < self.x + self.width
+            && self.y < other.y + other.height
+            && other.y < self.y + self.height
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Shelf {
+    y: usize,
+    height: usize,
+    used: usize,
+}
+
+/// Single RGBA image of atlas, filled with shelves of inserted images.
+#[derive(Debug, Clone)]
+pub struct AtlasBuilderPage {
+    width: usize,
+    height: usize,
+    data: Vec<u8>,
+    shelves: Vec<Shelf>,
+    pub(crate) dirty: bool,
+}
+
+impl AtlasBuilderPage {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            data: vec![0; width * height * ImageFormat::RGBA.bytesize()],
+            shelves: vec![],
+            dirty: true,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// RGBA pixels of page.
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Finds place for area of given size, picking shelf that wastes least height or opening
+    /// new shelf below the last one.
+    fn allocate(&mut self, width: usize, height: usize) -> Option<(usize, usize)> {
+        let best = self
+            .shelves
+            .iter()
+            .enumerate()
+            .filter(|(_, shelf)| shelf.height >= height && self.width - shelf.used >= width)
+            .min_by_key(|(_, shelf)| shelf.height - height)
+            .map(|(index, _)| index);
+        if let Some(index) = best {
+            let shelf = &mut self.shelves[index];
+            let result = (shelf.used, shelf.y);
+            shelf.used += width;
+            return Some(result);
+        }
+        let y = self
+            .shelves
+            .last()
+            .map(|shelf| shelf.y + shelf.height)
+            .unwrap_or_default();
+        if width <= self.width && y + height <= self.height {
+            self.shelves.push(Shelf {
+                y,
+                height,
+                used: width,
+            });
+            return Some((0, y));
+        }
+        None
+    }
+
+    fn write(&mut self, x: usize, y: usize, width: usize, height: usize, rgba: &[u8]) {
+        let bytesize = ImageFormat::RGBA.bytesize();
+        let row_size = width * bytesize;
+        for row in 0..height {
+            let from = row * row_size;
+            let to = ((y + row) * self.width + x) * bytesize;
+            self.data[to..(to + row_size)].copy_from_slice(&rgba[from..(from + row_size)]);
+        }
+        self.dirty = true;
+    }
+}
+
+/// Packs images registered at runtime (user generated content, dynamically rendered glyphs)
+/// into shared atlas pages, adding new pages when current ones are full.
+///
+/// Pages are turned into virtual images named `<name>/<page>` and each inserted image gets
+/// mapped as `<name>@<id>`, the same way images of atlas assets are, so they can be used by
+/// sprite animations and tilemaps.
+#[derive(Debug, Clone)]
+pub struct AtlasBuilder {
+    name: String,
+    page_width: usize,
+    page_height: usize,
+    /// Empty pixels kept between images to prevent texture bleeding.
+    padding: usize,
+    pub(crate) pages: Vec<AtlasBuilderPage>,
+    regions: HashMap<String, AtlasBuilderRegion>,
+    pub(crate) added: Vec<String>,
+}
+
+impl Default for AtlasBuilder {
+    fn default() -> Self {
+        Self::new("@runtime-atlas", 1024, 1024)
+    }
+}
+
+impl AtlasBuilder {
+    pub fn new(name: impl ToString, page_width: usize, page_height: usize) -> Self {
+        Self {
+            name: name.to_string(),
+            page_width: page_width.max(1),
+            page_height: page_height.max(1),
+            padding: 1,
+            pages: vec![],
+            regions: Default::default(),
+            added: vec![],
+        }
+    }
+
+    pub fn with_padding(mut self, padding: usize) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn page_size(&self) -> (usize, usize) {
+        (self.page_width, self.page_height)
+    }
+
+    pub fn padding(&self) -> usize {
+        self.padding
+    }
+
+    pub fn pages(&self) -> &[AtlasBuilderPage] {
+        &self.pages
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &AtlasBuilderRegion)> {
+        self.regions
+            .iter()
+            .map(|(id, region)| (id.as_str(), region))
+    }
+
+    pub fn region(&self, id: &str) -> Option<&AtlasBuilderRegion> {
+        self.regions.get(id)
+    }
+
+    pub fn has(&self, id: &str) -> bool {
+        self.regions.contains_key(id)
+    }
+
+    /// Name of virtual image of given page.
+    pub fn page_virtual_image_name(&self, page: usize) -> String {
+        format!("{}/{}", self.name, page)
+    }
+
+    /// Name under which inserted image gets mapped, usable as sprite animation frame.
+    pub fn region_name(&self, id: &str) -> String {
+        format!("{}@{}", self.name, id)
+    }
+
+    /// Inserts RGBA image and returns its region in atlas.
+    pub fn insert(
+        &mut self,
+        id: impl ToString,
+        width: usize,
+        height: usize,
+        rgba: &[u8],
+    ) -> Result<AtlasBuilderRegion, AtlasBuilderError> {
+        let id = id.to_string();
+        if self.regions.contains_key(&id) {
+            return Err(AtlasBuilderError::DuplicateId(id));
+        }
+        let size = width * height * ImageFormat::RGBA.bytesize();
+        if rgba.len() != size {
+            return Err(AtlasBuilderError::InvalidDataSize(id, rgba.len(), size));
+        }
+        let padded_width = width + self.padding;
+        let padded_height = height + self.padding;
+        if padded_width > self.page_width || padded_height > self.page_height {
+            return Err(AtlasBuilderError::TooLarge(
+                id,
+                width,
+                height,
+                self.page_width,
+                self.page_height,
+            ));
+        }
+        let found = self.pages.iter_mut().enumerate().find_map(|(index, page)| {
+            page.allocate(padded_width, padded_height)
+                .map(|(x, y)| (index, x, y))
+        });
+        let (page, x, y) = match found {
+            Some(found) => found,
+            None => {
+                let mut page = AtlasBuilderPage::new(self.page_width, self.page_height);
+                let (x, y) = page.allocate(padded_width, padded_height).unwrap();
+                self.pages.push(page);
+                (self.pages.len() - 1, x, y)
+            }
+        };
+        self.pages[page].write(x, y, width, height, rgba);
+        let region = AtlasBuilderRegion {
+            page,
+            x,
+            y,
+            width,
+            height,
+            uvs: rect(
+                x as Scalar / self.page_width as Scalar,
+                y as Scalar / self.page_height as Scalar,
+                width as Scalar / self.page_width as Scalar,
+                height as Scalar / self.page_height as Scalar,
+            ),
+        };
+        self.regions.insert(id.to_owned(), region);
+        self.added.push(id);
+        Ok(region)
+    }
+}
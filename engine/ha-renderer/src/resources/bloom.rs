@@ -0,0 +1,180 @@
+use crate::{
+    components::{material_instance::HaMaterialInstance, postprocess::HaPostProcess},
+    image::{ImageFiltering, ImageReference},
+    material::{common::MaterialValue, MaterialReference},
+    math::*,
+    pipeline::{stage::StageDescriptor, PipelineDescriptor},
+    render_target::{
+        render_target_virtual_image_name, RenderTargetDescriptor, RenderTargetSizeValue,
+        TargetBuffer, TargetBuffers,
+    },
+};
+use core::Scalar;
+use serde::{Deserialize, Serialize};
+
+/// Settings of bloom postprocess effect.
+///
+/// Bloom renders scene into offscreen `scene` target, extracts its bright parts into half size
+/// target, blurs them at two mip levels and adds them back on top of scene on main target.
+/// Use `pipeline` and `postprocess` to setup bloom camera - threshold and intensity changes get
+/// applied to its materials every frame, while changing blur passes requires building camera
+/// pipeline and postprocess again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BloomSettings {
+    /// Brightness above which pixels start to glow.
+    #[serde(default = "BloomSettings::default_threshold")]
+    pub threshold: Scalar,
+    /// Scale of glow added on top of scene.
+    #[serde(default = "BloomSettings::default_intensity")]
+    pub intensity: Scalar,
+    /// Number of horizontal and vertical blur pass pairs per mip level.
+    #[serde(default = "BloomSettings::default_blur_passes")]
+    pub blur_passes: usize,
+}
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            threshold: Self::default_threshold(),
+            intensity: Self::default_intensity(),
+            blur_passes: Self::default_blur_passes(),
+        }
+    }
+}
+
+impl BloomSettings {
+    pub const MIP_LEVELS: usize = 2;
+    pub const SCENE_RENDER_TARGET: &'static str = "scene";
+    pub const COLOR_BUFFER: &'static str = "finalColor";
+
+    fn default_threshold() -> Scalar {
+        0.8
+    }
+
+    fn default_intensity() -> Scalar {
+        1.0
+    }
+
+    fn default_blur_passes() -> usize {
+        2
+    }
+
+    /// Name of ping-pong render target used by given mip level.
+    pub fn render_target_name(mip: usize, pong: bool) -> String {
+        format!("bloom-{}-{}", mip, if pong { "b" } else { "a" })
+    }
+
+    /// Camera pipeline running `scene_stage` into scene target, followed by bloom passes done
+    /// with postprocess stage registered under `postprocess_stage_name`.
+    pub fn pipeline(
+        &self,
+        scene_stage: StageDescriptor,
+        postprocess_stage_name: &str,
+    ) -> PipelineDescriptor {
+        let mut result = PipelineDescriptor::default()
+            .render_target("main", RenderTargetDescriptor::Main)
+            .render_target(Self::SCENE_RENDER_TARGET, Self::render_target_descriptor(0));
+        for mip in 0..Self::MIP_LEVELS {
+            let level = -(mip as i8) - 1;
+            for pong in [false, true] {
+                result = result.render_target(
+                    Self::render_target_name(mip, pong),
+                    Self::render_target_descriptor(level),
+                );
+            }
+        }
+        result = result.stage(scene_stage.render_target(Self::SCENE_RENDER_TARGET));
+        for (render_target, _) in self.passes("") {
+            result = result.stage(
+                StageDescriptor::new(postprocess_stage_name)
+                    .render_target(render_target)
+                    .domain("@material/domain/screenspace"),
+            );
+        }
+        result
+    }
+
+    /// Postprocess materials of bloom passes, sampling render targets of pipeline used by camera
+    /// with given name.
+    pub fn postprocess(&self, camera: &str) -> HaPostProcess {
+        HaPostProcess {
+            stages: self
+                .passes(camera)
+                .into_iter()
+                .map(|(_, material)| material)
+                .collect(),
+        }
+    }
+
+    /// Writes threshold and intensity into postprocess materials that use them.
+    pub fn apply(&self, postprocess: &mut HaPostProcess) {
+        for stage in &mut postprocess.stages {
+            if let Some(value) = stage.values.get_mut("bloomThreshold") {
+                *value = self.threshold.into();
+            }
+            if let Some(value) = stage.values.get_mut("bloomIntensity") {
+                *value = self.intensity.into();
+            }
+        }
+    }
+
+    /// [(render target name, material)]
+    fn passes(&self, camera: &str) -> Vec<(String, HaMaterialInstance)> {
+        let sampler = |render_target: &str| MaterialValue::Sampler2d {
+            reference: ImageReference::VirtualAsset(render_target_virtual_image_name(
+                camera,
+                render_target,
+                Self::COLOR_BUFFER,
+            )),
+            filtering: ImageFiltering::Linear,
+        };
+        let blur = |from: &str, direction: Vec2| {
+            HaMaterialInstance::new(MaterialReference::Asset(
+                "@material/graph/screenspace/bloom/blur".to_owned(),
+            ))
+            .with_value("mainImage", sampler(from))
+            .with_value("blurDirection", direction.into())
+        };
+        let mut result = Vec::with_capacity(2 + Self::MIP_LEVELS * self.blur_passes.max(1) * 2);
+        result.push((
+            Self::render_target_name(0, false),
+            HaMaterialInstance::new(MaterialReference::Asset(
+                "@material/graph/screenspace/bloom/bright-pass".to_owned(),
+            ))
+            .with_value("mainImage", sampler(Self::SCENE_RENDER_TARGET))
+            .with_value("bloomThreshold", self.threshold.into()),
+        ));
+        for mip in 0..Self::MIP_LEVELS {
+            let ping = Self::render_target_name(mip, false);
+            let pong = Self::render_target_name(mip, true);
+            // first pass of next mip level reads previous level, which downsamples it.
+            let source = Self::render_target_name(mip.saturating_sub(1), false);
+            for pass in 0..self.blur_passes.max(1) {
+                let from = if pass == 0 { &source } else { &ping };
+                result.push((pong.to_owned(), blur(from, vec2(1.0, 0.0))));
+                result.push((ping.to_owned(), blur(&pong, vec2(0.0, 1.0))));
+            }
+        }
+        result.push((
+            "main".to_owned(),
+            HaMaterialInstance::new(MaterialReference::Asset(
+                "@material/graph/screenspace/bloom/composite".to_owned(),
+            ))
+            .with_value("mainImage", sampler(Self::SCENE_RENDER_TARGET))
+            .with_value("bloomImage0", sampler(&Self::render_target_name(0, false)))
+            .with_value("bloomImage1", sampler(&Self::render_target_name(1, false)))
+            .with_value("bloomIntensity", self.intensity.into()),
+        ));
+        result
+    }
+
+    fn render_target_descriptor(level: i8) -> RenderTargetDescriptor {
+        RenderTargetDescriptor::Custom {
+            buffers: TargetBuffers::default()
+                .with_color(TargetBuffer::color(Self::COLOR_BUFFER))
+                .unwrap(),
+            width: RenderTargetSizeValue::Screen { level },
+            height: RenderTargetSizeValue::Screen { level },
+        }
+    }
+}
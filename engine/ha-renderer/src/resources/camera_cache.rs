@@ -1,4 +1,4 @@
-use crate::components::camera::HaStageCameraInfo;
+use crate::{components::camera::HaStageCameraInfo, math::*};
 use core::ecs::Entity;
 use std::any::TypeId;
 
@@ -69,4 +69,89 @@ impl CameraCache {
     pub fn default_get_first<T: 'static>(&self) -> Option<&HaStageCameraInfo> {
         self.default_get_all::<T>().and_then(|mut iter| iter.next())
     }
+
+    /// Converts point in render target space (pixels) of camera stage into point on world XY
+    /// plane. Returns `None` if entity is not a cached camera rendering given stage.
+    pub fn screen_to_world_space<T: 'static>(&self, entity: Entity, point: Vec2) -> Option<Vec2> {
+        let info = self.get_first::<T>(entity)?;
+        let point = info.render_target_to_screen(point);
+        let matrix = info.screen_to_world();
+        let from = matrix.mul_point(Vec3::new(point.x, point.y, -1.0));
+        let to = matrix.mul_point(Vec3::new(point.x, point.y, 1.0));
+        let direction = to - from;
+        // ray from near to far plane gets intersected with world Z = 0 plane, so this works for
+        // both orthographic and perspective projections.
+        if direction.z.abs() <= Scalar::EPSILON {
+            return Some(Vec2::new(from.x, from.y));
+        }
+        let factor = -from.z / direction.z;
+        let result = from + direction * factor;
+        Some(Vec2::new(result.x, result.y))
+    }
+
+    /// Converts point on world XY plane into point in render target space (pixels) of camera
+    /// stage. Returns `None` if entity is not a cached camera rendering given stage.
+    pub fn world_to_screen_space<T: 'static>(&self, entity: Entity, point: Vec2) -> Option<Vec2> {
+        let info = self.get_first::<T>(entity)?;
+        let point = info.world_to_screen_point(Vec3::new(point.x, point.y, 0.0));
+        Some(info.screen_to_render_target(Vec2::new(point.x, point.y)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::camera::{HaCameraOrthographic, HaCameraOrtographicScaling};
+    use core::ecs::World;
+
+    #[test]
+    fn test_camera_cache_space_conversion() {
+        let mut world = World::new();
+        let entity = world.spawn(());
+        let other = world.spawn(());
+        let projection = HaCameraOrthographic {
+            scaling: HaCameraOrtographicScaling::FitVertical(10.0),
+            centered: true,
+            ignore_depth_planes: false,
+        };
+        let transform_matrix = Mat4::translation_3d(Vec3::new(3.0, -2.0, 0.0));
+        let info = HaStageCameraInfo {
+            x: 100,
+            y: 50,
+            width: 800,
+            height: 400,
+            transform_matrix,
+            view_matrix: transform_matrix.inverted(),
+            projection_matrix: projection.matrix(Vec2::new(800.0, 400.0)),
+        };
+        let cache = CameraCache {
+            default_entity: Some(entity),
+            info: vec![(entity, TypeId::of::<()>(), None, info)],
+        };
+
+        assert!(cache
+            .screen_to_world_space::<()>(other, Vec2::zero())
+            .is_none());
+        assert!(cache
+            .world_to_screen_space::<bool>(entity, Vec2::zero())
+            .is_none());
+
+        let center = cache
+            .screen_to_world_space::<()>(entity, Vec2::new(500.0, 250.0))
+            .unwrap();
+        assert!((center - Vec2::new(3.0, -2.0)).magnitude() < 1.0e-4);
+
+        for point in [
+            Vec2::new(100.0, 50.0),
+            Vec2::new(500.0, 250.0),
+            Vec2::new(123.0, 321.0),
+            Vec2::new(900.0, 450.0),
+        ] {
+            let world_point = cache.screen_to_world_space::<()>(entity, point).unwrap();
+            let screen_point = cache
+                .world_to_screen_space::<()>(entity, world_point)
+                .unwrap();
+            assert!((screen_point - point).magnitude() < 1.0e-3);
+        }
+    }
 }
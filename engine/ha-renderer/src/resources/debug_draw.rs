@@ -0,0 +1,241 @@
+use crate::{material::domains::gizmo::GizmoFactory, math::*};
+use core::Scalar;
+
+/// Coordinate space of debug draw primitive.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum DebugDrawSpace {
+    /// Primitive gets transformed by camera view and projection.
+    #[default]
+    World,
+    /// Primitive is placed in pixels of camera viewport, with origin in its top-left corner.
+    Screen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DebugDrawShape {
+    Line { from: Vec3, to: Vec3 },
+    Rect { min: Vec3, max: Vec3 },
+    Circle { center: Vec3, radius: Scalar },
+    Text { position: Vec3, text: String },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DebugDrawPrimitive {
+    pub shape: DebugDrawShape,
+    pub color: Rgba,
+    pub space: DebugDrawSpace,
+}
+
+impl DebugDrawPrimitive {
+    pub fn in_space(&mut self, space: DebugDrawSpace) -> &mut Self {
+        self.space = space;
+        self
+    }
+
+    pub fn screen_space(&mut self) -> &mut Self {
+        self.in_space(DebugDrawSpace::Screen)
+    }
+
+    pub fn world_space(&mut self) -> &mut Self {
+        self.in_space(DebugDrawSpace::World)
+    }
+}
+
+/// Immediate-mode debug drawing.
+///
+/// Primitives queued during frame get turned into gizmo lines and rendered by gizmo stage,
+/// then queue gets cleared - primitives have to be queued again every frame they should be
+/// visible. Rects, circles and text lie on XY plane at Z coordinate of their position, text
+/// uses simple built-in stroke font with rows going down the Y axis.
+#[derive(Debug, Clone)]
+pub struct DebugDraw {
+    primitives: Vec<DebugDrawPrimitive>,
+    /// Number of segments used to approximate circles.
+    pub circle_segments: usize,
+    /// Height of text glyphs.
+    pub text_size: Scalar,
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self {
+            primitives: vec![],
+            circle_segments: 32,
+            text_size: 16.0,
+        }
+    }
+}
+
+impl DebugDraw {
+    pub fn primitives(&self) -> &[DebugDrawPrimitive] {
+        &self.primitives
+    }
+
+    pub fn len(&self) -> usize {
+        self.primitives.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.primitives.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.primitives.clear();
+    }
+
+    pub fn push(&mut self, primitive: DebugDrawPrimitive) -> &mut DebugDrawPrimitive {
+        self.primitives.push(primitive);
+        self.primitives.last_mut().unwrap()
+    }
+
+    pub fn line(&mut self, from: Vec3, to: Vec3, color: Rgba) -> &mut DebugDrawPrimitive {
+        self.shape(DebugDrawShape::Line { from, to }, color)
+    }
+
+    pub fn rect(&mut self, min: Vec3, max: Vec3, color: Rgba) -> &mut DebugDrawPrimitive {
+        self.shape(DebugDrawShape::Rect { min, max }, color)
+    }
+
+    pub fn circle(&mut self, center: Vec3, radius: Scalar, color: Rgba) -> &mut DebugDrawPrimitive {
+        self.shape(DebugDrawShape::Circle { center, radius }, color)
+    }
+
+    /// Queues text with its top-left corner at given position.
+    pub fn text(&mut self, position: Vec3, text: &str, color: Rgba) -> &mut DebugDrawPrimitive {
+        self.shape(
+            DebugDrawShape::Text {
+                position,
+                text: text.to_owned(),
+            },
+            color,
+        )
+    }
+
+    /// Consumes queued primitives, writing world space ones into `world` and screen space ones
+    /// into `screen` factory.
+    pub fn flush(&mut self, world: &mut GizmoFactory, screen: &mut GizmoFactory) {
+        for primitive in std::mem::take(&mut self.primitives) {
+            let factory = match primitive.space {
+                DebugDrawSpace::World => &mut *world,
+                DebugDrawSpace::Screen => &mut *screen,
+            };
+            self.write_primitive(&primitive, factory);
+        }
+    }
+
+    fn shape(&mut self, shape: DebugDrawShape, color: Rgba) -> &mut DebugDrawPrimitive {
+        self.push(DebugDrawPrimitive {
+            shape,
+            color,
+            space: DebugDrawSpace::World,
+        })
+    }
+
+    fn write_primitive(&self, primitive: &DebugDrawPrimitive, factory: &mut GizmoFactory) {
+        let color = primitive.color.into();
+        match &primitive.shape {
+            DebugDrawShape::Line { from, to } => factory.line(color, *from, *to),
+            DebugDrawShape::Rect { min, max } => {
+                factory.polygon(
+                    color,
+                    [
+                        vec3(min.x, min.y, min.z),
+                        vec3(max.x, min.y, min.z),
+                        vec3(max.x, max.y, min.z),
+                        vec3(min.x, max.y, min.z),
+                    ]
+                    .into_iter(),
+                );
+            }
+            DebugDrawShape::Circle { center, radius } => {
+                let segments = self.circle_segments.max(3);
+                factory.polygon(
+                    color,
+                    (0..segments).map(|index| {
+                        let angle =
+                            std::f64::consts::TAU as Scalar * index as Scalar / segments as Scalar;
+                        *center + vec3(angle.cos(), angle.sin(), 0.0) * *radius
+                    }),
+                );
+            }
+            DebugDrawShape::Text { position, text } => {
+                let height = self.text_size;
+                let width = height * 0.5;
+                let advance = height * 0.75;
+                let point = |origin: Vec3, index: u8| {
+                    let col = (index % 3) as Scalar * 0.5;
+                    let row = (index / 3) as Scalar * 0.5;
+                    origin + vec3(col * width, row * height, 0.0)
+                };
+                let mut origin = *position;
+                for c in text.chars() {
+                    if c == '\n' {
+                        origin = vec3(position.x, origin.y + height * 1.5, position.z);
+                        continue;
+                    }
+                    factory.lines(
+                        color,
+                        glyph_strokes(c)
+                            .iter()
+                            .map(|(from, to)| (point(origin, *from), point(origin, *to))),
+                    );
+                    origin.x += advance;
+                }
+            }
+        }
+    }
+}
+
+/// Strokes of glyph as pairs of points on 3x3 grid (indices go row by row, starting at top-left
+/// corner). Lowercase letters are drawn as uppercase, unsupported characters are left empty.
+fn glyph_strokes(c: char) -> &'static [(u8, u8)] {
+    match c.to_ascii_uppercase() {
+        '0' => &[(0, 2), (2, 8), (8, 6), (6, 0), (6, 2)],
+        '1' => &[(0, 1), (1, 7), (6, 8)],
+        '2' => &[(0, 2), (2, 5), (5, 3), (3, 6), (6, 8)],
+        '3' => &[(0, 2), (2, 8), (8, 6), (3, 5)],
+        '4' => &[(0, 3), (3, 5), (2, 8)],
+        '5' | 'S' => &[(2, 0), (0, 3), (3, 5), (5, 8), (8, 6)],
+        '6' => &[(2, 0), (0, 6), (6, 8), (8, 5), (5, 3)],
+        '7' => &[(0, 2), (2, 8)],
+        '8' => &[(0, 2), (2, 8), (8, 6), (6, 0), (3, 5)],
+        '9' => &[(5, 3), (3, 0), (0, 2), (2, 8), (8, 6)],
+        'A' => &[(6, 0), (0, 2), (2, 8), (3, 5)],
+        'B' => &[(0, 6), (0, 1), (1, 5), (3, 5), (5, 7), (7, 6)],
+        'C' => &[(2, 0), (0, 6), (6, 8)],
+        'D' => &[(0, 6), (0, 1), (1, 5), (5, 7), (7, 6)],
+        'E' => &[(2, 0), (0, 6), (6, 8), (3, 4)],
+        'F' => &[(2, 0), (0, 6), (3, 4)],
+        'G' => &[(2, 0), (0, 6), (6, 8), (8, 5), (5, 4)],
+        'H' => &[(0, 6), (2, 8), (3, 5)],
+        'I' => &[(0, 2), (1, 7), (6, 8)],
+        'J' => &[(2, 8), (8, 6), (6, 3)],
+        'K' => &[(0, 6), (3, 2), (3, 8)],
+        'L' => &[(0, 6), (6, 8)],
+        'M' => &[(6, 0), (0, 4), (4, 2), (2, 8)],
+        'N' => &[(6, 0), (0, 8), (8, 2)],
+        'O' => &[(0, 2), (2, 8), (8, 6), (6, 0)],
+        'P' => &[(6, 0), (0, 2), (2, 5), (5, 3)],
+        'Q' => &[(0, 2), (2, 8), (8, 6), (6, 0), (4, 8)],
+        'R' => &[(6, 0), (0, 2), (2, 5), (5, 3), (4, 8)],
+        'T' => &[(0, 2), (1, 7)],
+        'U' => &[(0, 6), (6, 8), (8, 2)],
+        'V' => &[(0, 7), (7, 2)],
+        'W' => &[(0, 6), (6, 4), (4, 8), (8, 2)],
+        'X' => &[(0, 8), (2, 6)],
+        'Y' => &[(0, 4), (2, 4), (4, 7)],
+        'Z' => &[(0, 2), (2, 6), (6, 8)],
+        '-' => &[(3, 5)],
+        '+' => &[(3, 5), (1, 7)],
+        '_' => &[(6, 8)],
+        '=' => &[(3, 5), (6, 8)],
+        '/' => &[(6, 2)],
+        '.' | ',' => &[(6, 7)],
+        ':' => &[(1, 4)],
+        '(' => &[(1, 3), (3, 7)],
+        ')' => &[(1, 5), (5, 7)],
+        '<' => &[(2, 3), (3, 8)],
+        '>' => &[(0, 5), (5, 6)],
+        _ => &[],
+    }
+}
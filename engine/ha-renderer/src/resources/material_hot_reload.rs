@@ -0,0 +1,57 @@
+use core::{fetch::FetchProcess, Scalar};
+use std::collections::HashMap;
+
+#[derive(Default)]
+pub(crate) struct MaterialHotReloadEntry {
+    pub(crate) process: Option<Box<FetchProcess>>,
+    /// Last fetched bytes of asset - first fetch only records them as baseline.
+    pub(crate) content: Option<Vec<u8>>,
+}
+
+/// Watches material graph assets through fetch engine and reloads their materials when asset
+/// content changes, keeping meshes and uniform values of reloaded materials intact.
+pub struct MaterialHotReload {
+    /// Seconds between checks of watched assets.
+    pub interval: Scalar,
+    pub(crate) timer: Scalar,
+    pub(crate) watched: HashMap<String, MaterialHotReloadEntry>,
+}
+
+impl Default for MaterialHotReload {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl MaterialHotReload {
+    pub fn new(interval: Scalar) -> Self {
+        Self {
+            interval,
+            timer: 0.0,
+            watched: Default::default(),
+        }
+    }
+
+    /// Starts watching material asset (for example: `material://materials/sprite.json`).
+    pub fn watch(&mut self, asset_path: &str) {
+        self.watched
+            .entry(Self::clean_path(asset_path).to_owned())
+            .or_default();
+    }
+
+    pub fn unwatch(&mut self, asset_path: &str) {
+        self.watched.remove(Self::clean_path(asset_path));
+    }
+
+    pub fn is_watching(&self, asset_path: &str) -> bool {
+        self.watched.contains_key(Self::clean_path(asset_path))
+    }
+
+    pub fn watched(&self) -> impl Iterator<Item = &str> {
+        self.watched.keys().map(|path| path.as_str())
+    }
+
+    fn clean_path(asset_path: &str) -> &str {
+        asset_path.strip_prefix("material://").unwrap_or(asset_path)
+    }
+}
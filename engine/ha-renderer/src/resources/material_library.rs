@@ -7,7 +7,7 @@ use crate::{
             function::{MaterialFunction, MaterialFunctionContent},
             MaterialGraph,
         },
-        MaterialError,
+        MaterialError, MaterialId,
     },
     material_graph,
     math::*,
@@ -27,6 +27,7 @@ pub struct MaterialLibrary {
     functions: HashMap<String, MaterialFunction>,
     domains: HashMap<String, MaterialGraph>,
     middlewares: HashMap<String, MaterialGraph>,
+    reloads: HashMap<MaterialId, MaterialGraph>,
 }
 
 impl MaterialLibrary {
@@ -60,6 +61,18 @@ impl MaterialLibrary {
         self
     }
 
+    /// Registers custom function that material graphs can use as operation node by its name.
+    /// Unlike `add_function` it does not replace already registered function and validates
+    /// function signature (and graph content) right away.
+    pub fn register_function(&mut self, function: MaterialFunction) -> Result<(), MaterialError> {
+        if self.has_function(&function.name) {
+            return Err(MaterialError::FunctionAlreadyExists(function.name));
+        }
+        function.validate(self)?;
+        self.add_function(function);
+        Ok(())
+    }
+
     pub fn remove_function(&mut self, name: &str) -> Option<MaterialFunction> {
         self.functions.remove(name)
     }
@@ -128,11 +141,40 @@ impl MaterialLibrary {
         self.middlewares.len()
     }
 
+    /// Schedules replacing graph of material with new one. Graph gets validated right away and
+    /// renderer recompiles material programs on next frame, keeping previous ones when
+    /// compilation fails.
+    pub fn reload(&mut self, id: MaterialId, graph: MaterialGraph) -> Result<(), MaterialError> {
+        graph.validate(self)?;
+        self.reloads.insert(id, graph);
+        Ok(())
+    }
+
+    pub fn is_reload_pending(&self, id: MaterialId) -> bool {
+        self.reloads.contains_key(&id)
+    }
+
+    pub(crate) fn take_reloads(&mut self) -> HashMap<MaterialId, MaterialGraph> {
+        std::mem::take(&mut self.reloads)
+    }
+
     pub fn validate_material_compilation(
         vertex_layout: &VertexLayout,
         render_target: RenderTargetDescriptor,
         domain: &MaterialGraph,
         graph: &MaterialGraph,
+    ) -> Result<Option<BakedMaterialShaders>, MaterialError> {
+        Self::default().validate_compilation(vertex_layout, render_target, domain, graph)
+    }
+
+    /// Same as `validate_material_compilation` but uses functions and middlewares of this
+    /// library, so graphs using custom functions can be validated.
+    pub fn validate_compilation(
+        &self,
+        vertex_layout: &VertexLayout,
+        render_target: RenderTargetDescriptor,
+        domain: &MaterialGraph,
+        graph: &MaterialGraph,
     ) -> Result<Option<BakedMaterialShaders>, MaterialError> {
         let render_target = match render_target {
             RenderTargetDescriptor::Main => match RenderTarget::main() {
@@ -153,7 +195,7 @@ impl MaterialLibrary {
             None,
             vertex_layout.middlewares().into(),
         );
-        graph.bake(&signature, Some(domain), &Self::default(), true)
+        graph.bake(&signature, Some(domain), self, true)
     }
 
     pub fn assert_material_compilation(
@@ -162,25 +204,42 @@ impl MaterialLibrary {
         domain: &MaterialGraph,
         graph: &MaterialGraph,
     ) {
-        let baked =
-            Self::validate_material_compilation(vertex_layout, render_target, domain, graph)
-                .unwrap_or_else(|error| match &error {
-                    MaterialError::Baking(graph, error) => match &**error {
-                        MaterialError::GraphIsCyclic(nodes) => {
-                            let nodes = nodes
-                                .iter()
-                                .map(|id| (id, graph.node(*id).unwrap()))
-                                .collect::<Vec<_>>();
-                            panic!(
-                                "Could not bake shaders from material: {:?} | Cycle: {:#?}",
-                                error, nodes
-                            );
+        Self::default().assert_compilation(vertex_layout, render_target, domain, graph);
+    }
+
+    /// Same as `assert_material_compilation` but uses functions and middlewares of this
+    /// library.
+    pub fn assert_compilation(
+        &self,
+        vertex_layout: &VertexLayout,
+        render_target: RenderTargetDescriptor,
+        domain: &MaterialGraph,
+        graph: &MaterialGraph,
+    ) {
+        let baked = self
+            .validate_compilation(vertex_layout, render_target, domain, graph)
+            .unwrap_or_else(|error| match &error {
+                error_source @ MaterialError::Baking(graph, error) => match &**error {
+                    MaterialError::GraphIsCyclic(nodes) => {
+                        let nodes = nodes
+                            .iter()
+                            .map(|id| (id, graph.node(*id).unwrap()))
+                            .collect::<Vec<_>>();
+                        panic!(
+                            "Could not bake shaders from material: {:?} | Cycle: {:#?}",
+                            error, nodes
+                        );
+                    }
+                    error => match error_source.compile_error() {
+                        Some(details) => {
+                            panic!("Could not bake shaders from material: {}", details)
                         }
-                        _ => panic!("Could not bake shaders from material: {:?}", error),
+                        None => panic!("Could not bake shaders from material: {:?}", error),
                     },
-                    _ => panic!("Could not bake shaders from material: {:?}", error),
-                })
-                .expect("Baked shaders are empty");
+                },
+                _ => panic!("Could not bake shaders from material: {:?}", error),
+            })
+            .expect("Baked shaders are empty");
         println!("* compiled vertex material graph text:\n{}", baked.vertex);
         println!(
             "* compiled fragment material graph text:\n{}",
@@ -403,6 +462,13 @@ impl MaterialLibrary {
             {fn smoothstep_vec4(edge0: vec4, edge1: vec4, x: vec4) -> vec4}
             { "smoothstep" }
         });
+        self.add_functions(builtin_material_functions! {
+            {fn fwidth_float(p: float) -> float}
+            {fn fwidth_vec2(p: vec2) -> vec2}
+            {fn fwidth_vec3(p: vec3) -> vec3}
+            {fn fwidth_vec4(p: vec4) -> vec4}
+            { "fwidth" }
+        });
         self
     }
 
@@ -1538,6 +1604,57 @@ impl MaterialLibrary {
         self
     }
 
+    fn with_instancing_middleware(mut self) -> Self {
+        self.add_middleware(
+            "instancing".to_owned(),
+            material_graph! {
+                inputs {
+                    [vertex] in position as in_position: vec3 = {vec3(0.0, 0.0, 0.0)};
+                    [vertex] in textureCoord as in_textureCoord: vec3 = {vec3(0.0, 0.0, 0.0)};
+                    [vertex] in color as in_color: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+                    [vertex] in instanceMatrixA: vec4 = {vec4(1.0, 0.0, 0.0, 0.0)};
+                    [vertex] in instanceMatrixB: vec4 = {vec4(0.0, 1.0, 0.0, 0.0)};
+                    [vertex] in instanceMatrixC: vec4 = {vec4(0.0, 0.0, 1.0, 0.0)};
+                    [vertex] in instanceMatrixD: vec4 = {vec4(0.0, 0.0, 0.0, 1.0)};
+                    [vertex] in instanceColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+                    [vertex] in instanceTextureRect: vec4 = {vec4(0.0, 0.0, 1.0, 1.0)};
+                }
+
+                outputs {
+                    [vertex] out position as out_position: vec3;
+                    [vertex] out textureCoord as out_textureCoord: vec3;
+                    [vertex] out color as out_color: vec4;
+                }
+
+                [matrix = (make_mat4,
+                    a: instanceMatrixA,
+                    b: instanceMatrixB,
+                    c: instanceMatrixC,
+                    d: instanceMatrixD
+                )]
+                [pos = (append_vec4, a: in_position, b: {1.0})]
+                [(truncate_vec4, v: (mul_mat4_vec4, a: matrix, b: pos)) -> out_position]
+                [u = (add_float,
+                    a: (maskX_vec4, v: instanceTextureRect),
+                    b: (mul_float,
+                        a: (maskX_vec3, v: in_textureCoord),
+                        b: (maskZ_vec4, v: instanceTextureRect)
+                    )
+                )]
+                [v = (add_float,
+                    a: (maskY_vec4, v: instanceTextureRect),
+                    b: (mul_float,
+                        a: (maskY_vec3, v: in_textureCoord),
+                        b: (maskW_vec4, v: instanceTextureRect)
+                    )
+                )]
+                [(make_vec3, x: u, y: v, z: (maskZ_vec3, v: in_textureCoord)) -> out_textureCoord]
+                [(mul_vec4, a: in_color, b: instanceColor) -> out_color]
+            },
+        );
+        self
+    }
+
     fn with_deformer_middleware(mut self) -> Self {
         self.add_function(code_material_function! {
             fn deformer_sample_curve(bezier_matrix: mat4, t: float, control_points_x: vec4, control_points_y: vec4) -> vec2 {
@@ -1661,6 +1778,7 @@ impl Default for MaterialLibrary {
             functions: Default::default(),
             domains: Default::default(),
             middlewares: Default::default(),
+            reloads: Default::default(),
         }
         .with_angle_functions()
         .with_single_functions()
@@ -1681,5 +1799,6 @@ impl Default for MaterialLibrary {
         .with_vertanim_middleware()
         .with_skinning_middleware()
         .with_deformer_middleware()
+        .with_instancing_middleware()
     }
 }
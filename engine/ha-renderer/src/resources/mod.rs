@@ -1,4 +1,9 @@
+pub mod atlas_builder;
+pub mod bloom;
 pub mod camera_cache;
+pub mod debug_draw;
 pub mod gizmos;
+pub mod material_hot_reload;
 pub mod material_library;
 pub mod resource_mapping;
+pub mod spatial_index;
@@ -0,0 +1,246 @@
+use crate::math::*;
+use core::{ecs::Entity, Scalar};
+
+const DEFAULT_NODE_CAPACITY: usize = 8;
+const DEFAULT_MAX_DEPTH: usize = 8;
+
+#[derive(Debug, Clone)]
+struct SpatialIndexNode {
+    bounds: Rect,
+    items: Vec<(Entity, Rect)>,
+    children: Option<Box<[SpatialIndexNode; 4]>>,
+}
+
+impl SpatialIndexNode {
+    fn new(bounds: Rect) -> Self {
+        Self {
+            bounds,
+            items: vec![],
+            children: None,
+        }
+    }
+
+    fn insert(&mut self, entity: Entity, rect: Rect, depth: usize, capacity: usize) {
+        if let Some(children) = &mut self.children {
+            if let Some(child) = children
+                .iter_mut()
+                .find(|child| rect_contains_rect(child.bounds, rect))
+            {
+                child.insert(entity, rect, depth - 1, capacity);
+                return;
+            }
+            self.items.push((entity, rect));
+            return;
+        }
+        self.items.push((entity, rect));
+        if self.items.len() > capacity && depth > 0 {
+            self.split(depth, capacity);
+        }
+    }
+
+    fn split(&mut self, depth: usize, capacity: usize) {
+        let w = self.bounds.w * 0.5;
+        let h = self.bounds.h * 0.5;
+        let x = self.bounds.x;
+        let y = self.bounds.y;
+        self.children = Some(Box::new([
+            Self::new(Rect::new(x, y, w, h)),
+            Self::new(Rect::new(x + w, y, w, h)),
+            Self::new(Rect::new(x, y + h, w, h)),
+            Self::new(Rect::new(x + w, y + h, w, h)),
+        ]));
+        for (entity, rect) in std::mem::take(&mut self.items) {
+            self.insert(entity, rect, depth, capacity);
+        }
+    }
+
+    fn query(&self, test: &impl Fn(Rect) -> bool, result: &mut Vec<Entity>) {
+        if !test(self.bounds) {
+            return;
+        }
+        result.extend(
+            self.items
+                .iter()
+                .filter(|(_, rect)| test(*rect))
+                .map(|(entity, _)| *entity),
+        );
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.query(test, result);
+            }
+        }
+    }
+
+    fn nearest(&self, point: Vec2, best: &mut Option<(Entity, Scalar)>) {
+        if let Some((_, distance)) = best {
+            if rect_distance_squared(self.bounds, point) > *distance {
+                return;
+            }
+        }
+        for (entity, rect) in &self.items {
+            let distance = rect_distance_squared(*rect, point);
+            if best.map(|(_, d)| distance < d).unwrap_or(true) {
+                *best = Some((*entity, distance));
+            }
+        }
+        if let Some(children) = &self.children {
+            for child in children.iter() {
+                child.nearest(point, best);
+            }
+        }
+    }
+}
+
+/// Quadtree of entities world space 2D bounds (XY plane), rebuilt every frame
+/// by `ha_spatial_index_system` from entities with `HaTransform` and `HaVolume`.
+#[derive(Debug, Clone)]
+pub struct HaSpatialIndex {
+    node_capacity: usize,
+    max_depth: usize,
+    root: Option<SpatialIndexNode>,
+    count: usize,
+}
+
+impl Default for HaSpatialIndex {
+    fn default() -> Self {
+        Self::new(DEFAULT_NODE_CAPACITY, DEFAULT_MAX_DEPTH)
+    }
+}
+
+impl HaSpatialIndex {
+    pub fn new(node_capacity: usize, max_depth: usize) -> Self {
+        Self {
+            node_capacity: node_capacity.max(1),
+            max_depth,
+            root: None,
+            count: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.count = 0;
+    }
+
+    /// Replaces indexed content with given entities world space bounds.
+    pub fn rebuild(&mut self, items: impl IntoIterator<Item = (Entity, Rect)>) {
+        let items = items.into_iter().collect::<Vec<_>>();
+        self.clear();
+        let bounds = match items.iter().map(|(_, rect)| *rect).reduce(rect_union) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        let mut root = SpatialIndexNode::new(bounds);
+        for (entity, rect) in items {
+            root.insert(entity, rect, self.max_depth, self.node_capacity);
+            self.count += 1;
+        }
+        self.root = Some(root);
+    }
+
+    /// Entities which bounds overlap given rectangle.
+    pub fn query_rect(&self, rect: Rect) -> Vec<Entity> {
+        let mut result = vec![];
+        if let Some(root) = &self.root {
+            root.query(&|other| rects_overlap(rect, other), &mut result);
+        }
+        result
+    }
+
+    /// Entities which bounds overlap circle of given radius.
+    pub fn query_radius(&self, point: Vec2, radius: Scalar) -> Vec<Entity> {
+        let mut result = vec![];
+        if let Some(root) = &self.root {
+            let threshold = radius * radius;
+            root.query(
+                &|other| rect_distance_squared(other, point) <= threshold,
+                &mut result,
+            );
+        }
+        result
+    }
+
+    /// Entity which bounds are the closest to given point.
+    pub fn nearest(&self, point: Vec2) -> Option<Entity> {
+        let mut best = None;
+        if let Some(root) = &self.root {
+            root.nearest(point, &mut best);
+        }
+        best.map(|(entity, _)| entity)
+    }
+}
+
+fn rect_union(a: Rect, b: Rect) -> Rect {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    let w = (a.x + a.w).max(b.x + b.w) - x;
+    let h = (a.y + a.h).max(b.y + b.h) - y;
+    Rect::new(x, y, w, h)
+}
+
+fn rect_contains_rect(a: Rect, b: Rect) -> bool {
+    b.x >= a.x && b.y >= a.y && b.x + b.w <= a.x + a.w && b.y + b.h <= a.y + a.h
+}
+
+fn rects_overlap(a: Rect, b: Rect) -> bool {
+    a.x <= b.x + b.w && b.x <= a.x + a.w && a.y <= b.y + b.h && b.y <= a.y + a.h
+}
+
+fn rect_distance_squared(rect: Rect, point: Vec2) -> Scalar {
+    let x = (rect.x - point.x).max(point.x - rect.x - rect.w).max(0.0);
+    let y = (rect.y - point.y).max(point.y - rect.y - rect.h).max(0.0);
+    x * x + y * y
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::ecs::World;
+
+    #[test]
+    fn test_spatial_index_queries() {
+        let mut world = World::new();
+        let entities = (0..100)
+            .map(|index| {
+                let x = (index % 10) as Scalar * 10.0;
+                let y = (index / 10) as Scalar * 10.0;
+                (world.spawn(()), Rect::new(x, y, 1.0, 1.0))
+            })
+            .collect::<Vec<_>>();
+        let mut index = HaSpatialIndex::new(4, 8);
+        index.rebuild(entities.iter().copied());
+        assert_eq!(index.len(), 100);
+
+        let mut found = index.query_rect(Rect::new(15.0, 15.0, 20.0, 20.0));
+        found.sort();
+        let mut expected = entities
+            .iter()
+            .filter(|(_, r)| r.x >= 15.0 && r.x <= 35.0 && r.y >= 15.0 && r.y <= 35.0)
+            .map(|(e, _)| *e)
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(expected.len(), 4);
+        assert_eq!(found, expected);
+
+        let mut found = index.query_radius(Vec2::new(50.0, 50.0), 10.0);
+        found.sort();
+        let mut expected = entities
+            .iter()
+            .filter(|(_, r)| rect_distance_squared(*r, Vec2::new(50.0, 50.0)) <= 100.0)
+            .map(|(e, _)| *e)
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(expected.len(), 5);
+        assert_eq!(found, expected);
+
+        assert_eq!(index.nearest(Vec2::new(42.0, 71.0)), Some(entities[74].0));
+    }
+}
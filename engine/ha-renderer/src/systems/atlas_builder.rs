@@ -0,0 +1,87 @@
+use crate::{
+    ha_renderer::HaRenderer,
+    image::{
+        Image, ImageDescriptor, ImageId, ImageResourceMapping, VirtualImage, VirtualImageId,
+        VirtualImageSource,
+    },
+    resources::atlas_builder::AtlasBuilder,
+};
+use core::ecs::Universe;
+
+#[derive(Debug, Default)]
+pub struct HaAtlasBuilderSystemCache {
+    /// [(page image, page virtual image)]
+    pages: Vec<(ImageId, VirtualImageId)>,
+}
+
+pub type HaAtlasBuilderSystemResources<'a> = (
+    &'a mut HaRenderer,
+    &'a mut AtlasBuilder,
+    &'a mut HaAtlasBuilderSystemCache,
+    &'a mut ImageResourceMapping,
+);
+
+pub fn ha_atlas_builder_system(universe: &mut Universe) {
+    let (mut renderer, mut builder, mut cache, mut image_mapping) =
+        universe.query_resources::<HaAtlasBuilderSystemResources>();
+
+    for index in 0..builder.pages().len() {
+        let page = &builder.pages()[index];
+        if !page.dirty {
+            continue;
+        }
+        if let Some((image_id, _)) = cache.pages.get(index) {
+            if let Some(image) = renderer.image_mut(*image_id) {
+                if image.set_data(page.data().to_owned()).is_err() {
+                    continue;
+                }
+            }
+        } else {
+            let image = match Image::new(
+                ImageDescriptor::default(),
+                page.width(),
+                page.height(),
+                1,
+                page.data().to_owned(),
+            ) {
+                Ok(image) => image,
+                Err(_) => continue,
+            };
+            let image_id = match renderer.add_image(image) {
+                Ok(image_id) => image_id,
+                Err(_) => continue,
+            };
+            let virtual_image_id = renderer.virtual_images.add_named(
+                builder.page_virtual_image_name(index),
+                VirtualImage::new(VirtualImageSource::Image(image_id)),
+            );
+            cache.pages.push((image_id, virtual_image_id));
+        }
+        builder.pages[index].dirty = false;
+    }
+
+    let added = std::mem::take(&mut builder.added);
+    let mut pending = vec![];
+    for id in added {
+        let region = match builder.region(&id) {
+            Some(region) => *region,
+            None => continue,
+        };
+        let virtual_image_id = match cache.pages.get(region.page) {
+            Some((_, virtual_image_id)) => *virtual_image_id,
+            None => {
+                pending.push(id);
+                continue;
+            }
+        };
+        if let Some(virtual_image) = renderer.virtual_images.get_mut(virtual_image_id) {
+            let image_id = virtual_image.register_named_image_uvs(&id, region.uvs, 0);
+            image_mapping.map_virtual_resource(
+                builder.region_name(&id),
+                virtual_image_id,
+                image_id,
+            );
+        }
+    }
+    builder.added = pending;
+}
@@ -0,0 +1,12 @@
+use crate::{components::postprocess::HaPostProcess, resources::bloom::BloomSettings};
+use core::ecs::{Comp, Universe, WorldRef};
+
+pub type HaBloomSystemResources<'a> = (WorldRef, &'a BloomSettings, Comp<&'a mut HaPostProcess>);
+
+pub fn ha_bloom_system(universe: &mut Universe) {
+    let (world, settings, ..) = universe.query_resources::<HaBloomSystemResources>();
+
+    for (_, postprocess) in world.query::<&mut HaPostProcess>().iter() {
+        settings.apply(postprocess);
+    }
+}
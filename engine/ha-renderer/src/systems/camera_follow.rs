@@ -0,0 +1,57 @@
+use crate::{
+    components::{camera::HaCamera, camera_follow::HaCameraFollow, transform::HaTransform},
+    math::*,
+    resources::camera_cache::CameraCache,
+};
+use core::{
+    app::AppLifeCycle,
+    ecs::{hierarchy::Hierarchy, Comp, Universe, WorldRef},
+};
+
+pub type HaCameraFollowSystemResources<'a> = (
+    WorldRef,
+    &'a AppLifeCycle,
+    &'a Hierarchy,
+    &'a CameraCache,
+    Comp<&'a mut HaTransform>,
+    Comp<&'a HaCamera>,
+    Comp<&'a mut HaCameraFollow>,
+);
+
+pub fn ha_camera_follow_system(universe: &mut Universe) {
+    let (world, lifecycle, hierarchy, cache, ..) =
+        universe.query_resources::<HaCameraFollowSystemResources>();
+
+    let dt = lifecycle.delta_time_seconds();
+
+    for (entity, (transform, follow)) in world
+        .query::<(&mut HaTransform, &mut HaCameraFollow)>()
+        .with::<&HaCamera>()
+        .iter()
+    {
+        let target = match follow
+            .target
+            .as_ref()
+            .and_then(|name| hierarchy.find(None, name.as_str()))
+        {
+            Some(target) if target != entity => target,
+            _ => continue,
+        };
+        let to = match world.get::<&HaTransform>(target) {
+            Ok(transform) => transform.get_translation(),
+            Err(_) => continue,
+        };
+        // NOTE: camera size does not depend on its position so last frame view is good enough.
+        let view_size =
+            cache
+                .info
+                .iter()
+                .find(|(e, _, _, _)| *e == entity)
+                .map(|(_, _, _, info)| {
+                    let (min, max) = info.world_bounds();
+                    Vec2::new(max.x - min.x, max.y - min.y)
+                });
+        let from = transform.get_translation();
+        transform.set_translation(follow.update(from, to, view_size, dt));
+    }
+}
@@ -0,0 +1,43 @@
+use crate::components::{camera::HaCamera, camera_shake::HaCameraShake, transform::HaTransform};
+use core::{
+    app::AppLifeCycle,
+    ecs::{Comp, Universe, WorldRef},
+};
+
+pub type HaCameraShakeSystemResources<'a> = (
+    WorldRef,
+    &'a AppLifeCycle,
+    Comp<&'a mut HaTransform>,
+    Comp<&'a HaCamera>,
+    Comp<&'a mut HaCameraShake>,
+);
+
+/// Removes shake offset applied last frame, so other systems work on camera base position.
+pub fn ha_camera_shake_restore_system(universe: &mut Universe) {
+    let (world, ..) = universe.query_resources::<HaCameraShakeSystemResources>();
+
+    for (_, (transform, shake)) in world
+        .query::<(&mut HaTransform, &mut HaCameraShake)>()
+        .with::<&HaCamera>()
+        .iter()
+    {
+        let applied = std::mem::take(&mut shake.applied);
+        transform.change_translation(|translation| *translation -= applied);
+    }
+}
+
+pub fn ha_camera_shake_system(universe: &mut Universe) {
+    let (world, lifecycle, ..) = universe.query_resources::<HaCameraShakeSystemResources>();
+
+    let dt = lifecycle.delta_time_seconds();
+
+    for (_, (transform, shake)) in world
+        .query::<(&mut HaTransform, &mut HaCameraShake)>()
+        .with::<&HaCamera>()
+        .iter()
+    {
+        let offset = shake.update(dt);
+        transform.change_translation(|translation| *translation += offset);
+        shake.applied = offset;
+    }
+}
@@ -9,7 +9,11 @@ use crate::{
     image::{ImageReference, ImageResourceMapping},
     material::{
         common::MaterialValue,
-        domains::surface::{text::SurfaceTextFactory, SurfaceVertexText},
+        domains::{
+            font::SDF_FONT_MATERIAL_NAME,
+            surface::{text::SurfaceTextFactory, SurfaceVertexText},
+        },
+        MaterialReference,
     },
     mesh::{Mesh, MeshId, MeshReference},
 };
@@ -114,6 +118,9 @@ fn set_material_sampler(
     font: &FontAsset,
     image_mapping: &ImageResourceMapping,
 ) {
+    if font.distance_field && material.reference == MaterialReference::None {
+        material.reference = MaterialReference::Asset(SDF_FONT_MATERIAL_NAME.to_owned());
+    }
     if let Some((_, id)) = font.pages_image_assets.get(0) {
         if let Some(id) = image_mapping.resource_by_asset(*id) {
             material.values.insert(
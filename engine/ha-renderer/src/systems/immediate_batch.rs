@@ -44,9 +44,15 @@ where
         .iter()
     {
         mesh.reference = MeshReference::None;
+        if batch.sort {
+            batch.factory.sort();
+        }
+        mesh.batches = batch.mesh_batches();
         if let Ok(factory) = batch.factory.factory() {
             if let Some(id) = cache.meshes.get(&entity) {
                 if let Some(m) = renderer.mesh_mut(*id) {
+                    m.set_draw_index_limit(batch.draw_index_limit);
+                    m.set_draw_vertex_limit(batch.draw_vertex_limit);
                     if factory.write_into(m).is_ok() {
                         mesh.reference = MeshReference::Id(*id);
                     }
@@ -56,6 +62,8 @@ where
                 m.set_regenerate_bounds(false);
                 m.set_vertex_storage_all(BufferStorage::Dynamic);
                 m.set_index_storage(BufferStorage::Dynamic);
+                m.set_draw_index_limit(batch.draw_index_limit);
+                m.set_draw_vertex_limit(batch.draw_vertex_limit);
                 if factory.write_into(&mut m).is_ok() {
                     if let Ok(id) = renderer.add_mesh(m) {
                         mesh.reference = MeshReference::Id(id);
@@ -0,0 +1,99 @@
+use crate::{
+    asset_protocols::material::MaterialAsset,
+    material::MaterialResourceMapping,
+    resources::{material_hot_reload::MaterialHotReload, material_library::MaterialLibrary},
+};
+use core::{
+    app::AppLifeCycle, assets::database::AssetsDatabase, ecs::Universe, fetch::FetchStatus,
+};
+use std::str::from_utf8;
+
+pub type HaMaterialHotReloadSystemResources<'a> = (
+    &'a AppLifeCycle,
+    &'a mut AssetsDatabase,
+    &'a mut MaterialHotReload,
+    &'a mut MaterialLibrary,
+    &'a MaterialResourceMapping,
+);
+
+pub fn ha_material_hot_reload_system(universe: &mut Universe) {
+    let (lifecycle, mut assets, mut hot_reload, mut material_library, material_mapping) =
+        universe.query_resources::<HaMaterialHotReloadSystemResources>();
+
+    if hot_reload.watched.is_empty() {
+        return;
+    }
+    hot_reload.timer -= lifecycle.delta_time_seconds();
+    let refresh = hot_reload.timer <= 0.0;
+    if refresh {
+        hot_reload.timer = hot_reload.interval;
+    }
+
+    for (path, entry) in &mut hot_reload.watched {
+        let process = match &entry.process {
+            Some(process) => process,
+            None => {
+                if refresh {
+                    entry.process = assets
+                        .fetch_engine_mut()
+                        .and_then(|engine| engine.fetch(path).ok());
+                }
+                continue;
+            }
+        };
+        let bytes = match process.status() {
+            FetchStatus::Empty | FetchStatus::InProgress(_) => continue,
+            FetchStatus::Done => process.read(),
+            _ => None,
+        };
+        entry.process = None;
+        let bytes = match bytes {
+            Some(bytes) => bytes,
+            None => continue,
+        };
+        let changed = entry
+            .content
+            .as_ref()
+            .map(|content| content != &bytes)
+            .unwrap_or_default();
+        if changed {
+            reload(path, &bytes, &mut material_library, &material_mapping);
+        }
+        entry.content = Some(bytes);
+    }
+}
+
+fn reload(
+    path: &str,
+    bytes: &[u8],
+    material_library: &mut MaterialLibrary,
+    material_mapping: &MaterialResourceMapping,
+) {
+    let asset = if path.ends_with(".json") {
+        from_utf8(bytes)
+            .map_err(|error| error.to_string())
+            .and_then(|data| {
+                serde_json::from_str::<MaterialAsset>(data).map_err(|error| error.to_string())
+            })
+    } else {
+        bincode::deserialize::<MaterialAsset>(bytes).map_err(|error| error.to_string())
+    };
+    let graph = match asset {
+        Ok(MaterialAsset::Graph { content, .. }) => content,
+        Ok(_) => {
+            oxygengine_core::warn!("Hot reloaded material asset is not a graph: {}", path);
+            return;
+        }
+        Err(error) => {
+            oxygengine_core::error!("Could not decode material asset: {} | {}", path, error);
+            return;
+        }
+    };
+    let id = match material_mapping.resource_by_name(path) {
+        Some(id) => id,
+        None => return,
+    };
+    if let Err(error) = material_library.reload(id, graph) {
+        oxygengine_core::error!("Could not reload material: {} | {:#?}", path, error);
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
-    components::{gizmo::HaGizmo, mesh_instance::HaMeshInstance, transform::HaTransform},
+    components::{
+        gizmo::HaGizmo, mesh_instance::HaMeshInstance, tilemap_instance::HaTileMapInstance,
+        transform::HaTransform,
+    },
     ha_renderer::HaRenderer,
     resources::gizmos::Gizmos,
 };
@@ -12,50 +15,62 @@ pub type HaMeshBoundsGizmoSystemResources<'a> = (
     Comp<&'a HaTransform>,
     Comp<&'a HaGizmo>,
     Comp<&'a HaMeshInstance>,
+    Comp<&'a HaTileMapInstance>,
 );
 
 pub fn ha_mesh_bounds_gizmo_system(universe: &mut Universe) {
     let (world, renderer, mut gizmos, ..) =
         universe.query_resources::<HaMeshBoundsGizmoSystemResources>();
 
-    for (_, (transform, gizmo, instance)) in world
-        .query::<(&HaTransform, &HaGizmo, &HaMeshInstance)>()
+    for (_, (transform, gizmo, instance, tilemap)) in world
+        .query::<(
+            &HaTransform,
+            &HaGizmo,
+            &HaMeshInstance,
+            Option<&HaTileMapInstance>,
+        )>()
         .iter()
     {
         if !gizmo.visible {
             continue;
         }
-        let mut points = match instance
-            .reference
-            .id()
-            .and_then(|id| renderer.mesh(*id))
-            .and_then(|m| m.bounds())
-            .map(|b| b.box_vertices())
-        {
-            Some(points) => points,
-            None => continue,
-        };
         let matrix = transform.world_matrix();
-        for point in &mut points {
-            *point = matrix.mul_point(*point);
+        // tilemaps have no single mesh, so every chunk mesh gets its own bounds drawn.
+        let mesh_ids = instance.reference.id().copied().into_iter().chain(
+            tilemap
+                .into_iter()
+                .flat_map(|tilemap| tilemap.chunk_meshes().map(|(_, id)| id)),
+        );
+        for id in mesh_ids {
+            let mut points = match renderer
+                .mesh(id)
+                .and_then(|m| m.bounds())
+                .map(|b| b.box_vertices())
+            {
+                Some(points) => points,
+                None => continue,
+            };
+            for point in &mut points {
+                *point = matrix.mul_point(*point);
+            }
+            // TODO: replace with simpler direct write of points and their indices.
+            let vertices = [
+                (points[0], points[1]),
+                (points[1], points[2]),
+                (points[2], points[3]),
+                (points[3], points[0]),
+                (points[4], points[5]),
+                (points[5], points[6]),
+                (points[6], points[7]),
+                (points[7], points[4]),
+                (points[0], points[4]),
+                (points[1], points[5]),
+                (points[2], points[6]),
+                (points[3], points[7]),
+            ];
+            gizmos
+                .factory
+                .lines(gizmo.color.into(), vertices.into_iter());
         }
-        // TODO: replace with simpler direct write of points and their indices.
-        let vertices = [
-            (points[0], points[1]),
-            (points[1], points[2]),
-            (points[2], points[3]),
-            (points[3], points[0]),
-            (points[4], points[5]),
-            (points[5], points[6]),
-            (points[6], points[7]),
-            (points[7], points[4]),
-            (points[0], points[4]),
-            (points[1], points[5]),
-            (points[2], points[6]),
-            (points[3], points[7]),
-        ];
-        gizmos
-            .factory
-            .lines(gizmo.color.into(), vertices.into_iter());
     }
 }
@@ -1,17 +1,24 @@
 pub mod apply_sprite_animation_to_material;
 pub mod atlas;
+pub mod atlas_builder;
+pub mod bloom;
 pub mod camera_cache;
+pub mod camera_follow;
+pub mod camera_shake;
 pub mod font;
 pub mod immediate_batch;
+pub mod material_hot_reload;
 pub mod mesh_bounds_gizmo;
 pub mod render_forward_stage;
 pub mod render_gizmo_stage;
 pub mod render_postprocess_stage;
 pub mod renderer;
 pub mod rig;
+pub mod spatial_index;
 pub mod sprite_animation;
 pub mod tilemap;
 pub mod transform;
+pub mod virtual_image_streaming;
 pub mod virtual_image_uniforms;
 pub mod volume_overlap;
 pub mod volume_visibility;
@@ -1,36 +1,67 @@
 use crate::{
     components::{
         camera::HaCamera, material_instance::HaMaterialInstance, mesh_instance::HaMeshInstance,
-        transform::HaTransform, visibility::HaVisibility,
+        tilemap_instance::HaTileMapInstance, transform::HaTransform, visibility::HaVisibility,
     },
     constants::material_uniforms::*,
     ha_renderer::HaRenderer,
+    material::{common::MaterialValue, MaterialId},
     math::*,
-    pipeline::render_queue::RenderCommand,
+    mesh::{MeshDrawRange, MeshId},
+    pipeline::{
+        render_queue::{RenderCommand, RenderQueueAutoRecorder},
+        stage::StageProcessInfo,
+    },
 };
 use core::{
     app::AppLifeCycle,
     ecs::{components::Tag, Comp, Universe, WorldRef},
 };
+use std::collections::HashMap;
 
 pub type HaRenderForwardStageSystemResources<'a> = (
     WorldRef,
     &'a HaRenderer,
     &'a AppLifeCycle,
+    &'a mut HaRenderForwardStageCullingStats,
     Comp<&'a mut HaCamera>,
     Comp<&'a Tag>,
     Comp<&'a HaVisibility>,
     Comp<&'a HaTransform>,
     Comp<&'a HaMeshInstance>,
     Comp<&'a HaMaterialInstance>,
+    Comp<&'a HaTileMapInstance>,
 );
 
 pub struct RenderForwardStage;
 
+/// Frustum culling stats of forward stage from last frame.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HaRenderForwardStageCullingStats {
+    /// Number of renderables tested against camera frustum.
+    pub tested: usize,
+    /// Number of renderables skipped for being fully outside of camera frustum.
+    pub culled: usize,
+}
+
+impl HaRenderForwardStageCullingStats {
+    /// Tests world space bounds against camera frustum and tells if they should be rendered.
+    pub fn test(&mut self, info: &StageProcessInfo, bounds: &BoundsVolume) -> bool {
+        self.tested += 1;
+        let visible = info.is_world_bounds_visible(bounds);
+        if !visible {
+            self.culled += 1;
+        }
+        visible
+    }
+}
+
 pub fn ha_render_forward_stage_system(universe: &mut Universe) {
-    let (world, renderer, lifecycle, ..) =
+    let (world, renderer, lifecycle, mut stats, ..) =
         universe.query_resources::<HaRenderForwardStageSystemResources>();
 
+    *stats = Default::default();
+
     let time = vec4(
         lifecycle.time_seconds(),
         lifecycle.delta_time_seconds(),
@@ -57,76 +88,152 @@ pub fn ha_render_forward_stage_system(universe: &mut Universe) {
             };
             let mut recorder = render_queue.auto_recorder(None);
 
-            for (transform, mesh, material) in world
+            for (transform, mesh, material, tilemap) in world
                 .query::<(
                     Option<&Tag>,
                     Option<&HaVisibility>,
                     &HaTransform,
                     &HaMeshInstance,
                     &HaMaterialInstance,
+                    Option<&HaTileMapInstance>,
                 )>()
                 .iter()
-                .filter(|(_, (tag, visibility, _, _, _))| {
+                .filter(|(_, (tag, visibility, _, _, _, _))| {
                     visibility.map(|v| v.0).unwrap_or(true)
                         && tag.map(|t| info.filters.validate_tag(&t.0)).unwrap_or(true)
                 })
-                .map(|(_, (_, _, transform, mesh, material))| (transform, mesh, material))
+                .map(|(_, (_, _, transform, mesh, material, tilemap))| {
+                    (transform, mesh, material, tilemap)
+                })
             {
-                recorder.next_group();
-                let mesh_id = match mesh.reference.id() {
-                    Some(id) => *id,
-                    None => continue,
-                };
                 let material_id = match material.reference.id() {
                     Some(id) => *id,
                     None => continue,
                 };
-                let current_mesh = match renderer.mesh(mesh_id) {
-                    Some(mesh) => mesh,
-                    None => continue,
+                // tilemaps are drawn chunk by chunk, so only chunks within frustum get rendered.
+                if let Some(tilemap) = tilemap {
+                    for (_, mesh_id) in tilemap.chunk_meshes() {
+                        recorder.next_group();
+                        record_mesh(
+                            &mut recorder,
+                            &renderer,
+                            &info,
+                            &mut stats,
+                            time,
+                            transform,
+                            mesh_id,
+                            material_id,
+                            material,
+                            None,
+                            mesh.override_draw_range.to_owned().unwrap_or_default(),
+                        );
+                    }
+                }
+                let mesh_id = match mesh.reference.id() {
+                    Some(id) => *id,
+                    None => {
+                        recorder.next_group();
+                        continue;
+                    }
                 };
-                let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
-                let signature = info.make_material_signature(current_mesh.layout());
-                let _ = recorder.record(RenderCommand::ActivateMaterial(
-                    material_id,
-                    signature.to_owned(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    MODEL_MATRIX_NAME.into(),
-                    transform.world_matrix().into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    VIEW_MATRIX_NAME.into(),
-                    info.view_matrix.into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    PROJECTION_MATRIX_NAME.into(),
-                    info.projection_matrix.into(),
-                ));
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    TIME_NAME.into(),
-                    time.into(),
-                ));
-                for (key, value) in &material.values {
-                    let _ = recorder.record(RenderCommand::OverrideUniform(
-                        key.to_owned().into(),
-                        value.to_owned(),
-                    ));
+                // batches keep their order, each one drawn with its own material and uniforms.
+                for batch in &mesh.batches {
+                    recorder.next_group();
+                    record_mesh(
+                        &mut recorder,
+                        &renderer,
+                        &info,
+                        &mut stats,
+                        time,
+                        transform,
+                        mesh_id,
+                        batch.material.unwrap_or(material_id),
+                        material,
+                        Some(&batch.values),
+                        MeshDrawRange::Range(batch.range.to_owned()),
+                    );
                 }
-                if let Some(draw_options) = &material.override_draw_options {
-                    let _ =
-                        recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+                if !mesh.batches.is_empty() {
+                    continue;
                 }
-                let draw_range = mesh
-                    .override_draw_range
-                    .as_ref()
-                    .cloned()
-                    .unwrap_or_default();
-                let _ = recorder.record(RenderCommand::DrawMesh(draw_range));
-                let _ = recorder.record(RenderCommand::ResetUniforms);
+                recorder.next_group();
+                record_mesh(
+                    &mut recorder,
+                    &renderer,
+                    &info,
+                    &mut stats,
+                    time,
+                    transform,
+                    mesh_id,
+                    material_id,
+                    material,
+                    None,
+                    mesh.override_draw_range.to_owned().unwrap_or_default(),
+                );
             }
 
             let _ = recorder.record(RenderCommand::SortingBarrier);
         }
     }
 }
+
+#[allow(clippy::too_many_arguments)]
+fn record_mesh(
+    recorder: &mut RenderQueueAutoRecorder,
+    renderer: &HaRenderer,
+    info: &StageProcessInfo,
+    stats: &mut HaRenderForwardStageCullingStats,
+    time: Vec4,
+    transform: &HaTransform,
+    mesh_id: MeshId,
+    material_id: MaterialId,
+    material: &HaMaterialInstance,
+    values: Option<&HashMap<String, MaterialValue>>,
+    draw_range: MeshDrawRange,
+) {
+    let current_mesh = match renderer.mesh(mesh_id) {
+        Some(mesh) => mesh,
+        None => return,
+    };
+    if let Some(bounds) = current_mesh
+        .bounds()
+        .and_then(|bounds| bounds.transformed(transform.world_matrix()))
+    {
+        if !stats.test(info, &bounds) {
+            return;
+        }
+    }
+    let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
+    let signature = info.make_material_signature(current_mesh.layout());
+    let _ = recorder.record(RenderCommand::ActivateMaterial(
+        material_id,
+        signature.to_owned(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        MODEL_MATRIX_NAME.into(),
+        transform.world_matrix().into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        VIEW_MATRIX_NAME.into(),
+        info.view_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        PROJECTION_MATRIX_NAME.into(),
+        info.projection_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        TIME_NAME.into(),
+        time.into(),
+    ));
+    for (key, value) in material.values.iter().chain(values.into_iter().flatten()) {
+        let _ = recorder.record(RenderCommand::OverrideUniform(
+            key.to_owned().into(),
+            value.to_owned(),
+        ));
+    }
+    if let Some(draw_options) = &material.override_draw_options {
+        let _ = recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+    }
+    let _ = recorder.record(RenderCommand::DrawMesh(draw_range));
+    let _ = recorder.record(RenderCommand::ResetUniforms);
+}
@@ -3,20 +3,30 @@ use crate::{
     constants::material_uniforms::*,
     ha_renderer::HaRenderer,
     image::ImageResourceMapping,
-    material::{domains::gizmo::GizmoVertex, MaterialResourceMapping},
+    material::{
+        domains::gizmo::{GizmoFactory, GizmoVertex},
+        MaterialId, MaterialResourceMapping,
+    },
     math::*,
-    mesh::{vertex_factory::VertexType, BufferStorage, Mesh, MeshDrawRange, MeshId},
-    pipeline::render_queue::RenderCommand,
-    resources::gizmos::Gizmos,
+    mesh::{vertex_factory::VertexType, BufferStorage, Mesh, MeshDrawRange, MeshId, VertexLayout},
+    pipeline::{
+        render_queue::{RenderCommand, RenderQueueAutoRecorder},
+        stage::StageProcessInfo,
+    },
+    resources::{debug_draw::DebugDraw, gizmos::Gizmos},
 };
 use core::{
     app::AppLifeCycle,
     ecs::{Comp, Universe, WorldRef},
+    Scalar,
 };
 
 #[derive(Debug, Default, Clone)]
 pub struct HaRenderGizmoStageSystemCache {
     mesh: Option<MeshId>,
+    screen_mesh: Option<MeshId>,
+    /// Screen space debug draw primitives of current frame.
+    screen_factory: GizmoFactory,
 }
 
 pub type HaRenderGizmoStageSystemResources<'a> = (
@@ -24,6 +34,7 @@ pub type HaRenderGizmoStageSystemResources<'a> = (
     &'a mut HaRenderer,
     &'a AppLifeCycle,
     &'a mut Gizmos,
+    &'a mut DebugDraw,
     &'a MaterialResourceMapping,
     &'a ImageResourceMapping,
     &'a mut HaRenderGizmoStageSystemCache,
@@ -42,13 +53,18 @@ pub fn ha_render_gizmo_stage_system(universe: &mut Universe) {
         mut renderer,
         lifecycle,
         mut gizmos,
+        mut debug_draw,
         material_mapping,
         image_mapping,
         mut cache,
         ..,
     ) = universe.query_resources::<HaRenderGizmoStageSystemResources>();
 
-    if gizmos.factory.is_empty() {
+    let cache = &mut *cache;
+    cache.screen_factory.clear();
+    debug_draw.flush(&mut gizmos.factory, &mut cache.screen_factory);
+
+    if gizmos.factory.is_empty() && cache.screen_factory.is_empty() {
         return;
     }
 
@@ -57,33 +73,21 @@ pub fn ha_render_gizmo_stage_system(universe: &mut Universe) {
         Err(_) => return,
     };
 
-    let mesh_id = match cache.mesh {
-        Some(mesh_id) => mesh_id,
-        None => {
-            let mut m = Mesh::new(layout.to_owned());
-            m.set_regenerate_bounds(false);
-            m.set_vertex_storage_all(BufferStorage::Dynamic);
-            m.set_index_storage(BufferStorage::Dynamic);
-            match renderer.add_mesh(m) {
-                Ok(mesh_id) => {
-                    cache.mesh = Some(mesh_id);
-                    mesh_id
-                }
-                Err(_) => return,
-            }
-        }
+    let world_mesh_id = if gizmos.factory.is_empty() {
+        None
+    } else {
+        write_mesh(&mut renderer, &mut cache.mesh, &layout, &gizmos.factory)
+    };
+    let screen_mesh_id = if cache.screen_factory.is_empty() {
+        None
+    } else {
+        write_mesh(
+            &mut renderer,
+            &mut cache.screen_mesh,
+            &layout,
+            &cache.screen_factory,
+        )
     };
-    match renderer.mesh_mut(mesh_id) {
-        Some(mesh) => match gizmos.factory.factory() {
-            Ok(factory) => {
-                if factory.write_into(mesh).is_err() {
-                    return;
-                }
-            }
-            Err(_) => return,
-        },
-        None => return,
-    }
 
     gizmos
         .material
@@ -117,42 +121,116 @@ pub fn ha_render_gizmo_stage_system(universe: &mut Universe) {
             };
             let mut recorder = render_queue.auto_recorder(None);
 
-            let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
-            let signature = info.make_material_signature(&layout);
-            let _ = recorder.record(RenderCommand::ActivateMaterial(
-                material_id,
-                signature.to_owned(),
-            ));
-            let _ = recorder.record(RenderCommand::OverrideUniform(
-                MODEL_MATRIX_NAME.into(),
-                Mat4::identity().into(),
-            ));
-            let _ = recorder.record(RenderCommand::OverrideUniform(
-                VIEW_MATRIX_NAME.into(),
-                info.view_matrix.into(),
-            ));
-            let _ = recorder.record(RenderCommand::OverrideUniform(
-                PROJECTION_MATRIX_NAME.into(),
-                info.projection_matrix.into(),
-            ));
-            let _ = recorder.record(RenderCommand::OverrideUniform(
-                TIME_NAME.into(),
-                time.into(),
-            ));
-            for (key, value) in &gizmos.material.values {
-                let _ = recorder.record(RenderCommand::OverrideUniform(
-                    key.to_owned().into(),
-                    value.to_owned(),
-                ));
+            if let Some(mesh_id) = world_mesh_id {
+                record_mesh(
+                    &mut recorder,
+                    &gizmos,
+                    &info,
+                    &layout,
+                    mesh_id,
+                    material_id,
+                    time,
+                    info.view_matrix,
+                    info.projection_matrix,
+                );
             }
-            if let Some(draw_options) = &gizmos.material.override_draw_options {
-                let _ = recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+            if let Some(mesh_id) = screen_mesh_id {
+                let projection_matrix = Mat4::orthographic_without_depth_planes(FrustumPlanes {
+                    left: 0.0,
+                    right: info.width as Scalar,
+                    top: 0.0,
+                    bottom: info.height as Scalar,
+                    near: -1.0,
+                    far: 1.0,
+                });
+                record_mesh(
+                    &mut recorder,
+                    &gizmos,
+                    &info,
+                    &layout,
+                    mesh_id,
+                    material_id,
+                    time,
+                    Mat4::identity(),
+                    projection_matrix,
+                );
             }
-            let _ = recorder.record(RenderCommand::DrawMesh(MeshDrawRange::All));
-            let _ = recorder.record(RenderCommand::ResetUniforms);
-            let _ = recorder.record(RenderCommand::SortingBarrier);
         }
     }
 
     gizmos.factory.clear();
 }
+
+fn write_mesh(
+    renderer: &mut HaRenderer,
+    slot: &mut Option<MeshId>,
+    layout: &VertexLayout,
+    factory: &GizmoFactory,
+) -> Option<MeshId> {
+    let mesh_id = match *slot {
+        Some(mesh_id) => mesh_id,
+        None => {
+            let mut m = Mesh::new(layout.to_owned());
+            m.set_regenerate_bounds(false);
+            m.set_vertex_storage_all(BufferStorage::Dynamic);
+            m.set_index_storage(BufferStorage::Dynamic);
+            let mesh_id = renderer.add_mesh(m).ok()?;
+            *slot = Some(mesh_id);
+            mesh_id
+        }
+    };
+    factory
+        .factory()
+        .ok()?
+        .write_into(renderer.mesh_mut(mesh_id)?)
+        .ok()?;
+    Some(mesh_id)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn record_mesh(
+    recorder: &mut RenderQueueAutoRecorder,
+    gizmos: &Gizmos,
+    info: &StageProcessInfo,
+    layout: &VertexLayout,
+    mesh_id: MeshId,
+    material_id: MaterialId,
+    time: Vec4,
+    view_matrix: Mat4,
+    projection_matrix: Mat4,
+) {
+    let _ = recorder.record(RenderCommand::ActivateMesh(mesh_id));
+    let signature = info.make_material_signature(layout);
+    let _ = recorder.record(RenderCommand::ActivateMaterial(
+        material_id,
+        signature.to_owned(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        MODEL_MATRIX_NAME.into(),
+        Mat4::identity().into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        VIEW_MATRIX_NAME.into(),
+        view_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        PROJECTION_MATRIX_NAME.into(),
+        projection_matrix.into(),
+    ));
+    let _ = recorder.record(RenderCommand::OverrideUniform(
+        TIME_NAME.into(),
+        time.into(),
+    ));
+    for (key, value) in &gizmos.material.values {
+        let _ = recorder.record(RenderCommand::OverrideUniform(
+            key.to_owned().into(),
+            value.to_owned(),
+        ));
+    }
+    if let Some(draw_options) = &gizmos.material.override_draw_options {
+        let _ = recorder.record(RenderCommand::ApplyDrawOptions(draw_options.to_owned()));
+    }
+    let _ = recorder.record(RenderCommand::DrawMesh(MeshDrawRange::All));
+    let _ = recorder.record(RenderCommand::ResetUniforms);
+    let _ = recorder.record(RenderCommand::SortingBarrier);
+}
@@ -10,7 +10,7 @@ use crate::{
     math::rect,
     mesh::{Mesh, MeshResourceMapping},
     pipeline::{stage::StageQueueSorting, PipelineId},
-    render_target::RenderTargetDescriptor,
+    render_target::{render_target_virtual_image_name, RenderTargetDescriptor},
     resources::material_library::MaterialLibrary,
 };
 use core::{
@@ -98,11 +98,11 @@ pub fn ha_renderer_maintenance_system(universe: &mut Universe) {
 pub type HaRendererExecutionSystemResources<'a> = (
     &'a mut HaRenderer,
     &'a HaRendererMaintenanceSystemCache,
-    &'a MaterialLibrary,
+    &'a mut MaterialLibrary,
 );
 
 pub fn ha_renderer_execution_system(universe: &mut Universe) {
-    let (mut renderer, cache, material_library) =
+    let (mut renderer, cache, mut material_library) =
         universe.query_resources::<HaRendererExecutionSystemResources>();
 
     renderer.maintain_render_targets();
@@ -112,6 +112,10 @@ pub fn ha_renderer_execution_system(universe: &mut Universe) {
         &material_library,
         cache.fragment_high_precision_support.unwrap_or_default(),
     );
+    renderer.reload_materials(
+        &mut material_library,
+        cache.fragment_high_precision_support.unwrap_or_default(),
+    );
     execute_pipelines(&mut renderer);
 }
 
@@ -295,7 +299,7 @@ fn sync_cache(
                     for (rt_name, (descriptor, id)) in &pipeline.render_targets {
                         if let RenderTargetDescriptor::Custom { buffers, .. } = descriptor {
                             if let Some(n) = &buffers.depth_stencil {
-                                let path = format!("@render-target/{}/{}/{}", name.0, rt_name, n);
+                                let path = render_target_virtual_image_name(&name.0, rt_name, n);
                                 let mut virtual_image = VirtualImage::new(
                                     VirtualImageSource::RenderTargetDepthStencil(*id),
                                 );
@@ -316,7 +320,7 @@ fn sync_cache(
                             }
                             for color in &buffers.colors {
                                 let path =
-                                    format!("@render-target/{}/{}/{}", name.0, rt_name, color.id);
+                                    render_target_virtual_image_name(&name.0, rt_name, &color.id);
                                 let mut virtual_image = VirtualImage::new(
                                     VirtualImageSource::RenderTargetColor(*id, color.id.to_owned()),
                                 );
@@ -352,7 +356,11 @@ fn execute_pipelines(renderer: &mut HaRenderer) {
     };
     let mut stats = RenderStats::default();
     let resources = renderer.stage_resources();
-    for pipeline in renderer.pipelines.values() {
+    for id in renderer.pipelines_in_order() {
+        let pipeline = match renderer.pipelines.get(&id) {
+            Some(pipeline) => pipeline,
+            None => continue,
+        };
         for stage in pipeline.stages.iter() {
             if let Some((_, render_target)) = pipeline.render_targets.get(&stage.render_target) {
                 if let Some(render_target) = renderer.render_targets.get(*render_target) {
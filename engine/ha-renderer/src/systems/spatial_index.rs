@@ -0,0 +1,27 @@
+use crate::{
+    components::{transform::HaTransform, volume::HaVolume},
+    resources::spatial_index::HaSpatialIndex,
+};
+use core::ecs::{Comp, Universe, WorldRef};
+
+pub type HaSpatialIndexSystemResources<'a> = (
+    WorldRef,
+    &'a mut HaSpatialIndex,
+    Comp<&'a HaTransform>,
+    Comp<&'a HaVolume>,
+);
+
+pub fn ha_spatial_index_system(universe: &mut Universe) {
+    let (world, mut index, ..) = universe.query_resources::<HaSpatialIndexSystemResources>();
+
+    index.rebuild(
+        world
+            .query::<(&HaTransform, &HaVolume)>()
+            .iter()
+            .filter_map(|(entity, (transform, volume))| {
+                volume
+                    .world_space_rect(&transform.world_matrix())
+                    .map(|rect| (entity, rect))
+            }),
+    );
+}
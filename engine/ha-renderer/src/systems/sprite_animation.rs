@@ -88,6 +88,54 @@ macro_rules! select_state {
     };
 }
 
+/// Collects events of frames entered when playback moved from `time_before` to `time_after`
+/// (before wrapping), in order of playback.
+fn collect_frame_events(
+    state: &SpriteAnimationState,
+    time_before: Scalar,
+    time_after: Scalar,
+    entered: bool,
+    output: &mut Vec<String>,
+) {
+    let count = state.frames.len() as isize;
+    if state.events.is_empty() || count == 0 {
+        return;
+    }
+    let wraps = state.looping && !state.bounce;
+    let index = |time: Scalar| {
+        let index = time.floor() as isize;
+        if wraps {
+            index
+        } else {
+            index.max(0).min(count - 1)
+        }
+    };
+    let mut emit = |index: isize| {
+        let index = index.rem_euclid(count) as usize;
+        output.extend(
+            state
+                .events
+                .iter()
+                .filter(|(frame, _)| *frame == index)
+                .map(|(_, name)| name.to_owned()),
+        );
+    };
+    let from = index(time_before);
+    let to = index(time_after);
+    if !entered {
+        emit(from);
+    }
+    if to > from {
+        for index in (from + 1)..=to {
+            emit(index);
+        }
+    } else {
+        for index in (to..from).rev() {
+            emit(index);
+        }
+    }
+}
+
 macro_rules! process_change {
     ($animation: expr, $active: expr, $sprite: expr, $dt: expr) => {
         if let Some(state) = $animation.states.get(&$active.state) {
@@ -113,6 +161,14 @@ macro_rules! process_change {
                     })
                     .cloned(),
             );
+            collect_frame_events(
+                state,
+                time_before,
+                time_after,
+                $active.entered,
+                &mut $sprite.events,
+            );
+            $active.entered = true;
             if end {
                 if state.looping {
                     if state.bounce {
@@ -135,6 +191,7 @@ macro_rules! process_change {
                     $active.state = selected.to_owned();
                     $active.frame = 0.0;
                     $active.bounced = false;
+                    $active.entered = false;
                     $active.cached_frame = None;
                     true
                 } else {
@@ -172,6 +229,7 @@ pub fn ha_sprite_animation(universe: &mut Universe) {
     for (_, sprite) in world.query::<&mut HaSpriteAnimationInstance>().iter() {
         sprite.frame_changed = false;
         sprite.signals.clear();
+        sprite.events.clear();
 
         if let Some((animation, _)) = cache.map.get(&sprite.animation) {
             if sprite.playing && sprite.active.is_none() {
@@ -180,17 +238,99 @@ pub fn ha_sprite_animation(universe: &mut Universe) {
                 }
             }
 
-            if let (true, Some(active)) = (sprite.playing, sprite.active.as_mut()) {
-                let change = process_change!(animation, active, sprite, dt);
+            update_sprite(animation, sprite, dt);
+        }
+    }
+}
 
-                if let (true, Some(state)) = (change, animation.states.get(&active.state)) {
-                    let index = (active.frame.max(0.0) as usize).min(state.frames.len() - 1);
-                    if let Some(name) = state.frames.get(index) {
-                        active.cached_frame = Some(name.to_owned());
-                        sprite.frame_changed = true;
-                    }
-                }
+fn update_sprite(
+    animation: &SpriteAnimationAsset,
+    sprite: &mut HaSpriteAnimationInstance,
+    dt: Scalar,
+) {
+    if let (true, Some(active)) = (sprite.playing, sprite.active.as_mut()) {
+        let change = process_change!(animation, active, sprite, dt);
+
+        if let (true, Some(state)) = (change, animation.states.get(&active.state)) {
+            let index = (active.frame.max(0.0) as usize).min(state.frames.len() - 1);
+            if let Some(name) = state.frames.get(index) {
+                active.cached_frame = Some(name.to_owned());
+                sprite.frame_changed = true;
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(looping: bool, bounce: bool) -> SpriteAnimationAsset {
+        let mut states = HashMap::new();
+        states.insert(
+            "walk".to_owned(),
+            SpriteAnimationState {
+                frames: vec![
+                    "a".to_owned(),
+                    "b".to_owned(),
+                    "c".to_owned(),
+                    "d".to_owned(),
+                ],
+                events: vec![(0, "start".to_owned()), (2, "step".to_owned())],
+                speed: 1.0,
+                looping,
+                bounce,
+                ..Default::default()
+            },
+        );
+        SpriteAnimationAsset {
+            default_state: Some("walk".to_owned()),
+            speed: 1.0,
+            states,
+            rules: vec![],
+        }
+    }
+
+    fn step(
+        animation: &SpriteAnimationAsset,
+        sprite: &mut HaSpriteAnimationInstance,
+        dt: Scalar,
+    ) -> Vec<String> {
+        sprite.events.clear();
+        update_sprite(animation, sprite, dt);
+        sprite.received_events().to_vec()
+    }
+
+    #[test]
+    fn test_sprite_animation_frame_events() {
+        let animation = animation(false, false);
+        let mut sprite = HaSpriteAnimationInstance::default();
+        sprite.play("walk");
+        assert_eq!(step(&animation, &mut sprite, 0.5), vec!["start"]);
+        assert!(step(&animation, &mut sprite, 1.0).is_empty());
+        assert_eq!(step(&animation, &mut sprite, 1.0), vec!["step"]);
+        assert!(step(&animation, &mut sprite, 0.2).is_empty());
+        assert!(step(&animation, &mut sprite, 5.0).is_empty());
+        assert!(!sprite.playing);
+
+        let animation = self::animation(true, false);
+        let mut sprite = HaSpriteAnimationInstance::default();
+        sprite.play("walk");
+        assert_eq!(step(&animation, &mut sprite, 3.5), vec!["start", "step"]);
+        assert_eq!(step(&animation, &mut sprite, 1.0), vec!["start"]);
+        assert_eq!(step(&animation, &mut sprite, 2.0), vec!["step"]);
+        assert_eq!(step(&animation, &mut sprite, 4.0), vec!["start", "step"]);
+        assert!(sprite.playing);
+
+        let animation = self::animation(true, true);
+        let mut sprite = HaSpriteAnimationInstance::default();
+        sprite.play("walk");
+        assert_eq!(step(&animation, &mut sprite, 2.5), vec!["start", "step"]);
+        assert!(step(&animation, &mut sprite, 2.0).is_empty());
+        assert!(step(&animation, &mut sprite, 0.5).is_empty());
+        assert_eq!(step(&animation, &mut sprite, 0.75), vec!["step"]);
+        assert_eq!(step(&animation, &mut sprite, 3.0), vec!["start"]);
+        assert!(step(&animation, &mut sprite, 0.5).is_empty());
+        assert_eq!(step(&animation, &mut sprite, 2.0), vec!["step"]);
+    }
+}
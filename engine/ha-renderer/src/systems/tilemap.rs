@@ -1,7 +1,8 @@
 use crate::{
     components::{
-        material_instance::HaMaterialInstance, mesh_instance::HaMeshInstance,
-        tilemap_instance::HaTileMapInstance,
+        material_instance::HaMaterialInstance,
+        mesh_instance::HaMeshInstance,
+        tilemap_instance::{HaTileMapChunk, HaTileMapInstance},
     },
     ha_renderer::HaRenderer,
     image::{ImageFiltering, ImageId, ImageReference, VirtualImageSource},
@@ -9,14 +10,14 @@ use crate::{
         common::MaterialValue,
         domains::surface::{tilemap::SurfaceTileMapFactory, SurfaceVertexPT},
     },
-    mesh::{Mesh, MeshId, MeshReference},
+    mesh::{Mesh, MeshError, MeshId, MeshReference},
 };
 use core::ecs::{life_cycle::EntityChanges, Comp, Entity, Universe, WorldRef};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 #[derive(Debug, Default)]
 pub struct HaTileMapSystemCache {
-    meshes: HashMap<Entity, MeshId>,
+    meshes: HashMap<Entity, HashMap<HaTileMapChunk, MeshId>>,
 }
 
 pub type HaTileMapSystemResources<'a> = (
@@ -34,8 +35,10 @@ pub fn ha_tilemap_system(universe: &mut Universe) {
         universe.query_resources::<HaTileMapSystemResources>();
 
     for entity in changes.despawned() {
-        if let Some(id) = cache.meshes.remove(&entity) {
-            let _ = renderer.remove_mesh(id);
+        if let Some(meshes) = cache.meshes.remove(&entity) {
+            for id in meshes.into_values() {
+                let _ = renderer.remove_mesh(id);
+            }
         }
     }
 
@@ -47,47 +50,79 @@ pub fn ha_tilemap_system(universe: &mut Universe) {
         )>()
         .iter()
     {
-        if tilemap.dirty || !cache.meshes.contains_key(&entity) {
-            mesh.reference = MeshReference::None;
-            if let Ok(factory) =
-                SurfaceTileMapFactory::factory::<SurfaceVertexPT>(tilemap, &renderer.virtual_images)
-            {
-                let image_id = match renderer
-                    .virtual_images
-                    .get_named(tilemap.atlas())
-                    .unwrap()
-                    .source()
-                {
-                    VirtualImageSource::Image(image_id) => Some(*image_id),
-                    _ => None,
-                };
-                if let Some(image_id) = image_id {
-                    if let Some(id) = cache.meshes.get(&entity) {
-                        if let Some(m) = renderer.mesh_mut(*id) {
-                            m.set_vertex_storage_all(tilemap.change_frequency().into());
-                            m.set_index_storage(tilemap.change_frequency().into());
-                            if factory.write_into(m).is_ok() {
-                                tilemap.dirty = false;
-                                mesh.reference = MeshReference::Id(*id);
-                                set_material_sampler(material, image_id, tilemap.filtering);
-                            }
-                        }
-                    } else {
-                        let mut m = Mesh::new(factory.layout().to_owned());
+        let rebuild_all = tilemap.dirty || !cache.meshes.contains_key(&entity);
+        if !rebuild_all && tilemap.dirty_chunks.is_empty() {
+            continue;
+        }
+        let image_id = match renderer
+            .virtual_images
+            .get_named(tilemap.atlas())
+            .map(|virtual_image| virtual_image.source())
+        {
+            Some(VirtualImageSource::Image(image_id)) => *image_id,
+            _ => continue,
+        };
+        let meshes = cache.meshes.entry(entity).or_default();
+        let chunks = if rebuild_all {
+            meshes
+                .keys()
+                .copied()
+                .chain(tilemap.occupied_chunks())
+                .collect::<HashSet<_>>()
+        } else {
+            std::mem::take(&mut tilemap.dirty_chunks)
+        };
+        let mut failed = HashSet::with_capacity(chunks.len());
+        for chunk in chunks {
+            let factory = match SurfaceTileMapFactory::chunk_factory::<SurfaceVertexPT>(
+                tilemap,
+                chunk,
+                &renderer.virtual_images,
+            ) {
+                Ok(factory) => factory,
+                Err(MeshError::ZeroSize) => {
+                    if let Some(id) = meshes.remove(&chunk) {
+                        let _ = renderer.remove_mesh(id);
+                    }
+                    continue;
+                }
+                Err(_) => {
+                    failed.insert(chunk);
+                    continue;
+                }
+            };
+            if let Some(id) = meshes.get(&chunk) {
+                match renderer.mesh_mut(*id) {
+                    Some(m) => {
                         m.set_vertex_storage_all(tilemap.change_frequency().into());
                         m.set_index_storage(tilemap.change_frequency().into());
-                        if factory.write_into(&mut m).is_ok() {
-                            if let Ok(id) = renderer.add_mesh(m) {
-                                tilemap.dirty = false;
-                                mesh.reference = MeshReference::Id(id);
-                                set_material_sampler(material, image_id, tilemap.filtering);
-                                cache.meshes.insert(entity, id);
-                            }
+                        if factory.write_into(m).is_err() {
+                            failed.insert(chunk);
                         }
                     }
+                    None => {
+                        failed.insert(chunk);
+                    }
+                }
+            } else {
+                let mut m = Mesh::new(factory.layout().to_owned());
+                m.set_vertex_storage_all(tilemap.change_frequency().into());
+                m.set_index_storage(tilemap.change_frequency().into());
+                match factory.write_into(&mut m).map(|_| renderer.add_mesh(m)) {
+                    Ok(Ok(id)) => {
+                        meshes.insert(chunk, id);
+                    }
+                    _ => {
+                        failed.insert(chunk);
+                    }
                 }
             }
         }
+        tilemap.dirty = false;
+        tilemap.dirty_chunks = failed;
+        tilemap.chunk_meshes = meshes.to_owned();
+        mesh.reference = MeshReference::None;
+        set_material_sampler(material, image_id, tilemap.filtering);
     }
 }
 
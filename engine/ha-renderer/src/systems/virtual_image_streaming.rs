@@ -0,0 +1,52 @@
+use crate::{ha_renderer::HaRenderer, image::VirtualImageId};
+use core::ecs::Universe;
+
+pub type HaVirtualImageStreamingSystemResources<'a> = (&'a mut HaRenderer,);
+
+pub fn ha_virtual_image_streaming_system(universe: &mut Universe) {
+    let (mut renderer,) = universe.query_resources::<HaVirtualImageStreamingSystemResources>();
+
+    let ids = renderer
+        .virtual_images
+        .iter()
+        .filter(|(_, image)| {
+            image
+                .streaming()
+                .map(|streaming| streaming.has_pending_tiles())
+                .unwrap_or_default()
+        })
+        .map(|(id, _)| id)
+        .collect::<Vec<_>>();
+    for id in ids {
+        let _ = stream_virtual_image(&mut renderer, id);
+    }
+}
+
+fn stream_virtual_image(renderer: &mut HaRenderer, id: VirtualImageId) -> Option<()> {
+    let source_id = renderer.virtual_images.get(id)?.source().image()?;
+    let cache_id = match renderer.virtual_images.get(id)?.streaming()?.cache_image() {
+        Some(cache_id) => cache_id,
+        None => {
+            let format = renderer.image(source_id)?.format();
+            let cache = renderer
+                .virtual_images
+                .get(id)?
+                .streaming()?
+                .create_cache_image(format)
+                .ok()?;
+            let cache_id = renderer.add_image(cache).ok()?;
+            renderer
+                .virtual_images
+                .get_mut(id)?
+                .streaming_mut()?
+                .set_cache_image(Some(cache_id));
+            cache_id
+        }
+    };
+    // streaming state gets updated on a copy, so source and cache images can be borrowed.
+    let mut streaming = renderer.virtual_images.get(id)?.streaming()?.to_owned();
+    let uploads = streaming.prepare_pending_uploads(renderer.image(source_id)?);
+    let result = streaming.write_uploads(renderer.image_mut(cache_id)?, &uploads);
+    *renderer.virtual_images.get_mut(id)?.streaming_mut()? = streaming;
+    result.ok()
+}
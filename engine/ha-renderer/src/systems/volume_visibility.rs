@@ -1,6 +1,7 @@
 use crate::{
     components::{
-        camera::*, mesh_instance::*, transform::*, visibility::*, volume::*, volume_visibility::*,
+        camera::*, mesh_instance::*, tilemap_instance::*, transform::*, visibility::*, volume::*,
+        volume_visibility::*,
     },
     ha_renderer::HaRenderer,
     math::*,
@@ -25,6 +26,7 @@ pub type HaVolumeVisibilitySystemResources<'a> = (
     Comp<&'a HaVolumeVisibility>,
     Comp<&'a mut HaVisibility>,
     Comp<&'a HaMeshInstance>,
+    Comp<&'a HaTileMapInstance>,
     Comp<&'a HaCamera>,
 );
 
@@ -49,39 +51,43 @@ pub fn ha_volume_visibility_system(universe: &mut Universe) {
             }),
     );
 
-    for (_, (visibility, transform, volume, mesh)) in world
+    for (_, (visibility, transform, volume, mesh, tilemap)) in world
         .query::<(
             &mut HaVisibility,
             &HaTransform,
             &HaVolumeVisibility,
             &HaMeshInstance,
+            Option<&HaTileMapInstance>,
         )>()
         .iter()
     {
-        let mesh_id = match mesh.reference.id() {
-            Some(mesh_id) => *mesh_id,
-            None => continue,
-        };
-        let mesh = match renderer.mesh(mesh_id) {
-            Some(mesh) => mesh,
-            None => continue,
-        };
-        let bounds = match mesh.bounds() {
-            Some(bounds) => bounds,
-            None => continue,
-        };
-        let bounds = match bounds.transformed(transform.world_matrix()) {
-            Some(bounds) => bounds,
-            None => continue,
-        };
-        visibility.0 = cache.temp_tag_volumes.iter().any(|(t, b)| {
-            if volume.filters.validate_tag(t) {
-                match volume.mode {
-                    HaVolumeVisibilityMode::Sphere => return bounds.overlap_spheres(b),
-                    HaVolumeVisibilityMode::Box => return bounds.overlap_boxes(b),
+        let matrix = transform.world_matrix();
+        // tilemap is visible when any of its chunk meshes overlaps volume.
+        let mut bounds = mesh
+            .reference
+            .id()
+            .copied()
+            .into_iter()
+            .chain(
+                tilemap
+                    .into_iter()
+                    .flat_map(|tilemap| tilemap.chunk_meshes().map(|(_, id)| id)),
+            )
+            .filter_map(|id| renderer.mesh(id)?.bounds()?.transformed(matrix))
+            .peekable();
+        if bounds.peek().is_none() {
+            continue;
+        }
+        visibility.0 = bounds.any(|bounds| {
+            cache.temp_tag_volumes.iter().any(|(t, b)| {
+                if volume.filters.validate_tag(t) {
+                    match volume.mode {
+                        HaVolumeVisibilityMode::Sphere => return bounds.overlap_spheres(b),
+                        HaVolumeVisibilityMode::Box => return bounds.overlap_boxes(b),
+                    }
                 }
-            }
-            false
+                false
+            })
         });
     }
 
@@ -1,14 +1,38 @@
 #![cfg(test)]
 
 use crate::{
+    code_material_function,
+    components::{
+        camera::*, camera_follow::*, camera_shake::*, immediate_batch::HaImmediateBatch,
+        tilemap_instance::*, transform::*,
+    },
     graph_material_function,
-    material::{common::*, domains::surface::*},
+    ha_renderer::*,
+    image::{
+        Image, ImageDescriptor, ImageFiltering, ImageFormat, ImageId, ImageMipmap, ImageMode,
+        ImageReference, VirtualImage, VirtualImageSource, VirtualImageStreaming,
+        VirtualImageTileState,
+    },
+    material::{
+        common::*,
+        domains::{
+            gizmo::GizmoFactory,
+            screenspace::*,
+            surface::{tilemap::SurfaceTileMapFactory, *},
+        },
+        graph::MaterialGraph,
+        Material, MaterialError, MaterialId,
+    },
     material_graph,
     math::*,
-    mesh::vertex_factory::*,
+    mesh::{vertex_factory::*, Mesh, MeshDrawMode, MeshError, VertexValueType},
+    pipeline::{stage::*, *},
     render_target::*,
-    resources::material_library::*,
+    resources::{atlas_builder::*, bloom::*, debug_draw::*, material_library::*},
+    systems::{render_forward_stage::*, render_postprocess_stage::*, virtual_image_streaming::*},
+    vertex_type, Resources,
 };
+use core::ecs::Universe;
 
 macro_rules! material_signature {
     (
@@ -230,6 +254,125 @@ fn test_graph_function() {
     println!("* `times_two` material function: {:#?}", times_two);
 }
 
+#[test]
+fn test_custom_material_function() {
+    let mut library = MaterialLibrary::default();
+    library
+        .register_function(code_material_function! {
+            fn invert_color(color: vec4) -> vec4 {
+                "return vec4(vec3(1.0) - color.rgb, color.a);"
+            }
+        })
+        .unwrap();
+    assert!(matches!(
+        library.register_function(code_material_function! {
+            fn invert_color(color: vec4) -> vec4 {
+                "return color;"
+            }
+        }),
+        Err(MaterialError::FunctionAlreadyExists(_))
+    ));
+    assert!(matches!(
+        library.register_function(code_material_function! {
+            fn blend(a: vec4, a: vec4) -> vec4 {
+                "return a;"
+            }
+        }),
+        Err(MaterialError::InvalidFunctionInput { .. })
+    ));
+
+    let graph = material_graph! {
+        inputs {
+            [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [(invert_color, color: [TintColor => vColor]) -> BaseColor]
+    };
+    library.assert_compilation(
+        &SurfaceVertexPC::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &graph,
+    );
+    assert!(MaterialLibrary::validate_material_compilation(
+        &SurfaceVertexPC::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &graph,
+    )
+    .is_err());
+
+    let graph = material_graph! {
+        inputs {
+            [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [(invert_color, color: (truncate_vec4, v: [TintColor => vColor])) -> BaseColor]
+    };
+    match library.validate_compilation(
+        &SurfaceVertexPC::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &graph,
+    ) {
+        Err(MaterialError::Baking(_, error)) => match *error {
+            MaterialError::MismatchingConnectionTypes {
+                from_value_type,
+                to_value_type,
+                param,
+                ..
+            } => {
+                assert_eq!(from_value_type, Some(MaterialValueType::Vec3F));
+                assert_eq!(to_value_type, Some(MaterialValueType::Vec4F));
+                assert_eq!(param.as_deref(), Some("color"));
+            }
+            error => panic!("Unexpected error: {:?}", error),
+        },
+        result => panic!("Unexpected result: {:?}", result),
+    }
+}
+
+#[test]
+fn test_material_compile_error() {
+    let graph = material_graph! {
+        inputs {
+            [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [(mul_vec4, a: [TintColor => vColor], b: {vec2(0.5, 0.5)}) -> BaseColor]
+    };
+    let error = MaterialLibrary::validate_material_compilation(
+        &SurfaceVertexPC::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &graph,
+    )
+    .unwrap_err()
+    .compile_error()
+    .unwrap();
+    assert_eq!(error.node_name, "operation mul_vec4");
+    assert_eq!(error.shader_type, MaterialShaderType::Fragment);
+    assert_eq!(error.param.as_deref(), Some("b"));
+    assert_eq!(error.expected_type, Some(MaterialValueType::Vec4F));
+    assert_eq!(error.actual_type, Some(MaterialValueType::Vec2F));
+    assert_eq!(
+        error.to_string(),
+        "Fragment shader node operation mul_vec4 (param: b) expects vec4 but got vec2"
+    );
+}
+
 #[test]
 fn test_material_middlewares() {
     let mut library = MaterialLibrary::default();
@@ -346,3 +489,928 @@ fn test_compound_vertex_type() {
         SurfaceVertexASPT::vertex_layout()
     );
 }
+
+#[test]
+fn test_custom_vertex_attributes() {
+    vertex_type! {
+        #[derive(Debug, Default, Copy, Clone)]
+        pub struct FoliageVertex {
+            pub position: vec3 = position(0, bounds),
+            pub uv2: vec2 = uv2(0),
+            pub wind: float = wind(0),
+        }
+    }
+
+    let layout = FoliageVertex::vertex_layout().unwrap();
+    assert!(layout
+        .attributes()
+        .any(|attribute| attribute.id == "uv2" && attribute.value_type == VertexValueType::Vec2F));
+    assert!(
+        layout
+            .attributes()
+            .any(|attribute| attribute.id == "wind"
+                && attribute.value_type == VertexValueType::Scalar)
+    );
+    let inputs = FoliageVertex::material_graph_inputs().unwrap();
+    let wind = inputs.iter().find(|input| input.name == "wind").unwrap();
+    assert_eq!(wind.data_type, MaterialDataType::Attribute);
+    assert_eq!(wind.shader_type, MaterialShaderType::Vertex);
+    assert_eq!(wind.value_type, MaterialValueType::Scalar);
+    assert_eq!(wind.default_value, Some(MaterialValue::Scalar(0.0)));
+
+    let mut factory = StaticVertexFactory::new(layout.to_owned(), 3, 1, MeshDrawMode::Triangles);
+    factory
+        .vertices(
+            &[FoliageVertex {
+                wind: 0.5,
+                ..Default::default()
+            }; 3],
+            None,
+        )
+        .unwrap();
+
+    let mut graph = MaterialGraph::default();
+    let inputs = graph.add_vertex_inputs(&layout);
+    assert_eq!(inputs.len(), 3);
+    assert!(inputs.contains_key("uv2"));
+    assert!(inputs.contains_key("wind"));
+
+    let graph = material_graph! {
+        inputs {
+            [vertex] in uv2: vec2 = {vec2(0.0, 0.0)};
+            [vertex] in wind: float = {0.0};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [sway = [wind => flat vWind]]
+        [(make_vec4, x: (maskX_vec2, v: [uv2 => vUv2]), y: sway, z: sway, w: {1.0}) -> BaseColor]
+    };
+    let baked = MaterialLibrary::validate_material_compilation(
+        &layout,
+        RenderTargetDescriptor::Main,
+        &surface_flat_domain_graph(),
+        &graph,
+    )
+    .unwrap()
+    .unwrap();
+    assert!(baked.vertex.contains("in vec2 uv2;"));
+    assert!(baked.vertex.contains("flat out float vWind;"));
+    assert!(baked.fragment.contains("flat in float vWind;"));
+}
+
+#[test]
+fn test_immediate_batch_auto_flush() {
+    let mut factory = immediate::SurfaceImmediateFactory::<SurfaceVertexP>::default();
+    for _ in 0..10 {
+        factory.quad(Default::default());
+    }
+    let factory = factory.factory().unwrap();
+    let mut mesh = Mesh::new(factory.layout().to_owned());
+    factory.write_into(&mut mesh).unwrap();
+    assert_eq!(mesh.index_data().len(), 60);
+    assert_eq!(mesh.flush_ranges(0..60), vec![0..60]);
+    mesh.set_draw_index_limit(Some(25));
+    let ranges = mesh.flush_ranges(0..60);
+    assert_eq!(ranges, vec![0..24, 24..48, 48..60]);
+    assert!(ranges.iter().all(|range| range.len() % 3 == 0));
+
+    // every quad uses its own 4 vertices, so 8 vertices fit 2 quads.
+    mesh.set_draw_index_limit(None);
+    mesh.set_draw_vertex_limit(Some(8));
+    let ranges = mesh.flush_ranges(0..60);
+    assert_eq!(ranges, vec![0..12, 12..24, 24..36, 36..48, 48..60]);
+    assert_eq!(mesh.flush_ranges(6..18), vec![6..18]);
+    mesh.set_draw_vertex_limit(Some(2));
+    assert_eq!(mesh.flush_ranges(0..12), vec![0..3, 3..6, 6..9, 9..12]);
+    mesh.set_draw_index_limit(Some(18));
+    mesh.set_draw_vertex_limit(Some(12));
+    assert_eq!(
+        mesh.flush_ranges(0..60),
+        vec![0..18, 18..36, 36..54, 54..60]
+    );
+    mesh.set_draw_vertex_limit(Some(8));
+    assert_eq!(
+        mesh.flush_ranges(0..60),
+        vec![0..12, 12..18, 18..30, 30..36, 36..48, 48..54, 54..60]
+    );
+}
+
+#[test]
+fn test_immediate_batch_sorting() {
+    let materials = [MaterialId::new(), MaterialId::new()];
+    let mut factory = immediate::SurfaceImmediateFactory::<SurfaceVertexP>::default();
+    factory.set_layer_kind(0, immediate::SurfaceImmediateLayerKind::Opaque);
+    for index in [0, 1, 1, 0, 1, 0, 0, 1] {
+        factory.set_state(immediate::SurfaceImmediateState {
+            material: Some(materials[index]),
+            ..Default::default()
+        });
+        factory.quad(Default::default());
+    }
+    factory.set_state(immediate::SurfaceImmediateState {
+        layer: 1,
+        material: Some(materials[0]),
+        ..Default::default()
+    });
+    factory.triangle(Default::default());
+    factory.set_state(immediate::SurfaceImmediateState {
+        layer: 1,
+        material: Some(materials[1]),
+        ..Default::default()
+    });
+    factory.triangle(Default::default());
+    factory.set_state(immediate::SurfaceImmediateState {
+        layer: -1,
+        material: Some(materials[0]),
+        ..Default::default()
+    });
+    factory.triangle(Default::default());
+    assert_eq!(factory.batches().count(), 10);
+
+    factory.sort();
+    let batches = factory
+        .batches()
+        .map(|(state, range)| (state.layer, state.material.unwrap(), range))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        batches,
+        vec![
+            (-1, materials[0], 0..3),
+            (0, materials[0], 3..27),
+            (0, materials[1], 27..51),
+            (1, materials[0], 51..54),
+            (1, materials[1], 54..57),
+        ]
+    );
+    assert_eq!(factory.factory().unwrap().index_count(), 57);
+
+    let texture = ImageId::new();
+    let mut batch = HaImmediateBatch::<SurfaceVertexP>::default();
+    batch.factory.quad(Default::default());
+    assert!(batch.mesh_batches().is_empty());
+    batch.factory.set_state(immediate::SurfaceImmediateState {
+        material: Some(materials[1]),
+        texture: Some(texture),
+        ..Default::default()
+    });
+    batch.factory.triangle(Default::default());
+    let batches = batch.mesh_batches();
+    assert_eq!(batches.len(), 2);
+    assert_eq!(batches[0].range, 0..6);
+    assert_eq!(batches[0].material, None);
+    assert!(batches[0].values.is_empty());
+    assert_eq!(batches[1].range, 6..9);
+    assert_eq!(batches[1].material, Some(materials[1]));
+    assert_eq!(
+        batches[1].values.get("mainImage"),
+        Some(&MaterialValue::sampler_2d(ImageReference::Id(texture)))
+    );
+}
+
+#[test]
+fn test_mesh_bounds() {
+    let layout = SurfaceVertexP::vertex_layout().unwrap();
+    let mut factory = StaticVertexFactory::new(layout.to_owned(), 4, 2, MeshDrawMode::Triangles);
+    factory
+        .vertices(
+            &[
+                SurfaceVertexP {
+                    position: vek::Vec3::new(-2.0, -1.0, 0.0),
+                },
+                SurfaceVertexP {
+                    position: vek::Vec3::new(3.0, -1.0, 0.0),
+                },
+                SurfaceVertexP {
+                    position: vek::Vec3::new(3.0, 4.0, 0.0),
+                },
+                SurfaceVertexP {
+                    position: vek::Vec3::new(-2.0, 4.0, 0.0),
+                },
+            ],
+            None,
+        )
+        .unwrap();
+    let mut mesh = Mesh::new(layout.to_owned());
+    assert!(mesh.bounds().is_none());
+    factory.write_into(&mut mesh).unwrap();
+    let bounds = mesh.bounds().unwrap();
+    assert_eq!(bounds.origin, vec3(0.5, 1.5, 0.0));
+    assert_eq!(bounds.half_extents(), vec3(2.5, 2.5, 0.0));
+    let corners = bounds.box_vertices();
+    assert!(corners.contains(&vec3(-2.0, -1.0, 0.0)));
+    assert!(corners.contains(&vec3(3.0, 4.0, 0.0)));
+
+    let mut factory = StaticVertexFactory::new(layout.to_owned(), 1, 0, MeshDrawMode::Points);
+    factory
+        .vertices(
+            &[SurfaceVertexP {
+                position: vek::Vec3::new(1.0, 2.0, 3.0),
+            }],
+            None,
+        )
+        .unwrap();
+    factory.write_into(&mut mesh).unwrap();
+    let bounds = mesh.bounds().unwrap();
+    assert_eq!(bounds.origin, vec3(1.0, 2.0, 3.0));
+    assert_eq!(bounds.half_extents(), Vec3::zero());
+    assert_eq!(bounds.radius(), 0.0);
+
+    mesh.set_vertex_data(0, vec![]).unwrap();
+    assert!(mesh.bounds().is_none());
+}
+
+#[test]
+fn test_mesh_in_place_updates() {
+    let mut mesh = Mesh::new(SurfaceVertexP::vertex_layout().unwrap());
+    mesh.update_vertices(0, &[0; 48]).unwrap();
+    mesh.update_indices(&[0, 1, 2, 2, 3, 0], MeshDrawMode::Triangles);
+    let vertices = mesh.vertex_allocation(0).unwrap();
+    let indices = mesh.index_allocation();
+    assert_eq!(vertices.capacity, 48);
+    assert_eq!(vertices.length, 48);
+    assert_eq!(indices.capacity, 24);
+    assert_eq!(indices.length, 24);
+
+    mesh.update_vertices(0, &[0; 36]).unwrap();
+    mesh.update_indices(&[0, 1, 2], MeshDrawMode::Triangles);
+    let allocation = mesh.vertex_allocation(0).unwrap();
+    assert_eq!(allocation.generation, vertices.generation);
+    assert_eq!(allocation.capacity, 48);
+    assert_eq!(allocation.length, 36);
+    let allocation = mesh.index_allocation();
+    assert_eq!(allocation.generation, indices.generation);
+    assert_eq!(allocation.capacity, 24);
+    assert_eq!(allocation.length, 12);
+
+    mesh.update_vertices(0, &[0; 96]).unwrap();
+    let allocation = mesh.vertex_allocation(0).unwrap();
+    assert_ne!(allocation.generation, vertices.generation);
+    assert_eq!(allocation.capacity, 96);
+    assert_eq!(allocation.length, 96);
+    assert!(matches!(
+        mesh.update_vertices(0, &[0; 10]),
+        Err(MeshError::InvalidSize(10, 0))
+    ));
+    assert_eq!(mesh.vertex_data(0).unwrap().len(), 96);
+}
+
+#[test]
+fn test_camera_follow() {
+    let mut follow = HaCameraFollow::new("player").smoothing(Some(10.0));
+    let target = Vec3::new(10.0, 5.0, 0.0);
+    let mut position = Vec3::new(0.0, 0.0, 3.0);
+    let mut last_distance = position.distance(target);
+    for _ in 0..60 {
+        position = follow.update(position, target, None, 1.0 / 60.0);
+        let distance = Vec2::from(position).distance(Vec2::from(target));
+        assert!(distance < last_distance);
+        last_distance = distance;
+    }
+    assert!(last_distance < 1.0e-3);
+    assert_eq!(position.z, 3.0);
+
+    let mut follow = HaCameraFollow::new("player").bounds(Some(rect(0.0, 0.0, 20.0, 20.0)));
+    let view_size = Some(Vec2::new(8.0, 6.0));
+    let position = follow.update(
+        Vec3::zero(),
+        Vec3::new(-5.0, 19.0, 0.0),
+        view_size,
+        1.0 / 60.0,
+    );
+    assert_eq!(position, Vec3::new(4.0, 17.0, 0.0));
+}
+
+#[test]
+fn test_camera_shake() {
+    let mut shake = HaCameraShake::new(2.0, 10.0, 1.0).seed(42);
+    assert!(shake.is_active());
+    assert_eq!(shake.current_amplitude(), 2.0);
+    let mut last = shake.current_amplitude();
+    let mut offsets = vec![];
+    for _ in 0..20 {
+        let offset = shake.update(0.05);
+        let amplitude = shake.current_amplitude();
+        assert!(amplitude <= last);
+        assert!(offset.x.abs() <= amplitude + 1.0e-4);
+        assert!(offset.y.abs() <= amplitude + 1.0e-4);
+        assert_eq!(offset.z, 0.0);
+        offsets.push(offset);
+        last = amplitude;
+    }
+    assert!(!shake.is_active());
+    assert_eq!(shake.current_amplitude(), 0.0);
+    assert_eq!(shake.update(0.05), Vec3::zero());
+    assert!(offsets
+        .iter()
+        .any(|offset| offset.x != 0.0 || offset.y != 0.0));
+
+    let mut same = HaCameraShake::new(2.0, 10.0, 1.0).seed(42);
+    let mut other = HaCameraShake::new(2.0, 10.0, 1.0).seed(7);
+    let mut differs = false;
+    for offset in offsets {
+        assert_eq!(same.update(0.05), offset);
+        differs |= other.update(0.05) != offset;
+    }
+    assert!(differs);
+
+    shake.start();
+    assert!(shake.is_active());
+    assert_eq!(shake.current_amplitude(), 2.0);
+}
+
+#[test]
+fn test_tilemap_chunks() {
+    let mut tilemap = HaTileMapInstance::default();
+    tilemap.set_cols(100);
+    tilemap.set_rows(70);
+    tilemap.set_cell_size(vec2(1.0, 1.0));
+    tilemap.set_chunk_size(32);
+    tilemap.set_atlas("atlas");
+    assert_eq!(tilemap.chunks(), (4, 3));
+    assert_eq!(tilemap.chunk_of(31, 32), (0, 1));
+    tilemap.dirty = false;
+
+    assert!(tilemap.set_tile(40, 5, Some("grass".to_owned())));
+    assert_eq!(tilemap.dirty_chunks().collect::<Vec<_>>(), vec![(1, 0)]);
+    assert!(tilemap.is_chunk_dirty((1, 0)));
+    for col in 0..4 {
+        for row in 0..3 {
+            if (col, row) != (1, 0) {
+                assert!(!tilemap.is_chunk_dirty((col, row)));
+            }
+        }
+    }
+    assert!(!tilemap.set_tile(100, 5, Some("grass".to_owned())));
+    assert_eq!(tilemap.dirty_chunks().count(), 1);
+    assert!(tilemap.set_tile(2, 65, Some("water".to_owned())));
+    assert!(tilemap.set_tile(2, 65, None));
+    assert!(tilemap.tile(2, 65).is_none());
+    assert_eq!(tilemap.dirty_chunks().count(), 2);
+    assert!(tilemap.is_chunk_dirty((0, 2)));
+
+    tilemap.set_tile(41, 6, Some("grass".to_owned()));
+    assert_eq!(tilemap.chunk_tiles((1, 0)).count(), 2);
+    assert_eq!(tilemap.chunk_tiles((0, 0)).count(), 0);
+    assert_eq!(tilemap.occupied_chunks().collect::<Vec<_>>(), vec![(1, 0)]);
+    // removed tile gets replaced by last one, which has to stay reachable from its chunk.
+    assert!(tilemap.set_tile(40, 5, None));
+    assert_eq!(tilemap.tile(41, 6).unwrap().atlas_item, "grass");
+    assert_eq!(tilemap.chunk_tiles((1, 0)).count(), 1);
+    assert!(tilemap.set_tile(40, 5, Some("grass".to_owned())));
+    assert_eq!(tilemap.chunk_tiles((1, 0)).count(), 2);
+
+    let mut virtual_images = Resources::<VirtualImage>::default();
+    let mut atlas = VirtualImage::new(VirtualImageSource::Image(ImageId::new()));
+    atlas.register_named_image_uvs("grass", rect(0.0, 0.0, 0.5, 0.5), 0);
+    virtual_images.add_named("atlas".to_owned(), atlas);
+    let factory =
+        SurfaceTileMapFactory::chunk_factory::<SurfaceVertexPT>(&tilemap, (1, 0), &virtual_images)
+            .unwrap();
+    assert_eq!(factory.vertex_count(), 8);
+    assert!(matches!(
+        SurfaceTileMapFactory::chunk_factory::<SurfaceVertexPT>(&tilemap, (0, 0), &virtual_images),
+        Err(MeshError::ZeroSize)
+    ));
+}
+
+#[test]
+fn test_virtual_image_streaming() {
+    // 4x4 tiles of 64x64 pixels, budget fits only two of them.
+    let mut image = VirtualImage::new(VirtualImageSource::Image(ImageId::new()))
+        .with_streaming(VirtualImageStreaming::new(256, 256, 64, 2 * 64 * 64 * 4));
+    assert_eq!(image.streaming().unwrap().slots(), 2);
+    let a = rect(0.0, 0.0, 0.25, 0.25);
+    let b = rect(0.25, 0.0, 0.25, 0.25);
+    let c = rect(0.5, 0.0, 0.25, 0.25);
+
+    let region = image.request_region(a).unwrap();
+    assert_eq!(region.tiles.len(), 1);
+    assert_eq!(region.tiles[0].state, VirtualImageTileState::Requested);
+    assert!(!region.is_resident());
+    assert!(!image.is_region_resident(a));
+    let streaming = image.streaming_mut().unwrap();
+    assert_eq!(streaming.take_pending_tiles(), vec![(0, 0)]);
+    assert_eq!(streaming.mark_tile_resident((0, 0)), Some(0));
+    let region = image.request_region(a).unwrap();
+    assert!(region.is_resident());
+    assert_eq!(region.tiles[0].source_uvs, a);
+    assert_eq!(region.tiles[0].cache_uvs, Some(rect(0.0, 0.0, 0.5, 0.5)));
+
+    image.request_region(b).unwrap();
+    let streaming = image.streaming_mut().unwrap();
+    assert_eq!(streaming.take_pending_tiles(), vec![(1, 0)]);
+    assert_eq!(streaming.mark_tile_resident((1, 0)), Some(1));
+    assert!(image.is_region_resident(rect(0.0, 0.0, 0.5, 0.25)));
+
+    // tile of region B is the least recently requested one, so it gets evicted.
+    image.request_region(a).unwrap();
+    image.request_region(c).unwrap();
+    let streaming = image.streaming_mut().unwrap();
+    assert_eq!(streaming.take_pending_tiles(), vec![(2, 0)]);
+    assert_eq!(streaming.mark_tile_resident((2, 0)), Some(1));
+    assert!(image.is_region_resident(a));
+    assert!(!image.is_region_resident(b));
+    assert!(image.is_region_resident(c));
+    assert_eq!(image.streaming().unwrap().tile_state((1, 0)), None);
+    assert_eq!(image.streaming().unwrap().resident_tiles().count(), 2);
+}
+
+#[test]
+fn test_virtual_image_streaming_system() {
+    // every 64x64 tile of source image is filled with its own value.
+    let mut data = Vec::with_capacity(256 * 256 * 4);
+    for y in 0..256 {
+        for x in 0..256 {
+            let value = (x / 64 + (y / 64) * 4 + 1) as u8;
+            data.extend_from_slice(&[value; 4]);
+        }
+    }
+    let source = Image::new(
+        ImageDescriptor {
+            mode: ImageMode::Image2d,
+            format: ImageFormat::RGBA,
+            mipmap: ImageMipmap::None,
+        },
+        256,
+        256,
+        1,
+        data,
+    )
+    .unwrap();
+    let mut renderer = HaRenderer::new(());
+    let source = renderer.add_image(source).unwrap();
+    let id = renderer.virtual_images.add(
+        VirtualImage::new(VirtualImageSource::Image(source))
+            .with_streaming(VirtualImageStreaming::new(256, 256, 64, 2 * 64 * 64 * 4)),
+    );
+    let mut universe = Universe::default();
+    universe.insert_resource(renderer);
+    let slot_value = |universe: &Universe, slot: usize| {
+        let renderer = universe.resource::<HaRenderer>().unwrap();
+        let streaming = renderer
+            .virtual_images
+            .get(id)
+            .unwrap()
+            .streaming()
+            .unwrap();
+        let cache = renderer.image(streaming.cache_image().unwrap()).unwrap();
+        let (x, y, width, height) = streaming.slot_pixels(slot);
+        let value = cache.data()[(y * cache.width() + x) * 4];
+        for row in y..(y + height) {
+            let from = (row * cache.width() + x) * 4;
+            assert!(cache.data()[from..(from + width * 4)]
+                .iter()
+                .all(|v| *v == value));
+        }
+        value
+    };
+    let request = |universe: &Universe, uvs: Rect| {
+        universe
+            .resource_mut::<HaRenderer>()
+            .unwrap()
+            .virtual_images
+            .get_mut(id)
+            .unwrap()
+            .request_region(uvs)
+            .unwrap();
+    };
+    let a = rect(0.0, 0.0, 0.25, 0.25);
+    let b = rect(0.25, 0.0, 0.25, 0.25);
+    let c = rect(0.5, 0.0, 0.25, 0.25);
+
+    request(&universe, a);
+    ha_virtual_image_streaming_system(&mut universe);
+    {
+        let renderer = universe.resource::<HaRenderer>().unwrap();
+        let image = renderer.virtual_images.get(id).unwrap();
+        assert!(image.is_region_resident(a));
+        let streaming = image.streaming().unwrap();
+        assert!(!streaming.has_pending_tiles());
+        let cache = renderer.image(streaming.cache_image().unwrap()).unwrap();
+        assert_eq!(cache.width(), streaming.cache_size());
+        assert_eq!(cache.height(), streaming.cache_size());
+    }
+    assert_eq!(slot_value(&universe, 0), 1);
+
+    request(&universe, b);
+    ha_virtual_image_streaming_system(&mut universe);
+    assert_eq!(slot_value(&universe, 1), 2);
+
+    // tile of region B is the least recently requested one, so its slot gets reused.
+    request(&universe, a);
+    request(&universe, c);
+    ha_virtual_image_streaming_system(&mut universe);
+    {
+        let renderer = universe.resource::<HaRenderer>().unwrap();
+        let image = renderer.virtual_images.get(id).unwrap();
+        assert!(image.is_region_resident(a));
+        assert!(!image.is_region_resident(b));
+        assert!(image.is_region_resident(c));
+    }
+    assert_eq!(slot_value(&universe, 0), 1);
+    assert_eq!(slot_value(&universe, 1), 3);
+}
+
+#[test]
+fn test_atlas_builder() {
+    let mut builder = AtlasBuilder::new("atlas", 64, 64);
+    let sizes = [
+        (20, 10),
+        (30, 12),
+        (10, 10),
+        (40, 20),
+        (5, 30),
+        (63, 8),
+        (16, 16),
+        (25, 25),
+        (50, 40),
+    ];
+    for (index, (width, height)) in sizes.iter().enumerate() {
+        let rgba = vec![index as u8 + 1; width * height * 4];
+        let region = builder.insert(index, *width, *height, &rgba).unwrap();
+        assert_eq!(region.width, *width);
+        assert_eq!(region.height, *height);
+    }
+    assert!(builder.pages().len() > 1);
+    let regions = builder.regions().map(|(_, r)| *r).collect::<Vec<_>>();
+    assert_eq!(regions.len(), sizes.len());
+    for (index, a) in regions.iter().enumerate() {
+        assert!(a.x + a.width <= 64 && a.y + a.height <= 64);
+        assert!(a.uvs.x >= 0.0 && a.uvs.y >= 0.0);
+        assert!(a.uvs.x + a.uvs.w <= 1.0 && a.uvs.y + a.uvs.h <= 1.0);
+        for b in regions.iter().skip(index + 1) {
+            assert!(!a.overlaps(b));
+        }
+    }
+    let region = builder.region("3").unwrap();
+    let page = &builder.pages()[region.page];
+    let offset = ((region.y + 5) * page.width() + region.x + 5) * 4;
+    assert_eq!(page.data()[offset], 4);
+
+    assert_eq!(
+        builder.insert("3", 1, 1, &[0; 4]),
+        Err(AtlasBuilderError::DuplicateId("3".to_owned()))
+    );
+    assert!(matches!(
+        builder.insert("big", 64, 64, &[0; 64 * 64 * 4]),
+        Err(AtlasBuilderError::TooLarge(..))
+    ));
+    assert!(matches!(
+        builder.insert("bad", 2, 2, &[0; 4]),
+        Err(AtlasBuilderError::InvalidDataSize(..))
+    ));
+}
+
+#[test]
+fn test_render_to_texture() {
+    let scene_target = RenderTargetDescriptor::simple("finalColor")
+        .unwrap()
+        .with_size(
+            RenderTargetSizeValue::Exact {
+                value: 256,
+                level: 0,
+            },
+            RenderTargetSizeValue::Exact {
+                value: 128,
+                level: 0,
+            },
+        );
+    let mut renderer = HaRenderer::new(())
+        .with_stage::<RenderForwardStage>("forward")
+        .with_stage::<RenderPostProcessStage>("postprocess");
+    let screen = renderer
+        .add_pipeline(PipelineSource::Descriptor(
+            PipelineDescriptor::default()
+                .render_target("main", RenderTargetDescriptor::Main)
+                .stage(StageDescriptor::new("postprocess").render_target("main")),
+        ))
+        .unwrap();
+    let scene = renderer
+        .add_pipeline(PipelineSource::Descriptor(
+            PipelineDescriptor::default()
+                .render_target("scene", scene_target.to_owned())
+                .stage(StageDescriptor::new("forward").render_target("scene"))
+                .order(-1),
+        ))
+        .unwrap();
+    assert_eq!(renderer.pipelines_in_order(), vec![scene, screen]);
+
+    let size = |pipeline| {
+        let id = renderer
+            .pipeline(pipeline)
+            .unwrap()
+            .render_targets()
+            .next()
+            .unwrap();
+        renderer
+            .render_targets()
+            .get(id)
+            .unwrap()
+            .preferred_size(800, 600)
+    };
+    assert_eq!(size(scene), (256, 128));
+    assert_eq!(size(screen), (800, 600));
+    assert_eq!(
+        render_target_virtual_image_name("mirror", "scene", "finalColor"),
+        "@render-target/mirror/scene/finalColor"
+    );
+
+    // scene pass renders into offscreen target, then fullscreen quad samples it on main target.
+    MaterialLibrary::assert_material_compilation(
+        &SurfaceVertexP::vertex_layout().unwrap(),
+        scene_target,
+        &surface_flat_domain_graph(),
+        &default_surface_flat_color_material_graph(),
+    );
+    MaterialLibrary::assert_material_compilation(
+        &ScreenSpaceVertex::vertex_layout().unwrap(),
+        RenderTargetDescriptor::Main,
+        &screenspace_domain_graph(),
+        &default_screenspace_texture_material_graph(),
+    );
+}
+
+#[test]
+fn test_render_target_samples() {
+    assert_eq!(supported_render_target_samples(0, 8), 1);
+    assert_eq!(supported_render_target_samples(1, 8), 1);
+    assert_eq!(supported_render_target_samples(2, 8), 2);
+    assert_eq!(supported_render_target_samples(4, 8), 4);
+    assert_eq!(supported_render_target_samples(8, 8), 8);
+    assert_eq!(supported_render_target_samples(8, 4), 4);
+    assert_eq!(supported_render_target_samples(8, 3), 2);
+    assert_eq!(supported_render_target_samples(4, 0), 1);
+    assert_eq!(supported_render_target_samples(6, 16), 4);
+
+    let buffers = TargetBuffers::default()
+        .with_color(TargetBuffer::color("finalColor"))
+        .unwrap()
+        .with_samples(4);
+    assert_eq!(buffers.samples, 4);
+    let target = RenderTarget::new(
+        buffers,
+        RenderTargetSizeValue::default(),
+        RenderTargetSizeValue::default(),
+    );
+    // effective samples get resolved once target gets its context resources.
+    assert_eq!(target.samples(), 1);
+
+    let mut renderer = HaRenderer::new(()).with_main_render_target_samples(4);
+    let pipeline = renderer
+        .add_pipeline(PipelineSource::Descriptor(
+            PipelineDescriptor::default().render_target("main", RenderTargetDescriptor::Main),
+        ))
+        .unwrap();
+    let main = renderer.pipeline(pipeline).unwrap().render_targets["main"].1;
+    let target = renderer.render_target(main).unwrap();
+    assert!(target.is_backbuffer());
+    assert_eq!(target.buffers().samples, 4);
+    renderer.set_main_render_target_samples(2);
+    assert_eq!(renderer.main_render_target_samples(), 2);
+    assert_eq!(renderer.render_target(main).unwrap().buffers().samples, 2);
+}
+
+#[test]
+fn test_bloom_pipeline() {
+    let settings = BloomSettings {
+        threshold: 0.5,
+        intensity: 2.0,
+        blur_passes: 3,
+    };
+    let mut renderer = HaRenderer::new(())
+        .with_stage::<RenderForwardStage>("forward")
+        .with_stage::<RenderPostProcessStage>("postprocess");
+    let id = renderer
+        .add_pipeline(PipelineSource::Descriptor(
+            settings.pipeline(StageDescriptor::new("forward"), "postprocess"),
+        ))
+        .unwrap();
+    let mut postprocess = settings.postprocess("camera");
+    // bright pass, horizontal and vertical blur passes per mip level and composite.
+    let passes = 1 + BloomSettings::MIP_LEVELS * settings.blur_passes * 2 + 1;
+    assert_eq!(postprocess.stages.len(), passes);
+    let pipeline = renderer.pipeline(id).unwrap();
+    assert_eq!(pipeline.stages_count(), passes + 1);
+
+    let size = |name: &str| {
+        let (_, id) = pipeline.render_targets.get(name).unwrap();
+        renderer
+            .render_targets()
+            .get(*id)
+            .unwrap()
+            .preferred_size(800, 600)
+    };
+    assert_eq!(size(BloomSettings::SCENE_RENDER_TARGET), (800, 600));
+    assert_eq!(
+        size(&BloomSettings::render_target_name(0, false)),
+        (400, 300)
+    );
+    assert_eq!(
+        size(&BloomSettings::render_target_name(1, true)),
+        (200, 150)
+    );
+
+    let bright = &postprocess.stages[0];
+    assert_eq!(
+        bright.values.get("mainImage"),
+        Some(&MaterialValue::Sampler2d {
+            reference: ImageReference::VirtualAsset(render_target_virtual_image_name(
+                "camera",
+                BloomSettings::SCENE_RENDER_TARGET,
+                BloomSettings::COLOR_BUFFER,
+            )),
+            filtering: ImageFiltering::Linear,
+        })
+    );
+    assert_eq!(
+        bright.values.get("bloomThreshold"),
+        Some(&MaterialValue::Scalar(0.5))
+    );
+    let composite = postprocess.stages.last().unwrap();
+    assert_eq!(
+        composite.values.get("bloomIntensity"),
+        Some(&MaterialValue::Scalar(2.0))
+    );
+
+    let settings = BloomSettings {
+        threshold: 0.25,
+        intensity: 0.75,
+        ..settings
+    };
+    settings.apply(&mut postprocess);
+    assert_eq!(
+        postprocess.stages[0].values.get("bloomThreshold"),
+        Some(&MaterialValue::Scalar(0.25))
+    );
+    assert_eq!(
+        postprocess
+            .stages
+            .last()
+            .unwrap()
+            .values
+            .get("bloomIntensity"),
+        Some(&MaterialValue::Scalar(0.75))
+    );
+    assert!(!postprocess.stages[1].values.contains_key("bloomThreshold"));
+}
+
+#[test]
+fn test_material_reload() {
+    let mut library = MaterialLibrary::default();
+    library.add_domain("forward".to_owned(), surface_flat_domain_graph());
+    let signatures = std::iter::once(material_signature! {
+        mesh("position", "textureCoord", "color")
+        render_target("finalColor")
+        domain("forward")
+    })
+    .collect();
+    let fragment = |material: &Material| {
+        material
+            .detailed_info()
+            .versions
+            .values()
+            .next()
+            .unwrap()
+            .fragment
+            .to_owned()
+    };
+    let broken = material_graph! {
+        inputs {
+            [vertex] inout TintColor: vec4 = {vec4(1.0, 1.0, 1.0, 1.0)};
+        }
+
+        outputs {
+            [fragment] inout BaseColor: vec4;
+        }
+
+        [(doesNotExist, v: [TintColor => vColor]) -> BaseColor]
+    };
+
+    let mut material = Material::new_graph(default_surface_flat_color_material_graph());
+    material
+        .reload(
+            None,
+            default_surface_flat_color_material_graph(),
+            &signatures,
+            &library,
+            true,
+        )
+        .unwrap();
+    assert!(!fragment(&material).contains("mainImage"));
+    material.default_values.insert(
+        "mainImage".to_owned(),
+        MaterialValue::Sampler2d {
+            reference: Default::default(),
+            filtering: Default::default(),
+        },
+    );
+
+    material
+        .reload(
+            None,
+            default_surface_flat_texture_2d_material_graph(),
+            &signatures,
+            &library,
+            true,
+        )
+        .unwrap();
+    let compiled = fragment(&material);
+    assert!(compiled.contains("mainImage"));
+    assert!(material.default_values.contains_key("mainImage"));
+
+    assert!(material
+        .reload(None, broken.to_owned(), &signatures, &library, true)
+        .is_err());
+    assert_eq!(fragment(&material), compiled);
+    assert!(material
+        .graph()
+        .unwrap()
+        .inputs()
+        .any(|(_, input)| input.name == "mainImage"));
+
+    let id = MaterialId::new();
+    assert!(library.reload(id, broken).is_err());
+    assert!(!library.is_reload_pending(id));
+    library
+        .reload(id, default_surface_flat_texture_2d_material_graph())
+        .unwrap();
+    assert!(library.is_reload_pending(id));
+    assert_eq!(library.take_reloads().len(), 1);
+    assert!(!library.is_reload_pending(id));
+}
+
+#[test]
+fn test_frustum_culling() {
+    let projection = HaCameraProjection::Orthographic(HaCameraOrthographic {
+        centered: true,
+        ..Default::default()
+    });
+    let camera = HaTransform::translation(vec3(100.0, 0.0, 0.0));
+    let info = StageProcessInfo {
+        x: 0,
+        y: 0,
+        width: 800,
+        height: 600,
+        transform_matrix: camera.local_matrix(),
+        view_matrix: camera.inverse_local_matrix(),
+        projection_matrix: projection.matrix(vec2(800.0, 600.0)),
+        material_render_target_signature: MaterialRenderTargetSignature::new(
+            &RenderTarget::main().unwrap(),
+        ),
+        domain: None,
+        filters: Default::default(),
+    };
+    // camera sees world area from (-300, -300) to (500, 300).
+    let mesh_bounds = BoundsVolume::from_box(Default::default(), vec3(25.0, 25.0, 0.0));
+    let bounds = |position| {
+        mesh_bounds
+            .transformed(HaTransform::translation(position).local_matrix())
+            .unwrap()
+    };
+    let inside = bounds(vec3(0.0, 0.0, 0.0));
+    let outside = bounds(vec3(-400.0, 0.0, 0.0));
+    let straddling = bounds(vec3(500.0, 300.0, 0.0));
+
+    let mut stats = HaRenderForwardStageCullingStats::default();
+    assert!(stats.test(&info, &inside));
+    assert!(!stats.test(&info, &outside));
+    assert!(stats.test(&info, &straddling));
+    assert_eq!(stats.tested, 3);
+    assert_eq!(stats.culled, 1);
+}
+
+#[test]
+fn test_debug_draw() {
+    let lines = |factory: &GizmoFactory| factory.factory().unwrap().into_inner().2.len() / 2;
+
+    let mut debug_draw = DebugDraw {
+        circle_segments: 8,
+        ..Default::default()
+    };
+    let mut world = GizmoFactory::default();
+    let mut screen = GizmoFactory::default();
+    debug_draw.line(Vec3::zero(), Vec3::one(), Rgba::red());
+    debug_draw.rect(Vec3::zero(), Vec3::one(), Rgba::green());
+    debug_draw
+        .circle(Vec3::zero(), 10.0, Rgba::blue())
+        .screen_space();
+    debug_draw
+        .text(Vec3::zero(), "T", Rgba::white())
+        .screen_space();
+    assert_eq!(debug_draw.len(), 4);
+    assert_eq!(debug_draw.primitives()[2].space, DebugDrawSpace::Screen);
+
+    debug_draw.flush(&mut world, &mut screen);
+    assert!(debug_draw.is_empty());
+    assert_eq!(lines(&world), 1 + 4);
+    assert_eq!(lines(&screen), 8 + 2);
+
+    world.clear();
+    screen.clear();
+    debug_draw.flush(&mut world, &mut screen);
+    assert!(world.is_empty());
+    assert!(screen.is_empty());
+}
@@ -0,0 +1,22 @@
+use input::gamepad::{GamepadInputDevice, GamepadSource, GamepadState};
+
+/// Gamepad input device for desktop platform.
+pub type DesktopGamepadInputDevice = GamepadInputDevice<DesktopGamepadSource>;
+
+/// Desktop gamepads source. Native gamepads are not supported yet, so it never reports any
+/// connected gamepad - games can still register it to keep the same input mappings on all
+/// platforms.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DesktopGamepadSource;
+
+impl DesktopGamepadSource {
+    pub fn device() -> DesktopGamepadInputDevice {
+        GamepadInputDevice::new(Self)
+    }
+}
+
+impl GamepadSource for DesktopGamepadSource {
+    fn poll(&mut self) -> Vec<Option<GamepadState>> {
+        vec![]
+    }
+}
@@ -1,7 +1,10 @@
 use backend::resource::DesktopAppEvents;
 use core::{ecs::Universe, Scalar};
-use glutin::event::{ElementState, MouseButton, WindowEvent};
-use input::device::InputDevice;
+use glutin::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use input::{
+    device::InputDevice,
+    wheel::{MouseWheel, WheelDeltaMode, WHEEL_LINES_PER_NOTCH},
+};
 use std::any::Any;
 
 #[derive(Default)]
@@ -10,6 +13,7 @@ pub struct DesktopMouseInputDevice {
     left_button: bool,
     right_button: bool,
     middle_button: bool,
+    wheel: MouseWheel,
 }
 
 impl InputDevice for DesktopMouseInputDevice {
@@ -47,15 +51,35 @@ impl InputDevice for DesktopMouseInputDevice {
                     self.position.0 = position.x as Scalar;
                     self.position.1 = position.y as Scalar;
                 }
+                // NOTE: glutin reports positive values when scrolling up, browsers do the opposite.
+                WindowEvent::MouseWheel { delta, .. } => match delta {
+                    MouseScrollDelta::LineDelta(x, y) => {
+                        self.wheel.feed(
+                            -x as Scalar * WHEEL_LINES_PER_NOTCH,
+                            -y as Scalar * WHEEL_LINES_PER_NOTCH,
+                            WheelDeltaMode::Line,
+                        );
+                    }
+                    MouseScrollDelta::PixelDelta(position) => {
+                        self.wheel.feed(
+                            -position.x as Scalar,
+                            -position.y as Scalar,
+                            WheelDeltaMode::Pixel,
+                        );
+                    }
+                },
                 _ => {}
             }
         }
+        self.wheel.advance();
     }
 
     fn query_axis(&self, name: &str) -> Option<Scalar> {
         match name {
             "x" => Some(self.position.0),
             "y" => Some(self.position.1),
+            "wheel" => Some(self.wheel.delta_y()),
+            "wheel-x" => Some(self.wheel.delta_x()),
             _ => None,
         }
     }
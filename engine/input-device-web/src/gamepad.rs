@@ -0,0 +1,55 @@
+use core::Scalar;
+use input::gamepad::{GamepadInputDevice, GamepadSource, GamepadState};
+use wasm_bindgen::JsCast;
+use web_sys::*;
+
+/// Gamepad input device backed by browser Gamepad API.
+pub type WebGamepadInputDevice = GamepadInputDevice<WebGamepadSource>;
+
+/// Reads gamepads states from browser Gamepad API. Gamepads that do not report standard
+/// mapping still get their buttons and axes in browser order.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct WebGamepadSource;
+
+impl WebGamepadSource {
+    pub fn device() -> WebGamepadInputDevice {
+        GamepadInputDevice::new(Self)
+    }
+}
+
+impl GamepadSource for WebGamepadSource {
+    fn poll(&mut self) -> Vec<Option<GamepadState>> {
+        let gamepads = match window().and_then(|window| window.navigator().get_gamepads().ok()) {
+            Some(gamepads) => gamepads,
+            None => return vec![],
+        };
+        let mut result = vec![];
+        for gamepad in gamepads.iter() {
+            let gamepad = match gamepad.dyn_into::<Gamepad>() {
+                Ok(gamepad) if gamepad.connected() => gamepad,
+                _ => continue,
+            };
+            let index = gamepad.index() as usize;
+            if result.len() <= index {
+                result.resize(index + 1, None);
+            }
+            let buttons = gamepad
+                .buttons()
+                .iter()
+                .map(|button| {
+                    button
+                        .dyn_into::<GamepadButton>()
+                        .map(|button| button.value() as Scalar)
+                        .unwrap_or(0.0)
+                })
+                .collect();
+            let axes = gamepad
+                .axes()
+                .iter()
+                .map(|axis| axis.as_f64().unwrap_or(0.0) as Scalar)
+                .collect();
+            result[index] = Some(GamepadState { buttons, axes });
+        }
+        result
+    }
+}
@@ -2,11 +2,12 @@ extern crate oxygengine_backend_web as backend;
 extern crate oxygengine_core as core;
 extern crate oxygengine_input as input;
 
+pub mod gamepad;
 pub mod keyboard;
 pub mod mouse;
 pub mod touch;
 pub mod utils;
 
 pub mod prelude {
-    pub use crate::{keyboard::*, mouse::*, touch::*, utils::*};
+    pub use crate::{gamepad::*, keyboard::*, mouse::*, touch::*, utils::*};
 }
@@ -1,7 +1,14 @@
 use backend::closure::WebClosure;
 use core::{ecs::Universe, Scalar};
-use input::device::InputDevice;
-use std::{any::Any, cell::Cell, rc::Rc};
+use input::{
+    device::InputDevice,
+    wheel::{MouseWheel, WheelDeltaMode},
+};
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 use wasm_bindgen::{prelude::*, JsCast};
 use web_sys::*;
 
@@ -11,9 +18,11 @@ pub struct WebMouseInputDevice {
     left_button: Rc<Cell<bool>>,
     right_button: Rc<Cell<bool>>,
     middle_button: Rc<Cell<bool>>,
+    wheel: Rc<RefCell<MouseWheel>>,
     mouse_down_closure: WebClosure,
     mouse_up_closure: WebClosure,
     mouse_move_closure: WebClosure,
+    wheel_closure: WebClosure,
 }
 
 unsafe impl Send for WebMouseInputDevice {}
@@ -27,9 +36,11 @@ impl WebMouseInputDevice {
             left_button: Default::default(),
             right_button: Default::default(),
             middle_button: Default::default(),
+            wheel: Default::default(),
             mouse_down_closure: Default::default(),
             mouse_up_closure: Default::default(),
             mouse_move_closure: Default::default(),
+            wheel_closure: Default::default(),
         }
     }
 }
@@ -80,20 +91,39 @@ impl InputDevice for WebMouseInputDevice {
                 .unwrap();
             self.mouse_move_closure = WebClosure::acquire(closure);
         }
+        {
+            let wheel = self.wheel.clone();
+            let closure = Closure::wrap(Box::new(move |event: WheelEvent| {
+                wheel.borrow_mut().feed(
+                    event.delta_x() as Scalar,
+                    event.delta_y() as Scalar,
+                    WheelDeltaMode::from_dom(event.delta_mode()),
+                );
+            }) as Box<dyn FnMut(_)>);
+            self.element
+                .add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())
+                .unwrap();
+            self.wheel_closure = WebClosure::acquire(closure);
+        }
     }
 
     fn on_unregister(&mut self) {
         self.mouse_down_closure.release();
         self.mouse_up_closure.release();
         self.mouse_move_closure.release();
+        self.wheel_closure.release();
     }
 
-    fn process(&mut self, _: &mut Universe) {}
+    fn process(&mut self, _: &mut Universe) {
+        self.wheel.borrow_mut().advance();
+    }
 
     fn query_axis(&self, name: &str) -> Option<Scalar> {
         match name {
             "x" => Some(self.position.get().0),
             "y" => Some(self.position.get().1),
+            "wheel" => Some(self.wheel.borrow().delta_y()),
+            "wheel-x" => Some(self.wheel.borrow().delta_x()),
             _ => None,
         }
     }
@@ -0,0 +1,236 @@
+use crate::{device::InputDevice, resources::controller::InputController};
+use core::{ecs::Universe, Scalar};
+use std::any::Any;
+
+/// Button names in order of standard gamepad layout (same as web Gamepad API standard mapping).
+pub const GAMEPAD_BUTTONS: [&str; 17] = [
+    "a", "b", "x", "y", "lb", "rb", "lt", "rt", "select", "start", "ls", "rs", "up", "down",
+    "left", "right", "home",
+];
+
+/// Stick axis names in order of standard gamepad layout.
+pub const GAMEPAD_STICKS: [&str; 4] = ["leftx", "lefty", "rightx", "righty"];
+
+/// Analog buttons that are also reported as axes: (axis name, button index).
+pub const GAMEPAD_TRIGGERS: [(&str, usize); 2] = [("lt", 6), ("rt", 7)];
+
+/// Raw state of single gamepad, in standard layout order.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct GamepadState {
+    /// Button values in range `[0; 1]`.
+    pub buttons: Vec<Scalar>,
+    /// Stick axes values in range `[-1; 1]`.
+    pub axes: Vec<Scalar>,
+}
+
+impl GamepadState {
+    pub fn button(&self, index: usize) -> Scalar {
+        self.buttons.get(index).copied().unwrap_or(0.0)
+    }
+
+    pub fn axis(&self, index: usize) -> Scalar {
+        self.axes.get(index).copied().unwrap_or(0.0)
+    }
+}
+
+/// Platform specific provider of connected gamepads states.
+pub trait GamepadSource: Send + Sync {
+    /// Returns states of gamepads indexed by device index - `None` means no gamepad is
+    /// connected at that index.
+    fn poll(&mut self) -> Vec<Option<GamepadState>>;
+}
+
+/// Input device that exposes all connected gamepads.
+///
+/// Axes and triggers are addressed by gamepad index and name, for example: `0-leftx` or `1-a`.
+/// Use `map_gamepad` to map them into `gamepad<index>-<name>` controller names.
+pub struct GamepadInputDevice<S>
+where
+    S: GamepadSource,
+{
+    source: S,
+    /// Axes values with magnitude below this threshold are reported as 0 and the rest gets
+    /// rescaled to keep full range.
+    pub deadzone: Scalar,
+    /// Button values above this threshold are reported as pressed triggers.
+    pub press_threshold: Scalar,
+    gamepads: Vec<Option<GamepadState>>,
+}
+
+impl<S> GamepadInputDevice<S>
+where
+    S: GamepadSource,
+{
+    pub fn new(source: S) -> Self {
+        Self {
+            source,
+            deadzone: 0.15,
+            press_threshold: 0.5,
+            gamepads: vec![],
+        }
+    }
+
+    pub fn with_deadzone(mut self, deadzone: Scalar) -> Self {
+        self.deadzone = deadzone;
+        self
+    }
+
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    pub fn gamepad(&self, index: usize) -> Option<&GamepadState> {
+        self.gamepads.get(index)?.as_ref()
+    }
+
+    pub fn connected_count(&self) -> usize {
+        self.gamepads
+            .iter()
+            .filter(|gamepad| gamepad.is_some())
+            .count()
+    }
+
+    fn filter_deadzone(&self, value: Scalar) -> Scalar {
+        let deadzone = self.deadzone.max(0.0).min(1.0);
+        let magnitude = value.abs();
+        if magnitude <= deadzone || deadzone >= 1.0 {
+            0.0
+        } else {
+            value.signum() * ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0)
+        }
+    }
+}
+
+/// Splits `<index>-<name>` query into gamepad index and name.
+fn parse_name(name: &str) -> Option<(usize, &str)> {
+    let (index, name) = name.split_once('-')?;
+    Some((index.parse().ok()?, name))
+}
+
+impl<S> InputDevice for GamepadInputDevice<S>
+where
+    S: GamepadSource + 'static,
+{
+    fn name(&self) -> &str {
+        "gamepad"
+    }
+
+    fn process(&mut self, _: &mut Universe) {
+        self.gamepads = self.source.poll();
+    }
+
+    fn query_axis(&self, name: &str) -> Option<Scalar> {
+        let (index, name) = parse_name(name)?;
+        let gamepad = self.gamepad(index)?;
+        if let Some(axis) = GAMEPAD_STICKS.iter().position(|n| *n == name) {
+            return Some(self.filter_deadzone(gamepad.axis(axis)));
+        }
+        let (_, button) = GAMEPAD_TRIGGERS.iter().find(|(n, _)| *n == name)?;
+        Some(self.filter_deadzone(gamepad.button(*button)))
+    }
+
+    fn query_trigger(&self, name: &str) -> Option<bool> {
+        let (index, name) = parse_name(name)?;
+        let button = GAMEPAD_BUTTONS.iter().position(|n| *n == name)?;
+        let value = self
+            .gamepad(index)
+            .map(|gamepad| gamepad.button(button))
+            .unwrap_or(0.0);
+        Some(value > self.press_threshold)
+    }
+
+    fn query_text(&self) -> Option<String> {
+        None
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// Maps all standard axes and buttons of gamepad with given index into controller names like
+/// `gamepad0-leftx` or `gamepad0-a`.
+pub fn map_gamepad(input: &mut InputController, index: usize) {
+    for name in GAMEPAD_STICKS
+        .iter()
+        .chain(GAMEPAD_TRIGGERS.iter().map(|(name, _)| name))
+    {
+        input.map_axis(
+            &format!("gamepad{}-{}", index, name),
+            "gamepad",
+            &format!("{}-{}", index, name),
+        );
+    }
+    for name in GAMEPAD_BUTTONS {
+        input.map_trigger(
+            &format!("gamepad{}-{}", index, name),
+            "gamepad",
+            &format!("{}-{}", index, name),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::controller::TriggerState;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default, Clone)]
+    struct FakeGamepadSource(Arc<Mutex<Vec<Option<GamepadState>>>>);
+
+    impl GamepadSource for FakeGamepadSource {
+        fn poll(&mut self) -> Vec<Option<GamepadState>> {
+            self.0.lock().unwrap().clone()
+        }
+    }
+
+    #[test]
+    fn test_gamepad_mapping() {
+        let source = FakeGamepadSource::default();
+        let mut input = InputController::default();
+        input.register(GamepadInputDevice::new(source.clone()).with_deadzone(0.2));
+        map_gamepad(&mut input, 0);
+        map_gamepad(&mut input, 1);
+        let mut universe = Universe::default();
+
+        let mut buttons = vec![0.0; 17];
+        buttons[0] = 1.0;
+        buttons[7] = 0.6;
+        *source.0.lock().unwrap() = vec![
+            None,
+            Some(GamepadState {
+                buttons,
+                axes: vec![0.1, -0.6, 1.0, 0.0],
+            }),
+        ];
+        input.process(&mut universe);
+        assert_eq!(input.axis("gamepad0-leftx"), None);
+        assert_eq!(input.axis_or_default("gamepad1-leftx"), 0.0);
+        assert!((input.axis_or_default("gamepad1-lefty") + 0.5).abs() < 1.0e-6);
+        assert_eq!(input.axis_or_default("gamepad1-rightx"), 1.0);
+        assert!((input.axis_or_default("gamepad1-rt") - 0.5).abs() < 1.0e-6);
+        assert_eq!(
+            input.trigger_or_default("gamepad1-a"),
+            TriggerState::Pressed
+        );
+        assert_eq!(
+            input.trigger_or_default("gamepad1-rt"),
+            TriggerState::Pressed
+        );
+        assert_eq!(input.trigger_or_default("gamepad1-b"), TriggerState::Idle);
+        assert_eq!(input.trigger_or_default("gamepad0-a"), TriggerState::Idle);
+
+        *source.0.lock().unwrap() = vec![None, Some(GamepadState::default())];
+        input.process(&mut universe);
+        assert_eq!(input.axis_or_default("gamepad1-lefty"), 0.0);
+        assert_eq!(
+            input.trigger_or_default("gamepad1-a"),
+            TriggerState::Released
+        );
+    }
+}
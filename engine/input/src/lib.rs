@@ -2,15 +2,19 @@ extern crate oxygengine_core as core;
 
 pub mod component;
 pub mod device;
+pub mod gamepad;
 pub mod resources;
 pub mod system;
+pub mod wheel;
 
 pub mod prelude {
     pub use crate::{
         component::*,
         device::*,
-        resources::{controller::*, stack::*},
+        gamepad::*,
+        resources::{bindings::*, controller::*, stack::*},
         system::*,
+        wheel::*,
     };
 }
 
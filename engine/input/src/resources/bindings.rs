@@ -0,0 +1,169 @@
+use core::Scalar;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Maps logical actions into physical controller inputs, so players can remap controls.
+/// Physical inputs are controller trigger and axis names (for example `mouse-action` or
+/// `gamepad0-leftx`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputBindings {
+    /// {action name: [physical trigger name]}
+    #[serde(default)]
+    pub triggers: HashMap<String, HashSet<String>>,
+    /// {action name: {physical axis name: scale}}
+    #[serde(default)]
+    pub axes: HashMap<String, HashMap<String, Scalar>>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let mut result = Self::empty();
+        result.bind_trigger("action", "mouse-action");
+        result.bind_trigger("action", "gamepad0-a");
+        result.bind_axis("move-x", "move-right", 1.0);
+        result.bind_axis("move-x", "move-left", -1.0);
+        result.bind_axis("move-x", "gamepad0-leftx", 1.0);
+        result.bind_axis("move-y", "move-down", 1.0);
+        result.bind_axis("move-y", "move-up", -1.0);
+        result.bind_axis("move-y", "gamepad0-lefty", 1.0);
+        result
+    }
+}
+
+impl InputBindings {
+    pub fn empty() -> Self {
+        Self {
+            triggers: Default::default(),
+            axes: Default::default(),
+        }
+    }
+
+    /// Adds physical trigger to action trigger bindings.
+    pub fn bind_trigger(&mut self, action: &str, physical: &str) {
+        self.triggers
+            .entry(action.to_owned())
+            .or_default()
+            .insert(physical.to_owned());
+    }
+
+    /// Adds scaled physical axis to action axis bindings.
+    pub fn bind_axis(&mut self, action: &str, physical: &str, scale: Scalar) {
+        self.axes
+            .entry(action.to_owned())
+            .or_default()
+            .insert(physical.to_owned(), scale);
+    }
+
+    /// Replaces all physical triggers bound to action with given one.
+    pub fn rebind(&mut self, action: &str, physical: &str) {
+        let mut bindings = HashSet::with_capacity(1);
+        bindings.insert(physical.to_owned());
+        self.triggers.insert(action.to_owned(), bindings);
+    }
+
+    /// Replaces all physical axes bound to action with given one.
+    pub fn rebind_axis(&mut self, action: &str, physical: &str, scale: Scalar) {
+        let mut bindings = HashMap::with_capacity(1);
+        bindings.insert(physical.to_owned(), scale);
+        self.axes.insert(action.to_owned(), bindings);
+    }
+
+    pub fn unbind(&mut self, action: &str) {
+        self.triggers.remove(action);
+        self.axes.remove(action);
+    }
+
+    pub fn trigger_bindings(&self, action: &str) -> impl Iterator<Item = &str> {
+        self.triggers
+            .get(action)
+            .into_iter()
+            .flat_map(|bindings| bindings.iter().map(|name| name.as_str()))
+    }
+
+    pub fn axis_bindings(&self, action: &str) -> impl Iterator<Item = (&str, Scalar)> {
+        self.axes
+            .get(action)
+            .into_iter()
+            .flat_map(|bindings| bindings.iter().map(|(name, scale)| (name.as_str(), *scale)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{device::InputDevice, resources::controller::*};
+    use core::ecs::Universe;
+    use std::any::Any;
+
+    struct FakeKeyboard(HashSet<String>);
+
+    impl InputDevice for FakeKeyboard {
+        fn name(&self) -> &str {
+            "keyboard"
+        }
+
+        fn process(&mut self, _: &mut Universe) {}
+
+        fn query_axis(&self, name: &str) -> Option<Scalar> {
+            Some(if self.0.contains(name) { 1.0 } else { 0.0 })
+        }
+
+        fn query_trigger(&self, name: &str) -> Option<bool> {
+            Some(self.0.contains(name))
+        }
+
+        fn query_text(&self) -> Option<String> {
+            None
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_input_bindings() {
+        let bindings = toml::from_str::<InputBindings>(
+            r#"
+            [triggers]
+            jump = ["key-space"]
+
+            [axes.move-x]
+            key-d = 1.0
+            key-a = -1.0
+            "#,
+        )
+        .unwrap();
+        let mut input = InputController::default();
+        let mut keys = HashSet::new();
+        keys.insert("KeyJ".to_owned());
+        keys.insert("KeyA".to_owned());
+        input.register(FakeKeyboard(keys));
+        input.map_config(
+            toml::from_str(
+                r#"
+                [triggers.keyboard]
+                key-space = "Space"
+                key-j = "KeyJ"
+
+                [axes.keyboard]
+                key-a = "KeyA"
+                key-d = "KeyD"
+                "#,
+            )
+            .unwrap(),
+        );
+        input.set_bindings(bindings);
+        let mut universe = Universe::default();
+
+        input.process(&mut universe);
+        assert_eq!(input.action_trigger("jump"), TriggerState::Idle);
+        assert_eq!(input.action_axis("move-x"), -1.0);
+        assert_eq!(input.action_trigger("missing"), TriggerState::Idle);
+        assert_eq!(input.action_axis("missing"), 0.0);
+
+        input.bindings_mut().rebind("jump", "key-j");
+        input.process(&mut universe);
+        assert_eq!(input.action_trigger("jump"), TriggerState::Hold);
+    }
+}
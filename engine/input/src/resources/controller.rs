@@ -1,4 +1,4 @@
-use crate::device::InputDevice;
+use crate::{device::InputDevice, resources::bindings::InputBindings};
 use core::{ecs::Universe, Scalar};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -103,9 +103,12 @@ pub struct InputController {
     devices: HashMap<String, Box<dyn InputDevice>>,
     mapping_axes: HashMap<String, (String, String)>,
     mapping_triggers: HashMap<String, (String, String)>,
+    /// {chord name: [trigger name]}
+    chords: HashMap<String, Vec<String>>,
     axes: HashMap<String, Scalar>,
     triggers: HashMap<String, TriggerState>,
     text: String,
+    bindings: InputBindings,
 }
 
 impl InputController {
@@ -189,6 +192,58 @@ impl InputController {
         self.mapping_triggers.remove(name);
     }
 
+    pub fn bindings(&self) -> &InputBindings {
+        &self.bindings
+    }
+
+    pub fn bindings_mut(&mut self) -> &mut InputBindings {
+        &mut self.bindings
+    }
+
+    pub fn set_bindings(&mut self, bindings: InputBindings) {
+        self.bindings = bindings;
+    }
+
+    /// Resolves action trigger through bindings - when many physical triggers are bound, the
+    /// one with highest priority wins.
+    pub fn action_trigger(&self, action: &str) -> TriggerState {
+        self.bindings
+            .trigger_bindings(action)
+            .map(|name| self.trigger_or_default(name))
+            .max_by(|a, b| a.priority().cmp(&b.priority()))
+            .unwrap_or(TriggerState::Idle)
+    }
+
+    /// Resolves action axis through bindings - when many physical axes are bound, the scaled
+    /// value with highest magnitude wins.
+    pub fn action_axis(&self, action: &str) -> Scalar {
+        self.bindings
+            .axis_bindings(action)
+            .map(|(name, scale)| self.axis_or_default(name) * scale)
+            .fold(0.0, |a, b| if b.abs() > a.abs() { b } else { a })
+    }
+
+    /// Registers composite trigger that is on only while all given triggers are on, so it gets
+    /// pressed when the last of them completes the chord and released when any of them gets
+    /// released. When chord keys are part of another satisfied chord (like `Ctrl+S` inside
+    /// `Ctrl+Shift+S`), only the bigger chord is on and the smaller one stays off until one of
+    /// its keys gets pressed again.
+    pub fn register_chord(&mut self, name: &str, triggers: &[&str]) {
+        self.chords.insert(
+            name.to_owned(),
+            triggers.iter().map(|name| name.to_string()).collect(),
+        );
+    }
+
+    pub fn unregister_chord(&mut self, name: &str) {
+        self.chords.remove(name);
+        self.triggers.remove(name);
+    }
+
+    pub fn chords(&self) -> impl Iterator<Item = (&str, &[String])> {
+        self.chords.iter().map(|(k, v)| (k.as_str(), v.as_slice()))
+    }
+
     pub fn axes(&self) -> impl Iterator<Item = (&str, Scalar)> {
         self.axes.iter().map(|(k, v)| (k.as_str(), *v))
     }
@@ -342,5 +397,157 @@ impl InputController {
                 }
             }
         }
+        let satisfied = self
+            .chords
+            .iter()
+            .filter(|(_, names)| {
+                !names.is_empty()
+                    && names
+                        .iter()
+                        .all(|name| self.trigger_or_default(name).is_on())
+            })
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>();
+        let chords = self
+            .chords
+            .iter()
+            .map(|(name, names)| {
+                let prev = self.triggers.get(name).unwrap_or(&TriggerState::Idle);
+                // chord that was off turns on only when one of its keys gets newly pressed, so
+                // releasing extra key of bigger chord does not trigger chord hidden inside it.
+                let on = satisfied.contains(&name.as_str())
+                    && !satisfied.iter().any(|other| {
+                        let others = &self.chords[*other];
+                        others.len() > names.len() && names.iter().all(|n| others.contains(n))
+                    })
+                    && (prev.is_on()
+                        || names
+                            .iter()
+                            .any(|n| self.trigger_or_default(n).is_pressed()));
+                (name.to_owned(), prev.progress(on))
+            })
+            .collect::<Vec<_>>();
+        self.triggers.extend(chords);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        any::Any,
+        collections::HashSet,
+        sync::{Arc, Mutex},
+    };
+
+    #[derive(Default, Clone)]
+    struct FakeKeyboard(Arc<Mutex<HashSet<&'static str>>>);
+
+    impl FakeKeyboard {
+        fn set(&self, keys: &[&'static str]) {
+            *self.0.lock().unwrap() = keys.iter().copied().collect();
+        }
+    }
+
+    impl InputDevice for FakeKeyboard {
+        fn name(&self) -> &str {
+            "keyboard"
+        }
+
+        fn process(&mut self, _: &mut Universe) {}
+
+        fn query_axis(&self, _: &str) -> Option<Scalar> {
+            None
+        }
+
+        fn query_trigger(&self, name: &str) -> Option<bool> {
+            Some(self.0.lock().unwrap().contains(name))
+        }
+
+        fn query_text(&self) -> Option<String> {
+            None
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    fn setup() -> (InputController, FakeKeyboard) {
+        let keyboard = FakeKeyboard::default();
+        let mut input = InputController::default();
+        input.register(keyboard.clone());
+        input.map_trigger("ctrl", "keyboard", "ControlLeft");
+        input.map_trigger("shift", "keyboard", "ShiftLeft");
+        input.map_trigger("s", "keyboard", "KeyS");
+        input.register_chord("save", &["ctrl", "s"]);
+        input.register_chord("save-as", &["ctrl", "shift", "s"]);
+        (input, keyboard)
+    }
+
+    #[test]
+    fn test_chord_press_and_hold() {
+        let (mut input, keyboard) = setup();
+        let mut universe = Universe::default();
+
+        keyboard.set(&["ControlLeft"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Idle);
+
+        keyboard.set(&["ControlLeft", "KeyS"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Pressed);
+        assert!(input.trigger_or_default("save").is_pressed());
+
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Hold);
+        assert_eq!(input.trigger_or_default("save-as"), TriggerState::Idle);
+    }
+
+    #[test]
+    fn test_chord_partial_release() {
+        let (mut input, keyboard) = setup();
+        let mut universe = Universe::default();
+
+        keyboard.set(&["ControlLeft", "KeyS"]);
+        input.process(&mut universe);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Hold);
+
+        keyboard.set(&["ControlLeft"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Released);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Idle);
+
+        // pressing missing key again completes chord again.
+        keyboard.set(&["ControlLeft", "KeyS"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Pressed);
+    }
+
+    #[test]
+    fn test_overlapping_chords() {
+        let (mut input, keyboard) = setup();
+        let mut universe = Universe::default();
+
+        keyboard.set(&["ControlLeft", "ShiftLeft", "KeyS"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save-as"), TriggerState::Pressed);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Idle);
+
+        keyboard.set(&["ControlLeft", "KeyS"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save-as"), TriggerState::Released);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Idle);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Idle);
+
+        // pressing `S` again completes smaller chord on its own.
+        keyboard.set(&["ControlLeft"]);
+        input.process(&mut universe);
+        keyboard.set(&["ControlLeft", "KeyS"]);
+        input.process(&mut universe);
+        assert_eq!(input.trigger_or_default("save"), TriggerState::Pressed);
     }
 }
@@ -1,2 +1,3 @@
+pub mod bindings;
 pub mod controller;
 pub mod stack;
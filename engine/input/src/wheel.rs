@@ -0,0 +1,131 @@
+use core::Scalar;
+
+/// Pixels scrolled by single wheel notch in most browsers.
+pub const WHEEL_PIXELS_PER_NOTCH: Scalar = 100.0;
+/// Lines scrolled by single wheel notch in most browsers.
+pub const WHEEL_LINES_PER_NOTCH: Scalar = 3.0;
+
+/// Unit of reported wheel delta (mirrors browser `WheelEvent.deltaMode`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum WheelDeltaMode {
+    Pixel,
+    Line,
+    Page,
+}
+
+impl WheelDeltaMode {
+    /// Converts browser `WheelEvent.deltaMode` value, unknown values are treated as pixels.
+    pub fn from_dom(value: u32) -> Self {
+        match value {
+            1 => Self::Line,
+            2 => Self::Page,
+            _ => Self::Pixel,
+        }
+    }
+
+    /// Converts delta into wheel notches, so scrolling speed does not depend on platform.
+    pub fn normalize(self, delta: Scalar) -> Scalar {
+        match self {
+            Self::Pixel => delta / WHEEL_PIXELS_PER_NOTCH,
+            Self::Line => delta / WHEEL_LINES_PER_NOTCH,
+            Self::Page => delta,
+        }
+    }
+}
+
+/// Collects wheel deltas reported by events and exposes them as per-frame values.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct MouseWheel {
+    accumulated: (Scalar, Scalar),
+    delta: (Scalar, Scalar),
+}
+
+impl MouseWheel {
+    /// Adds event delta, normalized to wheel notches.
+    pub fn feed(&mut self, x: Scalar, y: Scalar, mode: WheelDeltaMode) {
+        self.accumulated.0 += mode.normalize(x);
+        self.accumulated.1 += mode.normalize(y);
+    }
+
+    /// Makes deltas accumulated since last call current frame deltas and starts new frame.
+    pub fn advance(&mut self) {
+        self.delta = std::mem::take(&mut self.accumulated);
+    }
+
+    /// Horizontal delta of current frame.
+    pub fn delta_x(&self) -> Scalar {
+        self.delta.0
+    }
+
+    /// Vertical delta of current frame.
+    pub fn delta_y(&self) -> Scalar {
+        self.delta.1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{device::InputDevice, resources::controller::InputController};
+    use core::ecs::Universe;
+    use std::{
+        any::Any,
+        sync::{Arc, Mutex},
+    };
+
+    struct FakeMouse(Arc<Mutex<MouseWheel>>);
+
+    impl InputDevice for FakeMouse {
+        fn name(&self) -> &str {
+            "mouse"
+        }
+
+        fn process(&mut self, _: &mut Universe) {
+            self.0.lock().unwrap().advance();
+        }
+
+        fn query_axis(&self, name: &str) -> Option<Scalar> {
+            match name {
+                "wheel" => Some(self.0.lock().unwrap().delta_y()),
+                "wheel-x" => Some(self.0.lock().unwrap().delta_x()),
+                _ => None,
+            }
+        }
+
+        fn query_trigger(&self, _: &str) -> Option<bool> {
+            None
+        }
+
+        fn query_text(&self) -> Option<String> {
+            None
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn test_mouse_wheel() {
+        let wheel = Arc::new(Mutex::new(MouseWheel::default()));
+        let mut input = InputController::default();
+        input.register(FakeMouse(wheel.clone()));
+        input.map_axis("mouse-wheel", "mouse", "wheel");
+        input.map_axis("mouse-wheel-x", "mouse", "wheel-x");
+        let mut universe = Universe::default();
+
+        {
+            let mut wheel = wheel.lock().unwrap();
+            wheel.feed(0.0, 100.0, WheelDeltaMode::Pixel);
+            wheel.feed(0.0, 3.0, WheelDeltaMode::from_dom(1));
+            wheel.feed(-0.5, 0.0, WheelDeltaMode::Page);
+        }
+        input.process(&mut universe);
+        assert_eq!(input.axis_or_default("mouse-wheel"), 2.0);
+        assert_eq!(input.axis_or_default("mouse-wheel-x"), -0.5);
+
+        input.process(&mut universe);
+        assert_eq!(input.axis_or_default("mouse-wheel"), 0.0);
+        assert_eq!(input.axis_or_default("mouse-wheel-x"), 0.0);
+    }
+}
@@ -1,13 +1,16 @@
 use crate::systems::render_ui_stage::HaRenderUiStageSystemCache;
-use oxygengine_core::prelude::*;
+use oxygengine_core::{prelude::*, Scalar};
 use oxygengine_ha_renderer::prelude::*;
-use oxygengine_user_interface::raui::core::{
-    layout::{CoordsMapping, Layout},
-    renderer::Renderer,
-    widget::unit::{
-        text::{TextBoxHorizontalAlign, TextBoxVerticalAlign},
-        WidgetUnit,
+use oxygengine_user_interface::raui::{
+    core::{
+        layout::{CoordsMapping, Layout},
+        renderer::Renderer,
+        widget::unit::{
+            text::{TextBoxHorizontalAlign, TextBoxVerticalAlign},
+            WidgetUnit,
+        },
     },
+    material::theme::ThemedTextMaterial,
 };
 use raui_tesselate_renderer::{
     renderer::TesselateRenderer,
@@ -178,6 +181,33 @@ impl<'a> Renderer<StreamingVertexFactory, Error> for RauiRenderer<'a> {
     }
 }
 
+/// Measures size of text rendered with theme text variant, using metrics of its loaded font.
+///
+/// # Arguments
+/// * `assets` - assets database with loaded fonts.
+/// * `text` - text content, with lines separated by new line characters.
+/// * `variant` - theme text variant.
+/// * `scale` - font scale, the same as scale of coords mapping used for rendering.
+///
+/// # Returns
+/// `Some` with width and height of text or `None` if font of variant is not loaded.
+pub fn measure_text(
+    assets: &AssetsDatabase,
+    text: &str,
+    variant: &ThemedTextMaterial,
+    scale: Scalar,
+) -> Option<(Scalar, Scalar)> {
+    let font = assets
+        .asset_by_path(&variant.font.name)?
+        .get::<FontAsset>()?;
+    let mut instance = HaTextInstance::default();
+    instance.set_content(text);
+    instance.set_font(&variant.font.name);
+    instance.set_size(variant.font.size * scale);
+    let size = SurfaceTextFactory::measure(&instance, font);
+    Some((size.x, size.y))
+}
+
 #[derive(Debug, Clone)]
 pub enum RenderBatch {
     Colored(Range<usize>),
@@ -1,5 +1,5 @@
 use crate::resources::{NavMesh, NavResult, NavTriangle, NavVec3};
-use bincode::deserialize;
+use bincode::{deserialize, serialize};
 use core::assets::protocol::{AssetLoadResult, AssetProtocol};
 use serde::{Deserialize, Serialize};
 
@@ -10,6 +10,17 @@ pub struct NavMeshAsset {
 }
 
 impl NavMeshAsset {
+    pub fn new(vertices: Vec<NavVec3>, triangles: Vec<NavTriangle>) -> Self {
+        Self {
+            vertices,
+            triangles,
+        }
+    }
+
+    pub fn from_nav_mesh(mesh: &NavMesh) -> Self {
+        Self::new(mesh.vertices().to_vec(), mesh.triangles().to_vec())
+    }
+
     pub fn vertices(&self) -> &[NavVec3] {
         &self.vertices
     }
@@ -23,6 +34,28 @@ impl NavMeshAsset {
     }
 }
 
+/// Stores nav mesh as bytes in `navmesh` asset format - only source geometry is stored, search
+/// acceleration structures get rebuilt on load.
+pub trait NavMeshBytes: Sized {
+    fn to_bytes(&self) -> Result<Vec<u8>, String>;
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String>;
+}
+
+impl NavMeshBytes for NavMesh {
+    fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        serialize(&NavMeshAsset::from_nav_mesh(self))
+            .map_err(|error| format!("Error serializing navmesh: {:?}", error))
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        deserialize::<NavMeshAsset>(bytes)
+            .map_err(|error| format!("Error deserializing navmesh: {:?}", error))?
+            .build_nav_mesh()
+            .map_err(|error| format!("Error building navmesh: {:?}", error))
+    }
+}
+
 pub struct NavMeshAssetProtocol;
 
 impl AssetProtocol for NavMeshAssetProtocol {
@@ -39,3 +72,46 @@ impl AssetProtocol for NavMeshAssetProtocol {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::resources::{NavPathMode, NavQuery};
+
+    #[test]
+    fn test_nav_mesh_bytes() {
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(),
+            (10.0, 0.0, 0.0).into(),
+            (20.0, 0.0, 0.0).into(),
+            (0.0, 10.0, 0.0).into(),
+            (10.0, 10.0, 0.0).into(),
+            (20.0, 10.0, 0.0).into(),
+        ];
+        let triangles = vec![
+            (0, 1, 4).into(),
+            (4, 3, 0).into(),
+            (1, 2, 5).into(),
+            (5, 4, 1).into(),
+        ];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+        let bytes = mesh.to_bytes().unwrap();
+        let loaded = NavMesh::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.vertices().len(), mesh.vertices().len());
+        assert_eq!(loaded.triangles().len(), mesh.triangles().len());
+
+        let from: NavVec3 = (1.0, 9.0, 0.0).into();
+        let to: NavVec3 = (19.0, 1.0, 0.0).into();
+        let a = mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        let b = loaded
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b.iter()) {
+            assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+        }
+        assert!(NavMesh::from_bytes(&bytes[..4]).is_err());
+    }
+}
@@ -1,4 +1,7 @@
-use crate::resources::{NavMeshID, NavPathMode, NavQuery, NavVec3};
+use crate::resources::{
+    nav_areas::NavAreaId, nav_jobs::NavPathRequestId, nav_mesh_queries::NavMeshQueries,
+    nav_offmesh_links::NavPathNode, NavMesh, NavMeshID, NavPathMode, NavQuery, NavVec3,
+};
 use core::{
     ecs::Entity,
     id::ID,
@@ -6,6 +9,7 @@ use core::{
     Scalar,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 /// Nav agent identifier.
 pub type NavAgentId = ID<NavAgent>;
@@ -36,6 +40,21 @@ impl NavAgentTarget {
     }
 }
 
+/// What happened to nav agent during single movement step.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum NavAgentEvent {
+    #[default]
+    None,
+    /// Agent has reached path point with given index.
+    WaypointReached(usize),
+    /// Agent has reached last path point.
+    DestinationReached,
+    /// Path to destination could not be found.
+    PathFailed,
+    /// Agent got teleported across off-mesh link to path point with given index.
+    OffMeshLinkTraversed(usize),
+}
+
 /// Nav agent destination descriptor.
 #[derive(Debug, Clone)]
 pub struct NavAgentDestination {
@@ -52,6 +71,8 @@ pub struct NavAgentDestination {
 /// Nav agent component.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NavAgent {
+    /// Regenerated for every loaded agent, so copies of the same prefab do not share it.
+    #[serde(skip)]
     id: NavAgentId,
     /// Current agent position in world space.
     pub position: NavVec3,
@@ -64,12 +85,53 @@ pub struct NavAgent {
     /// Mnimal distance to target (affects direction, tells how far look for point to go to in an
     /// instant).
     pub min_target_distance: Scalar,
+    /// When set, found paths get shortened with string pulling so agent does not follow jagged
+    /// lines along nav mesh edges.
+    #[serde(default)]
+    pub smooth_path: bool,
+    /// When set, agent position and destination point get snapped onto nav mesh before finding
+    /// path, so agents that drifted off nav mesh do not get stuck.
+    #[serde(default)]
+    pub snap_path_ends: bool,
+    /// Radius kept free from other agents - zero disables local avoidance.
+    #[serde(default)]
+    pub avoidance_radius: Scalar,
+    /// When set, path is requested through `NavJobQueue` with this priority instead of
+    /// being found immediately.
+    #[serde(default)]
+    pub path_request_priority: Option<i32>,
+    /// When set, agent gets moved to the end of off-mesh link at once instead of walking across
+    /// it - drivers that animate jumps leave it unset and check `traverses_offmesh_link`.
+    #[serde(default)]
+    pub teleport_offmesh_links: bool,
+    /// Area cost multipliers used instead of ones set for nav mesh (amphibious units ignore
+    /// water cost). Not applied to paths requested through `NavJobQueue`.
+    #[serde(default)]
+    pub area_costs: HashMap<NavAreaId, Scalar>,
     #[serde(skip)]
     pub(crate) destination: Option<NavAgentDestination>,
     #[serde(skip)]
     pub(crate) path: Option<Vec<NavVec3>>,
+    /// Tells for every path point if segment ending at it goes through off-mesh link.
+    #[serde(skip)]
+    pub(crate) path_links: Vec<bool>,
     #[serde(skip)]
     pub(crate) dirty_path: bool,
+    #[serde(skip)]
+    pub(crate) path_request: Option<NavPathRequestId>,
+    #[serde(skip)]
+    pub(crate) avoidance: NavVec3,
+    #[serde(skip)]
+    pub(crate) obstacles_revision: usize,
+    /// Index of next path point to reach.
+    #[serde(skip)]
+    pub(crate) waypoint: usize,
+    #[serde(skip)]
+    pub(crate) arrived: bool,
+    #[serde(skip)]
+    pub(crate) path_failed: bool,
+    #[serde(skip)]
+    pub(crate) last_event: NavAgentEvent,
 }
 
 impl Default for NavAgent {
@@ -91,9 +153,23 @@ impl NavAgent {
             speed: 10.0,
             radius: 1.0,
             min_target_distance: 1.0,
+            smooth_path: false,
+            snap_path_ends: false,
+            avoidance_radius: 0.0,
+            path_request_priority: None,
+            teleport_offmesh_links: false,
+            area_costs: Default::default(),
             destination: None,
             path: None,
+            path_links: vec![],
             dirty_path: false,
+            path_request: None,
+            avoidance: Default::default(),
+            obstacles_revision: 0,
+            waypoint: 0,
+            arrived: false,
+            path_failed: false,
+            last_event: Default::default(),
         }
     }
 
@@ -111,6 +187,13 @@ impl NavAgent {
         self.destination.as_ref()
     }
 
+    /// Nav mesh that agent moves on towards its destination.
+    pub fn destination_mesh(&self) -> Option<NavMeshID> {
+        self.destination
+            .as_ref()
+            .map(|destination| destination.mesh)
+    }
+
     /// Sets destination to go to.
     ///
     /// # Arguments
@@ -138,6 +221,16 @@ impl NavAgent {
         self.destination = None;
         self.dirty_path = false;
         self.path = None;
+        self.path_links.clear();
+        self.path_request = None;
+        self.waypoint = 0;
+        self.arrived = false;
+        self.path_failed = false;
+    }
+
+    /// Pending path request handle, if path is being found through `NavJobQueue`.
+    pub fn path_request(&self) -> Option<NavPathRequestId> {
+        self.path_request
     }
 
     pub fn recalculate_path(&mut self) {
@@ -152,11 +245,234 @@ impl NavAgent {
         }
     }
 
+    /// Path with information which of its segments go through off-mesh links.
+    pub fn path_nodes(&self) -> Option<Vec<NavPathNode>> {
+        self.path.as_ref().map(|path| {
+            path.iter()
+                .enumerate()
+                .map(|(index, position)| {
+                    NavPathNode::new(
+                        *position,
+                        self.path_links.get(index).copied().unwrap_or_default(),
+                    )
+                })
+                .collect()
+        })
+    }
+
+    pub fn set_path_nodes(&mut self, nodes: Vec<NavPathNode>) {
+        let (path, links) = nodes
+            .into_iter()
+            .map(|node| (node.position, node.via_link))
+            .unzip();
+        self.set_path(path);
+        self.path_links = links;
+    }
+
     pub fn set_path(&mut self, path: Vec<NavVec3>) {
+        // first path point is where agent starts.
+        self.waypoint = path.len().min(1);
+        self.path_links.clear();
         self.path = Some(path);
         self.dirty_path = false;
+        self.path_request = None;
+        self.arrived = false;
+        self.path_failed = false;
+    }
+
+    /// Index of next path point to reach - agent traverses segment ending at this point.
+    pub fn waypoint(&self) -> usize {
+        self.waypoint
+    }
+
+    /// Tells if agent is currently moving across off-mesh link.
+    pub fn traverses_offmesh_link(&self) -> bool {
+        !self.arrived
+            && self
+                .path_links
+                .get(self.waypoint)
+                .copied()
+                .unwrap_or_default()
+    }
+
+    /// Tells if agent has reached last point of its path.
+    pub fn destination_reached(&self) -> bool {
+        self.arrived
+    }
+
+    /// Event produced by last movement step done by nav driver.
+    pub fn last_event(&self) -> NavAgentEvent {
+        self.last_event
+    }
+
+    /// Moves agent along its path.
+    ///
+    /// # Arguments
+    /// * `delta_time` - time passed since last step.
+    ///
+    /// # Returns
+    /// Event describing what happened in this step - `DestinationReached` and `PathFailed` are
+    /// reported only once. Agents that teleport across off-mesh links do it in separate step,
+    /// reporting `OffMeshLinkTraversed`.
+    pub fn process(&mut self, delta_time: Scalar) -> NavAgentEvent {
+        if self.path_failed {
+            self.path_failed = false;
+            return NavAgentEvent::PathFailed;
+        }
+        if delta_time <= 0.0 || self.arrived {
+            return NavAgentEvent::None;
+        }
+        let path = match &self.path {
+            Some(path) if !path.is_empty() => path,
+            _ => return NavAgentEvent::None,
+        };
+        if self.teleport_offmesh_links && self.traverses_offmesh_link() {
+            let index = self.waypoint;
+            self.position = path[index];
+            self.waypoint += 1;
+            if self.waypoint >= path.len() {
+                self.arrived = true;
+                return NavAgentEvent::DestinationReached;
+            }
+            return NavAgentEvent::OffMeshLinkTraversed(index);
+        }
+        if let Some((target, _)) = NavMesh::path_target_point(
+            path,
+            self.position,
+            self.speed.max(self.min_target_distance.max(0.0)) * delta_time,
+        ) {
+            let diff = target + self.avoidance - self.position;
+            let dir = diff.normalize();
+            self.position =
+                self.position + dir * (self.speed.max(0.0) * delta_time).min(diff.magnitude());
+            self.direction = diff.normalize();
+        }
+        let mut result = NavAgentEvent::None;
+        let distance = self.min_target_distance.max(0.0);
+        while self.waypoint < path.len()
+            && (path[self.waypoint] - self.position).magnitude() <= distance
+        {
+            result = NavAgentEvent::WaypointReached(self.waypoint);
+            self.waypoint += 1;
+        }
+        if self.waypoint >= path.len() {
+            self.arrived = true;
+            result = NavAgentEvent::DestinationReached;
+        }
+        result
+    }
+
+    /// Moves agent onto nearest point of nav mesh.
+    ///
+    /// # Returns
+    /// `true` if agent position has changed.
+    pub fn snap_to_mesh(&mut self, mesh: &NavMesh) -> bool {
+        match mesh.project_point(self.position) {
+            Some(position) if (position - self.position).sqr_magnitude() > 0.0 => {
+                self.position = position;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Offset applied to agent target point to keep it away from neighbor agents.
+    pub fn avoidance(&self) -> NavVec3 {
+        self.avoidance
+    }
+
+    /// Calculates repulsion offset from neighbor agents.
+    ///
+    /// # Arguments
+    /// * `neighbors` - list of neighbor agents positions and avoidance radii.
+    ///
+    /// # Returns
+    /// Sum of vectors pushing agent away from neighbors closer than combined avoidance radius,
+    /// each scaled by how deep neighbor is inside that radius.
+    pub fn compute_avoidance(&self, neighbors: &[(NavVec3, Scalar)]) -> NavVec3 {
+        if self.avoidance_radius <= 0.0 {
+            return Default::default();
+        }
+        neighbors
+            .iter()
+            .fold(Default::default(), |result, (position, radius)| {
+                let radius = self.avoidance_radius + radius.max(0.0);
+                let diff = self.position - *position;
+                let distance = diff.magnitude();
+                if distance > 0.0 && distance < radius {
+                    result + diff.normalize() * (radius - distance)
+                } else {
+                    result
+                }
+            })
     }
 }
 
 impl Prefab for NavAgent {}
 impl PrefabComponent for NavAgent {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nav_agent_avoidance() {
+        let mut agent = NavAgent::new((0.0, 0.0, 0.0).into());
+        let neighbors: [(NavVec3, Scalar); 2] =
+            [((1.0, 0.0, 0.0).into(), 1.0), ((0.0, 5.0, 0.0).into(), 1.0)];
+        assert_eq!(agent.compute_avoidance(&neighbors).magnitude(), 0.0);
+
+        agent.avoidance_radius = 1.0;
+        let avoidance = agent.compute_avoidance(&neighbors);
+        assert!((avoidance.x + 1.0).abs() < 1.0e-6);
+        assert!(avoidance.y.abs() < 1.0e-6);
+        assert_eq!(agent.compute_avoidance(&[]).magnitude(), 0.0);
+    }
+
+    #[test]
+    fn test_nav_agent_prefab() {
+        let mut agent =
+            NavAgent::new_with_direction((1.0, 2.0, 3.0).into(), (0.0, 1.0, 0.0).into());
+        agent.speed = 4.5;
+        agent.min_target_distance = 0.25;
+        agent.smooth_path = true;
+        agent.set_path(vec![(0.0, 0.0, 0.0).into(), (5.0, 0.0, 0.0).into()]);
+        let data = agent.to_prefab_string().unwrap();
+        assert!(!data.contains("\"id\""));
+        assert!(!data.contains("\"path\""));
+        let loaded = NavAgent::from_prefab_str(&data).unwrap();
+        assert_ne!(loaded.id(), agent.id());
+        assert_eq!(loaded.speed, 4.5);
+        assert_eq!(loaded.min_target_distance, 0.25);
+        assert!(loaded.smooth_path);
+        assert_eq!(
+            (loaded.position.x, loaded.position.y, loaded.position.z),
+            (1.0, 2.0, 3.0)
+        );
+        assert_eq!(
+            (loaded.direction.x, loaded.direction.y, loaded.direction.z),
+            (0.0, 1.0, 0.0)
+        );
+        assert!(loaded.path().is_none());
+        assert!(!loaded.dirty_path);
+    }
+
+    #[test]
+    fn test_nav_agent_events() {
+        let mut agent = NavAgent::new((0.0, 0.0, 0.0).into());
+        agent.path_failed = true;
+        assert_eq!(agent.process(0.1), NavAgentEvent::PathFailed);
+        assert_eq!(agent.process(0.1), NavAgentEvent::None);
+
+        agent.set_path(vec![
+            (0.0, 0.0, 0.0).into(),
+            (5.0, 0.0, 0.0).into(),
+            (10.0, 0.0, 0.0).into(),
+        ]);
+        assert_eq!(agent.process(0.5), NavAgentEvent::WaypointReached(1));
+        assert!(!agent.destination_reached());
+        assert_eq!(agent.process(0.5), NavAgentEvent::DestinationReached);
+        assert!(agent.destination_reached());
+        assert_eq!(agent.process(0.5), NavAgentEvent::None);
+    }
+}
@@ -6,10 +6,15 @@ pub mod resources;
 pub mod systems;
 
 pub mod prelude {
+    #[cfg(feature = "oxygengine-ha-renderer")]
+    pub use crate::resources::nav_debug_draw::*;
     pub use crate::{
         asset_protocols::{nav_grid::*, nav_mesh::*, *},
         components::*,
-        resources::{nav_grids::*, nav_meshes::*, *},
+        resources::{
+            nav_areas::*, nav_grids::*, nav_jobs::*, nav_mesh_grid::*, nav_mesh_queries::*,
+            nav_meshes::*, nav_obstacles::*, nav_offmesh_links::*, *,
+        },
         systems::*,
     };
 }
@@ -17,12 +22,19 @@ pub mod prelude {
 use crate::{
     asset_protocols::{nav_grid::NavGridAssetProtocol, nav_mesh::NavMeshAssetProtocol},
     components::{NavAgent, SimpleNavDriverTag},
-    resources::{nav_grids::NavGrids, nav_meshes::NavMeshes},
+    resources::{nav_grids::NavGrids, nav_jobs::NavJobQueue, nav_meshes::NavMeshes},
     systems::{
-        nav_agent_maintain_system, simple_nav_driver_system, NavAgentMaintainSystemResources,
+        nav_agent_avoidance_system, nav_agent_maintain_system, nav_job_queue_system,
+        simple_nav_driver_system, NavAgentAvoidanceSystemResources,
+        NavAgentMaintainSystemResources, NavJobQueueSystemResources,
         SimpleNavDriverSystemResources,
     },
 };
+#[cfg(feature = "oxygengine-ha-renderer")]
+use crate::{
+    resources::nav_debug_draw::NavDebugDraw,
+    systems::{nav_debug_draw_system, NavDebugDrawSystemResources},
+};
 use core::{
     app::AppBuilder,
     assets::database::AssetsDatabase,
@@ -36,16 +48,36 @@ where
 {
     builder.install_resource(NavMeshes::default());
     builder.install_resource(NavGrids::default());
+    builder.install_resource(NavJobQueue::default());
+    builder.install_system::<NavJobQueueSystemResources>(
+        "nav-job-queue",
+        nav_job_queue_system,
+        &[],
+    )?;
     builder.install_system::<NavAgentMaintainSystemResources>(
         "nav-agent-maintain",
         nav_agent_maintain_system,
-        &[],
+        &["nav-job-queue"],
+    )?;
+    builder.install_system::<NavAgentAvoidanceSystemResources>(
+        "nav-agent-avoidance",
+        nav_agent_avoidance_system,
+        &["nav-agent-maintain"],
     )?;
     builder.install_system::<SimpleNavDriverSystemResources>(
         "simple-nav-driver",
         simple_nav_driver_system,
-        &["nav-agent-maintain"],
+        &["nav-agent-avoidance"],
     )?;
+    #[cfg(feature = "oxygengine-ha-renderer")]
+    {
+        builder.install_resource(NavDebugDraw::default());
+        builder.install_system::<NavDebugDrawSystemResources>(
+            "nav-debug-draw",
+            nav_debug_draw_system,
+            &["simple-nav-driver"],
+        )?;
+    }
     Ok(())
 }
 
@@ -1,4 +1,12 @@
+pub mod nav_areas;
+#[cfg(feature = "oxygengine-ha-renderer")]
+pub mod nav_debug_draw;
 pub mod nav_grids;
+pub mod nav_jobs;
+pub mod nav_mesh_grid;
+pub mod nav_mesh_queries;
 pub mod nav_meshes;
+pub mod nav_obstacles;
+pub mod nav_offmesh_links;
 
 pub use navmesh::*;
@@ -0,0 +1,211 @@
+use crate::resources::nav_mesh_queries::{
+    containing_triangle, edge_key, edge_triangles, triangle_points,
+};
+use core::Scalar;
+use navmesh::*;
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashMap},
+};
+
+/// Nav area type identifier (mud, water, road).
+pub type NavAreaId = u32;
+
+/// Area of triangles that were not tagged with any other area.
+pub const NAV_AREA_DEFAULT: NavAreaId = 0;
+
+/// Area types of nav mesh triangles and multipliers of cost of traversing them.
+#[derive(Debug, Default, Clone)]
+pub struct NavAreas {
+    triangles: Vec<NavAreaId>,
+    costs: HashMap<NavAreaId, Scalar>,
+}
+
+impl NavAreas {
+    /// Creates new areas descriptor.
+    ///
+    /// # Arguments
+    /// * `triangles` - area of every nav mesh triangle, in order of nav mesh triangles.
+    pub fn new(triangles: Vec<NavAreaId>) -> Self {
+        Self {
+            triangles,
+            costs: Default::default(),
+        }
+    }
+
+    /// Area of nav mesh triangle with given index.
+    pub fn triangle_area(&self, index: usize) -> NavAreaId {
+        self.triangles
+            .get(index)
+            .copied()
+            .unwrap_or(NAV_AREA_DEFAULT)
+    }
+
+    /// Multiplier of distance walked through given area - areas without cost set use 1.
+    pub fn area_cost(&self, area: NavAreaId) -> Scalar {
+        self.costs.get(&area).copied().unwrap_or(1.0)
+    }
+
+    pub fn set_area_cost(&mut self, area: NavAreaId, multiplier: Scalar) {
+        self.costs.insert(area, multiplier.max(0.0));
+    }
+
+    /// Tells if any area has cost different than the default one.
+    pub fn is_weighted(&self) -> bool {
+        self.costs.values().any(|cost| *cost != 1.0)
+    }
+
+    /// Cost multipliers of triangles of `mesh`, being either source nav mesh these areas were
+    /// made for or its carved version.
+    ///
+    /// # Arguments
+    /// * `source` - nav mesh these areas were made for.
+    /// * `mesh` - nav mesh to calculate costs for.
+    /// * `overrides` - area costs used instead of ones set in these areas.
+    pub(crate) fn triangle_costs(
+        &self,
+        source: &NavMesh,
+        mesh: &NavMesh,
+        overrides: &HashMap<NavAreaId, Scalar>,
+    ) -> Vec<Scalar> {
+        // carved nav mesh keeps vertices of its source, so triangles can be matched by them.
+        let areas = source
+            .triangles()
+            .iter()
+            .enumerate()
+            .map(|(index, triangle)| {
+                (
+                    (triangle.first, triangle.second, triangle.third),
+                    self.triangle_area(index),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        mesh.triangles()
+            .iter()
+            .map(|triangle| {
+                let area = areas
+                    .get(&(triangle.first, triangle.second, triangle.third))
+                    .copied()
+                    .unwrap_or(NAV_AREA_DEFAULT);
+                overrides
+                    .get(&area)
+                    .map(|cost| cost.max(0.0))
+                    .unwrap_or_else(|| self.area_cost(area))
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct Candidate {
+    cost: Scalar,
+    triangle: usize,
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // reversed, so binary heap pops the cheapest candidate first.
+        other
+            .cost
+            .partial_cmp(&self.cost)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.triangle.cmp(&self.triangle))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Finds path minimizing distance walked through triangles, multiplied by their costs.
+///
+/// # Arguments
+/// * `mesh` - nav mesh to find path on.
+/// * `costs` - cost multiplier of every nav mesh triangle.
+/// * `from` - path start point.
+/// * `to` - path end point.
+///
+/// # Returns
+/// `Some` with path going through centers of shared edges of visited triangles if found or
+/// `None` otherwise.
+pub(crate) fn find_weighted_path(
+    mesh: &NavMesh,
+    costs: &[Scalar],
+    from: NavVec3,
+    to: NavVec3,
+) -> Option<Vec<NavVec3>> {
+    let start = containing_triangle(mesh, from)?;
+    let goal = containing_triangle(mesh, to)?;
+    let edges = edge_triangles(mesh);
+    let count = mesh.triangles().len();
+    let anchor = |index: usize| {
+        if index == start {
+            from
+        } else if index == goal {
+            to
+        } else {
+            let (_, [a, b, c]) = triangle_points(mesh, index);
+            (a + b + c) * (1.0 / 3.0)
+        }
+    };
+    let cost = |index: usize| costs.get(index).copied().unwrap_or(1.0);
+    let mut totals = vec![Scalar::INFINITY; count];
+    // { triangle index: (previous triangle index, center of edge shared with it) }
+    let mut previous = vec![None; count];
+    let mut open = BinaryHeap::new();
+    totals[start] = 0.0;
+    open.push(Candidate {
+        cost: 0.0,
+        triangle: start,
+    });
+    while let Some(Candidate {
+        cost: total,
+        triangle,
+    }) = open.pop()
+    {
+        if triangle == goal {
+            break;
+        }
+        if total > totals[triangle] {
+            continue;
+        }
+        let (ids, points) = triangle_points(mesh, triangle);
+        let position = anchor(triangle);
+        for i in 0..3 {
+            let portal = (points[i] + points[(i + 1) % 3]) * 0.5;
+            let neighbors = match edges.get(&edge_key(ids[i], ids[(i + 1) % 3])) {
+                Some(neighbors) => neighbors,
+                None => continue,
+            };
+            for next in neighbors.iter().copied().filter(|next| *next != triangle) {
+                let value = total
+                    + (portal - position).magnitude() * cost(triangle)
+                    + (anchor(next) - portal).magnitude() * cost(next);
+                if value < totals[next] {
+                    totals[next] = value;
+                    previous[next] = Some((triangle, portal));
+                    open.push(Candidate {
+                        cost: value,
+                        triangle: next,
+                    });
+                }
+            }
+        }
+    }
+    if start != goal && previous[goal].is_none() {
+        return None;
+    }
+    let mut result = vec![to];
+    let mut current = goal;
+    while let Some((triangle, portal)) = previous[current] {
+        result.push(portal);
+        current = triangle;
+    }
+    result.push(from);
+    result.reverse();
+    Some(result)
+}
@@ -0,0 +1,96 @@
+use crate::{components::NavAgent, resources::NavVec3};
+use core::Scalar;
+use oxygengine_ha_renderer::{
+    math::{Rgba, Vec3},
+    resources::debug_draw::DebugDraw,
+};
+
+/// Settings of nav agents paths debug drawing, done through renderer `DebugDraw`.
+///
+/// Enabled by default only in debug builds.
+#[derive(Debug, Clone)]
+pub struct NavDebugDraw {
+    pub enabled: bool,
+    pub path_color: Rgba,
+    /// Color of path segment agent is currently traversing.
+    pub current_segment_color: Rgba,
+    pub destination_color: Rgba,
+    pub destination_radius: Scalar,
+}
+
+impl Default for NavDebugDraw {
+    fn default() -> Self {
+        Self {
+            enabled: cfg!(debug_assertions),
+            path_color: Rgba::new(0.0, 0.5, 1.0, 1.0),
+            current_segment_color: Rgba::new(1.0, 1.0, 0.0, 1.0),
+            destination_color: Rgba::new(1.0, 0.0, 0.0, 1.0),
+            destination_radius: 0.5,
+        }
+    }
+}
+
+impl NavDebugDraw {
+    /// Queues agent path line strip and its destination marker. Agents without path draw
+    /// nothing.
+    pub fn draw_agent(&self, agent: &NavAgent, debug_draw: &mut DebugDraw) {
+        let path = match agent.path() {
+            Some(path) if !path.is_empty() => path,
+            _ => return,
+        };
+        let current = agent.waypoint();
+        for (index, segment) in path.windows(2).enumerate() {
+            let color = if index + 1 == current {
+                self.current_segment_color
+            } else {
+                self.path_color
+            };
+            debug_draw.line(point(segment[0]), point(segment[1]), color);
+        }
+        if let Some(destination) = path.last() {
+            debug_draw.circle(
+                point(*destination),
+                self.destination_radius,
+                self.destination_color,
+            );
+        }
+    }
+}
+
+fn point(point: NavVec3) -> Vec3 {
+    Vec3::new(point.x, point.y, point.z)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use oxygengine_ha_renderer::resources::debug_draw::DebugDrawShape;
+
+    #[test]
+    fn test_nav_debug_draw() {
+        let settings = NavDebugDraw::default();
+        let mut debug_draw = DebugDraw::default();
+        let mut agent = NavAgent::default();
+        settings.draw_agent(&agent, &mut debug_draw);
+        assert!(debug_draw.is_empty());
+
+        agent.set_path(vec![]);
+        settings.draw_agent(&agent, &mut debug_draw);
+        assert!(debug_draw.is_empty());
+
+        agent.set_path(vec![
+            (0.0, 0.0, 0.0).into(),
+            (1.0, 0.0, 0.0).into(),
+            (1.0, 1.0, 0.0).into(),
+        ]);
+        settings.draw_agent(&agent, &mut debug_draw);
+        let primitives = debug_draw.primitives();
+        assert_eq!(primitives.len(), 3);
+        assert_eq!(primitives[0].color, settings.current_segment_color);
+        assert_eq!(primitives[1].color, settings.path_color);
+        assert!(matches!(
+            primitives[2].shape,
+            DebugDrawShape::Circle { radius, .. } if radius == settings.destination_radius
+        ));
+    }
+}
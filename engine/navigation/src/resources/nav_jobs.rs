@@ -0,0 +1,332 @@
+use crate::resources::{nav_areas::NavAreaId, nav_meshes::NavMeshes};
+use core::{id::ID, Scalar};
+use navmesh::*;
+use std::collections::HashMap;
+#[cfg(not(feature = "web"))]
+use std::time::{Duration, Instant};
+
+/// Path request identifier.
+pub type NavPathRequestId = ID<NavPathRequest>;
+
+/// Path finding request descriptor.
+#[derive(Debug, Clone)]
+pub struct NavPathRequest {
+    /// Nav mesh to find path on.
+    pub mesh: NavMeshID,
+    /// Path start point in world space.
+    pub from: NavVec3,
+    /// Path end point in world space.
+    pub to: NavVec3,
+    /// Query quality.
+    pub query: NavQuery,
+    /// Path finding quality.
+    pub mode: NavPathMode,
+    /// Custom cost multipliers of nav mesh areas used by this search.
+    pub area_costs: HashMap<NavAreaId, Scalar>,
+    /// Requests with higher priority are processed first.
+    pub priority: i32,
+}
+
+/// Path request status.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NavPathRequestStatus {
+    /// Request waits in queue.
+    Pending,
+    /// Request got processed - `None` means path was not found.
+    Done(Option<Vec<NavVec3>>),
+    /// There is no request with given identifier.
+    Unknown,
+}
+
+/// ECS resource that collects path requests and processes them within per-frame budget, so
+/// many agents repathing at once do not cause frame spikes. Agents keep following their old
+/// path until new one arrives. With `parallel` feature searches of single frame run on worker
+/// threads.
+///
+/// Budget is counted in requests and optionally in time shared by all searches of single frame.
+#[derive(Debug)]
+pub struct NavJobQueue {
+    /// Maximum number of path searches performed in single frame.
+    pub max_requests_per_frame: usize,
+    /// Time after which no more path searches get started in single frame. At least one search
+    /// is performed each frame, so queue always makes progress.
+    #[cfg(not(feature = "web"))]
+    pub max_time_per_frame: Option<Duration>,
+    /// Number of frames after which results that were never polled get dropped, so requests of
+    /// despawned agents do not pile up.
+    pub max_done_age: u64,
+    pending: Vec<(NavPathRequestId, u64, NavPathRequest)>,
+    /// (path, frame of completion)
+    done: HashMap<NavPathRequestId, (Option<Vec<NavVec3>>, u64)>,
+    counter: u64,
+    frame: u64,
+}
+
+/// Path request queue used by agents for asynchronous path finding - same resource as
+/// `NavJobQueue`.
+pub type NavPathRequestQueue = NavJobQueue;
+
+impl Default for NavJobQueue {
+    fn default() -> Self {
+        Self::new(16)
+    }
+}
+
+impl NavJobQueue {
+    /// Creates new path request queue.
+    ///
+    /// # Arguments
+    /// * `max_requests_per_frame` - maximum number of path searches performed in single frame.
+    pub fn new(max_requests_per_frame: usize) -> Self {
+        Self {
+            max_requests_per_frame,
+            #[cfg(not(feature = "web"))]
+            max_time_per_frame: None,
+            max_done_age: 60,
+            pending: Default::default(),
+            done: Default::default(),
+            counter: 0,
+            frame: 0,
+        }
+    }
+
+    /// Sets time budget shared by path searches of single frame.
+    #[cfg(not(feature = "web"))]
+    pub fn max_time_per_frame(mut self, value: Option<Duration>) -> Self {
+        self.max_time_per_frame = value;
+        self
+    }
+
+    /// Sets number of frames after which results that were never polled get dropped.
+    pub fn max_done_age(mut self, value: u64) -> Self {
+        self.max_done_age = value;
+        self
+    }
+
+    /// Number of requests waiting in queue.
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Puts path request in queue.
+    ///
+    /// # Returns
+    /// Handle used to poll request status.
+    pub fn enqueue(&mut self, request: NavPathRequest) -> NavPathRequestId {
+        let id = NavPathRequestId::new();
+        self.pending.push((id, self.counter, request));
+        self.counter = self.counter.wrapping_add(1);
+        id
+    }
+
+    /// Removes request from queue, whether it was processed or not.
+    pub fn cancel(&mut self, id: NavPathRequestId) {
+        self.pending.retain(|(i, _, _)| *i != id);
+        self.done.remove(&id);
+    }
+
+    /// Polls request status - completed requests are consumed by this call.
+    pub fn poll(&mut self, id: NavPathRequestId) -> NavPathRequestStatus {
+        if let Some((path, _)) = self.done.remove(&id) {
+            NavPathRequestStatus::Done(path)
+        } else if self.pending.iter().any(|(i, _, _)| *i == id) {
+            NavPathRequestStatus::Pending
+        } else {
+            NavPathRequestStatus::Unknown
+        }
+    }
+
+    /// Processes pending requests with highest priority, up to `max_requests_per_frame` and
+    /// until `max_time_per_frame` has passed. Results older than `max_done_age` frames are
+    /// dropped.
+    ///
+    /// # Returns
+    /// Identifiers of requests processed in this call, in processing order.
+    pub fn process(&mut self, meshes: &NavMeshes) -> Vec<NavPathRequestId> {
+        self.frame = self.frame.wrapping_add(1);
+        let (frame, max_done_age) = (self.frame, self.max_done_age);
+        self.done
+            .retain(|_, (_, done_frame)| frame.wrapping_sub(*done_frame) <= max_done_age);
+        // NOTE: requests with equal priority are processed in order they were enqueued.
+        self.pending
+            .sort_by(|a, b| b.2.priority.cmp(&a.2.priority).then(a.1.cmp(&b.1)));
+        let mut remaining = self.max_requests_per_frame.min(self.pending.len());
+        let mut result = Vec::with_capacity(remaining);
+        #[cfg(not(feature = "web"))]
+        let started = Instant::now();
+        while remaining > 0 {
+            #[cfg(not(feature = "web"))]
+            if let Some(budget) = self.max_time_per_frame {
+                if !result.is_empty() && started.elapsed() >= budget {
+                    break;
+                }
+            }
+            let count = self.batch_size().min(remaining);
+            remaining -= count;
+            let requests = self.pending.drain(0..count).collect::<Vec<_>>();
+            #[cfg(not(feature = "parallel"))]
+            let iter = requests.into_iter();
+            #[cfg(feature = "parallel")]
+            let iter = {
+                use rayon::prelude::*;
+                requests.into_par_iter()
+            };
+            let results = iter
+                .map(|(id, _, request)| {
+                    let path = meshes
+                        .find_path_nodes_with_costs(
+                            request.mesh,
+                            request.from,
+                            request.to,
+                            request.query,
+                            request.mode,
+                            &request.area_costs,
+                        )
+                        .map(|nodes| nodes.into_iter().map(|node| node.position).collect());
+                    (id, path)
+                })
+                .collect::<Vec<_>>();
+            for (id, path) in results {
+                self.done.insert(id, (path, self.frame));
+                result.push(id);
+            }
+        }
+        result
+    }
+
+    /// Number of searches performed between time budget checks.
+    fn batch_size(&self) -> usize {
+        #[cfg(not(feature = "web"))]
+        if self.max_time_per_frame.is_some() {
+            #[cfg(not(feature = "parallel"))]
+            return 1;
+            #[cfg(feature = "parallel")]
+            return rayon::current_num_threads().max(1);
+        }
+        usize::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square_mesh() -> NavMesh {
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(),
+            (10.0, 0.0, 0.0).into(),
+            (10.0, 10.0, 0.0).into(),
+            (0.0, 10.0, 0.0).into(),
+        ];
+        let triangles = vec![(0, 1, 2).into(), (2, 3, 0).into()];
+        NavMesh::new(vertices, triangles).unwrap()
+    }
+
+    fn request(mesh: NavMeshID, priority: i32) -> NavPathRequest {
+        NavPathRequest {
+            mesh,
+            from: (1.0, 1.0, 0.0).into(),
+            to: (9.0, 9.0, 0.0).into(),
+            query: NavQuery::Accuracy,
+            mode: NavPathMode::Accuracy,
+            area_costs: Default::default(),
+            priority,
+        }
+    }
+
+    #[test]
+    fn test_nav_job_queue() {
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(square_mesh());
+        let mut queue = NavJobQueue::new(2);
+        let ids = [0, 3, 1, 3, 2]
+            .iter()
+            .map(|priority| queue.enqueue(request(mesh, *priority)))
+            .collect::<Vec<_>>();
+
+        assert_eq!(queue.process(&meshes), vec![ids[1], ids[3]]);
+        assert_eq!(queue.poll(ids[0]), NavPathRequestStatus::Pending);
+        assert!(matches!(
+            queue.poll(ids[1]),
+            NavPathRequestStatus::Done(Some(_))
+        ));
+        assert_eq!(queue.poll(ids[1]), NavPathRequestStatus::Unknown);
+        assert_eq!(queue.process(&meshes), vec![ids[4], ids[2]]);
+        assert_eq!(queue.process(&meshes), vec![ids[0]]);
+        assert_eq!(queue.pending_count(), 0);
+        assert!(queue.process(&meshes).is_empty());
+    }
+
+    #[test]
+    fn test_nav_path_request_queue_budget() {
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(square_mesh());
+        let mut queue = NavPathRequestQueue::new(1);
+        let ids = (0..5)
+            .map(|_| queue.enqueue(request(mesh, 0)))
+            .collect::<Vec<_>>();
+        let mut resolved = 0;
+        let mut frames = 0;
+        while resolved < ids.len() {
+            assert!(frames < ids.len());
+            frames += 1;
+            queue.process(&meshes);
+            resolved += ids
+                .iter()
+                .filter(|id| matches!(queue.poll(**id), NavPathRequestStatus::Done(Some(_))))
+                .count();
+        }
+        assert_eq!(frames, ids.len());
+        assert_eq!(queue.pending_count(), 0);
+    }
+
+    #[test]
+    fn test_nav_path_request_queue_done_age() {
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(square_mesh());
+        let mut queue = NavJobQueue::new(1).max_done_age(2);
+        let polled = queue.enqueue(request(mesh, 1));
+        let abandoned = queue.enqueue(request(mesh, 0));
+        assert_eq!(queue.process(&meshes), vec![polled]);
+        assert_eq!(queue.process(&meshes), vec![abandoned]);
+        queue.process(&meshes);
+        assert!(matches!(
+            queue.poll(polled),
+            NavPathRequestStatus::Done(Some(_))
+        ));
+        queue.process(&meshes);
+        assert!(matches!(
+            queue.poll(abandoned),
+            NavPathRequestStatus::Done(Some(_))
+        ));
+
+        let abandoned = queue.enqueue(request(mesh, 0));
+        for _ in 0..4 {
+            queue.process(&meshes);
+        }
+        assert_eq!(queue.poll(abandoned), NavPathRequestStatus::Unknown);
+    }
+
+    #[test]
+    #[cfg(not(any(feature = "web", feature = "parallel")))]
+    fn test_nav_path_request_queue_time_budget() {
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(square_mesh());
+        // time budget runs out right after first search of each frame.
+        let mut queue = NavJobQueue::new(usize::MAX).max_time_per_frame(Some(Duration::ZERO));
+        let ids = [0, 2, 1]
+            .iter()
+            .map(|priority| queue.enqueue(request(mesh, *priority)))
+            .collect::<Vec<_>>();
+        assert_eq!(queue.process(&meshes), vec![ids[1]]);
+        assert_eq!(queue.process(&meshes), vec![ids[2]]);
+        assert_eq!(queue.process(&meshes), vec![ids[0]]);
+        assert!(queue.process(&meshes).is_empty());
+
+        queue.max_time_per_frame = Some(Duration::from_secs(60));
+        let ids = (0..3)
+            .map(|_| queue.enqueue(request(mesh, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(queue.process(&meshes), ids);
+    }
+}
@@ -0,0 +1,193 @@
+use core::Scalar;
+use navmesh::*;
+use std::collections::{BTreeSet, HashMap};
+
+/// Builds nav mesh out of grid of walkable and blocked cells (for example tilemap layer).
+pub trait NavMeshFromGrid: Sized {
+    /// Merges contiguous walkable cells into rectangles and triangulates them.
+    ///
+    /// # Arguments
+    /// * `cols` - number of grid columns.
+    /// * `rows` - number of grid rows.
+    /// * `cell_size` - size of single cell in world units.
+    /// * `blocked` - row-major list of cells where `true` means cell is not walkable. Missing
+    /// cells are treated as blocked.
+    /// * `diagonals` - when set, walkable cells touching only by corner get connected with small
+    /// bridge that cuts corner of neighbor blocked cell.
+    ///
+    /// # Returns
+    /// Nav mesh lying on XY plane, with first cell starting at origin.
+    fn from_grid(
+        cols: usize,
+        rows: usize,
+        cell_size: Scalar,
+        blocked: &[bool],
+        diagonals: bool,
+    ) -> NavResult<Self>;
+}
+
+impl NavMeshFromGrid for NavMesh {
+    fn from_grid(
+        cols: usize,
+        rows: usize,
+        cell_size: Scalar,
+        blocked: &[bool],
+        diagonals: bool,
+    ) -> NavResult<Self> {
+        let walkable = |col: usize, row: usize| {
+            col < cols && row < rows && !blocked.get(row * cols + col).copied().unwrap_or(true)
+        };
+
+        // NOTE: all points below use doubled grid coordinates so cell centers and edge midpoints
+        // can be expressed with integers.
+        let rects = merge_rects(cols, rows, &walkable);
+        let mut boundary_points = BTreeSet::new();
+        for (col, row, width, height) in &rects {
+            let (x0, y0) = (col * 2, row * 2);
+            let (x1, y1) = ((col + width) * 2, (row + height) * 2);
+            boundary_points.insert((x0, y0));
+            boundary_points.insert((x1, y0));
+            boundary_points.insert((x1, y1));
+            boundary_points.insert((x0, y1));
+        }
+        let mut bridges = vec![];
+        if diagonals {
+            for row in 1..rows {
+                for col in 1..cols {
+                    let top_left = walkable(col - 1, row - 1);
+                    let top_right = walkable(col, row - 1);
+                    let bottom_left = walkable(col - 1, row);
+                    let bottom_right = walkable(col, row);
+                    let (x, y) = (col * 2, row * 2);
+                    if top_left && bottom_right && !top_right && !bottom_left {
+                        // cuts corner of bottom left cell.
+                        bridges.push([(x - 1, y), (x, y), (x, y + 1)]);
+                    } else if top_right && bottom_left && !top_left && !bottom_right {
+                        // cuts corner of bottom right cell.
+                        bridges.push([(x + 1, y), (x, y + 1), (x, y)]);
+                    }
+                }
+            }
+        }
+        for bridge in &bridges {
+            boundary_points.extend(bridge.iter().copied());
+        }
+
+        let mut vertices = vec![];
+        let mut indices = HashMap::new();
+        let mut vertex = |point: (usize, usize)| {
+            *indices.entry(point).or_insert_with(|| {
+                vertices.push(NavVec3::from((
+                    point.0 as Scalar * cell_size * 0.5,
+                    point.1 as Scalar * cell_size * 0.5,
+                    0.0,
+                )));
+                vertices.len() as u32 - 1
+            })
+        };
+        let mut triangles = Vec::<NavTriangle>::new();
+        for (col, row, width, height) in &rects {
+            let (x0, y0) = (col * 2, row * 2);
+            let (x1, y1) = ((col + width) * 2, (row + height) * 2);
+            // rectangle edges get split at every point where other polygon touches them, so
+            // neighbor polygons share triangle edges.
+            let perimeter = (x0..x1)
+                .map(|x| (x, y0))
+                .chain((y0..y1).map(|y| (x1, y)))
+                .chain((x0 + 1..=x1).rev().map(|x| (x, y1)))
+                .chain((y0 + 1..=y1).rev().map(|y| (x0, y)))
+                .filter(|point| boundary_points.contains(point))
+                .collect::<Vec<_>>();
+            let center = vertex(((x0 + x1) / 2, (y0 + y1) / 2));
+            for (a, b) in perimeter.iter().zip(perimeter.iter().cycle().skip(1)) {
+                triangles.push((center, vertex(*a), vertex(*b)).into());
+            }
+        }
+        for [a, b, c] in bridges {
+            triangles.push((vertex(a), vertex(b), vertex(c)).into());
+        }
+        NavMesh::new(vertices, triangles)
+    }
+}
+
+/// Greedily merges walkable cells into rectangles, row by row.
+///
+/// # Returns
+/// List of rectangles: (column, row, width, height).
+fn merge_rects(
+    cols: usize,
+    rows: usize,
+    walkable: &impl Fn(usize, usize) -> bool,
+) -> Vec<(usize, usize, usize, usize)> {
+    let mut used = vec![false; cols * rows];
+    let mut result = vec![];
+    for row in 0..rows {
+        let mut col = 0;
+        while col < cols {
+            if used[row * cols + col] || !walkable(col, row) {
+                col += 1;
+                continue;
+            }
+            let width = (col..cols)
+                .take_while(|c| !used[row * cols + c] && walkable(*c, row))
+                .count();
+            let height = (row..rows)
+                .take_while(|r| (col..col + width).all(|c| !used[r * cols + c] && walkable(c, *r)))
+                .count();
+            for r in row..row + height {
+                for c in col..col + width {
+                    used[r * cols + c] = true;
+                }
+            }
+            result.push((col, row, width, height));
+            col += width;
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nav_mesh_from_grid() {
+        #[rustfmt::skip]
+        let blocked = [
+            false, false, false, false,
+            false, true, true, false,
+            false, true, true, false,
+            false, false, false, false,
+        ];
+        let mesh = NavMesh::from_grid(4, 4, 10.0, &blocked, false).unwrap();
+        let from: NavVec3 = (5.0, 5.0, 0.0).into();
+        let to: NavVec3 = (35.0, 35.0, 0.0).into();
+        let path = mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        let length = path
+            .windows(2)
+            .map(|w| (w[1] - w[0]).magnitude())
+            .sum::<Scalar>();
+        assert!(length > (to - from).magnitude() + 1.0);
+        for point in &path {
+            let inside_block = point.x > 10.0 && point.x < 30.0 && point.y > 10.0 && point.y < 30.0;
+            assert!(!inside_block);
+        }
+    }
+
+    #[test]
+    fn test_nav_mesh_from_grid_diagonals() {
+        let blocked = [false, true, true, false];
+        let from: NavVec3 = (5.0, 5.0, 0.0).into();
+        let to: NavVec3 = (15.0, 15.0, 0.0).into();
+        let mesh = NavMesh::from_grid(2, 2, 10.0, &blocked, false).unwrap();
+        assert!(mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .is_none());
+        let mesh = NavMesh::from_grid(2, 2, 10.0, &blocked, true).unwrap();
+        assert!(mesh
+            .find_path(from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .is_some());
+    }
+}
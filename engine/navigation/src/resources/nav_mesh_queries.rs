@@ -0,0 +1,337 @@
+use core::Scalar;
+use navmesh::*;
+use std::collections::HashMap;
+
+pub(crate) const EPSILON: Scalar = 1.0e-4;
+
+/// Additional spatial queries performed on nav mesh geometry.
+pub trait NavMeshQueries {
+    /// Shortens path with string pulling - removes path points as long as straight segments
+    /// between remaining ones stay on nav mesh.
+    ///
+    /// # Arguments
+    /// * `path` - path points on nav mesh.
+    ///
+    /// # Returns
+    /// Smoothed path that starts and ends at the same points as the source path.
+    fn smooth_path(&self, path: &[NavVec3]) -> Vec<NavVec3>;
+
+    /// Projects point onto nearest nav mesh triangle. Unlike `NavMesh::closest_point`, it
+    /// always checks all triangles and when point is equally distant from many of them, the one
+    /// with lowest index wins, so results are reproducible.
+    ///
+    /// # Arguments
+    /// * `point` - query point.
+    ///
+    /// # Returns
+    /// `Some` with point on nav mesh or `None` if nav mesh has no triangles.
+    fn project_point(&self, point: NavVec3) -> Option<NavVec3>;
+
+    /// Walks nav mesh triangles along segment, looking for place where it leaves nav mesh.
+    ///
+    /// # Arguments
+    /// * `from` - segment start point.
+    /// * `to` - segment end point.
+    ///
+    /// # Returns
+    /// `Some` with first point where segment crosses nav mesh boundary or `None` if whole
+    /// segment stays on nav mesh (or `from` is not on nav mesh at all).
+    fn raycast(&self, from: NavVec3, to: NavVec3) -> Option<NavVec3>;
+}
+
+impl NavMeshQueries for NavMesh {
+    fn smooth_path(&self, path: &[NavVec3]) -> Vec<NavVec3> {
+        if path.len() <= 2 {
+            return path.to_vec();
+        }
+        let edges = edge_triangles(self);
+        let mut result = Vec::with_capacity(path.len());
+        result.push(path[0]);
+        let mut current = 0;
+        while current < path.len() - 1 {
+            let next = (current + 2..path.len())
+                .rev()
+                .find(|index| {
+                    matches!(
+                        segment_exit(self, &edges, path[current], path[*index]),
+                        Some(None)
+                    )
+                })
+                .unwrap_or(current + 1);
+            result.push(path[next]);
+            current = next;
+        }
+        result
+    }
+
+    fn project_point(&self, point: NavVec3) -> Option<NavVec3> {
+        let mut result = None;
+        let mut best = Scalar::INFINITY;
+        for index in 0..self.triangles().len() {
+            let (_, [a, b, c]) = triangle_points(self, index);
+            let projected = closest_point_on_triangle(point, a, b, c);
+            let distance = (projected - point).sqr_magnitude();
+            if distance < best {
+                best = distance;
+                result = Some(projected);
+            }
+        }
+        result
+    }
+
+    fn raycast(&self, from: NavVec3, to: NavVec3) -> Option<NavVec3> {
+        segment_exit(self, &edge_triangles(self), from, to).flatten()
+    }
+}
+
+/// { sorted edge vertices: triangles using that edge }
+pub(crate) fn edge_triangles(mesh: &NavMesh) -> HashMap<(u32, u32), Vec<usize>> {
+    let mut result = HashMap::<_, Vec<_>>::with_capacity(mesh.triangles().len() * 3);
+    for (index, triangle) in mesh.triangles().iter().enumerate() {
+        let ids = [triangle.first, triangle.second, triangle.third];
+        for i in 0..3 {
+            result
+                .entry(edge_key(ids[i], ids[(i + 1) % 3]))
+                .or_default()
+                .push(index);
+        }
+    }
+    result
+}
+
+pub(crate) fn edge_key(a: u32, b: u32) -> (u32, u32) {
+    if a < b {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+pub(crate) fn triangle_points(mesh: &NavMesh, index: usize) -> ([u32; 3], [NavVec3; 3]) {
+    let triangle = &mesh.triangles()[index];
+    let ids = [triangle.first, triangle.second, triangle.third];
+    let vertices = mesh.vertices();
+    let points = [
+        vertices[ids[0] as usize],
+        vertices[ids[1] as usize],
+        vertices[ids[2] as usize],
+    ];
+    (ids, points)
+}
+
+/// Normal of triangle edge plane (perpendicular to triangle normal), pointing inside triangle.
+fn edge_normal(from: NavVec3, to: NavVec3, opposite: NavVec3, normal: NavVec3) -> NavVec3 {
+    let result = (to - from).cross(normal);
+    if result.dot(opposite - from) < 0.0 {
+        (result * -1.0).normalize()
+    } else {
+        result.normalize()
+    }
+}
+
+fn closest_point_on_triangle(p: NavVec3, a: NavVec3, b: NavVec3, c: NavVec3) -> NavVec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = p - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = p - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = p - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let denom = 1.0 / (va + vb + vc);
+    a + ab * (vb * denom) + ac * (vc * denom)
+}
+
+/// Finds triangle that contains given point - when point lies on shared edge or vertex, triangle
+/// with lowest index wins.
+pub(crate) fn containing_triangle(mesh: &NavMesh, point: NavVec3) -> Option<usize> {
+    let mut result = None;
+    let mut best = Scalar::INFINITY;
+    for index in 0..mesh.triangles().len() {
+        let (_, [a, b, c]) = triangle_points(mesh, index);
+        let normal = (b - a).cross(c - a);
+        if normal.sqr_magnitude() <= 0.0 {
+            continue;
+        }
+        let inside = [(a, b, c), (b, c, a), (c, a, b)]
+            .iter()
+            .all(|(u, v, w)| edge_normal(*u, *v, *w, normal).dot(point - *u) >= -EPSILON);
+        if inside {
+            let distance = normal.normalize().dot(point - a).abs();
+            if distance < best {
+                best = distance;
+                result = Some(index);
+            }
+        }
+    }
+    result
+}
+
+/// Walks triangles along segment.
+///
+/// # Returns
+/// `None` if `from` is not on nav mesh, `Some(None)` if whole segment stays on nav mesh,
+/// `Some(Some(point))` with point where segment leaves nav mesh otherwise.
+fn segment_exit(
+    mesh: &NavMesh,
+    edges: &HashMap<(u32, u32), Vec<usize>>,
+    from: NavVec3,
+    to: NavVec3,
+) -> Option<Option<NavVec3>> {
+    let mut current = containing_triangle(mesh, from)?;
+    let mut entry = None;
+    let mut t = 0.0;
+    for _ in 0..=mesh.triangles().len() {
+        let (ids, points) = triangle_points(mesh, current);
+        let normal = (points[1] - points[0]).cross(points[2] - points[0]);
+        let mut exit = None;
+        for i in 0..3 {
+            let key = edge_key(ids[i], ids[(i + 1) % 3]);
+            if entry == Some(key) {
+                continue;
+            }
+            let u = points[i];
+            let m = edge_normal(u, points[(i + 1) % 3], points[(i + 2) % 3], normal);
+            let d_from = m.dot(from - u);
+            let d_to = m.dot(to - u);
+            if d_to >= -EPSILON {
+                continue;
+            }
+            let edge_t = (d_from / (d_from - d_to)).max(t);
+            if exit.map(|(t, _)| edge_t < t).unwrap_or(true) {
+                exit = Some((edge_t, key));
+            }
+        }
+        let (edge_t, key) = match exit {
+            Some(exit) => exit,
+            None => return Some(None),
+        };
+        t = edge_t;
+        let next = edges
+            .get(&key)
+            .and_then(|triangles| triangles.iter().find(|index| **index != current));
+        match next {
+            Some(next) => {
+                current = *next;
+                entry = Some(key);
+            }
+            None => break,
+        }
+    }
+    Some(Some(from + (to - from) * t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path_length(path: &[NavVec3]) -> Scalar {
+        path.windows(2).map(|w| (w[1] - w[0]).magnitude()).sum()
+    }
+
+    // L shape made of three squares, with top-left square missing.
+    fn l_shape_mesh() -> NavMesh {
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(),
+            (10.0, 0.0, 0.0).into(),
+            (20.0, 0.0, 0.0).into(),
+            (0.0, 10.0, 0.0).into(),
+            (10.0, 10.0, 0.0).into(),
+            (20.0, 10.0, 0.0).into(),
+            (10.0, 20.0, 0.0).into(),
+            (20.0, 20.0, 0.0).into(),
+        ];
+        let triangles = vec![
+            (0, 1, 4).into(),
+            (4, 3, 0).into(),
+            (1, 2, 5).into(),
+            (5, 4, 1).into(),
+            (4, 5, 7).into(),
+            (7, 6, 4).into(),
+        ];
+        NavMesh::new(vertices, triangles).unwrap()
+    }
+
+    #[test]
+    fn test_smooth_path() {
+        let mesh = l_shape_mesh();
+        let path: Vec<NavVec3> = vec![
+            (1.0, 5.0, 0.0).into(),
+            (10.0, 5.0, 0.0).into(),
+            (15.0, 10.0, 0.0).into(),
+            (15.0, 19.0, 0.0).into(),
+        ];
+        let smoothed = mesh.smooth_path(&path);
+        assert_eq!(smoothed.len(), 3);
+        assert!(path_length(&smoothed) < path_length(&path));
+        assert_eq!(smoothed[0].x, path[0].x);
+        assert_eq!(smoothed[0].y, path[0].y);
+        assert_eq!(smoothed[2].x, path[3].x);
+        assert_eq!(smoothed[2].y, path[3].y);
+    }
+
+    #[test]
+    fn test_project_point() {
+        let mesh = l_shape_mesh();
+        let point = mesh.project_point((3.0, 4.0, 0.0).into()).unwrap();
+        assert!((point.x - 3.0).abs() < 1.0e-6 && (point.y - 4.0).abs() < 1.0e-6);
+        let point = mesh.project_point((25.0, 5.0, 2.0).into()).unwrap();
+        assert!((point.x - 20.0).abs() < 1.0e-6 && (point.y - 5.0).abs() < 1.0e-6);
+        assert!(point.z.abs() < 1.0e-6);
+        // equally distant from bottom-left and top-right squares.
+        let point = mesh.project_point((5.0, 15.0, 0.0).into()).unwrap();
+        assert!((point.x - 5.0).abs() < 1.0e-6 && (point.y - 10.0).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_raycast() {
+        // two triangles forming quad with notch at (5, 4).
+        let vertices = vec![
+            (0.0, 0.0, 0.0).into(),
+            (10.0, 0.0, 0.0).into(),
+            (10.0, 10.0, 0.0).into(),
+            (5.0, 4.0, 0.0).into(),
+        ];
+        let triangles = vec![(0, 1, 3).into(), (1, 2, 3).into()];
+        let mesh = NavMesh::new(vertices, triangles).unwrap();
+
+        assert!(mesh
+            .raycast((8.0, 1.0, 0.0).into(), (9.0, 8.0, 0.0).into())
+            .is_none());
+        assert!(mesh
+            .raycast((8.0, 1.0, 0.0).into(), (8.0, 1.0, 0.0).into())
+            .is_none());
+        assert!(mesh
+            .raycast((0.0, 9.0, 0.0).into(), (8.0, 1.0, 0.0).into())
+            .is_none());
+        let hit = mesh
+            .raycast((2.0, 1.0, 0.0).into(), (8.0, 9.0, 0.0).into())
+            .unwrap();
+        assert!((hit.x - 3.125).abs() < 1.0e-4);
+        assert!((hit.y - 2.5).abs() < 1.0e-4);
+    }
+}
@@ -1,9 +1,25 @@
+use crate::resources::{
+    nav_areas::{find_weighted_path, NavAreaId, NavAreas},
+    nav_mesh_queries::containing_triangle,
+    nav_obstacles::{carve, NavObstacle, NavObstacleId},
+    nav_offmesh_links::{find_linked_path, NavOffMeshLink, NavOffMeshLinkId, NavPathNode},
+};
+use core::Scalar;
 use navmesh::*;
 use std::collections::HashMap;
 
 /// ECS resource that holds and manages nav meshes.
 #[derive(Debug, Default)]
-pub struct NavMeshes(pub(crate) HashMap<NavMeshID, NavMesh>);
+pub struct NavMeshes {
+    meshes: HashMap<NavMeshID, NavMesh>,
+    obstacles: HashMap<NavObstacleId, NavObstacle>,
+    /// { source mesh id: source mesh without triangles blocked by obstacles }
+    carved: HashMap<NavMeshID, Option<NavMesh>>,
+    /// { mesh id: number of obstacles added so far }
+    revisions: HashMap<NavMeshID, usize>,
+    links: HashMap<NavOffMeshLinkId, NavOffMeshLink>,
+    areas: HashMap<NavMeshID, NavAreas>,
+}
 
 impl NavMeshes {
     /// Register new nav mesh.
@@ -16,7 +32,21 @@ impl NavMeshes {
     #[inline]
     pub fn register(&mut self, mesh: NavMesh) -> NavMeshID {
         let id = mesh.id();
-        self.0.insert(id, mesh);
+        self.meshes.insert(id, mesh);
+        id
+    }
+
+    /// Register new nav mesh with its triangles tagged with areas.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh object.
+    /// * `areas` - area of every nav mesh triangle, in order of nav mesh triangles.
+    ///
+    /// # Returns
+    /// Identifier of registered nav mesh.
+    pub fn register_with_areas(&mut self, mesh: NavMesh, areas: Vec<NavAreaId>) -> NavMeshID {
+        let id = self.register(mesh);
+        self.areas.insert(id, NavAreas::new(areas));
         id
     }
 
@@ -29,19 +59,29 @@ impl NavMeshes {
     /// `Some` with nav mesh object if nav mesh with given identifier was found, `None` otherwise.
     #[inline]
     pub fn unregister(&mut self, id: NavMeshID) -> Option<NavMesh> {
-        self.0.remove(&id)
+        self.obstacles.retain(|_, obstacle| obstacle.mesh() != id);
+        self.links.retain(|_, link| link.mesh() != id);
+        self.areas.remove(&id);
+        self.carved.remove(&id);
+        self.revisions.remove(&id);
+        self.meshes.remove(&id)
     }
 
     /// Unregister all nav meshes.
     #[inline]
     pub fn unregister_all(&mut self) {
-        self.0.clear()
+        self.meshes.clear();
+        self.obstacles.clear();
+        self.carved.clear();
+        self.revisions.clear();
+        self.links.clear();
+        self.areas.clear();
     }
 
     /// Get nav meshes iterator.
     #[inline]
     pub fn meshes_iter(&self) -> impl Iterator<Item = &NavMesh> {
-        self.0.values()
+        self.meshes.values()
     }
 
     /// Find nav mesh by its identifier.
@@ -53,10 +93,12 @@ impl NavMeshes {
     /// `Some` with nav mesh if exists or `None` otherwise.
     #[inline]
     pub fn find_mesh(&self, id: NavMeshID) -> Option<&NavMesh> {
-        self.0.get(&id)
+        self.meshes.get(&id)
     }
 
     /// Find nav mesh by its identifier.
+    /// Note that changes made to nav mesh are not reflected in its carved version until any of
+    /// its obstacles change.
     ///
     /// # Arguments
     /// * `id` - nav mesh identifier.
@@ -65,7 +107,7 @@ impl NavMeshes {
     /// `Some` with mutable nav mesh if exists or `None` otherwise.
     #[inline]
     pub fn find_mesh_mut(&mut self, id: NavMeshID) -> Option<&mut NavMesh> {
-        self.0.get_mut(&id)
+        self.meshes.get_mut(&id)
     }
 
     /// Find closest point on nav meshes.
@@ -77,7 +119,7 @@ impl NavMeshes {
     /// # Returns
     /// `Some` with nav mesh identifier and point on nav mesh if found or `None` otherwise.
     pub fn closest_point(&self, point: NavVec3, query: NavQuery) -> Option<(NavMeshID, NavVec3)> {
-        self.0
+        self.meshes
             .iter()
             .filter_map(|(id, mesh)| {
                 mesh.closest_point(point, query)
@@ -86,4 +128,490 @@ impl NavMeshes {
             .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
             .map(|(p, _, id)| (id, p))
     }
+
+    /// Find walkable part of nav mesh - nav mesh with triangles blocked by obstacles removed.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    ///
+    /// # Returns
+    /// `Some` with walkable nav mesh if exists or `None` otherwise.
+    pub fn find_walkable_mesh(&self, id: NavMeshID) -> Option<&NavMesh> {
+        match self.carved.get(&id) {
+            Some(mesh) => mesh.as_ref(),
+            None => self.meshes.get(&id),
+        }
+    }
+
+    /// Find path on walkable part of nav mesh, traversing its off-mesh links where it pays off.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `from` - path start point.
+    /// * `to` - path end point.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    ///
+    /// # Returns
+    /// `Some` with path points if found or `None` otherwise.
+    pub fn find_path(
+        &self,
+        id: NavMeshID,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+    ) -> Option<Vec<NavVec3>> {
+        self.find_path_nodes(id, from, to, query, mode)
+            .map(|nodes| nodes.into_iter().map(|node| node.position).collect())
+    }
+
+    /// Find path on walkable part of nav mesh, traversing its off-mesh links where it pays off.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `from` - path start point.
+    /// * `to` - path end point.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    ///
+    /// # Returns
+    /// `Some` with path nodes, telling which segments go through off-mesh links, if found or
+    /// `None` otherwise.
+    pub fn find_path_nodes(
+        &self,
+        id: NavMeshID,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+    ) -> Option<Vec<NavPathNode>> {
+        self.find_path_nodes_with_costs(id, from, to, query, mode, &Default::default())
+    }
+
+    /// Find path on walkable part of nav mesh, traversing its off-mesh links where it pays off
+    /// and using custom costs of given areas.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `from` - path start point.
+    /// * `to` - path end point.
+    /// * `query` - query quality.
+    /// * `mode` - path finding quality.
+    /// * `area_costs` - area cost multipliers used instead of ones set for nav mesh.
+    ///
+    /// # Returns
+    /// `Some` with path nodes, telling which segments go through off-mesh links, if found or
+    /// `None` otherwise.
+    pub fn find_path_nodes_with_costs(
+        &self,
+        id: NavMeshID,
+        from: NavVec3,
+        to: NavVec3,
+        query: NavQuery,
+        mode: NavPathMode,
+        area_costs: &HashMap<NavAreaId, Scalar>,
+    ) -> Option<Vec<NavPathNode>> {
+        let mesh = self.find_walkable_mesh(id)?;
+        let costs = match (self.meshes.get(&id), self.areas.get(&id)) {
+            (Some(source), Some(areas)) if areas.is_weighted() || !area_costs.is_empty() => {
+                Some(areas.triangle_costs(source, mesh, area_costs))
+            }
+            _ => None,
+        };
+        let walk = |from, to| match &costs {
+            Some(costs) => find_weighted_path(mesh, costs, from, to),
+            None => mesh.find_path(from, to, query, mode),
+        };
+        let links = self
+            .links
+            .values()
+            .filter(|link| link.mesh() == id)
+            .collect::<Vec<_>>();
+        if links.is_empty() {
+            return walk(from, to).map(|path| self.path_nodes(id, &path));
+        }
+        find_linked_path(&links, from, to, |from, to| {
+            let path = walk(from, to)?;
+            let cost = match &costs {
+                Some(costs) => path_cost(mesh, costs, &path),
+                None => path.windows(2).map(|w| (w[1] - w[0]).magnitude()).sum(),
+            };
+            Some((path, cost))
+        })
+    }
+
+    /// Turns path points into path nodes, marking segments that traverse off-mesh links of
+    /// given nav mesh.
+    pub fn path_nodes(&self, id: NavMeshID, path: &[NavVec3]) -> Vec<NavPathNode> {
+        path.iter()
+            .enumerate()
+            .map(|(index, position)| {
+                let via_link = index > 0
+                    && self
+                        .links
+                        .values()
+                        .any(|link| link.mesh() == id && link.connects(path[index - 1], *position));
+                NavPathNode::new(*position, via_link)
+            })
+            .collect()
+    }
+
+    /// Find areas of nav mesh triangles.
+    pub fn find_areas(&self, id: NavMeshID) -> Option<&NavAreas> {
+        self.areas.get(&id)
+    }
+
+    /// Sets multiplier of cost of walking through triangles of given area, so paths go around
+    /// expensive areas when it pays off.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `area` - area identifier.
+    /// * `multiplier` - cost of walking unit distance through area.
+    ///
+    /// # Returns
+    /// `false` if nav mesh does not exist.
+    pub fn set_area_cost(&mut self, id: NavMeshID, area: NavAreaId, multiplier: Scalar) -> bool {
+        if !self.meshes.contains_key(&id) {
+            return false;
+        }
+        self.areas
+            .entry(id)
+            .or_default()
+            .set_area_cost(area, multiplier);
+        true
+    }
+
+    /// Tells if paths on given nav mesh are found with area costs - smoothing them could cut
+    /// through expensive areas.
+    pub fn has_area_costs(&self, id: NavMeshID) -> bool {
+        self.areas
+            .get(&id)
+            .map(|areas| areas.is_weighted())
+            .unwrap_or_default()
+    }
+
+    /// Register off-mesh link between two points of nav mesh.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `from` - link start point.
+    /// * `to` - link end point.
+    /// * `bidirectional` - tells if link can be traversed from its end to its start too.
+    /// * `cost` - cost of traversing link, compared against length of walked paths.
+    ///
+    /// # Returns
+    /// `Some` with off-mesh link identifier or `None` if nav mesh does not exist.
+    pub fn add_offmesh_link(
+        &mut self,
+        id: NavMeshID,
+        from: NavVec3,
+        to: NavVec3,
+        bidirectional: bool,
+        cost: Scalar,
+    ) -> Option<NavOffMeshLinkId> {
+        if !self.meshes.contains_key(&id) {
+            return None;
+        }
+        let result = NavOffMeshLinkId::new();
+        self.links.insert(
+            result,
+            NavOffMeshLink::new(id, from, to, bidirectional, cost),
+        );
+        Some(result)
+    }
+
+    /// Unregister off-mesh link.
+    ///
+    /// # Arguments
+    /// * `id` - off-mesh link identifier.
+    ///
+    /// # Returns
+    /// `Some` with off-mesh link if found or `None` otherwise.
+    pub fn remove_offmesh_link(&mut self, id: NavOffMeshLinkId) -> Option<NavOffMeshLink> {
+        self.links.remove(&id)
+    }
+
+    /// Find off-mesh link by its identifier.
+    pub fn find_offmesh_link(&self, id: NavOffMeshLinkId) -> Option<&NavOffMeshLink> {
+        self.links.get(&id)
+    }
+
+    /// Get off-mesh links iterator.
+    pub fn offmesh_links_iter(&self) -> impl Iterator<Item = (NavOffMeshLinkId, &NavOffMeshLink)> {
+        self.links.iter().map(|(id, link)| (*id, link))
+    }
+
+    /// Register obstacle that carves nav mesh.
+    ///
+    /// # Arguments
+    /// * `id` - nav mesh identifier.
+    /// * `polygon` - convex polygon points.
+    ///
+    /// # Returns
+    /// `Some` with obstacle identifier or `None` if nav mesh does not exist or polygon is
+    /// degenerated.
+    pub fn add_obstacle(&mut self, id: NavMeshID, polygon: &[NavVec3]) -> Option<NavObstacleId> {
+        if !self.meshes.contains_key(&id) {
+            return None;
+        }
+        let obstacle = NavObstacle::new(id, polygon)?;
+        let result = NavObstacleId::new();
+        self.obstacles.insert(result, obstacle);
+        *self.revisions.entry(id).or_default() += 1;
+        self.carve(id);
+        Some(result)
+    }
+
+    /// Unregister obstacle.
+    ///
+    /// # Arguments
+    /// * `id` - obstacle identifier.
+    ///
+    /// # Returns
+    /// `Some` with obstacle if found or `None` otherwise.
+    pub fn remove_obstacle(&mut self, id: NavObstacleId) -> Option<NavObstacle> {
+        let result = self.obstacles.remove(&id)?;
+        self.carve(result.mesh());
+        Some(result)
+    }
+
+    /// Find obstacle by its identifier.
+    pub fn find_obstacle(&self, id: NavObstacleId) -> Option<&NavObstacle> {
+        self.obstacles.get(&id)
+    }
+
+    /// Get obstacles iterator.
+    pub fn obstacles_iter(&self) -> impl Iterator<Item = (NavObstacleId, &NavObstacle)> {
+        self.obstacles.iter().map(|(id, obstacle)| (*id, obstacle))
+    }
+
+    /// Number of obstacles added to nav mesh so far - used to detect paths that need to be
+    /// checked against new obstacles.
+    pub fn obstacles_revision(&self, id: NavMeshID) -> usize {
+        self.revisions.get(&id).copied().unwrap_or_default()
+    }
+
+    /// Tells if path crosses any obstacle placed on given nav mesh.
+    pub fn is_path_blocked(&self, id: NavMeshID, path: &[NavVec3]) -> bool {
+        self.obstacles
+            .values()
+            .any(|obstacle| obstacle.mesh() == id && obstacle.blocks_path(path))
+    }
+
+    fn carve(&mut self, id: NavMeshID) {
+        let mesh = match self.meshes.get(&id) {
+            Some(mesh) => mesh,
+            None => return,
+        };
+        let obstacles = self
+            .obstacles
+            .values()
+            .filter(|obstacle| obstacle.mesh() == id);
+        if obstacles.clone().next().is_some() {
+            self.carved.insert(id, carve(mesh, obstacles));
+        } else {
+            self.carved.remove(&id);
+        }
+    }
+}
+
+/// Sum of path segments lengths, multiplied by cost of triangles their centers lie in.
+fn path_cost(mesh: &NavMesh, costs: &[Scalar], path: &[NavVec3]) -> Scalar {
+    path.windows(2)
+        .map(|w| {
+            let cost = containing_triangle(mesh, (w[0] + w[1]) * 0.5)
+                .and_then(|index| costs.get(index).copied())
+                .unwrap_or(1.0);
+            (w[1] - w[0]).magnitude() * cost
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::components::{NavAgent, NavAgentEvent};
+
+    fn path_length(path: &[NavVec3]) -> Scalar {
+        path.windows(2).map(|w| (w[1] - w[0]).magnitude()).sum()
+    }
+
+    // 3x3 grid of 10 units wide cells, without cells of given indices.
+    fn grid_mesh(holes: &[u32]) -> NavMesh {
+        let vertices: Vec<NavVec3> = (0..16)
+            .map(|index| {
+                (
+                    (index % 4) as Scalar * 10.0,
+                    (index / 4) as Scalar * 10.0,
+                    0.0,
+                )
+                    .into()
+            })
+            .collect();
+        let triangles: Vec<NavTriangle> = (0u32..9)
+            .filter(|index| !holes.contains(index))
+            .flat_map(|index| -> Vec<NavTriangle> {
+                let first = (index / 3) * 4 + index % 3;
+                vec![
+                    (first, first + 1, first + 5).into(),
+                    (first + 5, first + 4, first).into(),
+                ]
+            })
+            .collect();
+        NavMesh::new(vertices, triangles).unwrap()
+    }
+
+    #[test]
+    fn test_nav_mesh_obstacles() {
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(grid_mesh(&[]));
+        let from: NavVec3 = (5.0, 15.0, 0.0).into();
+        let to: NavVec3 = (25.0, 15.0, 0.0).into();
+        let straight = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert_eq!(meshes.obstacles_revision(mesh), 0);
+
+        let polygon: [NavVec3; 4] = [
+            (12.0, 12.0, 0.0).into(),
+            (18.0, 12.0, 0.0).into(),
+            (18.0, 18.0, 0.0).into(),
+            (12.0, 18.0, 0.0).into(),
+        ];
+        let obstacle = meshes.add_obstacle(mesh, &polygon).unwrap();
+        assert_eq!(meshes.obstacles_revision(mesh), 1);
+        assert!(meshes.is_path_blocked(mesh, &straight));
+        let detour = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!(!meshes.is_path_blocked(mesh, &detour));
+        assert!(path_length(&detour) > path_length(&straight));
+
+        assert!(meshes.remove_obstacle(obstacle).is_some());
+        assert!(meshes.find_obstacle(obstacle).is_none());
+        let path = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!((path_length(&path) - path_length(&straight)).abs() < 1.0e-4);
+    }
+
+    #[test]
+    fn test_nav_mesh_offmesh_links() {
+        // gap in two lower cells of middle column makes path go around it.
+        let mut meshes = NavMeshes::default();
+        let mesh = meshes.register(grid_mesh(&[1, 4]));
+        let from: NavVec3 = (5.0, 5.0, 0.0).into();
+        let to: NavVec3 = (25.0, 5.0, 0.0).into();
+        let walk = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!(path_length(&walk) > 40.0);
+        assert!(meshes
+            .find_path_nodes(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap()
+            .iter()
+            .all(|node| !node.via_link));
+
+        let link = meshes
+            .add_offmesh_link(
+                mesh,
+                (8.0, 5.0, 0.0).into(),
+                (22.0, 5.0, 0.0).into(),
+                false,
+                14.0,
+            )
+            .unwrap();
+        let nodes = meshes
+            .find_path_nodes(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert_eq!(nodes.iter().filter(|node| node.via_link).count(), 1);
+        let path = nodes.iter().map(|node| node.position).collect::<Vec<_>>();
+        assert!(path_length(&path) < path_length(&walk));
+        assert_eq!(meshes.path_nodes(mesh, &path).len(), nodes.len());
+        assert!(meshes
+            .path_nodes(mesh, &path)
+            .iter()
+            .zip(nodes.iter())
+            .all(|(a, b)| a.via_link == b.via_link));
+
+        // one-way link is not used to go back.
+        let back = meshes
+            .find_path_nodes(mesh, to, from, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!(back.iter().all(|node| !node.via_link));
+
+        let mut agent = NavAgent::new(from);
+        agent.teleport_offmesh_links = true;
+        agent.set_path_nodes(nodes);
+        let mut teleported = false;
+        for _ in 0..100 {
+            match agent.process(0.1) {
+                NavAgentEvent::OffMeshLinkTraversed(index) => {
+                    teleported = true;
+                    assert!(agent.path_nodes().unwrap()[index].via_link);
+                    assert!((agent.position.x - 22.0).abs() < 1.0e-4);
+                }
+                NavAgentEvent::DestinationReached => break,
+                _ => {}
+            }
+        }
+        assert!(teleported);
+        assert!(agent.destination_reached());
+
+        assert!(meshes.remove_offmesh_link(link).is_some());
+        assert_eq!(meshes.offmesh_links_iter().count(), 0);
+    }
+
+    #[test]
+    fn test_nav_mesh_area_costs() {
+        // middle cell is mud.
+        let mut meshes = NavMeshes::default();
+        let areas = (0..18).map(|index| (index / 2 == 4) as NavAreaId).collect();
+        let mesh = meshes.register_with_areas(grid_mesh(&[]), areas);
+        let mud = NavObstacle::new(
+            mesh,
+            &[
+                (11.0, 11.0, 0.0).into(),
+                (19.0, 11.0, 0.0).into(),
+                (19.0, 19.0, 0.0).into(),
+                (11.0, 19.0, 0.0).into(),
+            ],
+        )
+        .unwrap();
+        let from: NavVec3 = (5.0, 15.0, 0.0).into();
+        let to: NavVec3 = (25.0, 15.0, 0.0).into();
+        let straight = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!(mud.blocks_path(&straight));
+        assert!(!meshes.has_area_costs(mesh));
+
+        assert!(meshes.set_area_cost(mesh, 1, 10.0));
+        assert!(meshes.has_area_costs(mesh));
+        assert_eq!(meshes.find_areas(mesh).unwrap().triangle_area(8), 1);
+        let detour = meshes
+            .find_path(mesh, from, to, NavQuery::Accuracy, NavPathMode::Accuracy)
+            .unwrap();
+        assert!(!mud.blocks_path(&detour));
+        assert!(path_length(&detour) > path_length(&straight));
+
+        // amphibious agents ignore mud cost.
+        let overrides = [(1, 1.0)].into_iter().collect();
+        let path = meshes
+            .find_path_nodes_with_costs(
+                mesh,
+                from,
+                to,
+                NavQuery::Accuracy,
+                NavPathMode::Accuracy,
+                &overrides,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|node| node.position)
+            .collect::<Vec<_>>();
+        assert!(mud.blocks_path(&path));
+    }
 }
@@ -0,0 +1,111 @@
+use crate::resources::nav_mesh_queries::EPSILON;
+use core::{id::ID, Scalar};
+use navmesh::*;
+
+/// Nav obstacle identifier.
+pub type NavObstacleId = ID<NavObstacle>;
+
+/// Convex polygon that blocks nav mesh triangles it overlaps.
+#[derive(Debug, Clone)]
+pub struct NavObstacle {
+    mesh: NavMeshID,
+    polygon: Vec<NavVec3>,
+    normal: NavVec3,
+}
+
+impl NavObstacle {
+    /// Creates new obstacle.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh identifier that obstacle carves.
+    /// * `polygon` - convex polygon points.
+    ///
+    /// # Returns
+    /// `None` if polygon has less than 3 points or all of them lie on one line.
+    pub fn new(mesh: NavMeshID, polygon: &[NavVec3]) -> Option<Self> {
+        let normal = polygon
+            .windows(3)
+            .map(|w| (w[1] - w[0]).cross(w[2] - w[0]))
+            .find(|normal| normal.sqr_magnitude() > EPSILON * EPSILON)?
+            .normalize();
+        Some(Self {
+            mesh,
+            polygon: polygon.to_vec(),
+            normal,
+        })
+    }
+
+    pub fn mesh(&self) -> NavMeshID {
+        self.mesh
+    }
+
+    pub fn polygon(&self) -> &[NavVec3] {
+        &self.polygon
+    }
+
+    /// Tells if convex shape (triangle, segment) overlaps obstacle polygon, when projected onto
+    /// obstacle plane.
+    pub fn overlaps(&self, points: &[NavVec3]) -> bool {
+        if points.is_empty() {
+            return false;
+        }
+        let axes = self
+            .polygon
+            .iter()
+            .zip(self.polygon.iter().cycle().skip(1))
+            .chain(points.iter().zip(points.iter().cycle().skip(1)))
+            .map(|(from, to)| (*to - *from).cross(self.normal))
+            .filter(|axis| axis.sqr_magnitude() > EPSILON * EPSILON);
+        for axis in axes {
+            let (a_min, a_max) = project(&self.polygon, axis);
+            let (b_min, b_max) = project(points, axis);
+            if a_max <= b_min + EPSILON || b_max <= a_min + EPSILON {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Tells if any path segment crosses obstacle.
+    pub fn blocks_path(&self, path: &[NavVec3]) -> bool {
+        path.windows(2).any(|segment| self.overlaps(segment))
+    }
+}
+
+fn project(points: &[NavVec3], axis: NavVec3) -> (Scalar, Scalar) {
+    points.iter().fold(
+        (Scalar::INFINITY, Scalar::NEG_INFINITY),
+        |(min, max), point| {
+            let value = axis.dot(*point);
+            (min.min(value), max.max(value))
+        },
+    )
+}
+
+/// Builds nav mesh without triangles blocked by obstacles.
+///
+/// # Returns
+/// `None` if there is no walkable triangle left.
+pub(crate) fn carve<'a>(
+    mesh: &NavMesh,
+    obstacles: impl Iterator<Item = &'a NavObstacle> + Clone,
+) -> Option<NavMesh> {
+    let vertices = mesh.vertices();
+    let triangles = mesh
+        .triangles()
+        .iter()
+        .filter(|triangle| {
+            let points = [
+                vertices[triangle.first as usize],
+                vertices[triangle.second as usize],
+                vertices[triangle.third as usize],
+            ];
+            !obstacles.clone().any(|obstacle| obstacle.overlaps(&points))
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    if triangles.is_empty() {
+        return None;
+    }
+    NavMesh::new(vertices.to_vec(), triangles).ok()
+}
@@ -0,0 +1,234 @@
+use crate::resources::nav_mesh_queries::{NavMeshQueries, EPSILON};
+use core::{id::ID, Scalar};
+use navmesh::*;
+
+/// Nav off-mesh link identifier.
+pub type NavOffMeshLinkId = ID<NavOffMeshLink>;
+
+/// Connection between two nav mesh points that is not walkable surface (ledge to jump off,
+/// teleporter, ladder), traversed by path finding as single edge.
+#[derive(Debug, Clone)]
+pub struct NavOffMeshLink {
+    mesh: NavMeshID,
+    from: NavVec3,
+    to: NavVec3,
+    bidirectional: bool,
+    cost: Scalar,
+}
+
+impl NavOffMeshLink {
+    /// Creates new off-mesh link.
+    ///
+    /// # Arguments
+    /// * `mesh` - nav mesh identifier that link connects points of.
+    /// * `from` - link start point.
+    /// * `to` - link end point.
+    /// * `bidirectional` - tells if link can be traversed from its end to its start too.
+    /// * `cost` - cost of traversing link, compared against length of walked paths.
+    pub fn new(
+        mesh: NavMeshID,
+        from: NavVec3,
+        to: NavVec3,
+        bidirectional: bool,
+        cost: Scalar,
+    ) -> Self {
+        Self {
+            mesh,
+            from,
+            to,
+            bidirectional,
+            cost: cost.max(0.0),
+        }
+    }
+
+    pub fn mesh(&self) -> NavMeshID {
+        self.mesh
+    }
+
+    pub fn from(&self) -> NavVec3 {
+        self.from
+    }
+
+    pub fn to(&self) -> NavVec3 {
+        self.to
+    }
+
+    pub fn bidirectional(&self) -> bool {
+        self.bidirectional
+    }
+
+    pub fn cost(&self) -> Scalar {
+        self.cost
+    }
+
+    /// Tells if segment between given points traverses this link.
+    pub fn connects(&self, from: NavVec3, to: NavVec3) -> bool {
+        (same_point(from, self.from) && same_point(to, self.to))
+            || (self.bidirectional && same_point(from, self.to) && same_point(to, self.from))
+    }
+}
+
+/// Point of path found with off-mesh links taken into account.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct NavPathNode {
+    pub position: NavVec3,
+    /// Tells if segment ending at this point goes through off-mesh link instead of nav mesh.
+    pub via_link: bool,
+}
+
+impl NavPathNode {
+    pub fn new(position: NavVec3, via_link: bool) -> Self {
+        Self { position, via_link }
+    }
+}
+
+/// Shortens walked sections of path with string pulling, leaving off-mesh link segments intact.
+///
+/// # Arguments
+/// * `mesh` - nav mesh that walked sections lie on.
+/// * `nodes` - path nodes.
+///
+/// # Returns
+/// Smoothed path nodes.
+pub fn smooth_path_nodes(mesh: &NavMesh, nodes: &[NavPathNode]) -> Vec<NavPathNode> {
+    fn walk(result: &mut Vec<NavPathNode>, mesh: &NavMesh, section: &[NavVec3]) {
+        // first point of section is already in result, unless it is path start.
+        let skip = usize::from(!result.is_empty());
+        result.extend(
+            mesh.smooth_path(section)
+                .into_iter()
+                .skip(skip)
+                .map(|position| NavPathNode::new(position, false)),
+        );
+    }
+
+    let mut result = Vec::with_capacity(nodes.len());
+    let mut section = vec![];
+    for node in nodes {
+        if node.via_link {
+            walk(&mut result, mesh, &section);
+            result.push(*node);
+            section.clear();
+        }
+        section.push(node.position);
+    }
+    walk(&mut result, mesh, &section);
+    result
+}
+
+enum Edge {
+    Walk(Vec<NavVec3>),
+    Link,
+}
+
+/// Finds path that walks nav mesh and traverses off-mesh links, minimizing sum of walked
+/// distance and costs of used links.
+///
+/// # Arguments
+/// * `links` - off-mesh links of nav mesh.
+/// * `from` - path start point.
+/// * `to` - path end point.
+/// * `walk` - finds path on nav mesh between two points, together with its cost.
+pub(crate) fn find_linked_path(
+    links: &[&NavOffMeshLink],
+    from: NavVec3,
+    to: NavVec3,
+    walk: impl Fn(NavVec3, NavVec3) -> Option<(Vec<NavVec3>, Scalar)>,
+) -> Option<Vec<NavPathNode>> {
+    // graph nodes: path start, path end, then start and end point of every link.
+    let points = [from, to]
+        .into_iter()
+        .chain(links.iter().flat_map(|link| [link.from, link.to]))
+        .collect::<Vec<_>>();
+    let count = points.len();
+    let mut costs = vec![Scalar::INFINITY; count];
+    let mut visited = vec![false; count];
+    let mut previous = (0..count).map(|_| None).collect::<Vec<_>>();
+    costs[0] = 0.0;
+    while let Some(current) = (0..count)
+        .filter(|index| !visited[*index] && costs[*index].is_finite())
+        .min_by(|a, b| costs[*a].partial_cmp(&costs[*b]).unwrap())
+    {
+        if current == 1 {
+            break;
+        }
+        visited[current] = true;
+        for (index, link) in links.iter().enumerate() {
+            let start = 2 + index * 2;
+            let end = start + 1;
+            if current == start {
+                relax(
+                    &mut costs,
+                    &mut previous,
+                    current,
+                    end,
+                    link.cost,
+                    Edge::Link,
+                );
+            } else if current == end && link.bidirectional {
+                relax(
+                    &mut costs,
+                    &mut previous,
+                    current,
+                    start,
+                    link.cost,
+                    Edge::Link,
+                );
+            }
+        }
+        for next in 1..count {
+            if visited[next] || next == current {
+                continue;
+            }
+            if let Some((path, length)) = walk(points[current], points[next]) {
+                relax(
+                    &mut costs,
+                    &mut previous,
+                    current,
+                    next,
+                    length,
+                    Edge::Walk(path),
+                );
+            }
+        }
+    }
+
+    let mut edges = vec![];
+    let mut current = 1;
+    while current != 0 {
+        let (source, edge) = previous[current].take()?;
+        edges.push((current, edge));
+        current = source;
+    }
+    let mut result = vec![NavPathNode::new(from, false)];
+    for (index, edge) in edges.into_iter().rev() {
+        match edge {
+            Edge::Walk(path) => result.extend(
+                path.into_iter()
+                    .skip(1)
+                    .map(|position| NavPathNode::new(position, false)),
+            ),
+            Edge::Link => result.push(NavPathNode::new(points[index], true)),
+        }
+    }
+    Some(result)
+}
+
+fn relax(
+    costs: &mut [Scalar],
+    previous: &mut [Option<(usize, Edge)>],
+    from: usize,
+    to: usize,
+    cost: Scalar,
+    edge: Edge,
+) {
+    let cost = costs[from] + cost;
+    if cost < costs[to] {
+        costs[to] = cost;
+        previous[to] = Some((from, edge));
+    }
+}
+
+fn same_point(a: NavVec3, b: NavVec3) -> bool {
+    (a - b).sqr_magnitude() <= EPSILON * EPSILON
+}
@@ -1,55 +1,173 @@
+#[cfg(feature = "oxygengine-ha-renderer")]
+use crate::resources::nav_debug_draw::NavDebugDraw;
 use crate::{
     components::{NavAgent, NavAgentTarget, SimpleNavDriverTag},
-    resources::{nav_meshes::NavMeshes, NavMesh},
+    resources::{
+        nav_jobs::{NavJobQueue, NavPathRequest, NavPathRequestStatus},
+        nav_mesh_queries::NavMeshQueries,
+        nav_meshes::NavMeshes,
+        nav_offmesh_links::smooth_path_nodes,
+    },
 };
 use core::{
     app::AppLifeCycle,
     ecs::{Comp, Universe, WorldRef},
 };
+#[cfg(feature = "oxygengine-ha-renderer")]
+use oxygengine_ha_renderer::resources::debug_draw::DebugDraw;
 
-pub type NavAgentMaintainSystemResources<'a> = (WorldRef, &'a NavMeshes, Comp<&'a mut NavAgent>);
+pub type NavJobQueueSystemResources<'a> = (&'a NavMeshes, &'a mut NavJobQueue);
+
+pub fn nav_job_queue_system(universe: &mut Universe) {
+    let (meshes, mut queue) = universe.query_resources::<NavJobQueueSystemResources>();
+
+    queue.process(&meshes);
+}
+
+pub type NavAgentMaintainSystemResources<'a> = (
+    WorldRef,
+    &'a NavMeshes,
+    &'a mut NavJobQueue,
+    Comp<&'a mut NavAgent>,
+);
 
 pub fn nav_agent_maintain_system(universe: &mut Universe) {
-    let (world, meshes, ..) = universe.query_resources::<NavAgentMaintainSystemResources>();
+    let (world, meshes, mut queue, ..) =
+        universe.query_resources::<NavAgentMaintainSystemResources>();
 
     for (entity, agent) in world.query::<&mut NavAgent>().iter() {
+        if let Some(id) = agent.path_request {
+            match queue.poll(id) {
+                NavPathRequestStatus::Done(Some(path)) => match agent.destination_mesh() {
+                    Some(mesh) => {
+                        let nodes = meshes.path_nodes(mesh, &path);
+                        // NOTE: smoothing could cut through areas that path goes around.
+                        let smooth = agent.smooth_path
+                            && agent.area_costs.is_empty()
+                            && !meshes.has_area_costs(mesh);
+                        let nodes = match meshes.find_walkable_mesh(mesh) {
+                            Some(mesh) if smooth => smooth_path_nodes(mesh, &nodes),
+                            _ => nodes,
+                        };
+                        agent.set_path_nodes(nodes);
+                    }
+                    None => agent.set_path(path),
+                },
+                NavPathRequestStatus::Done(None) => {
+                    agent.path_request = None;
+                    agent.path_failed = true;
+                }
+                NavPathRequestStatus::Pending => {}
+                _ => agent.path_request = None,
+            }
+        }
+        // NOTE: agents that switched to synchronous path finding do not wait for queued ones.
+        if agent.path_request_priority.is_none() {
+            if let Some(id) = agent.path_request.take() {
+                queue.cancel(id);
+            }
+        }
+        if let Some(destination) = &agent.destination {
+            let revision = meshes.obstacles_revision(destination.mesh);
+            if agent.obstacles_revision != revision {
+                agent.obstacles_revision = revision;
+                if let Some(path) = &agent.path {
+                    if meshes.is_path_blocked(destination.mesh, path) {
+                        agent.dirty_path = true;
+                    }
+                }
+            }
+        }
         if agent.dirty_path {
-            if let Some(destination) = &agent.destination {
-                if let Some(mesh) = meshes.0.get(&destination.mesh) {
-                    match destination.target {
-                        NavAgentTarget::Point(point) => {
-                            if let Some(path) = mesh.find_path(
-                                agent.position,
-                                point,
-                                destination.query,
-                                destination.mode,
-                            ) {
-                                agent.set_path(path);
-                            }
+            if let Some(destination) = agent.destination.clone() {
+                let mut to = match destination.target {
+                    NavAgentTarget::Point(point) => point,
+                    NavAgentTarget::Entity(other) => {
+                        if entity == other {
+                            continue;
                         }
-                        NavAgentTarget::Entity(other) => {
-                            if entity != other {
-                                if let Ok(other) =
-                                    unsafe { world.get_unchecked::<&NavAgent>(other) }
-                                {
-                                    if let Some(path) = mesh.find_path(
-                                        agent.position,
-                                        other.position,
-                                        destination.query,
-                                        destination.mode,
-                                    ) {
-                                        agent.set_path(path);
-                                    }
-                                }
-                            }
+                        match unsafe { world.get_unchecked::<&NavAgent>(other) } {
+                            Ok(other) => other.position,
+                            Err(_) => continue,
                         }
                     }
+                };
+                if agent.snap_path_ends {
+                    if let Some(mesh) = meshes.find_walkable_mesh(destination.mesh) {
+                        agent.snap_to_mesh(mesh);
+                        to = mesh.project_point(to).unwrap_or(to);
+                    }
+                }
+                if let Some(priority) = agent.path_request_priority {
+                    let request = NavPathRequest {
+                        mesh: destination.mesh,
+                        from: agent.position,
+                        to,
+                        query: destination.query,
+                        mode: destination.mode,
+                        area_costs: agent.area_costs.clone(),
+                        priority,
+                    };
+                    if let Some(id) = agent.path_request.take() {
+                        queue.cancel(id);
+                    }
+                    agent.path_request = Some(queue.enqueue(request));
+                    agent.dirty_path = false;
+                } else if let Some(nodes) = meshes.find_path_nodes_with_costs(
+                    destination.mesh,
+                    agent.position,
+                    to,
+                    destination.query,
+                    destination.mode,
+                    &agent.area_costs,
+                ) {
+                    // NOTE: smoothing could cut through areas that path goes around.
+                    let smooth = agent.smooth_path
+                        && agent.area_costs.is_empty()
+                        && !meshes.has_area_costs(destination.mesh);
+                    let nodes = match meshes.find_walkable_mesh(destination.mesh) {
+                        Some(mesh) if smooth => smooth_path_nodes(mesh, &nodes),
+                        _ => nodes,
+                    };
+                    agent.set_path_nodes(nodes);
+                } else {
+                    // NOTE: path is not searched again until destination changes.
+                    agent.dirty_path = false;
+                    agent.path_failed = true;
                 }
             }
         }
     }
 }
 
+pub type NavAgentAvoidanceSystemResources<'a> = (WorldRef, Comp<&'a mut NavAgent>);
+
+pub fn nav_agent_avoidance_system(universe: &mut Universe) {
+    let (world, ..) = universe.query_resources::<NavAgentAvoidanceSystemResources>();
+
+    // (entity, mesh, position, avoidance radius)
+    let agents = world
+        .query::<&NavAgent>()
+        .iter()
+        .filter(|(_, agent)| agent.avoidance_radius > 0.0)
+        .map(|(entity, agent)| {
+            let mesh = agent.destination.as_ref().map(|d| d.mesh);
+            (entity, mesh, agent.position, agent.avoidance_radius)
+        })
+        .collect::<Vec<_>>();
+
+    for (entity, agent) in world.query::<&mut NavAgent>().iter() {
+        let mesh = agent.destination.as_ref().map(|d| d.mesh);
+        // Agents without destination stand still, so they act as obstacles for everyone.
+        let neighbors = agents
+            .iter()
+            .filter(|(e, m, _, _)| *e != entity && (m.is_none() || mesh.is_none() || *m == mesh))
+            .map(|(_, _, position, radius)| (*position, *radius))
+            .collect::<Vec<_>>();
+        agent.avoidance = agent.compute_avoidance(&neighbors);
+    }
+}
+
 pub type SimpleNavDriverSystemResources<'a> = (
     WorldRef,
     &'a AppLifeCycle,
@@ -61,26 +179,32 @@ pub fn simple_nav_driver_system(universe: &mut Universe) {
     let (world, lifecycle, ..) = universe.query_resources::<SimpleNavDriverSystemResources>();
 
     let delta_time = lifecycle.delta_time_seconds();
-    if delta_time <= 0.0 {
-        return;
-    }
     for (_, agent) in world
         .query::<&mut NavAgent>()
         .with::<&SimpleNavDriverTag>()
         .iter()
     {
-        if let Some(path) = agent.path() {
-            if let Some((target, _)) = NavMesh::path_target_point(
-                path,
-                agent.position,
-                agent.speed.max(agent.min_target_distance.max(0.0)) * delta_time,
-            ) {
-                let diff = target - agent.position;
-                let dir = diff.normalize();
-                agent.position = agent.position
-                    + dir * (agent.speed.max(0.0) * delta_time).min(diff.magnitude());
-                agent.direction = diff.normalize();
-            }
-        }
+        agent.last_event = agent.process(delta_time);
+    }
+}
+
+#[cfg(feature = "oxygengine-ha-renderer")]
+pub type NavDebugDrawSystemResources<'a> = (
+    WorldRef,
+    &'a NavDebugDraw,
+    &'a mut DebugDraw,
+    Comp<&'a NavAgent>,
+);
+
+#[cfg(feature = "oxygengine-ha-renderer")]
+pub fn nav_debug_draw_system(universe: &mut Universe) {
+    let (world, settings, mut debug_draw, ..) =
+        universe.query_resources::<NavDebugDrawSystemResources>();
+
+    if !settings.enabled {
+        return;
+    }
+    for (_, agent) in world.query::<&NavAgent>().iter() {
+        settings.draw_agent(agent, &mut debug_draw);
     }
 }
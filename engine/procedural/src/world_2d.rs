@@ -1,6 +1,10 @@
+use crate::{
+    world_2d_erosion::{erode, ErosionParams},
+    world_2d_flow::{flow_accumulation, trace_rivers},
+};
 use oxygengine_utils::{grid_2d::Grid2d, noise_map_generator::NoiseMapGenerator, Scalar};
 use psyche_utils::switch::Switch;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 #[cfg(not(feature = "scalar64"))]
 use std::f32::{INFINITY as SCALAR_INFINITY, NEG_INFINITY as SCALAR_NEG_INFINITY};
 #[cfg(feature = "scalar64")]
@@ -11,6 +15,8 @@ pub type World2dField = Switch<Grid2d<Scalar>>;
 
 #[derive(Debug, Clone)]
 pub struct World2dConfig {
+    /// Main seed that all generated fields derive their noise seeds from (0 uses field seeds as-is).
+    pub seed: u64,
     pub size: usize,
     pub zoom: Scalar,
     pub altitude_seed: u32,
@@ -24,6 +30,7 @@ pub struct World2dConfig {
 impl Default for World2dConfig {
     fn default() -> Self {
         Self {
+            seed: 0,
             size: 100,
             zoom: 5.0,
             altitude_seed: 1,
@@ -84,7 +91,157 @@ pub struct World2dStats {
     pub surface_water: (Scalar, Scalar, Scalar),
 }
 
+/// Biome classes based on Whittaker diagram (with ocean and mountains on top of it).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Biome {
+    Ocean,
+    Mountain,
+    Tundra,
+    Taiga,
+    Grassland,
+    Forest,
+    Desert,
+    Savanna,
+    Rainforest,
+}
+
+/// Thresholds used to classify world cells into biomes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct World2dBiomeTable {
+    /// Cells below this altitude are ocean.
+    pub sea_level: Scalar,
+    /// Cells at or above this altitude are mountains.
+    pub mountain_level: Scalar,
+    /// Cells below this temperature are cold (tundra, taiga).
+    pub cold_temperature: Scalar,
+    /// Cells at or above this temperature are hot (desert, savanna, rainforest).
+    pub hot_temperature: Scalar,
+    /// Cells below this humidity are dry.
+    pub dry_humidity: Scalar,
+    /// Cells at or above this humidity are wet.
+    pub wet_humidity: Scalar,
+}
+
+impl Default for World2dBiomeTable {
+    fn default() -> Self {
+        Self {
+            sea_level: 30.0,
+            mountain_level: 80.0,
+            cold_temperature: 30.0,
+            hot_temperature: 70.0,
+            dry_humidity: 0.35,
+            wet_humidity: 0.7,
+        }
+    }
+}
+
+impl World2dBiomeTable {
+    pub fn classify(&self, altitude: Scalar, temperature: Scalar, humidity: Scalar) -> Biome {
+        if altitude < self.sea_level {
+            return Biome::Ocean;
+        }
+        if altitude >= self.mountain_level {
+            return Biome::Mountain;
+        }
+        // 0 - dry, 1 - moderate, 2 - wet
+        let humidity = if humidity < self.dry_humidity {
+            0
+        } else if humidity < self.wet_humidity {
+            1
+        } else {
+            2
+        };
+        if temperature < self.cold_temperature {
+            if humidity == 0 {
+                Biome::Tundra
+            } else {
+                Biome::Taiga
+            }
+        } else if temperature < self.hot_temperature {
+            if humidity == 0 {
+                Biome::Grassland
+            } else {
+                Biome::Forest
+            }
+        } else {
+            match humidity {
+                0 => Biome::Desert,
+                1 => Biome::Savanna,
+                _ => Biome::Rainforest,
+            }
+        }
+    }
+}
+
+impl Biome {
+    /// Color used to visualize biome (RGBA).
+    pub fn color(self) -> [u8; 4] {
+        match self {
+            Self::Ocean => [32, 64, 160, 255],
+            Self::Mountain => [128, 120, 112, 255],
+            Self::Tundra => [200, 212, 220, 255],
+            Self::Taiga => [64, 112, 96, 255],
+            Self::Grassland => [144, 184, 80, 255],
+            Self::Forest => [40, 128, 48, 255],
+            Self::Desert => [224, 200, 128, 255],
+            Self::Savanna => [184, 176, 72, 255],
+            Self::Rainforest => [16, 96, 32, 255],
+        }
+    }
+}
+
+/// Color ramp made of (factor, RGBA color) stops sorted by factor in range `[0; 1]`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct World2dGradient(pub Vec<(Scalar, [u8; 4])>);
+
+impl Default for World2dGradient {
+    fn default() -> Self {
+        Self(vec![
+            (0.0, [16, 32, 96, 255]),
+            (0.3, [48, 96, 192, 255]),
+            (0.32, [224, 208, 144, 255]),
+            (0.5, [64, 160, 64, 255]),
+            (0.8, [120, 100, 80, 255]),
+            (1.0, [255, 255, 255, 255]),
+        ])
+    }
+}
+
+impl World2dGradient {
+    pub fn sample(&self, factor: Scalar) -> [u8; 4] {
+        let factor = factor.max(0.0).min(1.0);
+        let index = self.0.iter().position(|(f, _)| *f > factor);
+        match index {
+            None => self.0.last().map(|(_, c)| *c).unwrap_or([0, 0, 0, 255]),
+            Some(0) => self.0[0].1,
+            Some(index) => {
+                let (fa, ca) = self.0[index - 1];
+                let (fb, cb) = self.0[index];
+                let f = (factor - fa) / (fb - fa);
+                let mut result = [0; 4];
+                for i in 0..4 {
+                    let a = ca[i] as Scalar;
+                    let b = cb[i] as Scalar;
+                    result[i] = (a + (b - a) * f).round() as u8;
+                }
+                result
+            }
+        }
+    }
+}
+
+/// World field to visualize.
+#[derive(Debug, Clone)]
+pub enum WorldChannel {
+    Altitude(World2dGradient),
+    Temperature,
+    Humidity,
+    SurfaceWater,
+    Biome,
+}
+
 pub struct World2d {
+    seed: u64,
     size: usize,
     altitude: Switch<Grid2d<Scalar>>,
     temperature: Switch<Grid2d<Scalar>>,
@@ -92,12 +249,18 @@ pub struct World2d {
     surface_water: Switch<Grid2d<Scalar>>,
     simulation: Box<dyn World2dSimulation>,
     stats: World2dStats,
+    biome_table: World2dBiomeTable,
+    flow: Grid2d<Scalar>,
 }
 
 impl World2d {
     pub fn new(config: &World2dConfig, mut simulation: Box<dyn World2dSimulation>) -> Self {
         let mut altitude = {
-            let gen = NoiseMapGenerator::new(config.altitude_seed, config.size, config.zoom);
+            let gen = NoiseMapGenerator::new(
+                derive_seed(config.seed, config.altitude_seed),
+                config.size,
+                config.zoom,
+            );
             let diff = config.altitude_range.end - config.altitude_range.start;
             Switch::new(
                 2,
@@ -106,7 +269,11 @@ impl World2d {
             )
         };
         let mut temperature = {
-            let gen = NoiseMapGenerator::new(config.temperature_seed, config.size, config.zoom);
+            let gen = NoiseMapGenerator::new(
+                derive_seed(config.seed, config.temperature_seed),
+                config.size,
+                config.zoom,
+            );
             let diff = config.temperature_range.end - config.temperature_range.start;
             Switch::new(
                 2,
@@ -115,7 +282,11 @@ impl World2d {
             )
         };
         let mut humidity = {
-            let gen = NoiseMapGenerator::new(config.humidity_seed, config.size, config.zoom);
+            let gen = NoiseMapGenerator::new(
+                derive_seed(config.seed, config.humidity_seed),
+                config.size,
+                config.zoom,
+            );
             let diff = config.humidity_range.end - config.humidity_range.start;
             Switch::new(
                 2,
@@ -131,6 +302,7 @@ impl World2d {
             surface_water.get_mut().unwrap(),
         );
         let mut result = Self {
+            seed: config.seed,
             size: config.size,
             altitude,
             temperature,
@@ -138,11 +310,16 @@ impl World2d {
             surface_water,
             simulation,
             stats: Default::default(),
+            biome_table: Default::default(),
+            flow: Default::default(),
         };
         result.calculate_stats();
+        result.calculate_flow();
         result
     }
 
+    /// Builds world out of fields provided by generator functions. Generators are responsible
+    /// for their own randomness, so recorded seed is 0 - use `World2d::with_seed` to change it.
     pub fn generate<FA, FT, FH, FSW>(
         size: usize,
         mut simulation: Box<dyn World2dSimulation>,
@@ -180,6 +357,7 @@ impl World2d {
             surface_water.get_mut().unwrap(),
         );
         let mut result = Self {
+            seed: 0,
             size,
             altitude,
             temperature,
@@ -187,11 +365,24 @@ impl World2d {
             surface_water,
             simulation,
             stats: Default::default(),
+            biome_table: Default::default(),
+            flow: Default::default(),
         };
         result.calculate_stats();
+        result.calculate_flow();
         result
     }
 
+    /// Records seed used to produce this world (stored in `World2dData`).
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
     pub fn stats(&self) -> &World2dStats {
         &self.stats
     }
@@ -216,6 +407,108 @@ impl World2d {
         self.surface_water.get().unwrap()
     }
 
+    pub fn altitude_at(&self, x: usize, y: usize) -> Option<Scalar> {
+        self.altitude().get(x, y)
+    }
+
+    pub fn temperature_at(&self, x: usize, y: usize) -> Option<Scalar> {
+        self.temperature().get(x, y)
+    }
+
+    pub fn humidity_at(&self, x: usize, y: usize) -> Option<Scalar> {
+        self.humidity().get(x, y)
+    }
+
+    pub fn surface_water_at(&self, x: usize, y: usize) -> Option<Scalar> {
+        self.surface_water().get(x, y)
+    }
+
+    /// Amount of water that passes through cell, when every cell gets one unit of rain that
+    /// flows along steepest descent.
+    pub fn flow_at(&self, x: usize, y: usize) -> Option<Scalar> {
+        self.flow.get(x, y)
+    }
+
+    pub fn flow(&self) -> &Grid2d<Scalar> {
+        &self.flow
+    }
+
+    /// Finds rivers made of cells with flow at least equal to threshold.
+    ///
+    /// # Returns
+    /// List of river polylines (cell coordinates) going downstream. Tributaries end at
+    /// confluence cell of river they join.
+    pub fn rivers(&self, threshold: Scalar) -> Vec<Vec<(usize, usize)>> {
+        trace_rivers(self.altitude(), &self.flow, threshold)
+    }
+
+    pub fn biome_table(&self) -> &World2dBiomeTable {
+        &self.biome_table
+    }
+
+    pub fn biome_table_mut(&mut self) -> &mut World2dBiomeTable {
+        &mut self.biome_table
+    }
+
+    /// Classifies cell using world biome table.
+    ///
+    /// # Returns
+    /// `None` if coordinates are out of world bounds.
+    pub fn biome_at(&self, x: usize, y: usize) -> Option<Biome> {
+        Some(self.biome_table.classify(
+            self.altitude_at(x, y)?,
+            self.temperature_at(x, y)?,
+            self.humidity_at(x, y)?,
+        ))
+    }
+
+    /// Renders world field into RGBA image buffer. Field values are mapped onto color ramp
+    /// between minimal and maximal value found in the world.
+    ///
+    /// # Returns
+    /// (width, height, RGBA bytes)
+    pub fn render_to_rgba(&self, channel: WorldChannel) -> (usize, usize, Vec<u8>) {
+        let (field, (min, max, _), gradient) = match &channel {
+            WorldChannel::Altitude(gradient) => {
+                (self.altitude(), self.stats.altitude, gradient.clone())
+            }
+            WorldChannel::Temperature => (
+                self.temperature(),
+                self.stats.temperature,
+                World2dGradient(vec![
+                    (0.0, [0, 0, 255, 255]),
+                    (0.5, [255, 255, 255, 255]),
+                    (1.0, [255, 0, 0, 255]),
+                ]),
+            ),
+            WorldChannel::Humidity => (
+                self.humidity(),
+                self.stats.humidity,
+                World2dGradient(vec![(0.0, [255, 224, 160, 255]), (1.0, [0, 64, 255, 255])]),
+            ),
+            WorldChannel::SurfaceWater | WorldChannel::Biome => (
+                self.surface_water(),
+                self.stats.surface_water,
+                World2dGradient(vec![(0.0, [0, 0, 0, 255]), (1.0, [0, 128, 255, 255])]),
+            ),
+        };
+        let diff = max - min;
+        let mut bytes = Vec::with_capacity(field.len() * 4);
+        for row in 0..field.rows() {
+            for col in 0..field.cols() {
+                let color = if let WorldChannel::Biome = channel {
+                    self.biome_at(col, row).unwrap().color()
+                } else if diff > 0.0 {
+                    gradient.sample((field[(col, row)] - min) / diff)
+                } else {
+                    gradient.sample(0.0)
+                };
+                bytes.extend_from_slice(&color);
+            }
+        }
+        (field.cols(), field.rows(), bytes)
+    }
+
     pub fn simulation(&self) -> &dyn World2dSimulation {
         self.simulation.borrow()
     }
@@ -227,6 +520,47 @@ impl World2d {
         self.simulation.as_any().downcast_ref::<T>()
     }
 
+    /// Snapshots full world state (fields and simulation of type `S`) into bytes.
+    ///
+    /// # Returns
+    /// Error if world simulation is not of type `S`.
+    pub fn to_bytes<S>(&self) -> Result<Vec<u8>, String>
+    where
+        S: World2dSimulation + Clone + Serialize,
+    {
+        if self.as_simulation::<S>().is_none() {
+            return Err("World simulation has different type".to_owned());
+        }
+        bincode::serialize(&World2dData::<S>::from(self))
+            .map_err(|error| format!("Error serializing world: {:?}", error))
+    }
+
+    /// Restores world snapshotted with `to_bytes`, so it continues simulation exactly where it
+    /// was stopped.
+    pub fn from_bytes<S>(bytes: &[u8]) -> Result<Self, String>
+    where
+        S: World2dSimulation + Clone + DeserializeOwned,
+    {
+        bincode::deserialize::<World2dData<S>>(bytes)
+            .map(|data| Self::from(&data))
+            .map_err(|error| format!("Error deserializing world: {:?}", error))
+    }
+
+    /// Runs droplet-based hydraulic erosion on altitude field, as one-time generation step.
+    /// Droplets are placed with generator seeded by world seed, so result is reproducible.
+    /// NOTE: simulation gets initialized with world fields on world creation, so it will not
+    /// know about eroded terrain.
+    pub fn apply_erosion(&mut self, params: ErosionParams, iterations: usize) {
+        erode(
+            self.altitude.get_mut().unwrap(),
+            &params,
+            iterations,
+            self.seed,
+        );
+        self.calculate_stats();
+        self.calculate_flow();
+    }
+
     pub fn process(&mut self) {
         self.simulation.process_world(
             &mut self.altitude,
@@ -235,6 +569,7 @@ impl World2d {
             &mut self.surface_water,
         );
         self.calculate_stats();
+        self.calculate_flow();
     }
 
     pub fn remap_region<F, T>(&self, mut range: Range<(usize, usize)>, mut f: F) -> Grid2d<T>
@@ -312,6 +647,10 @@ impl World2d {
         Grid2d::with_cells(range.end.0 - range.start.0, cells)
     }
 
+    fn calculate_flow(&mut self) {
+        self.flow = flow_accumulation(self.altitude.get().unwrap());
+    }
+
     fn calculate_stats(&mut self) {
         self.stats.altitude = {
             let (min, max, accum) = self
@@ -376,17 +715,35 @@ impl World2d {
     }
 }
 
+/// Mixes main world seed with field seed (SplitMix64 finalizer), so changing any of them gives
+/// completely different noise. World seed of 0 keeps field seeds as they are, so configs that
+/// do not set it generate the same worlds as before.
+fn derive_seed(seed: u64, field_seed: u32) -> u32 {
+    if seed == 0 {
+        return field_seed;
+    }
+    let mut z = seed ^ (field_seed as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^= z >> 31;
+    (z ^ (z >> 32)) as u32
+}
+
 #[derive(Clone, Serialize, Deserialize)]
 pub struct World2dData<S>
 where
     S: World2dSimulation,
 {
+    #[serde(default)]
+    seed: u64,
     size: usize,
     altitude: Grid2d<Scalar>,
     temperature: Grid2d<Scalar>,
     humidity: Grid2d<Scalar>,
     surface_water: Grid2d<Scalar>,
     simulation: S,
+    #[serde(default)]
+    biome_table: World2dBiomeTable,
 }
 
 impl<S> From<&World2d> for World2dData<S>
@@ -395,12 +752,14 @@ where
 {
     fn from(world: &World2d) -> Self {
         Self {
+            seed: world.seed,
             size: world.size,
             altitude: world.altitude.get().unwrap().clone(),
             temperature: world.temperature.get().unwrap().clone(),
             humidity: world.humidity.get().unwrap().clone(),
             surface_water: world.surface_water.get().unwrap().clone(),
             simulation: world.as_simulation::<S>().unwrap().clone(),
+            biome_table: world.biome_table.clone(),
         }
     }
 }
@@ -410,7 +769,8 @@ where
     S: World2dSimulation + Clone,
 {
     fn from(data: &World2dData<S>) -> Self {
-        Self {
+        let mut result = Self {
+            seed: data.seed,
             size: data.size,
             altitude: Switch::new(2, data.altitude.clone()),
             temperature: Switch::new(2, data.temperature.clone()),
@@ -418,6 +778,230 @@ where
             surface_water: Switch::new(2, data.surface_water.clone()),
             simulation: Box::new(data.simulation.clone()),
             stats: Default::default(),
+            biome_table: data.biome_table.clone(),
+            flow: Default::default(),
+        };
+        result.calculate_stats();
+        result.calculate_flow();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_2d_climate_simulation::*;
+
+    fn make_world_with_seed(seed: u64) -> World2d {
+        let config = World2dConfig {
+            seed,
+            size: 16,
+            ..Default::default()
+        };
+        let simulation = World2dClimateSimulation::new(Default::default());
+        World2d::new(&config, Box::new(simulation))
+    }
+
+    fn make_world() -> World2d {
+        make_world_with_seed(42)
+    }
+
+    fn assert_worlds_equal(a: &World2d, b: &World2d) {
+        assert!(a.altitude() == b.altitude());
+        assert!(a.temperature() == b.temperature());
+        assert!(a.humidity() == b.humidity());
+        assert!(a.surface_water() == b.surface_water());
+        let a = a.as_simulation::<World2dClimateSimulation>().unwrap();
+        let b = b.as_simulation::<World2dClimateSimulation>().unwrap();
+        assert_eq!(a.steps(), b.steps());
+        assert_eq!(a.years(), b.years());
+        assert!(a.velocity() == b.velocity());
+        assert!(a.pressure() == b.pressure());
+    }
+
+    #[test]
+    fn test_world_2d_bytes() {
+        let mut world = make_world();
+        for _ in 0..3 {
+            world.process();
+        }
+        let bytes = world.to_bytes::<World2dClimateSimulation>().unwrap();
+        assert!(world.to_bytes::<()>().is_err());
+        let mut restored = World2d::from_bytes::<World2dClimateSimulation>(&bytes).unwrap();
+        assert_worlds_equal(&world, &restored);
+        assert_eq!(restored.seed(), 42);
+        for _ in 0..5 {
+            world.process();
+            restored.process();
+        }
+        assert_worlds_equal(&world, &restored);
+        assert_eq!(
+            world
+                .as_simulation::<World2dClimateSimulation>()
+                .unwrap()
+                .steps(),
+            8
+        );
+    }
+
+    #[test]
+    fn test_world_2d_seed() {
+        let mut a = make_world_with_seed(7);
+        let mut b = make_world_with_seed(7);
+        assert_eq!(a.seed(), 7);
+        assert_worlds_equal(&a, &b);
+        for _ in 0..3 {
+            a.process();
+            b.process();
+        }
+        assert_worlds_equal(&a, &b);
+        let c = make_world_with_seed(8);
+        assert!(a.altitude() != c.altitude());
+        assert_eq!(derive_seed(0, 3), 3);
+        assert_ne!(derive_seed(7, 3), 3);
+    }
+
+    #[test]
+    fn test_world_2d_biomes() {
+        // (altitude, temperature, humidity)
+        let cells = [
+            (10.0, 50.0, 0.5),
+            (90.0, 50.0, 0.5),
+            (50.0, 10.0, 0.2),
+            (50.0, 10.0, 0.5),
+            (50.0, 50.0, 0.2),
+            (50.0, 50.0, 0.8),
+            (50.0, 90.0, 0.2),
+            (50.0, 90.0, 0.5),
+            (50.0, 90.0, 0.8),
+        ];
+        let world = World2d::generate(
+            3,
+            Box::new(()),
+            |col, row| cells[row * 3 + col].0,
+            |col, row| cells[row * 3 + col].1,
+            |col, row| cells[row * 3 + col].2,
+            |_, _| 0.0,
+        );
+        assert_eq!(world.altitude_at(1, 0), Some(90.0));
+        assert_eq!(world.temperature_at(0, 1), Some(10.0));
+        assert_eq!(world.humidity_at(2, 2), Some(0.8));
+        assert_eq!(world.altitude_at(3, 0), None);
+        assert_eq!(world.humidity_at(0, 3), None);
+        let biomes = (0..9)
+            .map(|i| world.biome_at(i % 3, i / 3).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            biomes,
+            vec![
+                Biome::Ocean,
+                Biome::Mountain,
+                Biome::Tundra,
+                Biome::Taiga,
+                Biome::Grassland,
+                Biome::Forest,
+                Biome::Desert,
+                Biome::Savanna,
+                Biome::Rainforest,
+            ]
+        );
+        assert_eq!(world.biome_at(3, 3), None);
+    }
+
+    #[test]
+    fn test_world_2d_render_to_rgba() {
+        let world = World2d::generate(
+            4,
+            Box::new(()),
+            |_, _| 50.0,
+            |_, _| 50.0,
+            |_, _| 0.5,
+            |_, _| 0.0,
+        );
+        let channels = [
+            WorldChannel::Altitude(Default::default()),
+            WorldChannel::Temperature,
+            WorldChannel::Humidity,
+            WorldChannel::SurfaceWater,
+            WorldChannel::Biome,
+        ];
+        for channel in channels {
+            let (width, height, bytes) = world.render_to_rgba(channel);
+            assert_eq!(width, 4);
+            assert_eq!(height, 4);
+            assert_eq!(bytes.len(), width * height * 4);
+            assert!(bytes.chunks(4).all(|color| color == &bytes[0..4]));
+        }
+
+        let world = World2d::generate(
+            2,
+            Box::new(()),
+            |col, _| col as Scalar,
+            |_, _| 0.0,
+            |_, _| 0.0,
+            |_, _| 0.0,
+        );
+        let gradient = World2dGradient(vec![(0.0, [0, 0, 0, 255]), (1.0, [200, 100, 50, 255])]);
+        let (_, _, bytes) = world.render_to_rgba(WorldChannel::Altitude(gradient));
+        assert_eq!(&bytes[0..4], &[0, 0, 0, 255]);
+        assert_eq!(&bytes[4..8], &[200, 100, 50, 255]);
+        assert_eq!(
+            World2dGradient(vec![(0.0, [0, 0, 0, 0]), (1.0, [100, 200, 50, 255])]).sample(0.5),
+            [50, 100, 25, 128]
+        );
+    }
+
+    #[test]
+    fn test_world_2d_erosion() {
+        let make_world = || {
+            let config = World2dConfig {
+                seed: 5,
+                size: 32,
+                ..Default::default()
+            };
+            World2d::new(&config, Box::new(()))
+        };
+        let mut world = make_world();
+        let before = world.altitude().clone();
+        world.apply_erosion(Default::default(), 500);
+        assert!(world.altitude() != &before);
+        let volume_before = before.iter().sum::<Scalar>();
+        let volume_after = world.altitude().iter().sum::<Scalar>();
+        assert!((volume_before - volume_after).abs() < volume_before * 1.0e-3);
+
+        let mut other = make_world();
+        other.apply_erosion(Default::default(), 500);
+        assert!(world.altitude() == other.altitude());
+    }
+
+    #[test]
+    fn test_world_2d_flow() {
+        let world = World2d::generate(
+            8,
+            Box::new(()),
+            |col, row| 100.0 - col as Scalar * 10.0 + row as Scalar * 0.1,
+            |_, _| 0.0,
+            |_, _| 0.0,
+            |_, _| 0.0,
+        );
+        for row in 0..8 {
+            for col in 1..8 {
+                assert!(world.flow_at(col, row).unwrap() > world.flow_at(col - 1, row).unwrap());
+            }
+        }
+        assert_eq!(world.flow_at(8, 0), None);
+        // last column drains towards first row.
+        assert_eq!(world.flow_at(7, 0), Some(64.0));
+
+        let rivers = world.rivers(4.0);
+        assert_eq!(rivers.len(), 8);
+        assert_eq!(rivers[0], vec![(3, 0), (4, 0), (5, 0), (6, 0), (7, 0)]);
+        assert_eq!(rivers[7].last(), Some(&(7, 6)));
+        for river in &rivers {
+            for pair in river.windows(2) {
+                let (a, b) = (pair[0], pair[1]);
+                assert!(world.flow_at(b.0, b.1).unwrap() > world.flow_at(a.0, a.1).unwrap());
+            }
         }
     }
 }
@@ -8,7 +8,7 @@ use oxygengine_utils::{
 use psyche_utils::switch::Switch;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 #[cfg(not(feature = "scalar64"))]
 use std::f32::consts::{E, PI};
 #[cfg(feature = "scalar64")]
@@ -176,6 +176,10 @@ pub struct World2dClimateSimulationConfig {
     pub sun_heating_adaptive_correction_factor: Scalar,
     pub sun_heating_absorption_surface_water_range: Range<Scalar>,
     pub thermal_radiation: Scalar,
+    /// Process cells in parallel (only with `parallel` feature enabled). Parallel and serial
+    /// steps produce identical results.
+    #[serde(default)]
+    pub parallel: bool,
 }
 
 impl Default for World2dClimateSimulationConfig {
@@ -201,6 +205,7 @@ impl Default for World2dClimateSimulationConfig {
             sun_heating_adaptive_correction_factor: 1.0,
             sun_heating_absorption_surface_water_range: 1.0..0.01,
             thermal_radiation: 1.0,
+            parallel: cfg!(feature = "parallel"),
         }
     }
 }
@@ -281,6 +286,16 @@ impl World2dClimateSimulation {
         self.slopeness = None;
     }
 
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        bincode::serialize(self)
+            .map_err(|error| format!("Error serializing climate simulation: {:?}", error))
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        bincode::deserialize(bytes)
+            .map_err(|error| format!("Error deserializing climate simulation: {:?}", error))
+    }
+
     fn heat_exchange(
         &mut self,
         temperature: &mut World2dField,
@@ -297,24 +312,22 @@ impl World2dClimateSimulation {
             - self.config.sun_heating_absorption_surface_water_range.start;
         let target_average_temp =
             (self.config.temperature_range.start + self.config.temperature_range.end) * 0.5;
-        temperature.with(|col, row, value| {
+        let config = &self.config;
+        fill(config.parallel, temperature, |col, row, value| {
             let water = surface_water[(col, row)];
-            let f = if self.config.water_capacity > 0.0 {
-                (water / self.config.water_capacity).max(0.0).min(1.0)
+            let f = if config.water_capacity > 0.0 {
+                (water / config.water_capacity).max(0.0).min(1.0)
             } else {
                 0.0
             };
             let absorption =
-                self.config.sun_heating_absorption_surface_water_range.start + absorption_diff * f;
+                config.sun_heating_absorption_surface_water_range.start + absorption_diff * f;
             let f = (PI * ((row as Scalar + 0.5) / rows + seasons_phase)).sin();
             let sun_value = (sun_heating * f * absorption).max(0.0);
             value + world_core_heating + sun_value - thermal_radiation
         });
         let size = cols * rows;
-        #[cfg(not(feature = "parallel"))]
-        let average_temp = temperature.iter().sum::<Scalar>() / size;
-        #[cfg(feature = "parallel")]
-        let average_temp = temperature.par_iter().sum::<Scalar>() / size;
+        let average_temp = field_sum(self.config.parallel, temperature) / size;
         let dtemp = logistic_sigmoid_simple_signed(target_average_temp - average_temp);
         let f = self.config.sun_heating_adaptive_correction_factor;
         self.config.sun_heating = (self.config.sun_heating + dtemp * f).max(0.0);
@@ -322,7 +335,12 @@ impl World2dClimateSimulation {
 
     fn surface_water_transfer(&self, altitude: &Grid2d<Scalar>, surface_water: &mut World2dField) {
         let surface_water = surface_water.iterate().unwrap();
-        diffuse_with_barriers(altitude, surface_water.0, surface_water.1);
+        diffuse_with_barriers(
+            self.config.parallel,
+            altitude,
+            surface_water.0,
+            surface_water.1,
+        );
     }
 
     fn rainfall_and_evaporation(
@@ -336,35 +354,8 @@ impl World2dClimateSimulation {
         }
         let surface_water = surface_water.iterate().unwrap();
         let humidity = humidity.iterate().unwrap();
-        #[cfg(not(feature = "parallel"))]
-        {
-            let it = surface_water
-                .0
-                .iter()
-                .zip(surface_water.1.iter_mut())
-                .zip(humidity.0.iter())
-                .zip(humidity.1.iter_mut())
-                .zip(temperature.iter());
-            for ((((swp, swn), hp), hn), t) in it {
-                let limit = remap_in_ranges(
-                    *t,
-                    self.config.temperature_range.clone(),
-                    self.config.humidity_limit_range.clone(),
-                );
-                let h = *hp - limit;
-                let h = if h > 0.0 {
-                    h * self.config.rainfall_factor
-                } else {
-                    h * self.config.evaporation_factor
-                };
-                let w = (h * self.config.water_capacity).max(-swp);
-                let h = w / self.config.water_capacity;
-                *hn = hp - h;
-                *swn = swp + w;
-            }
-        }
         #[cfg(feature = "parallel")]
-        {
+        if self.config.parallel {
             surface_water
                 .0
                 .par_iter()
@@ -373,23 +364,47 @@ impl World2dClimateSimulation {
                 .zip(humidity.1.par_iter_mut())
                 .zip(temperature.par_iter())
                 .for_each(|((((swp, swn), hp), hn), t)| {
-                    let limit = remap_in_ranges(
-                        *t,
-                        self.config.temperature_range.clone(),
-                        self.config.humidity_limit_range.clone(),
-                    );
-                    let h = *hp - limit;
-                    let h = if h > 0.0 {
-                        h * self.config.rainfall_factor
-                    } else {
-                        h * self.config.evaporation_factor
-                    };
-                    let w = (h * self.config.water_capacity).max(-swp);
-                    let h = w / self.config.water_capacity;
-                    *hn = hp - h;
-                    *swn = swp + w;
+                    let (w, h) = self.rainfall_cell(*swp, *hp, *t);
+                    *swn = w;
+                    *hn = h;
                 });
+            return;
         }
+        let it = surface_water
+            .0
+            .iter()
+            .zip(surface_water.1.iter_mut())
+            .zip(humidity.0.iter())
+            .zip(humidity.1.iter_mut())
+            .zip(temperature.iter());
+        for ((((swp, swn), hp), hn), t) in it {
+            let (w, h) = self.rainfall_cell(*swp, *hp, *t);
+            *swn = w;
+            *hn = h;
+        }
+    }
+
+    /// (surface water, humidity)
+    fn rainfall_cell(
+        &self,
+        surface_water: Scalar,
+        humidity: Scalar,
+        temperature: Scalar,
+    ) -> (Scalar, Scalar) {
+        let limit = remap_in_ranges(
+            temperature,
+            self.config.temperature_range.clone(),
+            self.config.humidity_limit_range.clone(),
+        );
+        let h = humidity - limit;
+        let h = if h > 0.0 {
+            h * self.config.rainfall_factor
+        } else {
+            h * self.config.evaporation_factor
+        };
+        let w = (h * self.config.water_capacity).max(-surface_water);
+        let h = w / self.config.water_capacity;
+        (surface_water + w, humidity - h)
     }
 }
 
@@ -461,6 +476,7 @@ impl World2dSimulation for World2dClimateSimulation {
         {
             {
                 diffuse_scalar(
+                    self.config.parallel,
                     temperature,
                     self.config.mass_diffuse_iterations,
                     self.config.mass_diffuse_factor,
@@ -468,6 +484,7 @@ impl World2dSimulation for World2dClimateSimulation {
             }
             {
                 diffuse_scalar(
+                    self.config.parallel,
                     humidity,
                     self.config.mass_diffuse_iterations,
                     self.config.mass_diffuse_factor,
@@ -480,6 +497,7 @@ impl World2dSimulation for World2dClimateSimulation {
                     let temperature_prev = temperature.0;
                     let temperature_next = temperature.1;
                     advect_scalar(
+                        self.config.parallel,
                         temperature_prev,
                         temperature_next,
                         velocity,
@@ -491,6 +509,7 @@ impl World2dSimulation for World2dClimateSimulation {
                     let humidity_prev = humidity.0;
                     let humidity_next = humidity.1;
                     advect_scalar(
+                        self.config.parallel,
                         humidity_prev,
                         humidity_next,
                         velocity,
@@ -510,6 +529,7 @@ impl World2dSimulation for World2dClimateSimulation {
                                 {
                                     let velocity = velocity.get_mut().unwrap();
                                     consider_obstacles(
+                                        self.config.parallel,
                                         velocity,
                                         slopeness,
                                         self.config.slopeness_refraction_power,
@@ -517,6 +537,7 @@ impl World2dSimulation for World2dClimateSimulation {
                                 }
                                 // TODO: test if it is needed.
                                 conserve_mass(
+                                    self.config.parallel,
                                     velocity,
                                     pressure,
                                     divergence,
@@ -527,11 +548,13 @@ impl World2dSimulation for World2dClimateSimulation {
                         // diffuse velocity
                         {
                             diffuse_vector(
+                                self.config.parallel,
                                 velocity,
                                 self.config.viscosity_iterations,
                                 self.config.viscosity_factor,
                             );
                             conserve_mass(
+                                self.config.parallel,
                                 velocity,
                                 pressure,
                                 divergence,
@@ -545,6 +568,7 @@ impl World2dSimulation for World2dClimateSimulation {
                                 let velocity_prev = velocity.0;
                                 let velocity_next = velocity.1;
                                 advect_vector(
+                                    self.config.parallel,
                                     velocity_prev,
                                     velocity_next,
                                     velocity_prev,
@@ -552,6 +576,7 @@ impl World2dSimulation for World2dClimateSimulation {
                                 );
                             }
                             conserve_mass(
+                                self.config.parallel,
                                 velocity,
                                 pressure,
                                 divergence,
@@ -589,6 +614,31 @@ impl From<&World2dClimateSimulationData> for World2dClimateSimulation {
     }
 }
 
+impl Clone for World2dClimateSimulation {
+    fn clone(&self) -> Self {
+        Self::from(&World2dClimateSimulationData::from(self))
+    }
+}
+
+impl Serialize for World2dClimateSimulation {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        World2dClimateSimulationData::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for World2dClimateSimulation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let data = World2dClimateSimulationData::deserialize(deserializer)?;
+        Ok(Self::from(&data))
+    }
+}
+
 fn apply_duplicate_boundaries(field: &mut Grid2d<Scalar>) {
     let cols = field.cols();
     let rows = field.rows();
@@ -624,13 +674,14 @@ fn apply_mirror_boundaries(field: &mut Grid2d<World2dClimateSimulationVector>) {
 }
 
 fn consider_obstacles(
+    parallel: bool,
     velocity: &mut Grid2d<World2dClimateSimulationVector>,
     slopeness: &Grid2d<World2dClimateSimulationVector>,
     refraction_power: Scalar,
 ) {
     let cols = velocity.cols();
     let rows = velocity.rows();
-    velocity.with(|col, row, value| {
+    fill(parallel, velocity, |col, row, value| {
         if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
             (0.0, 0.0).into()
         } else {
@@ -645,7 +696,7 @@ fn consider_obstacles(
 }
 
 // a.k.a. Jacobi
-fn diffuse_scalar(field: &mut World2dField, iterations: usize, factor: Scalar) {
+fn diffuse_scalar(parallel: bool, field: &mut World2dField, iterations: usize, factor: Scalar) {
     let cols = field.get().unwrap().cols();
     let rows = field.get().unwrap().rows();
     let fa = (cols as Scalar * rows as Scalar) * factor;
@@ -654,7 +705,7 @@ fn diffuse_scalar(field: &mut World2dField, iterations: usize, factor: Scalar) {
         let field = field.iterate().unwrap();
         let field_prev = field.0;
         let field_next = field.1;
-        field_next.with(|col, row, _| {
+        fill(parallel, field_next, |col, row, _| {
             if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
                 0.0
             } else {
@@ -672,6 +723,7 @@ fn diffuse_scalar(field: &mut World2dField, iterations: usize, factor: Scalar) {
 
 // a.k.a. Jacobi
 fn diffuse_vector(
+    parallel: bool,
     field: &mut Switch<Grid2d<World2dClimateSimulationVector>>,
     iterations: usize,
     factor: Scalar,
@@ -684,7 +736,7 @@ fn diffuse_vector(
         let field = field.iterate().unwrap();
         let field_prev = field.0;
         let field_next = field.1;
-        field_next.with(|col, row, _| {
+        fill(parallel, field_next, |col, row, _| {
             if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
                 (0.0, 0.0).into()
             } else {
@@ -701,6 +753,7 @@ fn diffuse_vector(
 }
 
 fn advect_scalar(
+    parallel: bool,
     density_prev: &Grid2d<Scalar>,
     density_next: &mut Grid2d<Scalar>,
     velocity: &Grid2d<World2dClimateSimulationVector>,
@@ -708,7 +761,7 @@ fn advect_scalar(
 ) {
     let cols = density_prev.cols();
     let rows = density_prev.rows();
-    density_next.with(|col, row, _| {
+    fill(parallel, density_next, |col, row, _| {
         if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
             0.0
         } else {
@@ -738,6 +791,7 @@ fn advect_scalar(
 }
 
 fn advect_vector(
+    parallel: bool,
     field_prev: &Grid2d<World2dClimateSimulationVector>,
     field_next: &mut Grid2d<World2dClimateSimulationVector>,
     velocity: &Grid2d<World2dClimateSimulationVector>,
@@ -745,7 +799,7 @@ fn advect_vector(
 ) {
     let cols = field_prev.cols();
     let rows = field_prev.rows();
-    field_next.with(|col, row, _| {
+    fill(parallel, field_next, |col, row, _| {
         if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
             (0.0, 0.0).into()
         } else {
@@ -775,6 +829,7 @@ fn advect_vector(
 }
 
 fn conserve_mass(
+    parallel: bool,
     velocity: &mut Switch<Grid2d<World2dClimateSimulationVector>>,
     pressure: &mut World2dField,
     divergence: &mut Grid2d<Scalar>,
@@ -782,18 +837,30 @@ fn conserve_mass(
 ) {
     {
         let velocity = velocity.get().unwrap();
-        calculate_poisson_pressure(velocity, pressure, divergence, poisson_pressure_iterations);
+        calculate_poisson_pressure(
+            parallel,
+            velocity,
+            pressure,
+            divergence,
+            poisson_pressure_iterations,
+        );
     }
     {
         let velocity = velocity.iterate().unwrap();
         let velocity_prev = velocity.0;
         let velocity_next = velocity.1;
         let pressure = pressure.get().unwrap();
-        calculate_convergence_from_pressure_gradient(velocity_prev, velocity_next, pressure);
+        calculate_convergence_from_pressure_gradient(
+            parallel,
+            velocity_prev,
+            velocity_next,
+            pressure,
+        );
     }
 }
 
 fn calculate_poisson_pressure(
+    parallel: bool,
     velocity: &Grid2d<World2dClimateSimulationVector>,
     pressure: &mut World2dField,
     divergence: &mut Grid2d<Scalar>,
@@ -801,7 +868,7 @@ fn calculate_poisson_pressure(
 ) {
     let cols = divergence.cols();
     let rows = divergence.rows();
-    divergence.with(|col, row, _| {
+    fill(parallel, divergence, |col, row, _| {
         if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
             0.0
         } else {
@@ -818,7 +885,7 @@ fn calculate_poisson_pressure(
         let pressure = pressure.iterate().unwrap();
         let pressure_prev = pressure.0;
         let pressure_next = pressure.1;
-        pressure_next.with(|col, row, _| {
+        fill(parallel, pressure_next, |col, row, _| {
             if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
                 0.0
             } else {
@@ -835,13 +902,14 @@ fn calculate_poisson_pressure(
 }
 
 fn calculate_convergence_from_pressure_gradient(
+    parallel: bool,
     velocity_prev: &Grid2d<World2dClimateSimulationVector>,
     velocity_next: &mut Grid2d<World2dClimateSimulationVector>,
     pressure: &Grid2d<Scalar>,
 ) {
     let cols = velocity_prev.cols();
     let rows = velocity_prev.rows();
-    velocity_next.with(|col, row, _| {
+    fill(parallel, velocity_next, |col, row, _| {
         if col == 0 || col == cols - 1 || row == 0 || row == rows - 1 {
             (0.0, 0.0).into()
         } else {
@@ -859,12 +927,13 @@ fn calculate_convergence_from_pressure_gradient(
 }
 
 fn diffuse_with_barriers(
+    parallel: bool,
     barriers: &Grid2d<Scalar>,
     field_prev: &Grid2d<Scalar>,
     field_next: &mut Grid2d<Scalar>,
 ) {
     let levels = (barriers + field_prev).unwrap();
-    field_next.with(|col, row, _| {
+    fill(parallel, field_next, |col, row, _| {
         let sample_coord = (if col == 0 { 0 } else { 1 }, if row == 0 { 0 } else { 1 });
         let barriers_sample = barriers.neighbor_sample((col, row));
         let values_sample = field_prev.neighbor_sample((col, row));
@@ -895,16 +964,50 @@ fn diffuse_with_barriers(
         }
     });
     // error correction.
-    #[cfg(not(feature = "parallel"))]
-    let before = field_prev.iter().sum::<Scalar>();
+    let before = field_sum(parallel, field_prev);
+    let after = field_sum(parallel, field_next);
+    let diff = (before - after) / field_prev.len() as Scalar;
+    fill(parallel, field_next, |_, _, value| *value + diff);
+}
+
+/// Computes every cell of the field, in parallel when enabled. Cells only read other buffers or
+/// their own previous value, so both ways give identical results.
+fn fill<T, F>(parallel: bool, field: &mut Grid2d<T>, f: F)
+where
+    T: Clone + Send + Sync,
+    F: FnMut(usize, usize, &T) -> T + Clone + Sync,
+{
     #[cfg(feature = "parallel")]
-    let before = field_prev.par_iter().sum::<Scalar>();
+    if parallel {
+        field.par_with(f);
+        return;
+    }
     #[cfg(not(feature = "parallel"))]
-    let after = field_next.iter().sum::<Scalar>();
+    let _ = parallel;
+    field.with(f);
+}
+
+/// Sums field rows first and then row sums in order, so result does not depend on whether rows
+/// were summed in parallel.
+fn field_sum(parallel: bool, field: &Grid2d<Scalar>) -> Scalar {
+    let cols = field.cols().max(1);
     #[cfg(feature = "parallel")]
-    let after = field_next.par_iter().sum::<Scalar>();
-    let diff = (before - after) / field_prev.len() as Scalar;
-    field_next.with(|_, _, value| *value + diff);
+    if parallel {
+        return field
+            .cells()
+            .par_chunks(cols)
+            .map(|row| row.iter().sum::<Scalar>())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .sum();
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    field
+        .cells()
+        .chunks(cols)
+        .map(|row| row.iter().sum::<Scalar>())
+        .sum()
 }
 
 #[inline]
@@ -986,3 +1089,43 @@ impl From<&World2dClimateSimulation> for World2dClimateSimulationData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::world_2d::{World2d, World2dConfig};
+
+    fn make_world(parallel: bool) -> World2d {
+        let config = World2dConfig {
+            seed: 13,
+            size: 24,
+            ..Default::default()
+        };
+        let simulation = World2dClimateSimulation::new(World2dClimateSimulationConfig {
+            sun_heating: 1.0,
+            parallel,
+            ..Default::default()
+        });
+        World2d::new(&config, Box::new(simulation))
+    }
+
+    #[test]
+    fn test_parallel_and_serial_steps_agree() {
+        let mut serial = make_world(false);
+        let mut parallel = make_world(true);
+        for _ in 0..10 {
+            serial.process();
+            parallel.process();
+        }
+        assert!(serial.temperature() == parallel.temperature());
+        assert!(serial.humidity() == parallel.humidity());
+        assert!(serial.surface_water() == parallel.surface_water());
+        let serial = serial.as_simulation::<World2dClimateSimulation>().unwrap();
+        let parallel = parallel
+            .as_simulation::<World2dClimateSimulation>()
+            .unwrap();
+        assert!(serial.velocity() == parallel.velocity());
+        assert!(serial.pressure() == parallel.pressure());
+        assert_eq!(serial.config().sun_heating, parallel.config().sun_heating);
+    }
+}
@@ -0,0 +1,150 @@
+use oxygengine_utils::{grid_2d::Grid2d, Scalar};
+use serde::{Deserialize, Serialize};
+
+/// Droplet-based hydraulic erosion settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ErosionParams {
+    /// How much droplet keeps its direction instead of following terrain slope, in range
+    /// `[0; 1]`.
+    pub inertia: Scalar,
+    /// Sediment capacity factor - how much sediment droplet can carry per speed and water.
+    pub capacity: Scalar,
+    /// Minimal sediment capacity, so droplets erode even flat terrain a little.
+    pub min_capacity: Scalar,
+    /// Part of surplus sediment that gets deposited every step, in range `[0; 1]`.
+    pub deposition_rate: Scalar,
+    /// Part of free sediment capacity that gets eroded every step, in range `[0; 1]`.
+    pub erosion_rate: Scalar,
+    /// Part of droplet water that evaporates every step, in range `[0; 1]`.
+    pub evaporation_rate: Scalar,
+    pub gravity: Scalar,
+    pub initial_water: Scalar,
+    pub initial_speed: Scalar,
+    /// Maximal number of steps that single droplet makes.
+    pub max_lifetime: usize,
+}
+
+impl Default for ErosionParams {
+    fn default() -> Self {
+        Self {
+            inertia: 0.05,
+            capacity: 4.0,
+            min_capacity: 0.01,
+            deposition_rate: 0.3,
+            erosion_rate: 0.3,
+            evaporation_rate: 0.01,
+            gravity: 4.0,
+            initial_water: 1.0,
+            initial_speed: 1.0,
+            max_lifetime: 30,
+        }
+    }
+}
+
+/// Simulates `iterations` water droplets (each starting at random place picked with seeded
+/// generator) that erode altitude field when flowing downhill and deposit sediment when they
+/// slow down. Sediment left in droplet when it dies is deposited in place, so total altitude
+/// volume is preserved.
+pub fn erode(altitude: &mut Grid2d<Scalar>, params: &ErosionParams, iterations: usize, seed: u64) {
+    let cols = altitude.cols();
+    let rows = altitude.rows();
+    if cols < 2 || rows < 2 {
+        return;
+    }
+    let max_x = (cols - 1) as Scalar;
+    let max_y = (rows - 1) as Scalar;
+    let inertia = params.inertia.max(0.0).min(1.0);
+    let mut rng = ErosionRng(seed);
+    for _ in 0..iterations {
+        let mut x = rng.next_scalar() * max_x;
+        let mut y = rng.next_scalar() * max_y;
+        let mut dir_x = 0.0;
+        let mut dir_y = 0.0;
+        let mut speed = params.initial_speed;
+        let mut water = params.initial_water;
+        let mut sediment = 0.0;
+        for _ in 0..params.max_lifetime {
+            let (height, gradient_x, gradient_y) = height_and_gradient(altitude, x, y);
+            dir_x = dir_x * inertia - gradient_x * (1.0 - inertia);
+            dir_y = dir_y * inertia - gradient_y * (1.0 - inertia);
+            let len = (dir_x * dir_x + dir_y * dir_y).sqrt();
+            if len <= 0.0 {
+                break;
+            }
+            dir_x /= len;
+            dir_y /= len;
+            let next_x = x + dir_x;
+            let next_y = y + dir_y;
+            if next_x < 0.0 || next_x >= max_x || next_y < 0.0 || next_y >= max_y {
+                break;
+            }
+            let delta = height_and_gradient(altitude, next_x, next_y).0 - height;
+            let capacity = (-delta * speed * water * params.capacity).max(params.min_capacity);
+            if sediment > capacity || delta > 0.0 {
+                let amount = if delta > 0.0 {
+                    delta.min(sediment)
+                } else {
+                    (sediment - capacity) * params.deposition_rate
+                };
+                sediment -= amount;
+                spread(altitude, x, y, amount);
+            } else {
+                let amount = ((capacity - sediment) * params.erosion_rate).min(-delta);
+                sediment += amount;
+                spread(altitude, x, y, -amount);
+            }
+            speed = (speed * speed - delta * params.gravity).max(0.0).sqrt();
+            water *= 1.0 - params.evaporation_rate;
+            x = next_x;
+            y = next_y;
+        }
+        spread(altitude, x, y, sediment);
+    }
+}
+
+/// (height, gradient x, gradient y) bilinearly interpolated at given position.
+fn height_and_gradient(field: &Grid2d<Scalar>, x: Scalar, y: Scalar) -> (Scalar, Scalar, Scalar) {
+    let col = (x as usize).min(field.cols() - 2);
+    let row = (y as usize).min(field.rows() - 2);
+    let fx = x - col as Scalar;
+    let fy = y - row as Scalar;
+    let nw = field[(col, row)];
+    let ne = field[(col + 1, row)];
+    let sw = field[(col, row + 1)];
+    let se = field[(col + 1, row + 1)];
+    let height =
+        nw * (1.0 - fx) * (1.0 - fy) + ne * fx * (1.0 - fy) + sw * (1.0 - fx) * fy + se * fx * fy;
+    let gradient_x = (ne - nw) * (1.0 - fy) + (se - sw) * fy;
+    let gradient_y = (sw - nw) * (1.0 - fx) + (se - ne) * fx;
+    (height, gradient_x, gradient_y)
+}
+
+/// Adds amount to four cells around position, weighted by distance.
+fn spread(field: &mut Grid2d<Scalar>, x: Scalar, y: Scalar, amount: Scalar) {
+    let col = (x as usize).min(field.cols() - 2);
+    let row = (y as usize).min(field.rows() - 2);
+    let fx = x - col as Scalar;
+    let fy = y - row as Scalar;
+    field[(col, row)] += amount * (1.0 - fx) * (1.0 - fy);
+    field[(col + 1, row)] += amount * fx * (1.0 - fy);
+    field[(col, row + 1)] += amount * (1.0 - fx) * fy;
+    field[(col + 1, row + 1)] += amount * fx * fy;
+}
+
+/// SplitMix64 generator - erosion has to stay reproducible for given world seed.
+struct ErosionRng(u64);
+
+impl ErosionRng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Random number in range `[0; 1)`.
+    fn next_scalar(&mut self) -> Scalar {
+        (self.next_u64() >> 40) as Scalar / (1u64 << 24) as Scalar
+    }
+}
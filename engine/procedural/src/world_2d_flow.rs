@@ -0,0 +1,112 @@
+use oxygengine_utils::{grid_2d::Grid2d, Scalar};
+use std::cmp::Ordering;
+
+/// (column offset, row offset, distance)
+const NEIGHBORS: [(isize, isize, Scalar); 8] = [
+    (-1, -1, std::f64::consts::SQRT_2 as Scalar),
+    (0, -1, 1.0),
+    (1, -1, std::f64::consts::SQRT_2 as Scalar),
+    (-1, 0, 1.0),
+    (1, 0, 1.0),
+    (-1, 1, std::f64::consts::SQRT_2 as Scalar),
+    (0, 1, 1.0),
+    (1, 1, std::f64::consts::SQRT_2 as Scalar),
+];
+
+/// Finds neighbor cell with steepest descent from given cell.
+///
+/// # Returns
+/// `None` if there is no lower neighbor (cell is local minimum or lies on flat area).
+pub fn downstream(altitude: &Grid2d<Scalar>, col: usize, row: usize) -> Option<(usize, usize)> {
+    let height = altitude.get(col, row)?;
+    let mut result = None;
+    let mut best = 0.0;
+    for (dc, dr, distance) in NEIGHBORS {
+        let c = col as isize + dc;
+        let r = row as isize + dr;
+        if c < 0 || r < 0 {
+            continue;
+        }
+        let (c, r) = (c as usize, r as usize);
+        if let Some(value) = altitude.get(c, r) {
+            let slope = (height - value) / distance;
+            if slope > best {
+                best = slope;
+                result = Some((c, r));
+            }
+        }
+    }
+    result
+}
+
+/// Calculates how much water passes through each cell when every cell gets one unit of rain
+/// that flows along steepest descent. Water flows only to strictly lower cells, so it stops at
+/// local minima (lakes) instead of looping.
+pub fn flow_accumulation(altitude: &Grid2d<Scalar>) -> Grid2d<Scalar> {
+    let cols = altitude.cols();
+    let mut order = (0..altitude.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| {
+        let a = altitude.cells()[*a];
+        let b = altitude.cells()[*b];
+        b.partial_cmp(&a).unwrap_or(Ordering::Equal)
+    });
+    let mut result = Grid2d::new(cols, altitude.rows(), 1.0);
+    for index in order {
+        let (col, row) = (index % cols, index / cols);
+        if let Some(target) = downstream(altitude, col, row) {
+            let value = result[(col, row)];
+            result[target] += value;
+        }
+    }
+    result
+}
+
+/// Traces rivers made of cells with flow at least equal to threshold. Every river starts at
+/// cell without river inflow and goes downstream until it reaches sink or joins other river
+/// (then it ends at confluence cell).
+pub fn trace_rivers(
+    altitude: &Grid2d<Scalar>,
+    flow: &Grid2d<Scalar>,
+    threshold: Scalar,
+) -> Vec<Vec<(usize, usize)>> {
+    let cols = altitude.cols();
+    let rows = altitude.rows();
+    let is_river = |col: usize, row: usize| flow[(col, row)] >= threshold;
+    let mut has_inflow = Grid2d::new(cols, rows, false);
+    for row in 0..rows {
+        for col in 0..cols {
+            if is_river(col, row) {
+                if let Some(target) = downstream(altitude, col, row) {
+                    has_inflow[target] = true;
+                }
+            }
+        }
+    }
+    let mut visited = Grid2d::new(cols, rows, false);
+    let mut result = vec![];
+    for row in 0..rows {
+        for col in 0..cols {
+            if !is_river(col, row) || has_inflow[(col, row)] {
+                continue;
+            }
+            let mut river = vec![(col, row)];
+            visited[(col, row)] = true;
+            let mut current = (col, row);
+            while let Some(next) = downstream(altitude, current.0, current.1) {
+                if !is_river(next.0, next.1) {
+                    break;
+                }
+                river.push(next);
+                if visited[next] {
+                    break;
+                }
+                visited[next] = true;
+                current = next;
+            }
+            if river.len() > 1 {
+                result.push(river);
+            }
+        }
+    }
+    result
+}
@@ -4,6 +4,7 @@ extern crate oxygengine_input as input;
 pub mod component;
 pub mod resource;
 pub mod system;
+pub mod transition;
 pub mod ui_theme_asset_protocol;
 
 // reexport macros.
@@ -14,7 +15,9 @@ pub use raui_core::{
 };
 
 pub mod prelude {
-    pub use crate::{component::*, resource::*, system::*, ui_theme_asset_protocol::*};
+    pub use crate::{
+        component::*, resource::*, system::*, transition::*, ui_theme_asset_protocol::*,
+    };
 }
 pub mod raui {
     pub mod core {
@@ -1,7 +1,9 @@
+use crate::{component::UserInterfaceView, system::UserInterfaceSystemCache};
 use raui_core::{
     application::Application, interactive::default_interactions_engine::DefaultInteractionsEngine,
     layout::CoordsMapping, signals::Signal,
 };
+use raui_material::theme::ThemeProps;
 use std::collections::HashMap;
 
 pub mod input_mappings {
@@ -42,6 +44,9 @@ impl ApplicationData {
 pub struct UserInterface {
     pub(crate) data: HashMap<String, ApplicationData>,
     pub(crate) setup_application: Option<fn(&mut Application)>,
+    themes: HashMap<String, ThemeProps>,
+    theme: Option<String>,
+    pub(crate) theme_dirty: bool,
 }
 
 impl UserInterface {
@@ -49,9 +54,49 @@ impl UserInterface {
         Self {
             data: Default::default(),
             setup_application: Some(setup_application),
+            themes: Default::default(),
+            theme: None,
+            theme_dirty: false,
         }
     }
 
+    pub fn register_theme(&mut self, name: impl ToString, theme: ThemeProps) {
+        let name = name.to_string();
+        if self.theme.as_ref() == Some(&name) {
+            self.theme_dirty = true;
+        }
+        self.themes.insert(name, theme);
+    }
+
+    pub fn unregister_theme(&mut self, name: &str) -> Option<ThemeProps> {
+        self.themes.remove(name)
+    }
+
+    /// Theme used by views that do not specify their own one.
+    pub fn theme(&self) -> Option<&str> {
+        self.theme.as_deref()
+    }
+
+    /// Switches theme used by views that do not specify their own one - these views
+    /// get rebuilt with new theme on next user interface system run.
+    pub fn set_theme(&mut self, theme: Option<String>) {
+        if self.theme != theme {
+            self.theme = theme;
+            self.theme_dirty = true;
+        }
+    }
+
+    /// Finds theme applied to given view, looking first at registered themes and
+    /// then at loaded UI theme assets.
+    pub fn resolve_theme<'a>(
+        &'a self,
+        view: &UserInterfaceView,
+        cache: &'a UserInterfaceSystemCache,
+    ) -> Option<&'a ThemeProps> {
+        let name = view.theme().or(self.theme.as_deref())?;
+        self.themes.get(name).or_else(|| cache.theme(name))
+    }
+
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = (&str, &ApplicationData)> {
         self.data.iter().map(|(n, d)| (n.as_str(), d))
@@ -130,3 +175,43 @@ impl UserInterface {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_switching() {
+        let mut light = ThemeProps::default();
+        light.icons_level_sizes = vec![1.0];
+        let mut dark = ThemeProps::default();
+        dark.icons_level_sizes = vec![2.0];
+        let cache = UserInterfaceSystemCache::default();
+        let mut ui = UserInterface::default();
+        ui.register_theme("light", light);
+        ui.register_theme("dark", dark);
+        let mut view = UserInterfaceView::new("app".to_owned());
+        let root = view.root().clone();
+        assert!(ui.resolve_theme(&view, &cache).is_none());
+
+        ui.set_theme(Some("light".to_owned()));
+        assert!(ui.theme_dirty);
+        assert_eq!(
+            ui.resolve_theme(&view, &cache).unwrap().icons_level_sizes,
+            vec![1.0]
+        );
+
+        ui.set_theme(Some("dark".to_owned()));
+        assert_eq!(
+            ui.resolve_theme(&view, &cache).unwrap().icons_level_sizes,
+            vec![2.0]
+        );
+        assert_eq!(view.root(), &root);
+
+        view.set_theme(Some("light".to_owned()));
+        assert_eq!(
+            ui.resolve_theme(&view, &cache).unwrap().icons_level_sizes,
+            vec![1.0]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::{
     component::UserInterfaceView,
     resource::{input_mappings::*, ApplicationData, UserInterface},
+    transition::{setup as transition_setup, UiTransitionTick},
     ui_theme_asset_protocol::UiThemeAsset,
     FeedProcessContext,
 };
@@ -98,6 +99,7 @@ where
                 let mut application = Application::new();
                 application.setup(core_setup);
                 application.setup(material_setup);
+                application.setup(transition_setup);
                 if let Some(setup_application) = ui.setup_application {
                     setup_application(&mut application);
                 }
@@ -123,22 +125,26 @@ where
                 );
             }
 
+            if ui.theme_dirty && view.theme().is_none() {
+                view.dirty = true;
+            }
+
             if view.dirty {
                 view.dirty = false;
+                let theme = ui.resolve_theme(&view, &cache).cloned();
                 let app = ui.application_mut(view.app_id()).unwrap();
                 let mut root = app
                     .deserialize_node(view.root().clone())
                     .expect("Could not deserialize UI node");
-                if let Some(theme) = view.theme() {
-                    if let Some(theme) = cache.themes_cache.get(theme) {
-                        if let Some(p) = root.shared_props_mut() {
-                            p.write(theme.clone());
-                        }
+                if let Some(theme) = theme {
+                    if let Some(p) = root.shared_props_mut() {
+                        p.write(theme);
                     }
                 }
                 app.apply(root);
             }
         }
+        ui.theme_dirty = false;
 
         let result = world
             .query::<&UserInterfaceView>()
@@ -152,6 +158,7 @@ where
         .expect_resource::<AppLifeCycle>()
         .delta_time_seconds();
     let mut context = ProcessContext::new();
+    context.insert_owned(UiTransitionTick(dt));
     let extras = universe.query_resources::<Q>();
     extras.feed_process_context(&mut context);
 
@@ -0,0 +1,321 @@
+use raui_core::{application::Application, prelude::*, unpack_named_slots, widget};
+use serde::{Deserialize, Serialize};
+
+/// Delta time fed into UI process context, used to tick transitions.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct UiTransitionTick(pub Scalar);
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UiTransitionEase {
+    Linear,
+    InQuad,
+    OutQuad,
+    InOutQuad,
+    InCubic,
+    OutCubic,
+    InOutCubic,
+}
+
+impl Default for UiTransitionEase {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+impl UiTransitionEase {
+    pub fn apply(self, t: Scalar) -> Scalar {
+        let t = t.max(0.0).min(1.0);
+        match self {
+            Self::Linear => t,
+            Self::InQuad => t * t,
+            Self::OutQuad => t * (2.0 - t),
+            Self::InOutQuad => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+            Self::InCubic => t * t * t,
+            Self::OutCubic => {
+                let t = t - 1.0;
+                t * t * t + 1.0
+            }
+            Self::InOutCubic => {
+                if t < 0.5 {
+                    4.0 * t * t * t
+                } else {
+                    let t = 2.0 * t - 2.0;
+                    0.5 * t * t * t + 1.0
+                }
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub enum UiTransitionKind {
+    Fade,
+    /// Slides from given offset (in layout units) into place.
+    Slide(Vec2),
+    /// Scales from given factor into full size.
+    Scale(Scalar),
+}
+
+impl Default for UiTransitionKind {
+    fn default() -> Self {
+        Self::Fade
+    }
+}
+
+#[derive(PropsData, Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiTransition {
+    #[serde(default)]
+    pub kind: UiTransitionKind,
+    #[serde(default = "UiTransition::default_duration")]
+    pub duration: Scalar,
+    #[serde(default)]
+    pub ease: UiTransitionEase,
+}
+
+impl Default for UiTransition {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            duration: Self::default_duration(),
+            ease: Default::default(),
+        }
+    }
+}
+
+impl UiTransition {
+    fn default_duration() -> Scalar {
+        0.25
+    }
+}
+
+/// Interpolated visual state of transitioned widget.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UiTransitionFrame {
+    pub alpha: Scalar,
+    pub offset: Vec2,
+    pub scale: Scalar,
+}
+
+impl Default for UiTransitionFrame {
+    fn default() -> Self {
+        Self {
+            alpha: 1.0,
+            offset: Default::default(),
+            scale: 1.0,
+        }
+    }
+}
+
+#[derive(PropsData, Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UiTransitionState {
+    #[serde(default)]
+    pub transition: UiTransition,
+    /// Linear progress of transition, where 0 means fully exited and 1 means fully entered.
+    #[serde(default)]
+    progress: Scalar,
+    #[serde(default)]
+    visible: bool,
+}
+
+impl UiTransitionState {
+    /// Creates state that plays enter transition from hidden state.
+    pub fn enter(transition: UiTransition) -> Self {
+        Self {
+            transition,
+            progress: 0.0,
+            visible: true,
+        }
+    }
+
+    pub fn progress(&self) -> Scalar {
+        self.progress
+    }
+
+    pub fn visible(&self) -> bool {
+        self.visible
+    }
+
+    /// Retargets transition - progress continues from where it is now so there are no jumps.
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    pub fn is_done(&self) -> bool {
+        if self.visible {
+            self.progress >= 1.0
+        } else {
+            self.progress <= 0.0
+        }
+    }
+
+    /// Tells if widget has finished exit transition and should not be rendered.
+    pub fn is_hidden(&self) -> bool {
+        !self.visible && self.progress <= 0.0
+    }
+
+    pub fn process(&mut self, delta_time: Scalar) {
+        let step = if self.transition.duration > 0.0 {
+            delta_time / self.transition.duration
+        } else {
+            1.0
+        };
+        self.progress = if self.visible {
+            (self.progress + step).min(1.0)
+        } else {
+            (self.progress - step).max(0.0)
+        };
+    }
+
+    pub fn factor(&self) -> Scalar {
+        self.transition.ease.apply(self.progress)
+    }
+
+    pub fn frame(&self) -> UiTransitionFrame {
+        let factor = self.factor();
+        match self.transition.kind {
+            UiTransitionKind::Fade => UiTransitionFrame {
+                alpha: factor,
+                ..Default::default()
+            },
+            UiTransitionKind::Slide(offset) => UiTransitionFrame {
+                offset: Vec2 {
+                    x: offset.x * (1.0 - factor),
+                    y: offset.y * (1.0 - factor),
+                },
+                ..Default::default()
+            },
+            UiTransitionKind::Scale(from) => UiTransitionFrame {
+                scale: from + (1.0 - from) * factor,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[derive(PropsData, Debug, Clone, Serialize, Deserialize)]
+pub struct UiTransitionProps {
+    #[serde(default)]
+    pub transition: UiTransition,
+    /// Changing this plays enter or exit transition.
+    #[serde(default = "UiTransitionProps::default_visible")]
+    pub visible: bool,
+}
+
+impl Default for UiTransitionProps {
+    fn default() -> Self {
+        Self {
+            transition: Default::default(),
+            visible: Self::default_visible(),
+        }
+    }
+}
+
+impl UiTransitionProps {
+    fn default_visible() -> bool {
+        true
+    }
+}
+
+/// Wraps `content` named slot and plays its enter/exit transition.
+pub fn ui_transition_box(context: WidgetContext) -> WidgetNode {
+    let WidgetContext {
+        key,
+        props,
+        state,
+        process_context,
+        named_slots,
+        ..
+    } = context;
+    unpack_named_slots!(named_slots => content);
+
+    let UiTransitionProps {
+        transition,
+        visible,
+    } = props.read_cloned_or_default();
+    let mut transition_state = match state.read_cloned::<UiTransitionState>() {
+        Ok(transition_state) => transition_state,
+        Err(_) => UiTransitionState::enter(transition),
+    };
+    transition_state.transition = transition;
+    transition_state.set_visible(visible);
+    if !transition_state.is_done() {
+        let dt = process_context
+            .owned_ref::<UiTransitionTick>()
+            .map(|tick| tick.0)
+            .unwrap_or_default();
+        transition_state.process(dt);
+        let _ = state.write(transition_state);
+    }
+    if transition_state.is_hidden() {
+        return Default::default();
+    }
+
+    let frame = transition_state.frame();
+    let inset = (1.0 - frame.scale) * 0.5;
+    if let Some(p) = content.props_mut() {
+        p.write(ContentBoxItemLayout {
+            anchors: Rect {
+                left: inset,
+                right: 1.0 - inset,
+                top: inset,
+                bottom: 1.0 - inset,
+            },
+            margin: Rect {
+                left: frame.offset.x,
+                right: -frame.offset.x,
+                top: frame.offset.y,
+                bottom: -frame.offset.y,
+            },
+            ..Default::default()
+        });
+    }
+
+    widget! {
+        (#{key} content_box | {WidgetAlpha(frame.alpha)} [
+            {content}
+        ])
+    }
+}
+
+pub fn setup(app: &mut Application) {
+    app.register_props::<UiTransition>("UiTransition");
+    app.register_props::<UiTransitionState>("UiTransitionState");
+    app.register_props::<UiTransitionProps>("UiTransitionProps");
+    app.register_component("ui_transition_box", ui_transition_box);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transition_fade_in() {
+        let mut state = UiTransitionState::enter(UiTransition {
+            kind: UiTransitionKind::Fade,
+            duration: 1.0,
+            ease: UiTransitionEase::InOutQuad,
+        });
+        assert_eq!(state.frame().alpha, 0.0);
+
+        state.process(0.5);
+        let alpha = state.frame().alpha;
+        assert!(alpha > 0.0 && alpha < 1.0);
+        assert!((alpha - 0.5).abs() < 1.0e-4);
+
+        state.set_visible(false);
+        state.process(0.25);
+        assert!((state.progress() - 0.25).abs() < 1.0e-4);
+        assert!(!state.is_hidden());
+
+        state.set_visible(true);
+        state.process(1.0);
+        assert!(state.is_done());
+        assert_eq!(state.frame().alpha, 1.0);
+    }
+}